@@ -0,0 +1,42 @@
+use crate::config::AnkiSettings;
+use crate::publish::markdown_html;
+use crate::session::{ConversationSession, ConversationTurn};
+
+// Anki flashcards from a session: one card per round, the question on the
+// front, the answer and its review on the back. Written as Anki's plain-text
+// import format (File → Import), with header lines naming the deck, the
+// columns and that fields are HTML.
+
+// Anki reads fields like CSV: quoted, with quotes doubled, so the HTML can keep
+// its tabs and newlines
+fn field(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+// Tags can't contain spaces
+fn tag(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+fn back(turn: &ConversationTurn) -> String {
+    let mut back = markdown_html(&turn.moonshot_answer);
+    if let Some(review) = &turn.deepseek_review {
+        let verdict = turn.verdict.map(|severity| format!(" — {}", severity.label())).unwrap_or_default();
+        back.push_str(&format!("<hr>\n<p><small>🔍 {}{}</small></p>\n<small>{}</small>", turn.review_model, verdict, markdown_html(review)));
+    }
+    back
+}
+
+// The import file and the number of cards in it
+pub fn render(session: &ConversationSession, settings: &AnkiSettings, deck: &str) -> (String, usize) {
+    let mut tags: Vec<String> = settings.tags.iter().chain(&session.tags).map(|name| tag(name)).collect();
+    tags.dedup();
+    // Front and Back are the fields of Anki's Basic note type
+    let mut out = format!("#separator:tab\n#html:true\n#deck:{}\n#columns:Front\tBack\tTags\n", deck);
+    let mut cards = 0;
+    for turn in session.turns.iter().filter(|turn| settings.include_unreviewed || turn.deepseek_review.is_some()) {
+        out.push_str(&format!("{}\t{}\t{}\n", field(&markdown_html(&turn.user_question)), field(&back(turn)), tags.join(" ")));
+        cards += 1;
+    }
+    (out, cards)
+}
@@ -0,0 +1,973 @@
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use reqwest::{Client, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::config::{AiConfig, Http2Mode, NetworkSettings};
+use crate::dry_run;
+use crate::events;
+use crate::i18n::tr;
+use crate::provenance;
+use crate::recovery;
+use crate::ui;
+use crate::usage::{self, UsageRecord};
+use crate::vcr::{self, Reply};
+
+// Define structures for OpenAI-compatible API requests/responses
+#[derive(Serialize, Clone)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+    // An assistant turn that asked for tools, and the replies to those calls
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn tool_result(call: &ToolCall, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(call.id.clone()),
+            ..Self::new("tool", content)
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new("system", content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new("user", content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new("assistant", content)
+    }
+}
+
+// Unless a `/persona` sets its own
+const DEFAULT_TEMPERATURE: f32 = 0.7;
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: String,
+    messages: &'a [ChatMessage],
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tools: &'a [ToolSpec],
+}
+
+// A function the model may call (`tools` in the request)
+#[derive(Serialize)]
+pub struct ToolSpec {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub function: FunctionSpec,
+}
+
+#[derive(Serialize)]
+pub struct FunctionSpec {
+    pub name: String,
+    pub description: String,
+    // JSON Schema of the arguments object
+    pub parameters: serde_json::Value,
+}
+
+impl ToolSpec {
+    pub fn function(name: impl Into<String>, description: impl Into<String>, parameters: serde_json::Value) -> Self {
+        Self {
+            kind: "function",
+            function: FunctionSpec {
+                name: name.into(),
+                description: description.into(),
+                parameters,
+            },
+        }
+    }
+}
+
+// A call the model asked for; `arguments` is a JSON object encoded as a string
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_kind")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn function_kind() -> String {
+    "function".to_string()
+}
+
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: MessageContent,
+    // "length" when the response was cut off at `max_tokens`
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+impl ChatChoice {
+    fn finish(&self) -> Option<Finish> {
+        Finish::from_response(self.finish_reason.as_deref(), self.message.refusal.as_deref())
+    }
+}
+
+#[derive(Deserialize)]
+struct MessageContent {
+    // null when the model only asks for tools
+    #[serde(default)]
+    content: Option<String>,
+    // Reasoning trace of reasoning models (deepseek-reasoner), apart from the content
+    #[serde(default)]
+    reasoning_content: Option<String>,
+    // Set instead of `content` when the model declines (OpenAI)
+    #[serde(default)]
+    refusal: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCall>,
+}
+
+impl MessageContent {
+    // The content, or the refusal when the model declined
+    fn text(&self) -> String {
+        match (&self.content, &self.refusal) {
+            (Some(content), _) if !content.is_empty() => content.clone(),
+            (_, Some(refusal)) => refusal.clone(),
+            (content, None) => content.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub completion_tokens: u32,
+    // Which of the provider's keys paid for it (see `keys`)
+    #[serde(skip)]
+    pub key: Option<String>,
+    // Attempts it took beyond the first
+    #[serde(skip)]
+    pub retries: u32,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+}
+
+// Server-sent event payloads when `stream: true`
+#[derive(Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+}
+
+#[derive(Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: StreamDelta,
+    #[serde(default)]
+    finish_reason: Option<String>,
+    // Moonshot reports usage on the final choice rather than at the top level
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+}
+
+#[derive(Deserialize, Default)]
+struct StreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    refusal: Option<String>,
+}
+
+// A streamed answer; `interrupted` means the user stopped it with Ctrl+C
+// and `content` holds whatever arrived before that
+pub struct StreamedAnswer {
+    pub content: String,
+    pub interrupted: bool,
+    // Why the provider stopped ("stop", "length", ...), if it said
+    pub finish_reason: Option<String>,
+    pub refusal: Option<String>,
+}
+
+impl StreamedAnswer {
+    pub fn finish(&self) -> Option<Finish> {
+        Finish::from_response(self.finish_reason.as_deref(), self.refusal.as_deref())
+    }
+}
+
+// How a response ended when it isn't a complete answer, stored with the turn
+// so it isn't mistaken for one. A normal stop is None.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Finish {
+    // Cut off at a token limit
+    Truncated,
+    // Stopped by the provider's content filter
+    Filtered,
+    // The model declined to answer
+    Refused,
+}
+
+impl Finish {
+    pub fn from_response(finish_reason: Option<&str>, refusal: Option<&str>) -> Option<Self> {
+        if refusal.is_some_and(|r| !r.trim().is_empty()) {
+            return Some(Self::Refused);
+        }
+        match finish_reason? {
+            "length" => Some(Self::Truncated),
+            "content_filter" | "sensitive" => Some(Self::Filtered),
+            "refusal" => Some(Self::Refused),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Truncated => tr!("finish.truncated"),
+            Self::Filtered => tr!("finish.filtered"),
+            Self::Refused => tr!("finish.refused"),
+        }
+    }
+}
+
+// Rough token estimate used for rate limiting: ~4 ASCII chars per token,
+// ~1 token per CJK (or other non-ASCII) character.
+pub fn estimate_tokens(text: &str) -> u32 {
+    let (ascii, other) = text
+        .chars()
+        .fold((0u32, 0u32), |(a, o), c| if c.is_ascii() { (a + 1, o) } else { (a, o + 1) });
+    ascii.div_ceil(4) + other
+}
+
+fn estimate_request_tokens(request: &ChatRequest) -> u32 {
+    request.messages.iter().map(|m| estimate_tokens(&m.content)).sum()
+}
+
+// Exponential backoff: base * 2^(attempt-1), capped at max_delay_ms
+fn backoff_delay(config: &AiConfig, attempt: u32) -> Duration {
+    let factor = 1u64 << (attempt.saturating_sub(1)).min(16);
+    let delay_ms = config.retry.base_delay_ms.saturating_mul(factor);
+    Duration::from_millis(delay_ms.min(config.retry.max_delay_ms))
+}
+
+// Honour a `Retry-After: <seconds>` header when the provider sends one, capped
+// at max_delay_ms; negative or non-finite values are ignored
+fn retry_after(config: &AiConfig, response: &Response) -> Option<Duration> {
+    let seconds = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim().parse::<f64>().ok()?;
+    let delay = Duration::try_from_secs_f64(seconds).ok()?;
+    Some(delay.min(Duration::from_millis(config.retry.max_delay_ms)))
+}
+
+// `--local-only`: nothing may leave for a host other than this machine
+static LOCAL_ONLY: AtomicBool = AtomicBool::new(false);
+
+pub fn set_local_only() {
+    LOCAL_ONLY.store(true, Ordering::Relaxed);
+}
+
+pub fn local_only() -> bool {
+    LOCAL_ONLY.load(Ordering::Relaxed)
+}
+
+// Whether an http(s) URL points at a loopback host (localhost, 127.0.0.0/8, ::1)
+fn is_local_url(url: &str) -> bool {
+    let Some((_, rest)) = url.split_once("://") else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host_port.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    }
+    .to_ascii_lowercase();
+    host == "localhost" || host.ends_with(".localhost") || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+// Refuse a non-local destination under --local-only
+pub fn check_destination(name: &str, base_url: &str) -> Result<()> {
+    if local_only() && !is_local_url(base_url) {
+        anyhow::bail!(
+            "--local-only: refusing to send requests to {} at {} (set [providers.NAME] base_url to a localhost server)",
+            name,
+            base_url
+        );
+    }
+    Ok(())
+}
+
+// Requests are answered without the network (`--replay`, `--dry-run`)
+pub fn offline() -> bool {
+    vcr::replaying() || dry_run::enabled()
+}
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+fn build_client(settings: &NetworkSettings) -> Result<Client> {
+    let seconds = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+    let mut builder = Client::builder()
+        .pool_max_idle_per_host(settings.pool_max_idle_per_host)
+        .pool_idle_timeout(seconds(settings.pool_idle_timeout_secs))
+        .tcp_keepalive(seconds(settings.tcp_keepalive_secs))
+        .tcp_nodelay(true)
+        .gzip(true)
+        .brotli(true);
+    // A redirect or a proxy would carry requests off this machine
+    if local_only() {
+        builder = builder.redirect(reqwest::redirect::Policy::none()).no_proxy();
+    }
+    if let Some(timeout) = seconds(settings.connect_timeout_secs) {
+        builder = builder.connect_timeout(timeout);
+    }
+    builder = match settings.http2 {
+        Http2Mode::Auto => builder,
+        Http2Mode::PriorKnowledge => builder.http2_prior_knowledge(),
+        Http2Mode::Off => builder.http1_only(),
+    };
+    if settings.http2 != Http2Mode::Off {
+        builder = builder
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(seconds(settings.http2_keepalive_secs))
+            .http2_keep_alive_while_idle(true);
+    }
+    builder.build().context("Failed to set up the HTTP client ([network])")
+}
+
+// Build the shared client from `[network]`; call once, before `client()`
+pub fn init_client(settings: &NetworkSettings) -> Result<()> {
+    let _ = CLIENT.set(build_client(settings)?);
+    Ok(())
+}
+
+// The one HTTP client for provider requests, so pooled connections (and their
+// TLS sessions) carry over from round to round. Cloning it is cheap.
+pub fn client() -> Client {
+    CLIENT.get_or_init(|| build_client(&NetworkSettings::default()).unwrap_or_default()).clone()
+}
+
+// The JSON body of a request, gzipped (true) when the provider takes
+// compressed requests and it is big enough to be worth it
+fn request_bytes(config: &AiConfig, request_body: &impl Serialize) -> Result<(Vec<u8>, bool)> {
+    let json = serde_json::to_vec(request_body).context("Failed to encode the request")?;
+    match config.compress_over {
+        Some(min) if json.len() >= min => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&json)?;
+            Ok((encoder.finish()?, true))
+        }
+        _ => Ok((json, false)),
+    }
+}
+
+// Send a request through the provider's rate limiter, retrying 429s, 5xx
+// responses and transient network errors with backoff. A 429 moves on to the
+// provider's next key, if it has one ready, without waiting. Returns the label
+// of the key that got through and the number of retries.
+async fn send_with_retry(
+    client: &Client,
+    config: &AiConfig,
+    request_body: &impl Serialize,
+    estimated_tokens: u32,
+) -> Result<(Reply, Option<String>, u32)> {
+    if dry_run::enabled() {
+        return Ok((Reply::canned(dry_run::respond(request_body)), None, 0));
+    }
+    if vcr::replaying() {
+        return Ok((Reply::replayed(&config.name, request_body)?, None, 0));
+    }
+    check_destination(&config.name, &config.base_url)?;
+    let (body, compressed) = request_bytes(config, request_body)?;
+    let max_attempts = config.retry.max_attempts.max(1);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        ui::detail(ui::status(tr!("api.sending", config.name, attempt, max_attempts, estimated_tokens)));
+        let waited = config.limiter.acquire(estimated_tokens).await;
+        if waited >= Duration::from_secs(1) {
+            ui::notice(ui::status(tr!("api.rate_limited", format!("{:.1}", waited.as_secs_f64()), config.name)));
+        }
+
+        let key = config.keys.pick();
+        let mut request = client
+            .post(&config.base_url)
+            .header("Authorization", format!("Bearer {}", config.keys.value(key)))
+            .header("Content-Type", "application/json");
+        for (name, value) in &config.headers {
+            request = request.header(name, value);
+        }
+        if !config.query.is_empty() {
+            request = request.query(&config.query);
+        }
+        if compressed {
+            request = request.header("Content-Encoding", "gzip");
+        }
+        let sent = Instant::now();
+        let result = request.body(body.clone()).send().await;
+
+        let delay = match result {
+            Ok(response) if response.status().is_success() => {
+                return Ok((Reply::live(response, sent, &config.name, request_body), config.keys.label(key), attempt - 1));
+            }
+            Ok(response) => {
+                let status = response.status();
+                let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                if !retryable || attempt >= max_attempts {
+                    let error_text = response.text().await.unwrap_or_default();
+                    return Err(anyhow::anyhow!("API Error from {}: {}", config.name, error_text));
+                }
+                let delay = retry_after(config, &response).unwrap_or_else(|| backoff_delay(config, attempt));
+                if status == StatusCode::TOO_MANY_REQUESTS && config.keys.rate_limited(key, delay) {
+                    ui::notice(ui::status(tr!("keys.switching", config.name, attempt, max_attempts)));
+                    log_retry(config, attempt, status.to_string(), Duration::ZERO);
+                    continue;
+                }
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    config.limiter.pause_for(delay);
+                }
+                ui::notice(ui::status(tr!("api.retry_status", config.name, status, format!("{:.1}", delay.as_secs_f64()), attempt, max_attempts)));
+                log_retry(config, attempt, status.to_string(), delay);
+                delay
+            }
+            Err(e) if attempt < max_attempts && (e.is_timeout() || e.is_connect()) => {
+                let delay = backoff_delay(config, attempt);
+                ui::notice(ui::status(tr!("api.retry_network", config.name, format!("{:.1}", delay.as_secs_f64()), attempt, max_attempts)));
+                log_retry(config, attempt, e.to_string(), delay);
+                delay
+            }
+            Err(e) => return Err(e).context(format!("Failed to send request to {}", config.name)),
+        };
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn log_retry(config: &AiConfig, attempt: u32, reason: String, delay: Duration) {
+    events::log("retry", json!({ "provider": config.name, "attempt": attempt, "reason": reason, "delay_ms": delay.as_millis() as u64 }));
+}
+
+// Every request (successful or not) goes into the usage ledger
+fn record_usage(config: &AiConfig, started: Instant, result: Result<&TokenUsage, String>) {
+    let (tokens, error) = match result {
+        Ok(tokens) => (Some(tokens), None),
+        Err(e) => (None, Some(e)),
+    };
+    let elapsed = started.elapsed().as_secs_f64();
+    match tokens {
+        Some(t) => ui::detail(ui::status(tr!(
+            "api.request_done",
+            config.name,
+            config.model,
+            format!("{:.1}", elapsed),
+            t.prompt_tokens,
+            t.completion_tokens
+        ))),
+        None => ui::detail(ui::status(tr!("api.request_failed", config.name, config.model, format!("{:.1}", elapsed)))),
+    }
+    if let Some(error) = &error {
+        events::log("failure", json!({ "provider": config.name, "model": config.model, "error": error }));
+    }
+    usage::record(&UsageRecord {
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        provider: config.name.clone(),
+        model: config.model.clone(),
+        prompt_tokens: tokens.map_or(0, |t| t.prompt_tokens),
+        completion_tokens: tokens.map_or(0, |t| t.completion_tokens),
+        ok: error.is_none(),
+        error,
+        key: tokens.and_then(|t| t.key.clone()),
+        latency_ms: started.elapsed().as_millis() as u64,
+        temperature: Some(config.temperature.unwrap_or(DEFAULT_TEMPERATURE)),
+        max_tokens: config.max_tokens,
+        retries: tokens.map_or(0, |t| t.retries),
+        user: usage::current_user(),
+        endpoint: Some(provenance::endpoint_hash(&config.base_url)),
+    });
+}
+
+// A finished (non-streamed) completion with its token counts and wall-clock latency
+pub struct Completion {
+    pub content: String,
+    // What a reasoning model thought before answering, if it says
+    pub reasoning: Option<String>,
+    // Set when the response is truncated, filtered or a refusal
+    pub finish: Option<Finish>,
+    pub usage: TokenUsage,
+    pub latency_ms: u64,
+}
+
+pub async fn call_ai_api(client: &Client, config: &AiConfig, messages: Vec<ChatMessage>) -> Result<String> {
+    complete(client, config, messages).await.map(|c| c.content)
+}
+
+pub async fn complete(client: &Client, config: &AiConfig, mut messages: Vec<ChatMessage>) -> Result<Completion> {
+    ui::notice(ui::status(tr!("api.thinking", config.name)));
+
+    let started = Instant::now();
+    let asked = messages.len();
+    let (mut content, mut reasoning, mut finish, mut total) = (String::new(), None, None, TokenUsage::default());
+    for part in 0..=config.auto_continue {
+        let request_started = Instant::now();
+        let result = request_message(client, config, &messages, &[]).await;
+        record_usage(config, request_started, result.as_ref().map(|(_, tokens)| tokens).map_err(|e| e.to_string()));
+        // A failed continuation keeps what came before, still marked as truncated
+        let (choice, usage) = match result {
+            Err(e) if part > 0 => {
+                ui::notice(ui::warning(tr!("api.continue_failed", config.name, format!("{:#}", e))));
+                break;
+            }
+            result => result?,
+        };
+        total.prompt_tokens += usage.prompt_tokens;
+        total.completion_tokens += usage.completion_tokens;
+        total.key = usage.key;
+        finish = choice.finish();
+        content.push_str(&choice.message.text());
+        if reasoning.is_none() {
+            reasoning = choice.message.reasoning_content.filter(|r| !r.trim().is_empty());
+        }
+        if part == config.auto_continue || !cut_off(config, choice.finish_reason.as_deref()) {
+            break;
+        }
+        continue_from(&mut messages, asked, &content, config, part);
+    }
+
+    Ok(Completion {
+        content,
+        reasoning,
+        finish,
+        usage: total,
+        latency_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+// A response the provider cut off at its own length limit, rather than at the
+// role's `max_tokens`
+fn cut_off(config: &AiConfig, finish_reason: Option<&str>) -> bool {
+    finish_reason == Some("length") && config.max_tokens.is_none()
+}
+
+const CONTINUE_PROMPT: &str = "You were cut off. Continue exactly where you stopped, without repeating anything and without any introduction. If you stopped inside a code block, continue the code without opening a new block.";
+
+// Turn the request into one asking to continue `so_far`; `asked` is the
+// length of the original conversation
+fn continue_from(messages: &mut Vec<ChatMessage>, asked: usize, so_far: &str, config: &AiConfig, part: usize) {
+    ui::notice(ui::status(tr!("api.continuing", config.name, part + 1, config.auto_continue)));
+    messages.truncate(asked);
+    messages.push(ChatMessage::assistant(so_far));
+    messages.push(ChatMessage::user(CONTINUE_PROMPT));
+}
+
+async fn request_message(client: &Client, config: &AiConfig, messages: &[ChatMessage], tools: &[ToolSpec]) -> Result<(ChatChoice, TokenUsage)> {
+    let request_body = ChatRequest {
+        model: config.model.clone(),
+        messages,
+        temperature: config.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+        max_tokens: config.max_tokens,
+        stream: false,
+        stream_options: None,
+        tools,
+    };
+    log_request(config, &request_body);
+
+    let started = Instant::now();
+    let _slot = config.limiter.slot().await;
+    let (response, key, retries) = send_with_retry(client, config, &request_body, estimate_request_tokens(&request_body)).await?;
+
+    let chat_response: ChatResponse = response
+        .json()
+        .await
+        .context(format!("Failed to parse response from {}", config.name))?;
+
+    let choice = chat_response
+        .choices
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No choices returned from {}", config.name))?;
+
+    let usage = TokenUsage {
+        key,
+        retries,
+        ..chat_response.usage.unwrap_or_default()
+    };
+    log_response(config, &choice.message.text(), &choice.message.tool_calls, choice.finish_reason.as_deref(), &usage, started);
+    Ok((choice, usage))
+}
+
+// The request as sent, and what came back, for the event log
+fn log_request(config: &AiConfig, request_body: &ChatRequest) {
+    if events::enabled() {
+        events::log("request", json!({ "provider": config.name, "model": config.model, "body": request_body }));
+    }
+}
+
+fn log_response(config: &AiConfig, content: &str, tool_calls: &[ToolCall], finish_reason: Option<&str>, usage: &TokenUsage, started: Instant) {
+    if !events::enabled() {
+        return;
+    }
+    events::log(
+        "response",
+        json!({
+            "provider": config.name,
+            "model": config.model,
+            "content": content,
+            "tool_calls": tool_calls,
+            "finish_reason": finish_reason,
+            "prompt_tokens": usage.prompt_tokens,
+            "completion_tokens": usage.completion_tokens,
+            "retries": usage.retries,
+            "latency_ms": started.elapsed().as_millis() as u64,
+        }),
+    );
+}
+
+// Most tool round trips before the model has to answer
+const MAX_TOOL_STEPS: usize = 8;
+
+// A completion in which the model may call `tools`. Each call is handed to
+// `run_tool` and its result sent back, until the model answers in text.
+pub async fn complete_with_tools(
+    client: &Client,
+    config: &AiConfig,
+    mut messages: Vec<ChatMessage>,
+    tools: &[ToolSpec],
+    mut run_tool: impl FnMut(&ToolCall) -> String,
+) -> Result<Completion> {
+    ui::notice(ui::status(tr!("api.thinking", config.name)));
+
+    let started = Instant::now();
+    let mut total = TokenUsage::default();
+    // Text the model wrote alongside its tool calls
+    let mut said = String::new();
+    for step in 0..=MAX_TOOL_STEPS {
+        // The last request offers no tools, so the model has to answer
+        let offered = if step < MAX_TOOL_STEPS { tools } else { &[] };
+        let request_started = Instant::now();
+        let result = request_message(client, config, &messages, offered).await;
+        record_usage(config, request_started, result.as_ref().map(|(_, tokens)| tokens).map_err(|e| e.to_string()));
+        let (choice, usage) = match result {
+            Err(e) if !said.trim().is_empty() => {
+                ui::notice(ui::warning(tr!("api.continue_failed", config.name, format!("{:#}", e))));
+                return Ok(Completion {
+                    content: said,
+                    reasoning: None,
+                    finish: Some(Finish::Truncated),
+                    usage: total,
+                    latency_ms: started.elapsed().as_millis() as u64,
+                });
+            }
+            result => result?,
+        };
+        let finish = choice.finish();
+        let message = choice.message;
+        total.prompt_tokens += usage.prompt_tokens;
+        total.completion_tokens += usage.completion_tokens;
+
+        if message.tool_calls.is_empty() {
+            return Ok(Completion {
+                content: message.text(),
+                reasoning: message.reasoning_content.filter(|r| !r.trim().is_empty()),
+                finish,
+                usage: total,
+                latency_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+        said.push_str(&message.text());
+        let results: Vec<ChatMessage> = message.tool_calls.iter().map(|call| ChatMessage::tool_result(call, run_tool(call))).collect();
+        messages.push(ChatMessage {
+            tool_calls: message.tool_calls,
+            ..ChatMessage::assistant(message.content.unwrap_or_default())
+        });
+        messages.extend(results);
+    }
+    anyhow::bail!("{} kept calling tools without answering", config.name)
+}
+
+// Stream a completion, handing each text delta to `on_delta` as it arrives.
+// Ctrl+C stops generation but keeps the partial answer.
+pub async fn stream_ai_api(
+    client: &Client,
+    config: &AiConfig,
+    mut messages: Vec<ChatMessage>,
+    mut on_delta: impl FnMut(&str),
+) -> Result<StreamedAnswer> {
+    ui::notice(ui::status(tr!("api.thinking_stream", config.name)));
+
+    let asked = messages.len();
+    let mut answer = StreamedAnswer {
+        content: String::new(),
+        interrupted: false,
+        finish_reason: None,
+        refusal: None,
+    };
+    // A cut-off answer is continued in place, the next part streaming right after it
+    for part in 0..=config.auto_continue {
+        let started = Instant::now();
+        let result = request_stream(client, config, &messages, &mut on_delta).await;
+        record_usage(config, started, result.as_ref().map(|(_, tokens)| tokens).map_err(|e| e.to_string()));
+        // The parts already streamed were shown; keep them, still marked as truncated
+        let (streamed, _) = match result {
+            Err(e) if part > 0 => {
+                ui::notice(ui::warning(tr!("api.continue_failed", config.name, format!("{:#}", e))));
+                break;
+            }
+            result => result?,
+        };
+        answer.content.push_str(&streamed.content);
+        answer.interrupted = streamed.interrupted;
+        answer.finish_reason = streamed.finish_reason;
+        answer.refusal = streamed.refusal;
+        if part == config.auto_continue || answer.interrupted || !cut_off(config, answer.finish_reason.as_deref()) {
+            break;
+        }
+        continue_from(&mut messages, asked, &answer.content, config, part);
+    }
+    Ok(answer)
+}
+
+async fn request_stream(
+    client: &Client,
+    config: &AiConfig,
+    messages: &[ChatMessage],
+    on_delta: &mut impl FnMut(&str),
+) -> Result<(StreamedAnswer, TokenUsage)> {
+    let request_body = ChatRequest {
+        model: config.model.clone(),
+        messages,
+        temperature: config.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+        max_tokens: config.max_tokens,
+        stream: true,
+        stream_options: Some(StreamOptions { include_usage: true }),
+        tools: &[],
+    };
+    log_request(config, &request_body);
+
+    let started = Instant::now();
+    // Claimed before the request, so Ctrl+C while waiting for the rate limiter,
+    // connecting or backing off cancels the answer instead of exiting
+    let mut interrupt = recovery::claim_interrupts();
+    let sent = tokio::select! {
+        sent = async {
+            let slot = config.limiter.slot().await;
+            send_with_retry(client, config, &request_body, estimate_request_tokens(&request_body)).await.map(|sent| (sent, slot))
+        } => sent?,
+        _ = interrupt.interrupted() => {
+            let answer = StreamedAnswer {
+                content: String::new(),
+                interrupted: true,
+                finish_reason: None,
+                refusal: None,
+            };
+            return Ok((answer, TokenUsage::default()));
+        }
+    };
+    let ((mut response, key, retries), _slot) = sent;
+
+    let mut pending: Vec<u8> = Vec::new();
+    let mut content = String::new();
+    let mut usage = TokenUsage::default();
+    let mut interrupted = false;
+    let mut finish_reason = None;
+    let mut refusal: Option<String> = None;
+
+    'stream: loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => {
+                chunk.context(format!("Stream from {} was interrupted", config.name))?
+            }
+            _ = interrupt.interrupted() => {
+                interrupted = true;
+                break 'stream;
+            }
+        };
+        let Some(chunk) = chunk else { break };
+        pending.extend_from_slice(&chunk);
+
+        // SSE events are newline-delimited `data: {...}` lines
+        while let Some(newline) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else { continue };
+            let data = data.trim();
+            if data == "[DONE]" {
+                break 'stream;
+            }
+            let Ok(event) = serde_json::from_str::<StreamChunk>(data) else { continue };
+            if let Some(tokens) = event.usage {
+                usage = tokens;
+            }
+            for choice in event.choices {
+                if let Some(tokens) = choice.usage {
+                    usage = tokens;
+                }
+                if choice.finish_reason.is_some() {
+                    finish_reason = choice.finish_reason;
+                }
+                if let Some(delta) = choice.delta.content.filter(|d| !d.is_empty()) {
+                    on_delta(&delta);
+                    content.push_str(&delta);
+                }
+                // A refusal streams in place of the content; show it the same way
+                if let Some(delta) = choice.delta.refusal.filter(|d| !d.is_empty()) {
+                    on_delta(&delta);
+                    content.push_str(&delta);
+                    refusal.get_or_insert_with(String::new).push_str(&delta);
+                }
+            }
+        }
+    }
+
+    usage.key = key;
+    usage.retries = retries;
+    log_response(config, &content, &[], finish_reason.as_deref(), &usage, started);
+    Ok((
+        StreamedAnswer {
+            content,
+            interrupted,
+            finish_reason,
+            refusal,
+        },
+        usage,
+    ))
+}
+
+// OpenAI-compatible `/moderations` request, for the `[guard]` pass
+#[derive(Serialize)]
+struct ModerationRequest<'a> {
+    #[serde(skip_serializing_if = "str::is_empty")]
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ModerationResult {
+    flagged: bool,
+    #[serde(default)]
+    categories: HashMap<String, bool>,
+}
+
+#[derive(Deserialize)]
+struct ModerationResponse {
+    results: Vec<ModerationResult>,
+}
+
+// Categories the endpoint flagged `input` for, sorted; empty if it passed.
+// `config.base_url` is the moderations endpoint.
+pub async fn moderate(client: &Client, config: &AiConfig, input: &str) -> Result<Vec<String>> {
+    let request_body = ModerationRequest { model: &config.model, input };
+    let _slot = config.limiter.slot().await;
+    let (response, _, _) = send_with_retry(client, config, &request_body, estimate_tokens(input)).await?;
+    let parsed: ModerationResponse = response
+        .json()
+        .await
+        .context(format!("Failed to parse moderation result from {}", config.name))?;
+    let mut flagged = Vec::new();
+    for result in parsed.results.into_iter().filter(|result| result.flagged) {
+        let before = flagged.len();
+        flagged.extend(result.categories.into_iter().filter(|(_, hit)| *hit).map(|(name, _)| name));
+        // Flagged without naming a category
+        if flagged.len() == before {
+            flagged.push("flagged".to_string());
+        }
+    }
+    flagged.sort();
+    flagged.dedup();
+    Ok(flagged)
+}
+
+// OpenAI-compatible `/embeddings` request, for the cross-session memory
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: usize,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+}
+
+// One vector per input, in input order. `config.base_url` is the embeddings endpoint.
+pub async fn embed(client: &Client, config: &AiConfig, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+    let started = Instant::now();
+    let result = request_embeddings(client, config, inputs).await;
+    record_usage(config, started, result.as_ref().map(|(_, tokens)| tokens).map_err(|e| e.to_string()));
+    result.map(|(vectors, _)| vectors)
+}
+
+async fn request_embeddings(client: &Client, config: &AiConfig, inputs: &[String]) -> Result<(Vec<Vec<f32>>, TokenUsage)> {
+    let request_body = EmbeddingRequest {
+        model: &config.model,
+        input: inputs,
+    };
+    let estimated_tokens = inputs.iter().map(|input| estimate_tokens(input)).sum();
+    let _slot = config.limiter.slot().await;
+    let (response, key, retries) = send_with_retry(client, config, &request_body, estimated_tokens).await?;
+    let mut parsed: EmbeddingResponse = response
+        .json()
+        .await
+        .context(format!("Failed to parse embeddings from {}", config.name))?;
+    if parsed.data.len() != inputs.len() {
+        anyhow::bail!("{} returned {} embeddings for {} inputs", config.name, parsed.data.len(), inputs.len());
+    }
+    parsed.data.sort_by_key(|d| d.index);
+    let usage = TokenUsage {
+        key,
+        retries,
+        ..parsed.usage.unwrap_or_default()
+    };
+    Ok((parsed.data.into_iter().map(|d| d.embedding).collect(), usage))
+}
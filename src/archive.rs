@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use crate::config::{ArchiveSettings, SaveMode};
+use crate::crypto;
+
+// Per-project settings file, merged over ~/.ai_vs_ai.toml
+pub const PROJECT_SETTINGS_FILE: &str = ".aivsai.toml";
+
+static CONVERSATIONS_DIR: OnceLock<PathBuf> = OnceLock::new();
+static JOURNAL: AtomicBool = AtomicBool::new(false);
+
+pub fn init(settings: &ArchiveSettings) {
+    if let Some(dir) = &settings.dir {
+        let _ = CONVERSATIONS_DIR.set(PathBuf::from(dir));
+    }
+    JOURNAL.store(settings.save == SaveMode::Journal, Ordering::Relaxed);
+}
+
+// `[archive] save = "journal"`: sessions are appended to daily journals
+pub fn journal() -> bool {
+    JOURNAL.load(Ordering::Relaxed)
+}
+
+// The daily journal of a session, by the day it started: `2024-06-12.md`
+pub fn journal_name(start_time: &str) -> String {
+    format!("{}.md", start_time.get(..10).unwrap_or(start_time))
+}
+
+// `path` as `write_file` writes it: with `.age` appended while encryption is on
+pub fn written_path(path: &Path) -> PathBuf {
+    if !crypto::enabled() {
+        return path.to_path_buf();
+    }
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", crypto::EXTENSION));
+    PathBuf::from(name)
+}
+
+// Find project directory by looking for Cargo.toml or .aivsai.toml in current dir or parents
+pub fn find_project_dir() -> Result<PathBuf> {
+    let mut current_dir = env::current_dir()
+        .context("Failed to get current directory")?;
+    
+    loop {
+        // Check if Cargo.toml or a project settings file exists in current directory
+        if current_dir.join("Cargo.toml").exists() || current_dir.join(PROJECT_SETTINGS_FILE).exists() {
+            return Ok(current_dir);
+        }
+        
+        // Try parent directory
+        match current_dir.parent() {
+            Some(parent) => current_dir = parent.to_path_buf(),
+            None => break,
+        }
+    }
+    
+    // If no Cargo.toml found, fall back to current directory
+    env::current_dir().context("Failed to get current directory")
+}
+
+// Where conversations are kept: `[archive] dir` (relative to the project
+// directory) or conversations/ in the project directory
+pub fn conversations_path() -> Result<PathBuf> {
+    let project_dir = find_project_dir()?;
+    Ok(match CONVERSATIONS_DIR.get() {
+        Some(dir) => project_dir.join(dir),
+        None => project_dir.join("conversations"),
+    })
+}
+
+// Find conversations directory, creating it if needed
+pub fn conversations_dir() -> Result<PathBuf> {
+    let conversations_dir = conversations_path()?;
+
+    // Create conversations directory if it doesn't exist
+    if !conversations_dir.exists() {
+        std::fs::create_dir_all(&conversations_dir)
+            .context("Failed to create conversations directory")?;
+    }
+    
+    Ok(conversations_dir)
+}
+
+// Write a saved file, encrypted with `.age` appended when `[archive] encryption`
+// is on; returns the path actually written
+pub fn write_file(path: &Path, content: &str) -> Result<PathBuf> {
+    write_bytes(path, content.as_bytes())
+}
+
+// Same as `write_file`, for binary content such as PDFs
+pub fn write_bytes(path: &Path, content: &[u8]) -> Result<PathBuf> {
+    if !crypto::enabled() {
+        std::fs::write(path, content).context(format!("Failed to write {:?}", path))?;
+        return Ok(path.to_path_buf());
+    }
+    let path = written_path(path);
+    std::fs::write(&path, crypto::encrypt(content)?).context(format!("Failed to write {:?}", path))?;
+    Ok(path)
+}
+
+// Read a saved file, decrypting `.age` files
+pub fn read_file(path: &Path) -> Result<String> {
+    if !crypto::is_encrypted_name(&path.to_string_lossy()) {
+        return std::fs::read_to_string(path).context(format!("Failed to read {:?}", path));
+    }
+    let bytes = std::fs::read(path).context(format!("Failed to read {:?}", path))?;
+    let text = crypto::decrypt(&bytes).context(format!("Failed to decrypt {:?}", path))?;
+    String::from_utf8(text).context(format!("{:?} is not UTF-8 text", path))
+}
+
+// JSON files next to the sessions that aren't sessions: the index manifest and
+// the memory index
+const SIDE_FILES: &[&str] = &["INDEX.json", "memory.json"];
+
+// Sessions saved with /save (their JSON side files), newest first
+pub fn saved_sessions() -> Vec<String> {
+    let Ok(dir) = conversations_path() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".json") || name.ends_with(".json.age"))
+        .filter(|name| !SIDE_FILES.contains(&name.strip_suffix(".age").unwrap_or(name)))
+        .collect();
+    // File names start with the save timestamp
+    names.sort_unstable_by(|a, b| b.cmp(a));
+    names
+}
+
+// Resolve a /load argument: a file in conversations/ (with or without
+// extension, .md or .json, encrypted or not) or a path
+pub fn session_file(name: &str) -> Result<PathBuf> {
+    let name = name.strip_suffix(".age").unwrap_or(name);
+    let direct = PathBuf::from(name);
+    let candidate = if direct.components().count() > 1 || direct.is_absolute() {
+        direct
+    } else {
+        conversations_dir()?.join(name)
+    };
+    let json = candidate.with_extension("json");
+    let encrypted = candidate.with_extension("json.age");
+    if json.exists() {
+        Ok(json)
+    } else if encrypted.exists() {
+        Ok(encrypted)
+    } else {
+        anyhow::bail!("No saved session at {:?} (sessions saved before /load existed have no .json file)", json)
+    }
+}
@@ -0,0 +1,126 @@
+use rustyline::ExternalPrinter;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+use tokio::task::JoinHandle;
+
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::session::ConversationTurn;
+use crate::usage::{self, Totals, UsageRecord};
+use crate::{notify, review_turn, turn_review_block, ui, webhook};
+
+// Background reviews (`[review] background`, `/background`): once the answer is
+// shown the prompt comes back, and the review runs in a task of its own. A
+// finished review is printed above the prompt right away and slotted into its
+// round before the next input is handled.
+
+type Printer = Arc<Mutex<Box<dyn ExternalPrinter + Send>>>;
+
+struct Reviewed {
+    turn: ConversationTurn,
+    reviewed: bool,
+    requests: Vec<UsageRecord>,
+}
+
+struct Pending {
+    session_start: String,
+    round: usize,
+    started: Instant,
+    task: JoinHandle<Reviewed>,
+}
+
+#[derive(Default)]
+pub struct Background {
+    pending: Vec<Pending>,
+    // Prints above the prompt while it is shown; None where the terminal can't,
+    // and reviews are printed when they are slotted in instead
+    printer: Option<Printer>,
+    printer_tried: bool,
+}
+
+fn printer(repl: &mut Repl) -> Option<Printer> {
+    if !repl.background.printer_tried {
+        repl.background.printer_tried = true;
+        repl.background.printer = repl
+            .rl
+            .create_external_printer()
+            .ok()
+            .map(|printer| Arc::new(Mutex::new(Box::new(printer) as Box<dyn ExternalPrinter + Send>)));
+    }
+    repl.background.printer.clone()
+}
+
+// Review the latest round (its meta taken at `started`) in the background
+pub fn start(repl: &mut Repl, started: Instant) {
+    let Some(mut turn) = repl.session.turns.last().cloned() else {
+        return;
+    };
+    let printer = printer(repl);
+    let (client, reviewer, summarizer) = (repl.client.clone(), repl.reviewer.clone(), repl.summarizer.clone());
+    let (profile, tools) = (repl.profile.clone(), repl.settings.tools.clone());
+    let round = turn.round;
+    let task = tokio::spawn(async move {
+        let (reviewed, requests) = usage::collect(review_turn(&client, &reviewer, summarizer.as_ref(), &profile, &tools, &mut turn, false)).await;
+        if let Some(printer) = printer {
+            let announcement = format!("{}\n{}", ui::success(tr!("background.done", round)), turn_review_block(&reviewer.name, &turn).unwrap_or_default());
+            let _ = printer.lock().unwrap_or_else(PoisonError::into_inner).print(announcement);
+        }
+        Reviewed { turn, reviewed, requests }
+    });
+    repl.background.pending.push(Pending {
+        session_start: repl.session.start_time.clone(),
+        round,
+        started,
+        task,
+    });
+    ui::notice(ui::status(tr!("background.started", round)));
+}
+
+// Slot in the reviews that have finished
+pub async fn collect_finished(repl: &mut Repl) {
+    let (finished, running) = std::mem::take(&mut repl.background.pending).into_iter().partition(|pending| pending.task.is_finished());
+    repl.background.pending = running;
+    for pending in finished {
+        slot(repl, pending).await;
+    }
+}
+
+// Wait for every review still running, e.g. before exiting
+pub async fn wait_all(repl: &mut Repl) {
+    if repl.background.pending.is_empty() {
+        return;
+    }
+    ui::notice(ui::status(tr!("background.waiting", repl.background.pending.len())));
+    for pending in std::mem::take(&mut repl.background.pending) {
+        slot(repl, pending).await;
+    }
+}
+
+async fn slot(repl: &mut Repl, pending: Pending) {
+    let Ok(done) = pending.task.await else {
+        println!("{}", ui::warning(tr!("background.lost", pending.round)));
+        return;
+    };
+    // The round may be gone (/undo, /load) or its answer edited since
+    let current = (repl.session.start_time == pending.session_start).then(|| repl.session.turn_mut(pending.round)).flatten();
+    let Some(turn) = current.filter(|turn| turn.moonshot_answer == done.turn.moonshot_answer) else {
+        println!("{}", ui::warning(tr!("background.dropped", pending.round)));
+        return;
+    };
+    turn.adopt_review(done.turn);
+    if let Some(meta) = turn.meta.as_mut() {
+        meta.extend(done.requests, pending.started, &repl.settings.usage);
+    }
+    let usage = turn.meta.as_ref().map(|meta| Totals::of(&meta.requests)).unwrap_or_default();
+    notify::round_finished(&repl.settings.notify, pending.round, pending.started.elapsed(), turn.deepseek_review.as_deref());
+    webhook::round_finished(&pending.session_start, turn, usage, pending.started.elapsed());
+    if repl.background.printer.is_none() {
+        println!("{}", ui::success(tr!("background.done", pending.round)));
+        println!("{}", turn_review_block(&repl.reviewer.name, turn).unwrap_or_default());
+    }
+    if !done.reviewed {
+        println!("{}", ui::warning(tr!("round.saved_unreviewed", pending.round)));
+    }
+    repl.session.mark_dirty();
+    repl.scrollback.invalidate(pending.round);
+}
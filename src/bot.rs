@@ -0,0 +1,467 @@
+use anyhow::{Context, Result};
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::api;
+use crate::config::{BotSettings, Profile, QuotaSettings, Settings};
+use crate::health;
+use crate::http::{self, Request, Response};
+use crate::i18n::tr;
+use crate::metrics;
+use crate::pipeline::Pipeline;
+use crate::session::{ConversationSession, ConversationTurn};
+use crate::{quota, share, ui, usage};
+
+// `aivsai bot --platform slack|feishu|dingtalk`: serve the platform's event
+// callback over HTTP, run each message through the answer + review pipeline and
+// reply in the chat with the answer and its review.
+//
+//   slack     Events API (app_mention, and direct messages)
+//             SLACK_BOT_TOKEN, SLACK_SIGNING_SECRET
+//   feishu    Event subscription, im.message.receive_v1, no Encrypt Key
+//             FEISHU_APP_ID, FEISHU_APP_SECRET, FEISHU_VERIFICATION_TOKEN
+//   dingtalk  Outgoing robot (HTTP mode); replies through the message's sessionWebhook
+//             DINGTALK_APP_SECRET
+//
+// Every request is checked against the platform's signature or token before it
+// is looked at. Platforms retry callbacks that aren't acknowledged in a few
+// seconds, so requests are acknowledged at once and answered in the background.
+
+#[derive(Clone, Copy, PartialEq)]
+enum Platform {
+    Slack,
+    Feishu,
+    DingTalk,
+}
+
+impl Platform {
+    fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "slack" => Ok(Self::Slack),
+            "feishu" | "lark" => Ok(Self::Feishu),
+            "dingtalk" => Ok(Self::DingTalk),
+            _ => anyhow::bail!("Unknown platform {:?} (expected slack, feishu or dingtalk)", name),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Slack => "Slack",
+            Self::Feishu => "Feishu",
+            Self::DingTalk => "DingTalk",
+        }
+    }
+
+    // Longest reply the platform takes in one message, in characters
+    fn max_reply(self) -> usize {
+        match self {
+            Self::Slack => 39_000,
+            Self::Feishu => 30_000,
+            Self::DingTalk => 5_000,
+        }
+    }
+}
+
+enum Credentials {
+    Slack { bot_token: String, signing_secret: String },
+    Feishu { app_id: String, app_secret: String, verification_token: String },
+    DingTalk { app_secret: String },
+}
+
+pub fn secret(name: &str) -> Result<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty()).context(format!("{} is not set", name))
+}
+
+impl Credentials {
+    fn from_env(platform: Platform) -> Result<Self> {
+        Ok(match platform {
+            Platform::Slack => Self::Slack {
+                bot_token: secret("SLACK_BOT_TOKEN")?,
+                signing_secret: secret("SLACK_SIGNING_SECRET")?,
+            },
+            Platform::Feishu => Self::Feishu {
+                app_id: secret("FEISHU_APP_ID")?,
+                app_secret: secret("FEISHU_APP_SECRET")?,
+                verification_token: secret("FEISHU_VERIFICATION_TOKEN")?,
+            },
+            Platform::DingTalk => Self::DingTalk {
+                app_secret: secret("DINGTALK_APP_SECRET")?,
+            },
+        })
+    }
+}
+
+// Where a reply goes
+enum ReplyTo {
+    Slack { channel: String, thread_ts: String },
+    Feishu { message_id: String },
+    DingTalk { session_webhook: String },
+}
+
+// Event ids remembered to drop redelivered callbacks
+const SEEN: usize = 1000;
+
+struct Bot {
+    platform: Platform,
+    credentials: Credentials,
+    feishu_base_url: String,
+    // Callback path, e.g. "/" or "/slack/events"
+    path: String,
+    // Serve `metrics::PATH` too
+    metrics: bool,
+    quota: QuotaSettings,
+    pipeline: Pipeline,
+    // Identifies this run in saved payloads and webhooks
+    session_start: String,
+    rounds: AtomicUsize,
+    client: Client,
+    seen: Mutex<VecDeque<String>>,
+    // Feishu tenant_access_token and when it stops being usable
+    feishu_token: tokio::sync::Mutex<Option<(String, Instant)>>,
+}
+
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Compare without stopping at the first difference
+pub fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+pub fn unix_seconds() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+// Drop @-mentions of the bot: Slack `<@U123>`, Feishu `@_user_1`
+fn strip_mentions(text: &str) -> String {
+    text.split_whitespace()
+        .filter(|word| !(word.starts_with("@_user_") || word.starts_with("<@") && word.ends_with('>')))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+// The review as posted in chat, under a heading naming the reviewer
+pub fn review_text(turn: &ConversationTurn) -> String {
+    let review = match (&turn.deepseek_review, &turn.review_error) {
+        (Some(review), _) => review.clone(),
+        (None, Some(error)) => tr!("export.review_failed", error),
+        (None, None) => tr!("export.not_reviewed").to_string(),
+    };
+    format!("🔍 {}\n{}", tr!("bot.review_heading", turn.review_model), review)
+}
+
+fn reply_text(turn: Option<&ConversationTurn>) -> String {
+    let Some(turn) = turn else {
+        return tr!("bot.failed").to_string();
+    };
+    format!("{}\n\n{}", turn.moonshot_answer, review_text(turn))
+}
+
+impl Bot {
+    // True the first time an event id is seen
+    fn first_delivery(&self, id: &str) -> bool {
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        if seen.iter().any(|known| known == id) {
+            return false;
+        }
+        if seen.len() >= SEEN {
+            seen.pop_front();
+        }
+        seen.push_back(id.to_string());
+        true
+    }
+
+    fn verified(&self, request: &Request, body: &Value) -> bool {
+        match &self.credentials {
+            // v0=hex(HMAC-SHA256(secret, "v0:{timestamp}:{body}")), at most 5 minutes old
+            Credentials::Slack { signing_secret, .. } => {
+                let (Some(timestamp), Some(signature)) = (request.header("x-slack-request-timestamp"), request.header("x-slack-signature")) else {
+                    return false;
+                };
+                if timestamp.parse::<u64>().map_or(true, |ts| unix_seconds().abs_diff(ts) > 300) {
+                    return false;
+                }
+                let mut signed = format!("v0:{}:", timestamp).into_bytes();
+                signed.extend_from_slice(&request.body);
+                let expected = format!("v0={}", hex(&hmac_sha256(signing_secret.as_bytes(), &signed)));
+                same(expected.as_bytes(), signature.as_bytes())
+            }
+            // The verification token, in the body (url_verification) or its header
+            Credentials::Feishu { verification_token, .. } => {
+                let token = body.get("token").or_else(|| body.pointer("/header/token")).and_then(Value::as_str).unwrap_or_default();
+                same(token.as_bytes(), verification_token.as_bytes())
+            }
+            // base64(HMAC-SHA256(secret, "{timestamp}\n{secret}")), timestamp in ms, within an hour
+            Credentials::DingTalk { app_secret } => {
+                let (Some(timestamp), Some(sign)) = (request.header("timestamp"), request.header("sign")) else {
+                    return false;
+                };
+                if timestamp.parse::<u64>().map_or(true, |ms| (unix_seconds() * 1000).abs_diff(ms) > 3_600_000) {
+                    return false;
+                }
+                let expected = base64::engine::general_purpose::STANDARD
+                    .encode(hmac_sha256(app_secret.as_bytes(), format!("{}\n{}", timestamp, app_secret).as_bytes()));
+                same(expected.as_bytes(), sign.as_bytes())
+            }
+        }
+    }
+
+    // The question, who asked it and where to answer it, if the event is a
+    // message for the bot
+    fn message(&self, body: &Value) -> Option<(String, Option<String>, ReplyTo)> {
+        let text = |value: Option<&Value>| value.and_then(Value::as_str).unwrap_or_default().to_string();
+        let (id, question, user, reply_to) = match self.platform {
+            Platform::Slack => {
+                let event = body.get("event")?;
+                let kind = event.get("type").and_then(Value::as_str)?;
+                // Mentions anywhere, plain messages only in direct messages; never the bot's own
+                let direct = kind == "message" && event.get("channel_type").and_then(Value::as_str) == Some("im");
+                if !(kind == "app_mention" || direct) || event.get("bot_id").is_some() || event.get("subtype").is_some() {
+                    return None;
+                }
+                let ts = text(event.get("ts"));
+                let thread_ts = event.get("thread_ts").and_then(Value::as_str).map_or(ts, str::to_string);
+                (
+                    text(body.get("event_id")),
+                    text(event.get("text")),
+                    text(event.get("user")),
+                    ReplyTo::Slack {
+                        channel: text(event.get("channel")),
+                        thread_ts,
+                    },
+                )
+            }
+            Platform::Feishu => {
+                if body.pointer("/header/event_type").and_then(Value::as_str) != Some("im.message.receive_v1")
+                    || body.pointer("/event/sender/sender_type").and_then(Value::as_str) == Some("app")
+                {
+                    return None;
+                }
+                let message = body.pointer("/event/message")?;
+                if message.get("message_type").and_then(Value::as_str) != Some("text") {
+                    return None;
+                }
+                // `content` is itself JSON: {"text": "@_user_1 question"}
+                let content: Value = serde_json::from_str(message.get("content").and_then(Value::as_str)?).ok()?;
+                (
+                    text(body.pointer("/header/event_id")),
+                    text(content.get("text")),
+                    text(body.pointer("/event/sender/sender_id/open_id")),
+                    ReplyTo::Feishu {
+                        message_id: text(message.get("message_id")),
+                    },
+                )
+            }
+            Platform::DingTalk => (
+                text(body.get("msgId")),
+                text(body.pointer("/text/content")),
+                text(body.get("senderStaffId").or_else(|| body.get("senderId"))),
+                ReplyTo::DingTalk {
+                    session_webhook: text(body.get("sessionWebhook")),
+                },
+            ),
+        };
+        let question = strip_mentions(&question);
+        if question.is_empty() || (!id.is_empty() && !self.first_delivery(&id)) {
+            return None;
+        }
+        Some((question, Some(user).filter(|user| !user.is_empty()), reply_to))
+    }
+
+    async fn handle(self: Arc<Self>, request: Request) -> Response {
+        if self.metrics && request.path == metrics::PATH {
+            return metrics::response();
+        }
+        if let Some(response) = health::respond(&request, &self.client, &self.pipeline.roles()).await {
+            return response;
+        }
+        if let Some(response) = share::respond(&request) {
+            return response;
+        }
+        if request.path != self.path {
+            return Response::text(404, "Not found");
+        }
+        if request.method != "POST" {
+            return Response::text(405, "POST only");
+        }
+        let Ok(body) = request.json() else {
+            return Response::text(400, "Expected JSON");
+        };
+        if body.get("encrypt").is_some() {
+            ui::notice(ui::warning(tr!("bot.encrypted")));
+            return Response::text(400, "Encrypted events are not supported");
+        }
+        if !self.verified(&request, &body) {
+            ui::notice(ui::warning(tr!("bot.rejected", self.platform.name())));
+            return Response::text(401, "Signature check failed");
+        }
+        // Endpoint checks when the callback URL is configured
+        if body.get("type").and_then(Value::as_str) == Some("url_verification") {
+            return Response::json(&json!({ "challenge": body.get("challenge") }));
+        }
+        // Slack redelivers after 3s without an answer; the first delivery is being handled
+        if request.header("x-slack-retry-num").is_some() {
+            return Response::json(&json!({}));
+        }
+        if let Some((question, user, reply_to)) = self.message(&body) {
+            let bot = Arc::clone(&self);
+            tokio::spawn(async move { bot.answer(question, user, reply_to).await });
+        }
+        Response::json(&json!({}))
+    }
+
+    async fn answer(&self, question: String, user: Option<String>, reply_to: ReplyTo) {
+        ui::notice(ui::heading(tr!("bot.question", self.platform.name(), question)));
+        let reply = match user {
+            Some(user) => match quota::exceeded(&self.quota, &user) {
+                Some((limit, used)) => {
+                    ui::notice(ui::warning(tr!("quota.refused", user, used, limit)));
+                    tr!("quota.exceeded", used, limit)
+                }
+                None => usage::as_user(user, self.reply(&question)).await,
+            },
+            None => self.reply(&question).await,
+        };
+        if let Err(e) = self.send(&reply_to, &reply).await {
+            ui::notice(ui::warning(tr!("bot.reply_failed", self.platform.name(), format!("{:#}", e))));
+        }
+    }
+
+    // Answer and review the question, as posted in the chat
+    async fn reply(&self, question: &str) -> String {
+        let round = self.rounds.fetch_add(1, Ordering::Relaxed) + 1;
+        let turn = self.pipeline.run_round(&self.session_start, round, question).await;
+        clip(&reply_text(turn.as_ref()), self.platform.max_reply())
+    }
+
+    async fn post(&self, url: &str, bearer: Option<&str>, payload: &Value) -> Result<Value> {
+        api::check_destination(self.platform.name(), url)?;
+        let mut request = self.client.post(url).json(payload);
+        if let Some(token) = bearer {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        let response = request.send().await.context(format!("Request to {} failed", url))?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("HTTP {} from {}: {}", status, url, body);
+        }
+        Ok(body)
+    }
+
+    async fn feishu_token(&self) -> Result<String> {
+        let Credentials::Feishu { app_id, app_secret, .. } = &self.credentials else {
+            anyhow::bail!("Not a Feishu bot");
+        };
+        let mut cached = self.feishu_token.lock().await;
+        if let Some((token, _)) = cached.as_ref().filter(|(_, expires)| Instant::now() < *expires) {
+            return Ok(token.clone());
+        }
+        let url = format!("{}/open-apis/auth/v3/tenant_access_token/internal", self.feishu_base_url);
+        let body = self.post(&url, None, &json!({ "app_id": app_id, "app_secret": app_secret })).await?;
+        let token = body.get("tenant_access_token").and_then(Value::as_str).context(format!("No tenant_access_token from Feishu: {}", body))?;
+        // Renew a few minutes early
+        let lifetime = body.get("expire").and_then(Value::as_u64).unwrap_or(7200).saturating_sub(300);
+        *cached = Some((token.to_string(), Instant::now() + Duration::from_secs(lifetime)));
+        Ok(token.to_string())
+    }
+
+    async fn send(&self, reply_to: &ReplyTo, reply: &str) -> Result<()> {
+        match (reply_to, &self.credentials) {
+            (ReplyTo::Slack { channel, thread_ts }, Credentials::Slack { bot_token, .. }) => {
+                let body = self
+                    .post("https://slack.com/api/chat.postMessage", Some(bot_token), &json!({ "channel": channel, "thread_ts": thread_ts, "text": reply }))
+                    .await?;
+                // Slack reports errors in a 200 response
+                if body.get("ok").and_then(Value::as_bool) != Some(true) {
+                    anyhow::bail!("chat.postMessage: {}", body.get("error").unwrap_or(&body));
+                }
+            }
+            (ReplyTo::Feishu { message_id }, Credentials::Feishu { .. }) => {
+                let token = self.feishu_token().await?;
+                let url = format!("{}/open-apis/im/v1/messages/{}/reply", self.feishu_base_url, message_id);
+                let content = json!({ "text": reply }).to_string();
+                let body = self.post(&url, Some(&token), &json!({ "msg_type": "text", "content": content })).await?;
+                if body.get("code").and_then(Value::as_i64) != Some(0) {
+                    anyhow::bail!("Feishu reply: {}", body);
+                }
+            }
+            (ReplyTo::DingTalk { session_webhook }, Credentials::DingTalk { .. }) => {
+                let payload = json!({ "msgtype": "markdown", "markdown": { "title": "AIvsAI", "text": reply } });
+                let body = self.post(session_webhook, None, &payload).await?;
+                if body.get("errcode").and_then(Value::as_i64).is_some_and(|code| code != 0) {
+                    anyhow::bail!("DingTalk reply: {}", body);
+                }
+            }
+            _ => anyhow::bail!("Reply target doesn't match the platform"),
+        }
+        Ok(())
+    }
+}
+
+struct BotArgs {
+    platform: Platform,
+    listen: String,
+}
+
+fn parse_args(args: &[String], settings: &BotSettings) -> Result<BotArgs> {
+    let mut platform = None;
+    let mut listen = settings.listen.clone();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--platform" => platform = Some(Platform::parse(iter.next().context("--platform needs slack, feishu or dingtalk")?)?),
+            "--listen" => listen = iter.next().context("--listen needs an address, e.g. 127.0.0.1:8787")?.clone(),
+            other => anyhow::bail!("Unknown option for bot: {} (expected --platform NAME, --listen ADDR)", other),
+        }
+    }
+    Ok(BotArgs {
+        platform: platform.context("Usage: aivsai bot --platform slack|feishu|dingtalk [--listen 127.0.0.1:8787]")?,
+        listen,
+    })
+}
+
+// `aivsai bot --platform slack|feishu|dingtalk [--listen ADDR]`
+pub async fn run(args: &[String], settings: &Settings, profile: &Profile) -> Result<()> {
+    let args = parse_args(args, &settings.bot)?;
+    let bot = Arc::new(Bot {
+        platform: args.platform,
+        credentials: Credentials::from_env(args.platform)?,
+        feishu_base_url: settings.bot.feishu_base_url.trim_end_matches('/').to_string(),
+        path: settings.bot.path.clone(),
+        metrics: settings.bot.metrics,
+        quota: settings.quota.clone(),
+        pipeline: Pipeline::new(settings, profile, false)?,
+        session_start: ConversationSession::new().start_time,
+        rounds: AtomicUsize::new(0),
+        client: api::client(),
+        seen: Mutex::new(VecDeque::new()),
+        feishu_token: tokio::sync::Mutex::new(None),
+    });
+    if let Err(e) = usage::load_user_tokens() {
+        ui::notice(ui::warning(format!("{:#}", e)));
+    }
+    ui::notice(ui::success(tr!("bot.listening", args.platform.name(), args.listen)));
+    http::serve(&args.listen, move |request| Arc::clone(&bot).handle(request)).await
+}
@@ -0,0 +1,621 @@
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::api;
+use crate::bot::{review_text, secret};
+use crate::config::{BridgeSettings, Profile, QuotaSettings, Settings};
+use crate::health;
+use crate::http::{self, Request, Response};
+use crate::i18n::tr;
+use crate::metrics;
+use crate::pipeline::Pipeline;
+use crate::session::ConversationSession;
+use crate::{quota, share, ui, usage};
+
+// `aivsai bridge --platform telegram|discord`: a long-running chat bot that keeps
+// a conversation per chat, like a REPL session per Telegram chat (or forum topic)
+// and per Discord channel (threads are channels). Earlier rounds are sent as
+// context, the answer streams in by editing its message, and the review follows.
+//
+//   telegram  Long polling (getUpdates), so no public URL is needed
+//             TELEGRAM_BOT_TOKEN
+//   discord   Slash commands through the Interactions Endpoint URL, served over HTTP
+//             DISCORD_BOT_TOKEN, DISCORD_APPLICATION_ID, DISCORD_PUBLIC_KEY
+//
+// In chat: a plain message (Telegram) or /ask (Discord) asks a question, /save
+// saves the chat's conversation like the REPL's /save, /new starts over and /help
+// says so. Conversations live in memory until saved.
+
+const TELEGRAM_API: &str = "https://api.telegram.org";
+const DISCORD_API: &str = "https://discord.com/api/v10";
+// Seconds a getUpdates call waits for new messages
+const POLL_TIMEOUT: u64 = 50;
+const POLL_RETRY: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy)]
+enum Platform {
+    Telegram,
+    Discord,
+}
+
+impl Platform {
+    fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "telegram" => Ok(Self::Telegram),
+            "discord" => Ok(Self::Discord),
+            _ => anyhow::bail!("Unknown platform {:?} (expected telegram or discord)", name),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Telegram => "Telegram",
+            Self::Discord => "Discord",
+        }
+    }
+
+    // Longest message the platform takes, in characters
+    fn max_message(self) -> usize {
+        match self {
+            Self::Telegram => 4096,
+            Self::Discord => 2000,
+        }
+    }
+}
+
+enum Credentials {
+    Telegram { token: String },
+    // Boxed: a verifying key is far larger than a token
+    Discord { bot_token: String, application_id: String, public_key: Box<VerifyingKey> },
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 == 1 {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
+
+impl Credentials {
+    fn from_env(platform: Platform) -> Result<Self> {
+        Ok(match platform {
+            Platform::Telegram => Self::Telegram {
+                token: secret("TELEGRAM_BOT_TOKEN")?,
+            },
+            Platform::Discord => {
+                let key = secret("DISCORD_PUBLIC_KEY")?;
+                let bytes = unhex(&key).and_then(|bytes| <[u8; 32]>::try_from(bytes).ok());
+                let public_key = bytes
+                    .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+                    .context("DISCORD_PUBLIC_KEY is not a hex Ed25519 public key")?;
+                Self::Discord {
+                    bot_token: secret("DISCORD_BOT_TOKEN")?,
+                    application_id: secret("DISCORD_APPLICATION_ID")?,
+                    public_key: Box::new(public_key),
+                }
+            }
+        })
+    }
+}
+
+// Where a command came from (and who sent it), and so where its replies go
+enum Chat {
+    Telegram { chat_id: i64, thread_id: Option<i64>, message_id: i64, user: Option<String> },
+    // The interaction token edits the deferred reply and posts follow-ups for 15 minutes
+    Discord { channel_id: String, token: String, user: Option<String> },
+}
+
+impl Chat {
+    fn id(&self) -> String {
+        match self {
+            Self::Telegram { chat_id, .. } => chat_id.to_string(),
+            Self::Discord { channel_id, .. } => channel_id.clone(),
+        }
+    }
+
+    // Platform id of the member who sent the command
+    fn user(&self) -> Option<&str> {
+        match self {
+            Self::Telegram { user, .. } | Self::Discord { user, .. } => user.as_deref(),
+        }
+    }
+
+    // The conversation this chat continues
+    fn key(&self) -> String {
+        match self {
+            Self::Telegram { chat_id, thread_id: Some(thread), .. } => format!("{}/{}", chat_id, thread),
+            _ => self.id(),
+        }
+    }
+}
+
+// A message already posted, which can be edited
+enum Posted {
+    Telegram { chat_id: i64, message_id: i64 },
+    Discord { token: String, message_id: String },
+}
+
+enum Command {
+    Ask(String),
+    Save,
+    New,
+    Help,
+}
+
+impl Command {
+    // "/save@aivsai_bot" → Save; text that isn't a command is a question
+    fn parse(text: &str, username: &str) -> Option<Self> {
+        let text = text.trim();
+        let Some(command) = text.strip_prefix('/') else {
+            let mention = format!("@{}", username);
+            let question = text.split_whitespace().filter(|word| !word.eq_ignore_ascii_case(&mention)).collect::<Vec<_>>().join(" ");
+            return (!question.is_empty()).then_some(Self::Ask(question));
+        };
+        let (name, rest) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        let name = name.split('@').next().unwrap_or_default();
+        Some(match name.to_lowercase().as_str() {
+            "ask" if !rest.trim().is_empty() => Self::Ask(rest.trim().to_string()),
+            "save" => Self::Save,
+            "new" | "reset" => Self::New,
+            _ => Self::Help,
+        })
+    }
+}
+
+// Split a message into pieces the platform accepts; platforms refuse empty messages
+fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec!["…".to_string()];
+    }
+    chars.chunks(max_chars).map(|chunk| chunk.iter().collect()).collect()
+}
+
+// What a streaming answer's message shows: the start of the answer and a cursor
+fn preview(text: &str, max_chars: usize) -> String {
+    let text = match text.char_indices().nth(max_chars.saturating_sub(3)) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    };
+    format!("{} ▌", text.trim_end())
+}
+
+struct Bridge {
+    platform: Platform,
+    credentials: Credentials,
+    settings: BridgeSettings,
+    pipeline: Pipeline,
+    system_prompt: String,
+    client: Client,
+    // Telegram username, dropped from "@bot question" mentions
+    username: String,
+    quota: QuotaSettings,
+    // Conversation per chat member (per chat without `per_user`); its lock is held
+    // for a whole round, so its questions are answered in order
+    chats: Mutex<HashMap<String, Arc<tokio::sync::Mutex<ConversationSession>>>>,
+}
+
+impl Bridge {
+    fn session(&self, chat: &Chat) -> Arc<tokio::sync::Mutex<ConversationSession>> {
+        let key = match chat.user().filter(|_| self.settings.per_user) {
+            Some(user) => format!("{}:{}", chat.key(), user),
+            None => chat.key(),
+        };
+        let mut chats = self.chats.lock().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(chats.entry(key).or_insert_with(|| Arc::new(tokio::sync::Mutex::new(ConversationSession::new()))))
+    }
+
+    async fn telegram(&self, method: &str, payload: &Value) -> Result<Value> {
+        let Credentials::Telegram { token } = &self.credentials else {
+            anyhow::bail!("Not a Telegram bridge");
+        };
+        api::check_destination("Telegram", TELEGRAM_API)?;
+        // The URL holds the bot token, so keep it out of errors
+        let response = self
+            .client
+            .post(format!("{}/bot{}/{}", TELEGRAM_API, token, method))
+            .json(payload)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Telegram {} failed: {}", method, e.without_url()))?;
+        let body: Value = response.json().await.unwrap_or_default();
+        if body.get("ok").and_then(Value::as_bool) != Some(true) {
+            anyhow::bail!("Telegram {}: {}", method, body.get("description").unwrap_or(&body));
+        }
+        Ok(body.get("result").cloned().unwrap_or_default())
+    }
+
+    async fn discord(&self, method: Method, path: &str, payload: &Value) -> Result<Value> {
+        let Credentials::Discord { bot_token, .. } = &self.credentials else {
+            anyhow::bail!("Not a Discord bridge");
+        };
+        api::check_destination("Discord", DISCORD_API)?;
+        // Webhook paths hold the interaction token, so keep them out of errors
+        let response = self
+            .client
+            .request(method, format!("{}{}", DISCORD_API, path))
+            .header("Authorization", format!("Bot {}", bot_token))
+            .json(payload)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Discord request failed: {}", e.without_url()))?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("HTTP {} from Discord: {}", status, body);
+        }
+        Ok(body)
+    }
+
+    fn discord_webhook(&self, token: &str) -> String {
+        match &self.credentials {
+            Credentials::Discord { application_id, .. } => format!("/webhooks/{}/{}", application_id, token),
+            Credentials::Telegram { .. } => String::new(),
+        }
+    }
+
+    // The first reply to a command: a Telegram reply, or the deferred Discord response
+    async fn reply(&self, chat: &Chat, text: &str) -> Result<Posted> {
+        match chat {
+            Chat::Telegram { chat_id, thread_id, message_id, .. } => {
+                let payload = json!({
+                    "chat_id": chat_id,
+                    "message_thread_id": thread_id,
+                    "text": text,
+                    "reply_parameters": { "message_id": message_id, "allow_sending_without_reply": true },
+                });
+                let sent = self.telegram("sendMessage", &payload).await?;
+                Ok(Posted::Telegram {
+                    chat_id: *chat_id,
+                    message_id: sent.get("message_id").and_then(Value::as_i64).context("Telegram sent no message_id")?,
+                })
+            }
+            Chat::Discord { token, .. } => {
+                let posted = Posted::Discord {
+                    token: token.clone(),
+                    message_id: "@original".to_string(),
+                };
+                self.edit(&posted, text).await?;
+                Ok(posted)
+            }
+        }
+    }
+
+    // Another message in the same chat
+    async fn follow_up(&self, chat: &Chat, text: &str) -> Result<()> {
+        match chat {
+            Chat::Telegram { chat_id, thread_id, .. } => {
+                self.telegram("sendMessage", &json!({ "chat_id": chat_id, "message_thread_id": thread_id, "text": text })).await?;
+            }
+            Chat::Discord { token, .. } => {
+                let payload = json!({ "content": text, "allowed_mentions": { "parse": [] } });
+                self.discord(Method::POST, &self.discord_webhook(token), &payload).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn edit(&self, posted: &Posted, text: &str) -> Result<()> {
+        match posted {
+            Posted::Telegram { chat_id, message_id } => {
+                self.telegram("editMessageText", &json!({ "chat_id": chat_id, "message_id": message_id, "text": text })).await?;
+            }
+            Posted::Discord { token, message_id } => {
+                // No pings from whatever the model wrote
+                let payload = json!({ "content": text, "allowed_mentions": { "parse": [] } });
+                let path = format!("{}/messages/{}", self.discord_webhook(token), message_id);
+                self.discord(Method::PATCH, &path, &payload).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn run_command(&self, chat: Chat, command: Command) {
+        let result = if !self.settings.allow.is_empty() && !self.settings.allow.contains(&chat.id()) {
+            let refusal = tr!("bridge.not_allowed", chat.id());
+            self.reply(&chat, &refusal).await.map(drop)
+        } else {
+            match command {
+                Command::Ask(question) => self.ask(&chat, question).await,
+                Command::Save => self.save(&chat).await,
+                Command::New => self.reset(&chat).await,
+                Command::Help => self.reply(&chat, tr!("bridge.help")).await.map(drop),
+            }
+        };
+        if let Err(e) = result {
+            ui::notice(ui::warning(tr!("bot.reply_failed", self.platform.name(), format!("{:#}", e))));
+        }
+    }
+
+    async fn ask(&self, chat: &Chat, question: String) -> Result<()> {
+        ui::notice(ui::heading(tr!("bot.question", self.platform.name(), question)));
+        if let Some(user) = chat.user() {
+            if let Some((limit, used)) = quota::exceeded(&self.quota, user) {
+                ui::notice(ui::warning(tr!("quota.refused", user, used, limit)));
+                // Bound first: `tr!`'s arguments aren't `Send` across the await
+                let text = tr!("quota.exceeded", used, limit);
+                return self.reply(chat, &text).await.map(drop);
+            }
+        }
+        let session = self.session(chat);
+        let mut session = session.lock().await;
+        let posted = self.reply(chat, tr!("bridge.thinking")).await?;
+        let max = self.platform.max_message();
+
+        let streamed = Mutex::new(String::new());
+        let done = AtomicBool::new(false);
+        let context = session.context_messages(&self.system_prompt);
+        let session_start = session.start_time.clone();
+        let round = session.last_round() + 1;
+        let answering = async {
+            let mut on_delta = |delta: &str| streamed.lock().unwrap_or_else(PoisonError::into_inner).push_str(delta);
+            let round = self.pipeline.run_round_with(&session_start, round, context, &question, Some(&mut on_delta));
+            let turn = match chat.user() {
+                Some(user) => usage::as_user(user.to_string(), round).await,
+                None => round.await,
+            };
+            done.store(true, Ordering::Relaxed);
+            turn
+        };
+        // Edit the message with what has arrived so far, at most once per interval
+        let editing = async {
+            let mut shown = String::new();
+            while !done.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(self.settings.edit_interval_ms)).await;
+                let text = streamed.lock().unwrap_or_else(PoisonError::into_inner).clone();
+                if text.trim().is_empty() || text == shown || done.load(Ordering::Relaxed) {
+                    continue;
+                }
+                if let Err(e) = self.edit(&posted, &preview(&text, max)).await {
+                    ui::detail(ui::warning(tr!("bot.reply_failed", self.platform.name(), format!("{:#}", e))));
+                }
+                shown = text;
+            }
+        };
+        let (turn, ()) = tokio::join!(answering, editing);
+
+        let Some(turn) = turn else {
+            return self.edit(&posted, tr!("bot.failed")).await;
+        };
+        let answer = split_message(&turn.moonshot_answer, max);
+        let review = split_message(&review_text(&turn), max);
+        session.add_turn(turn);
+        drop(session);
+
+        let mut answer = answer.into_iter();
+        self.edit(&posted, &answer.next().unwrap_or_default()).await?;
+        for part in answer.chain(review) {
+            self.follow_up(chat, &part).await?;
+        }
+        Ok(())
+    }
+
+    async fn save(&self, chat: &Chat) -> Result<()> {
+        let session = self.session(chat);
+        let mut session = session.lock().await;
+        let reply = if session.is_empty() {
+            tr!("bridge.nothing_to_save").to_string()
+        } else {
+            match self.pipeline.save(&session) {
+                Ok(path) => {
+                    session.mark_saved();
+                    ui::notice(ui::success(tr!("save.saved", path.display())));
+                    // Only the file name: the chat has no business knowing the server's paths
+                    let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                    tr!("bridge.saved", session.len(), name)
+                }
+                Err(e) => tr!("bridge.save_failed", format!("{:#}", e)),
+            }
+        };
+        drop(session);
+        self.reply(chat, &reply).await.map(drop)
+    }
+
+    async fn reset(&self, chat: &Chat) -> Result<()> {
+        let session = self.session(chat);
+        let mut session = session.lock().await;
+        let reply = if session.has_unsaved_changes() {
+            tr!("bridge.new_unsaved", session.len())
+        } else {
+            tr!("bridge.new").to_string()
+        };
+        *session = ConversationSession::new();
+        drop(session);
+        self.reply(chat, &reply).await.map(drop)
+    }
+
+    // A command from a Telegram message, if it's one for the bridge
+    fn telegram_command(&self, message: &Value) -> Option<(Chat, Command)> {
+        if message.pointer("/from/is_bot").and_then(Value::as_bool) == Some(true) {
+            return None;
+        }
+        let command = Command::parse(message.get("text").and_then(Value::as_str)?, &self.username)?;
+        // Only forum topics are threads of their own; replies carry the id too
+        let topic = message.get("is_topic_message").and_then(Value::as_bool) == Some(true);
+        let chat = Chat::Telegram {
+            chat_id: message.pointer("/chat/id").and_then(Value::as_i64)?,
+            thread_id: message.get("message_thread_id").and_then(Value::as_i64).filter(|_| topic),
+            message_id: message.get("message_id").and_then(Value::as_i64)?,
+            user: message.pointer("/from/id").and_then(Value::as_i64).map(|id| id.to_string()),
+        };
+        Some((chat, command))
+    }
+
+    async fn poll(self: Arc<Self>) -> Result<()> {
+        let mut offset = 0;
+        loop {
+            let payload = json!({ "offset": offset, "timeout": POLL_TIMEOUT, "allowed_updates": ["message"] });
+            let updates = match self.telegram("getUpdates", &payload).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    ui::notice(ui::warning(tr!("bridge.poll_failed", format!("{:#}", e))));
+                    tokio::time::sleep(POLL_RETRY).await;
+                    continue;
+                }
+            };
+            for update in updates.as_array().into_iter().flatten() {
+                if let Some(id) = update.get("update_id").and_then(Value::as_i64) {
+                    offset = offset.max(id + 1);
+                }
+                if let Some((chat, command)) = update.get("message").and_then(|message| self.telegram_command(message)) {
+                    let bridge = Arc::clone(&self);
+                    tokio::spawn(async move { bridge.run_command(chat, command).await });
+                }
+            }
+        }
+    }
+
+    // Ed25519 over timestamp + body, which Discord checks the endpoint enforces
+    fn verified(&self, request: &Request) -> bool {
+        let Credentials::Discord { public_key, .. } = &self.credentials else {
+            return false;
+        };
+        let (Some(signature), Some(timestamp)) = (request.header("x-signature-ed25519"), request.header("x-signature-timestamp")) else {
+            return false;
+        };
+        let Some(signature) = unhex(signature).and_then(|bytes| <[u8; 64]>::try_from(bytes).ok()) else {
+            return false;
+        };
+        let mut signed = timestamp.as_bytes().to_vec();
+        signed.extend_from_slice(&request.body);
+        public_key.verify(&signed, &Signature::from_bytes(&signature)).is_ok()
+    }
+
+    // A command from a Discord slash command interaction
+    fn discord_command(body: &Value) -> Option<(Chat, Command)> {
+        let text = |pointer: &str| body.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+        let command = match text("/data/name")?.as_str() {
+            "ask" => Command::Ask(text("/data/options/0/value").filter(|question| !question.trim().is_empty())?),
+            "save" => Command::Save,
+            "new" => Command::New,
+            _ => Command::Help,
+        };
+        let chat = Chat::Discord {
+            channel_id: text("/channel_id")?,
+            token: text("/token")?,
+            // `member` in servers, `user` in direct messages
+            user: text("/member/user/id").or_else(|| text("/user/id")),
+        };
+        Some((chat, command))
+    }
+
+    async fn handle(self: Arc<Self>, request: Request) -> Response {
+        if self.settings.metrics && request.path == metrics::PATH {
+            return metrics::response();
+        }
+        if let Some(response) = health::respond(&request, &self.client, &self.pipeline.roles()).await {
+            return response;
+        }
+        if let Some(response) = share::respond(&request) {
+            return response;
+        }
+        if request.path != self.settings.path {
+            return Response::text(404, "Not found");
+        }
+        if request.method != "POST" {
+            return Response::text(405, "POST only");
+        }
+        if !self.verified(&request) {
+            ui::notice(ui::warning(tr!("bot.rejected", self.platform.name())));
+            return Response::text(401, "Invalid request signature");
+        }
+        let Ok(body) = request.json() else {
+            return Response::text(400, "Expected JSON");
+        };
+        match body.get("type").and_then(Value::as_u64) {
+            // PING, sent when the endpoint URL is saved
+            Some(1) => Response::json(&json!({ "type": 1 })),
+            // Slash command: answer "thinking…" now (it must come within 3s) and fill it in later
+            Some(2) => match Self::discord_command(&body) {
+                Some((chat, command)) => {
+                    let bridge = Arc::clone(&self);
+                    tokio::spawn(async move { bridge.run_command(chat, command).await });
+                    Response::json(&json!({ "type": 5 }))
+                }
+                None => Response::json(&json!({ "type": 4, "data": { "content": tr!("bridge.help") } })),
+            },
+            _ => Response::text(400, "Unsupported interaction"),
+        }
+    }
+
+    // Create (or update) the slash commands, for every server the app is in
+    async fn register_commands(&self) -> Result<()> {
+        let Credentials::Discord { application_id, .. } = &self.credentials else {
+            anyhow::bail!("Not a Discord bridge");
+        };
+        let commands = json!([
+            {
+                "name": "ask",
+                "description": "Ask a question; a second model reviews the answer",
+                "options": [{ "type": 3, "name": "question", "description": "Your question", "required": true }],
+            },
+            { "name": "save", "description": "Save this channel's conversation" },
+            { "name": "new", "description": "Start a new conversation in this channel" },
+            { "name": "help", "description": "What this bot can do" },
+        ]);
+        let path = format!("/applications/{}/commands", application_id);
+        self.discord(Method::PUT, &path, &commands).await.context("Failed to register Discord slash commands")?;
+        Ok(())
+    }
+}
+
+struct BridgeArgs {
+    platform: Platform,
+    listen: String,
+}
+
+fn parse_args(args: &[String], settings: &BridgeSettings) -> Result<BridgeArgs> {
+    let mut platform = None;
+    let mut listen = settings.listen.clone();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--platform" => platform = Some(Platform::parse(iter.next().context("--platform needs telegram or discord")?)?),
+            "--listen" => listen = iter.next().context("--listen needs an address, e.g. 127.0.0.1:8788")?.clone(),
+            other => anyhow::bail!("Unknown option for bridge: {} (expected --platform NAME, --listen ADDR)", other),
+        }
+    }
+    Ok(BridgeArgs {
+        platform: platform.context("Usage: aivsai bridge --platform telegram|discord [--listen 127.0.0.1:8788]")?,
+        listen,
+    })
+}
+
+// `aivsai bridge --platform telegram|discord [--listen ADDR]`
+pub async fn run(args: &[String], settings: &Settings, profile: &Profile) -> Result<()> {
+    let args = parse_args(args, &settings.bridge)?;
+    let mut bridge = Bridge {
+        platform: args.platform,
+        credentials: Credentials::from_env(args.platform)?,
+        settings: settings.bridge.clone(),
+        pipeline: Pipeline::new(settings, profile, false)?,
+        system_prompt: profile.answer_system_prompt(&settings.answer),
+        client: api::client(),
+        username: String::new(),
+        quota: settings.quota.clone(),
+        chats: Mutex::new(HashMap::new()),
+    };
+    if let Err(e) = usage::load_user_tokens() {
+        ui::notice(ui::warning(format!("{:#}", e)));
+    }
+    match args.platform {
+        Platform::Telegram => {
+            let me = bridge.telegram("getMe", &json!({})).await.context("Failed to reach Telegram (is TELEGRAM_BOT_TOKEN right?)")?;
+            bridge.username = me.get("username").and_then(Value::as_str).unwrap_or_default().to_string();
+            ui::notice(ui::success(tr!("bridge.telegram_ready", bridge.username)));
+            Arc::new(bridge).poll().await
+        }
+        Platform::Discord => {
+            bridge.register_commands().await?;
+            ui::notice(ui::success(tr!("bridge.discord_ready", args.listen)));
+            let bridge = Arc::new(bridge);
+            http::serve(&args.listen, move |request| Arc::clone(&bridge).handle(request)).await
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::{Context, Result};
+use base64::Engine as _;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+
+use crate::recovery;
+
+// Fenced code blocks in answers, numbered from 1 in order of appearance, so
+// commands can refer to "block 2 of round 3", and what can be done with one:
+// copy it, run it, or `cargo check` it. Running is not sandboxed; the commands
+// ask first.
+
+pub struct CodeBlock {
+    // As written after the opening fence, e.g. "rust"; may be empty
+    pub language: String,
+    pub code: String,
+}
+
+// "```" for "```rust", if the line opens a code fence
+pub fn opening_fence(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let fence: String = trimmed.chars().take_while(|c| *c == marker).collect();
+    (fence.len() >= 3).then_some(fence)
+}
+
+pub fn closes(line: &str, fence: &str) -> bool {
+    let trimmed = line.trim();
+    let marker = fence.chars().next().unwrap_or('`');
+    trimmed.len() >= fence.len() && trimmed.chars().all(|c| c == marker)
+}
+
+// Every fenced block in `markdown`
+pub fn extract(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    // Fence, language and lines of the open block
+    let mut open: Option<(String, String, Vec<&str>)> = None;
+    for line in markdown.lines() {
+        match open.take() {
+            Some((fence, language, lines)) if closes(line, &fence) => blocks.push(CodeBlock {
+                language,
+                code: lines.join("\n"),
+            }),
+            Some((fence, language, mut lines)) => {
+                lines.push(line);
+                open = Some((fence, language, lines));
+            }
+            None => {
+                if let Some(fence) = opening_fence(line) {
+                    let language = line.trim_start()[fence.len()..].trim().to_string();
+                    open = Some((fence, language, Vec::new()));
+                }
+            }
+        }
+    }
+    // A truncated answer may end inside a block; it still counts
+    if let Some((_, language, lines)) = open {
+        blocks.push(CodeBlock {
+            language,
+            code: lines.join("\n"),
+        });
+    }
+    blocks
+}
+
+// Copy to the clipboard with the first clipboard tool found, or else through
+// the terminal (OSC 52, which also works over SSH in most terminals); returns
+// what was used
+pub fn copy(text: &str) -> Result<&'static str> {
+    let tools: [(&'static str, &[&str]); 5] = [
+        ("pbcopy", &[]),
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+        ("clip.exe", &[]),
+    ];
+    for (program, args) in tools {
+        let Ok(mut child) = Command::new(program).args(args).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn() else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).context(format!("Failed to write to {}", program))?;
+        }
+        if child.wait().context(format!("{} failed", program))?.success() {
+            return Ok(program);
+        }
+    }
+    print!("\x1b]52;c;{}\x07", base64::engine::general_purpose::STANDARD.encode(text));
+    std::io::stdout().flush()?;
+    Ok("OSC 52")
+}
+
+// Interpreter and file extension for a block's language
+fn runner(language: &str) -> Option<(&'static [&'static str], &'static str)> {
+    let language = language.split_whitespace().next().unwrap_or_default().to_lowercase();
+    Some(match language.as_str() {
+        "sh" | "shell" | "console" => (&["sh"], "sh"),
+        "bash" => (&["bash"], "sh"),
+        "zsh" => (&["zsh"], "sh"),
+        "python" | "py" | "python3" => (&["python3"], "py"),
+        "javascript" | "js" | "node" => (&["node"], "js"),
+        "ruby" | "rb" => (&["ruby"], "rb"),
+        "go" => (&["go", "run"], "go"),
+        "rust" | "rs" => (&["rustc"], "rs"),
+        _ => return None,
+    })
+}
+
+pub fn can_run(block: &CodeBlock) -> bool {
+    runner(&block.language).is_some()
+}
+
+// A scratch directory under the system temp dir, emptied first
+fn scratch_dir(name: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("aivsai-{}-{}", name, std::process::id()));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).context(format!("Failed to clear {:?}", dir))?;
+    }
+    std::fs::create_dir_all(&dir).context(format!("Failed to create {:?}", dir))?;
+    Ok(dir)
+}
+
+// Run a child with the terminal attached; Ctrl+C goes to it, not to us
+fn run_attached(command: &mut Command) -> Result<ExitStatus> {
+    let _claim = recovery::claim_interrupts();
+    let program = command.get_program().to_string_lossy().into_owned();
+    command.status().context(format!("Failed to start {} (is it installed?)", program))
+}
+
+// Run the block with its language's interpreter in the current directory. Rust
+// is compiled with rustc first.
+pub fn run(block: &CodeBlock) -> Result<ExitStatus> {
+    let (interpreter, extension) = runner(&block.language).context(format!("Don't know how to run {:?} code", block.language))?;
+    let dir = scratch_dir("run")?;
+    let file = dir.join(format!("snippet.{}", extension));
+    std::fs::write(&file, format!("{}\n", block.code)).context(format!("Failed to write {:?}", file))?;
+    if extension == "rs" {
+        let binary = dir.join("snippet");
+        let status = run_attached(Command::new("rustc").arg("--edition=2021").arg("-o").arg(&binary).arg(&file))?;
+        if !status.success() {
+            return Ok(status);
+        }
+        return run_attached(&mut Command::new(binary));
+    }
+    run_attached(Command::new(interpreter[0]).args(&interpreter[1..]).arg(&file))
+}
+
+// `cargo check` a Rust block in a scratch crate: a binary if it has `fn main`,
+// otherwise a library. Dependencies it uses aren't declared, so those imports fail.
+pub fn check(block: &CodeBlock) -> Result<ExitStatus> {
+    let dir = scratch_dir("check")?;
+    std::fs::create_dir_all(dir.join("src"))?;
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"snippet\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n")
+        .context("Failed to write the scratch Cargo.toml")?;
+    let source = if block.code.contains("fn main") { "main.rs" } else { "lib.rs" };
+    std::fs::write(dir.join("src").join(source), format!("{}\n", block.code)).context("Failed to write the scratch source")?;
+    run_attached(Command::new("cargo").arg("check").arg("--quiet").current_dir(&dir))
+}
@@ -0,0 +1,49 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct BackgroundReview;
+
+impl Command for BackgroundReview {
+    fn name(&self) -> &'static str {
+        "/background"
+    }
+
+    fn args(&self) -> &'static str {
+        "[on|off]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.background.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.background.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        vec!["on".to_string(), "off".to_string()]
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let review = &mut repl.settings.review;
+            review.background = match args {
+                "" => !review.background,
+                "on" => true,
+                "off" => false,
+                _ => {
+                    println!("{}", ui::warning(tr!("background.usage")));
+                    return Ok(());
+                }
+            };
+            let state = if review.background { tr!("background.on") } else { tr!("background.off") };
+            println!("{}", ui::success(state));
+            if review.background && repl.panel.is_some() {
+                println!("{}", ui::warning(tr!("background.panel")));
+            }
+            Ok(())
+        })
+    }
+}
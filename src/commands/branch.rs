@@ -0,0 +1,55 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{recovery, save_and_report, ui};
+
+pub struct Branch;
+
+impl Command for Branch {
+    fn name(&self) -> &'static str {
+        "/branch"
+    }
+
+    fn args(&self) -> &'static str {
+        "[N]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.branch.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.branch.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if repl.session.is_empty() {
+                println!("{}", ui::warning(tr!("branch.empty")));
+                return Ok(());
+            }
+            let round = if args.is_empty() {
+                repl.session.last_round()
+            } else {
+                match args.parse::<usize>() {
+                    Ok(n) if repl.session.turn(n).is_some() => n,
+                    _ => {
+                        println!("{}", ui::warning(tr!("branch.usage")));
+                        return Ok(());
+                    }
+                }
+            };
+            // Keep the transcript being branched from; it goes on without the new rounds
+            if repl.session.has_unsaved_changes() && !save_and_report(&mut repl.session, &repl.answerer, &repl.reviewer) {
+                return Ok(());
+            }
+            let parent = repl.session.start_time.clone();
+            repl.session = repl.session.branch(round);
+            repl.round_counter = round;
+            repl.scrollback.clear();
+            recovery::update(None);
+            println!("{}", ui::success(tr!("branch.created", parent, round, repl.session.len())));
+            Ok(())
+        })
+    }
+}
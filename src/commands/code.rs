@@ -0,0 +1,192 @@
+use anyhow::Context;
+use std::path::Path;
+
+use super::{Command, CommandFuture};
+use crate::codeblocks::{self, CodeBlock};
+use crate::completion::ReplEditor;
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+// Code block BLOCK of the latest answer, or of round ROUND: `BLOCK` or
+// `ROUND BLOCK`, the order /quote uses. Problems are reported here.
+fn find_block(repl: &Repl, numbers: &[&str], usage: &str) -> Option<CodeBlock> {
+    let numbers: Result<Vec<usize>, _> = numbers.iter().map(|n| n.parse::<usize>()).collect();
+    let (round, number) = match numbers.as_deref() {
+        Ok([number]) if *number > 0 => (repl.session.last_round(), *number),
+        Ok([round, number]) if *number > 0 => (*round, *number),
+        _ => {
+            println!("{}", ui::warning(usage));
+            return None;
+        }
+    };
+    let Some(turn) = repl.session.turn(round) else {
+        println!("{}", ui::warning(tr!("code.no_round", round)));
+        return None;
+    };
+    let mut blocks = codeblocks::extract(&turn.moonshot_answer);
+    if number > blocks.len() {
+        println!("{}", ui::warning(tr!("code.no_block", round, blocks.len())));
+        return None;
+    }
+    Some(blocks.swap_remove(number - 1))
+}
+
+fn confirm(rl: &mut ReplEditor, prompt: &str) -> bool {
+    match rl.readline(prompt) {
+        Ok(line) => matches!(line.trim().to_lowercase().as_str(), "y" | "yes"),
+        Err(_) => false,
+    }
+}
+
+fn report_status(status: std::process::ExitStatus) {
+    if status.success() {
+        println!("{}", ui::success(tr!("code.finished")));
+    } else {
+        println!("{}", ui::warning(tr!("code.failed", status)));
+    }
+}
+
+pub struct Copy;
+
+impl Command for Copy {
+    fn name(&self) -> &'static str {
+        "/copy"
+    }
+
+    fn args(&self) -> &'static str {
+        "[ROUND] BLOCK"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.copy.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.copy.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let numbers: Vec<&str> = args.split_whitespace().collect();
+            let Some(block) = find_block(repl, &numbers, tr!("code.copy_usage")) else {
+                return Ok(());
+            };
+            let via = codeblocks::copy(&block.code).context("Failed to copy the code block")?;
+            println!("{}", ui::success(tr!("code.copied", block.code.lines().count(), via)));
+            Ok(())
+        })
+    }
+}
+
+pub struct Write;
+
+impl Command for Write {
+    fn name(&self) -> &'static str {
+        "/write"
+    }
+
+    fn args(&self) -> &'static str {
+        "[ROUND] BLOCK FILE"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.write.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.write.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let mut words: Vec<&str> = args.split_whitespace().collect();
+            let Some(file) = words.pop().filter(|_| !words.is_empty()) else {
+                println!("{}", ui::warning(tr!("code.write_usage")));
+                return Ok(());
+            };
+            let Some(block) = find_block(repl, &words, tr!("code.write_usage")) else {
+                return Ok(());
+            };
+            let path = Path::new(file);
+            if path.exists() && !confirm(&mut repl.rl, &tr!("code.confirm_overwrite", path.display())) {
+                return Ok(());
+            }
+            std::fs::write(path, format!("{}\n", block.code)).context(format!("Failed to write {:?}", path))?;
+            println!("{}", ui::success(tr!("code.written", block.code.lines().count(), path.display())));
+            Ok(())
+        })
+    }
+}
+
+pub struct Run;
+
+impl Command for Run {
+    fn name(&self) -> &'static str {
+        "/run"
+    }
+
+    fn args(&self) -> &'static str {
+        "[ROUND] BLOCK"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.run.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.run.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let numbers: Vec<&str> = args.split_whitespace().collect();
+            let Some(block) = find_block(repl, &numbers, tr!("code.run_usage")) else {
+                return Ok(());
+            };
+            if !codeblocks::can_run(&block) {
+                println!("{}", ui::warning(tr!("code.cannot_run", block.language)));
+                return Ok(());
+            }
+            // It's code from a model: show it and ask, as for the shell tool
+            println!("{}", ui::code(&block.code));
+            if !confirm(&mut repl.rl, &tr!("code.confirm_run", block.language)) {
+                return Ok(());
+            }
+            report_status(codeblocks::run(&block)?);
+            Ok(())
+        })
+    }
+}
+
+pub struct Check;
+
+impl Command for Check {
+    fn name(&self) -> &'static str {
+        "/check"
+    }
+
+    fn args(&self) -> &'static str {
+        "[ROUND] BLOCK"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.check.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.check.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let numbers: Vec<&str> = args.split_whitespace().collect();
+            let Some(block) = find_block(repl, &numbers, tr!("code.check_usage")) else {
+                return Ok(());
+            };
+            println!("{}", ui::status(tr!("code.checking")));
+            report_status(codeblocks::check(&block)?);
+            Ok(())
+        })
+    }
+}
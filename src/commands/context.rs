@@ -0,0 +1,120 @@
+use super::{Command, CommandFuture};
+use crate::api::estimate_tokens;
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct Clear;
+
+impl Command for Clear {
+    fn name(&self) -> &'static str {
+        "/clear"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.clear.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.clear.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, _args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let dropped = repl.session.clear_context();
+            println!("{}", ui::success(tr!("context.cleared", dropped)));
+            Ok(())
+        })
+    }
+}
+
+pub struct Forget;
+
+impl Command for Forget {
+    fn name(&self) -> &'static str {
+        "/forget"
+    }
+
+    fn args(&self) -> &'static str {
+        "N"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.forget.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.forget.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            match forget_count(args) {
+                Some(n) => {
+                    let dropped = repl.session.forget_last(n);
+                    println!("{}", ui::success(tr!("context.forgot", dropped)));
+                }
+                None => println!("{}", ui::warning(tr!("context.forget_usage"))),
+            }
+            Ok(())
+        })
+    }
+}
+
+// How many turns /forget drops: a positive number
+fn forget_count(args: &str) -> Option<usize> {
+    args.parse().ok().filter(|&n| n > 0)
+}
+
+pub struct ShowContext;
+
+impl Command for ShowContext {
+    fn name(&self) -> &'static str {
+        "/context"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.context.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.context.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, _args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let messages = repl.session.context_messages(&repl.profile.answer_system_prompt(&repl.settings.answer));
+            println!(
+                "{}",
+                ui::heading(tr!("context.heading", repl.answerer.name, messages.len()))
+            );
+            for (i, message) in messages.iter().enumerate() {
+                let preview: String = message.content.chars().take(100).collect();
+                let ellipsis = if message.content.chars().count() > 100 { "…" } else { "" };
+                println!(
+                    "  {:>2}. [{}] {}{} {}",
+                    i + 1,
+                    message.role,
+                    preview.replace('\n', " "),
+                    ellipsis,
+                    ui::status(tr!("context.tokens", estimate_tokens(&message.content)))
+                );
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forget_needs_a_positive_count() {
+        assert_eq!(forget_count("2"), Some(2));
+        assert_eq!(forget_count("0"), None);
+        assert_eq!(forget_count("-1"), None);
+        assert_eq!(forget_count(""), None);
+        assert_eq!(forget_count("all"), None);
+    }
+}
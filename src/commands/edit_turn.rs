@@ -0,0 +1,68 @@
+use super::{Command, CommandFuture};
+use crate::editor;
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct EditTurn;
+
+impl Command for EditTurn {
+    fn name(&self) -> &'static str {
+        "/edit-turn"
+    }
+
+    fn args(&self) -> &'static str {
+        "N [answer|review]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.edit_turn.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.edit_turn.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        vec!["answer".to_string(), "review".to_string()]
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let mut words = args.split_whitespace();
+            let (Some(Ok(round)), part, None) = (words.next().map(str::parse::<usize>), words.next().unwrap_or("answer"), words.next()) else {
+                println!("{}", ui::warning(tr!("edit_turn.usage")));
+                return Ok(());
+            };
+            let Some(turn) = repl.session.turn_mut(round) else {
+                println!("{}", ui::warning(tr!("edit_turn.no_round", round)));
+                return Ok(());
+            };
+            let text = match part {
+                "answer" => &mut turn.moonshot_answer,
+                "review" => match turn.deepseek_review.as_mut() {
+                    Some(review) => review,
+                    None => {
+                        println!("{}", ui::warning(tr!("edit_turn.not_reviewed", round)));
+                        return Ok(());
+                    }
+                },
+                _ => {
+                    println!("{}", ui::warning(tr!("edit_turn.usage")));
+                    return Ok(());
+                }
+            };
+            match editor::edit(text, &format!("round-{}-{}", round, part))? {
+                Some(edited) => {
+                    *text = edited;
+                    turn.edited = true;
+                    repl.session.mark_dirty();
+                    repl.scrollback.invalidate(round);
+                    println!("{}", ui::success(tr!("edit_turn.saved", part, round)));
+                }
+                None => println!("{}", ui::status(tr!("edit_turn.unchanged"))),
+            }
+            Ok(())
+        })
+    }
+}
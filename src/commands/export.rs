@@ -0,0 +1,109 @@
+use anyhow::Context;
+
+use super::{Command, CommandFuture};
+use crate::archive::{self, conversations_dir};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{anki, generate_filename, notebook, pdf, save_rounds_export, ui};
+
+pub struct Export;
+
+// "2-4,7" → [2, 3, 4, 7]; None if it doesn't parse
+fn parse_rounds(spec: &str) -> Option<Vec<usize>> {
+    let mut rounds = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (first.trim().parse::<usize>().ok()?, last.trim().parse::<usize>().ok()?);
+                if first > last {
+                    return None;
+                }
+                rounds.extend(first..=last);
+            }
+            None => rounds.push(part.parse().ok()?),
+        }
+    }
+    rounds.sort_unstable();
+    rounds.dedup();
+    (!rounds.is_empty()).then_some(rounds)
+}
+
+impl Command for Export {
+    fn name(&self) -> &'static str {
+        "/export"
+    }
+
+    fn args(&self) -> &'static str {
+        "rounds N-M,K [md|html] | anki [DECK] | ipynb | pdf"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.export.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.export.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        vec!["rounds".to_string(), "anki".to_string(), "ipynb".to_string(), "pdf".to_string()]
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if repl.session.is_empty() {
+                println!("{}", ui::warning(tr!("save.empty")));
+                return Ok(());
+            }
+            if let Some(deck) = args.strip_prefix("anki").filter(|rest| rest.is_empty() || rest.starts_with(' ')) {
+                let deck = if deck.trim().is_empty() { repl.settings.anki.deck.as_str() } else { deck.trim() };
+                let (cards, count) = anki::render(&repl.session, &repl.settings.anki, deck);
+                if count == 0 {
+                    println!("{}", ui::warning(tr!("anki.empty")));
+                    return Ok(());
+                }
+                let filepath = conversations_dir()?.join(generate_filename(&repl.session.start_time, "anki")).with_extension("txt");
+                let filepath = archive::write_file(&filepath, &cards).context("Failed to write Anki cards")?;
+                println!("{}", ui::success(tr!("anki.saved", count, deck, filepath.display())));
+                return Ok(());
+            }
+            if args == "ipynb" {
+                let (json, code_cells) = notebook::render(&repl.session);
+                let filepath = conversations_dir()?.join(generate_filename(&repl.session.start_time, "notebook")).with_extension("ipynb");
+                let filepath = archive::write_file(&filepath, &json).context("Failed to write the notebook")?;
+                println!("{}", ui::success(tr!("notebook.saved", code_cells, filepath.display())));
+                return Ok(());
+            }
+            if args == "pdf" {
+                println!("{}", ui::status(tr!("pdf.converting")));
+                let filepath = conversations_dir()?.join(generate_filename(&repl.session.start_time, "session")).with_extension("pdf");
+                let filepath = pdf::export(&repl.session, &repl.settings.pdf, &filepath).context("Failed to export the PDF")?;
+                println!("{}", ui::success(tr!("pdf.saved", filepath.display())));
+                return Ok(());
+            }
+            let words: Vec<&str> = args.split_whitespace().collect();
+            let (spec, format) = match words.as_slice() {
+                ["rounds", spec] => (*spec, "md"),
+                ["rounds", spec, format] => (*spec, *format),
+                _ => {
+                    println!("{}", ui::warning(tr!("export.usage")));
+                    return Ok(());
+                }
+            };
+            let (Some(rounds), "md" | "html") = (parse_rounds(spec), format) else {
+                println!("{}", ui::warning(tr!("export.usage")));
+                return Ok(());
+            };
+            let missing: Vec<String> = rounds.iter().filter(|&&round| repl.session.turn(round).is_none()).map(|round| round.to_string()).collect();
+            if !missing.is_empty() {
+                println!("{}", ui::warning(tr!("export.missing_rounds", missing.join(", "))));
+                return Ok(());
+            }
+            let turns: Vec<_> = rounds.iter().filter_map(|&round| repl.session.turn(round)).collect();
+            let filepath = save_rounds_export(&repl.session, &turns, spec, format == "html", &repl.answerer, &repl.reviewer)
+                .context("Failed to export rounds")?;
+            println!("{}", ui::success(tr!("export.saved", turns.len(), filepath.display())));
+            Ok(())
+        })
+    }
+}
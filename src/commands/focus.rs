@@ -0,0 +1,55 @@
+use super::{Command, CommandFuture};
+use crate::focus::{self, Aspect, Focus};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct FocusCommand;
+
+impl Command for FocusCommand {
+    fn name(&self) -> &'static str {
+        "/focus"
+    }
+
+    fn args(&self) -> &'static str {
+        "[ASPECT[=WEIGHT] ...|off]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.focus.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.focus.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        Aspect::NAMES.iter().chain(&["off"]).map(|name| name.to_string()).collect()
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if args.eq_ignore_ascii_case("off") {
+                repl.focus.clear();
+                println!("{}", ui::success(tr!("focus.off")));
+                return Ok(());
+            }
+            // Check them all before changing anything
+            let focuses: Option<Vec<Focus>> = args.split_whitespace().map(Focus::parse).collect();
+            let Some(focuses) = focuses else {
+                println!("{}", ui::warning(tr!("focus.usage", Aspect::NAMES.join(", "), focus::MAX_WEIGHT)));
+                return Ok(());
+            };
+            for focus in focuses {
+                focus::stack(&mut repl.focus, focus);
+            }
+            if repl.focus.is_empty() {
+                println!("{}", ui::status(tr!("focus.none")));
+            } else {
+                let labels: Vec<String> = repl.focus.iter().map(Focus::label).collect();
+                println!("{}", ui::success(tr!("focus.current", labels.join(", "))));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct Force;
+
+impl Command for Force {
+    fn name(&self) -> &'static str {
+        "/force"
+    }
+
+    fn args(&self) -> &'static str {
+        "QUESTION"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.force.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.force.details")
+    }
+
+    // The question is handed back to the REPL loop, which sends it as usual
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if args.is_empty() {
+                println!("{}", ui::warning(tr!("force.usage")));
+            } else {
+                repl.forced_question = Some(args.to_string());
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,55 @@
+use super::{find, usage, Command, CommandFuture, COMMANDS};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct Help;
+
+impl Command for Help {
+    fn name(&self) -> &'static str {
+        "/help"
+    }
+
+    fn args(&self) -> &'static str {
+        "[command]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.help.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.help.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        COMMANDS.iter().map(|c| c.name()[1..].to_string()).collect()
+    }
+
+    fn run<'a>(&'a self, _repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            print_help(args);
+            Ok(())
+        })
+    }
+}
+
+fn print_help(topic: &str) {
+    if topic.is_empty() {
+        println!("{}", ui::heading(tr!("help.heading")));
+        for command in COMMANDS {
+            println!("  {:<24} {}", usage(*command), command.summary());
+        }
+        println!("  {:<24} {}", "exit, quit", tr!("help.exit"));
+        println!("{}", ui::status(tr!("help.footer")));
+        return;
+    }
+    match find(topic) {
+        Some(command) => {
+            println!("{}", ui::heading(usage(command)));
+            println!("  {}", command.summary());
+            println!("  {}", command.details());
+        }
+        None => println!("{}", ui::warning(tr!("help.unknown", topic))),
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+
+use super::{Command, CommandFuture};
+use crate::archive::{saved_sessions, session_file};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::session::ConversationSession;
+use crate::{confirm_discard, recovery, ui};
+
+pub struct Load;
+
+impl Command for Load {
+    fn name(&self) -> &'static str {
+        "/load"
+    }
+
+    fn args(&self) -> &'static str {
+        "[NAME]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.load.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.load.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        saved_sessions()
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if args.is_empty() {
+                list_sessions();
+                return Ok(());
+            }
+            if repl.session.has_unsaved_changes() && !confirm_discard(&mut repl.rl) {
+                return Ok(());
+            }
+            let loaded = load(args)?;
+            repl.round_counter = loaded.last_round();
+            repl.session = loaded;
+            repl.scrollback.clear();
+            recovery::update(None);
+            println!(
+                "{}",
+                ui::success(tr!("load.loaded", repl.session.len(), repl.round_counter + 1))
+            );
+            Ok(())
+        })
+    }
+}
+
+fn load(name: &str) -> Result<ConversationSession> {
+    session_file(name).and_then(|path| ConversationSession::load(&path)).context("Failed to load session")
+}
+
+fn list_sessions() {
+    let sessions = saved_sessions();
+    if sessions.is_empty() {
+        println!("{}", ui::status(tr!("load.empty")));
+        return;
+    }
+    println!("{}", ui::heading(tr!("load.heading")));
+    for name in sessions.iter().take(10) {
+        println!("  {}", name);
+    }
+    println!("{}", ui::status(tr!("load.hint")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loading_a_missing_session_fails() {
+        let missing = std::env::temp_dir().join("aivsai-no-such-dir").join("2024-01-01_00-00-00_missing");
+        let error = load(&missing.to_string_lossy()).err().expect("there is no such session");
+        assert!(format!("{:#}", error).starts_with("Failed to load session"));
+    }
+
+    #[test]
+    fn loading_a_broken_session_fails() {
+        let dir = std::env::temp_dir().join(format!("aivsai-load-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("broken.json"), "not a session").unwrap();
+        let result = load(&dir.join("broken").to_string_lossy());
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(result.is_err());
+    }
+}
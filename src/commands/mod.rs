@@ -0,0 +1,192 @@
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::repl::Repl;
+
+mod background;
+mod branch;
+mod code;
+mod context;
+mod edit_turn;
+mod export;
+mod force;
+mod focus;
+mod help;
+mod load;
+mod model;
+mod pager;
+mod persona;
+mod preview;
+mod profile;
+mod publish;
+mod quote;
+mod review;
+mod save;
+mod reasoning;
+mod scroll;
+mod star;
+mod stats;
+mod strict;
+mod style;
+mod tag;
+mod translate;
+mod undo;
+
+pub type CommandFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + 'a>>;
+
+// A REPL slash command. The metadata drives /help, the startup hint and tab
+// completion; `run` gets everything after the command name as `args`.
+// Usage problems are reported by the command itself; errors returned from
+// `run` are printed by the dispatcher.
+pub trait Command: Sync {
+    fn name(&self) -> &'static str;
+
+    fn args(&self) -> &'static str {
+        ""
+    }
+
+    fn summary(&self) -> &'static str;
+
+    fn details(&self) -> &'static str;
+
+    // Values offered by tab completion for the argument
+    fn completions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a>;
+}
+
+pub static COMMANDS: &[&dyn Command] = &[
+    &help::Help,
+    &save::Save,
+    &load::Load,
+    &model::Model,
+    &style::Length,
+    &style::Style,
+    &profile::Profile,
+    &persona::PersonaCommand,
+    &persona::Personas,
+    &review::ReviewRetry,
+    &focus::FocusCommand,
+    &strict::Strict,
+    &background::BackgroundReview,
+    &reasoning::Reasoning,
+    &star::Star,
+    &star::Starred,
+    &export::Export,
+    &publish::Publish,
+    &tag::Tag,
+    &branch::Branch,
+    &undo::Undo,
+    &edit_turn::EditTurn,
+    &context::Clear,
+    &context::Forget,
+    &context::ShowContext,
+    &preview::Preview,
+    &stats::Stats,
+    &pager::Pager,
+    &scroll::Prev,
+    &scroll::Next,
+    &translate::Translate,
+    &force::Force,
+    &quote::Quote,
+    &code::Copy,
+    &code::Write,
+    &code::Run,
+    &code::Check,
+];
+
+pub fn find(name: &str) -> Option<&'static dyn Command> {
+    let name = name.trim();
+    let name = name.strip_prefix('/').unwrap_or(name);
+    COMMANDS.iter().copied().find(|c| c.name()[1..].eq_ignore_ascii_case(name))
+}
+
+// An optional round number, as /star and /review-retry take: None when empty
+fn round_arg(args: &str) -> Result<Option<usize>, std::num::ParseIntError> {
+    match args {
+        "" => Ok(None),
+        n => n.parse().map(Some),
+    }
+}
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    COMMANDS.iter().map(|c| c.name())
+}
+
+pub fn usage(command: &dyn Command) -> String {
+    if command.args().is_empty() {
+        command.name().to_string()
+    } else {
+        format!("{} {}", command.name(), command.args())
+    }
+}
+
+// Run `input` if it starts with a known command; returns false otherwise
+pub async fn dispatch(repl: &mut Repl, input: &str) -> bool {
+    let (word, args) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    if !word.starts_with('/') {
+        return false;
+    }
+    let Some(command) = find(word) else {
+        return false;
+    };
+    crate::events::log("command", serde_json::json!({ "command": command.name(), "args": args.trim() }));
+    if let Err(e) = command.run(repl, args.trim()).await {
+        eprintln!("{}", crate::ui::error(format!("✗ {:#}", e)));
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_takes_names_with_or_without_the_slash() {
+        assert_eq!(find("/star").map(|c| c.name()), Some("/star"));
+        assert_eq!(find("star").map(|c| c.name()), Some("/star"));
+        assert_eq!(find("  /STARRED ").map(|c| c.name()), Some("/starred"));
+        assert_eq!(find("/review-retry").map(|c| c.name()), Some("/review-retry"));
+    }
+
+    #[test]
+    fn find_rejects_unknown_names_and_prefixes() {
+        assert!(find("/nope").is_none());
+        assert!(find("/").is_none());
+        assert!(find("").is_none());
+        // No prefix matching: "/sta" could be /star, /starred or /stats
+        assert!(find("/sta").is_none());
+        assert!(find("/review").is_none());
+        assert!(find("//star").is_none());
+    }
+
+    #[test]
+    fn command_names_are_unique_slash_commands() {
+        let mut names: Vec<&str> = names().collect();
+        assert!(names.iter().all(|name| name.starts_with('/') && name.len() > 1));
+        names.sort_unstable();
+        let count = names.len();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn usage_shows_the_arguments() {
+        assert_eq!(usage(find("/forget").unwrap()), "/forget N");
+        assert_eq!(usage(find("/star").unwrap()), "/star [N]");
+        assert_eq!(usage(find("/save").unwrap()), "/save");
+    }
+
+    #[test]
+    fn round_arg_is_empty_or_a_number() {
+        assert_eq!(round_arg(""), Ok(None));
+        assert_eq!(round_arg("3"), Ok(Some(3)));
+        assert!(round_arg("three").is_err());
+        assert!(round_arg("-1").is_err());
+        assert!(round_arg("1 2").is_err());
+        assert!(round_arg("2.5").is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+// Answering models offered by /model completion
+pub const ANSWER_MODELS: &[&str] = &["moonshot-v1-8k", "moonshot-v1-32k", "moonshot-v1-128k"];
+
+pub struct Model;
+
+impl Command for Model {
+    fn name(&self) -> &'static str {
+        "/model"
+    }
+
+    fn args(&self) -> &'static str {
+        "[NAME]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.model.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.model.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        ANSWER_MODELS.iter().map(|m| m.to_string()).collect()
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if args.is_empty() {
+                println!("{}", ui::status(tr!("model.current", repl.answerer.model, repl.reviewer.model)));
+                println!("{}", ui::status(tr!("model.hint", ANSWER_MODELS.join(", "))));
+            } else {
+                repl.answerer.model = args.to_string();
+                println!("{}", ui::success(tr!("model.switched", repl.answerer.name, args)));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,45 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct Pager;
+
+impl Command for Pager {
+    fn name(&self) -> &'static str {
+        "/pager"
+    }
+
+    fn args(&self) -> &'static str {
+        "[on|off]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.pager.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.pager.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        vec!["on".to_string(), "off".to_string()]
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            repl.pager_enabled = match args {
+                "" => !repl.pager_enabled,
+                "on" => true,
+                "off" => false,
+                _ => {
+                    println!("{}", ui::warning(tr!("pager.usage")));
+                    return Ok(());
+                }
+            };
+            let state = if repl.pager_enabled { tr!("pager.on") } else { tr!("pager.off") };
+            println!("{}", ui::success(state));
+            Ok(())
+        })
+    }
+}
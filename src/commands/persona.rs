@@ -0,0 +1,115 @@
+use super::{Command, CommandFuture};
+use crate::config::Settings;
+use crate::i18n::tr;
+use crate::persona::{self, Persona};
+use crate::repl::Repl;
+use crate::ui;
+
+// Make `persona` the answerer's (None: back to the profile's prompt)
+fn apply(repl: &mut Repl, persona: Option<Persona>) {
+    repl.profile.persona = persona;
+    repl.answerer.max_tokens = repl.profile.answer_token_limit(&repl.settings.answer);
+    repl.answerer.temperature = repl.profile.persona.as_ref().and_then(|persona| persona.temperature);
+}
+
+// "(temperature 0.3, 1200 tokens max)", or empty
+fn parameters(persona: &Persona) -> String {
+    let mut parameters = Vec::new();
+    if let Some(temperature) = persona.temperature {
+        parameters.push(tr!("persona.temperature", temperature));
+    }
+    if let Some(max_tokens) = persona.max_tokens {
+        parameters.push(tr!("persona.max_tokens", max_tokens));
+    }
+    if parameters.is_empty() {
+        String::new()
+    } else {
+        format!("({})", parameters.join(", "))
+    }
+}
+
+pub struct PersonaCommand;
+
+impl Command for PersonaCommand {
+    fn name(&self) -> &'static str {
+        "/persona"
+    }
+
+    fn args(&self) -> &'static str {
+        "[NAME|off]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.persona.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.persona.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        let dir = Settings::load().map(|s| s.answer.personas).unwrap_or_default();
+        let mut names: Vec<String> = persona::load_all(&dir).unwrap_or_default().into_iter().map(|persona| persona.file).collect();
+        names.push("off".to_string());
+        names
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            match args {
+                "" => match &repl.profile.persona {
+                    Some(persona) => println!("{}", ui::status(tr!("persona.current", persona.name, parameters(persona)))),
+                    None => println!("{}", ui::status(tr!("persona.none"))),
+                },
+                "off" => {
+                    apply(repl, None);
+                    println!("{}", ui::success(tr!("persona.off")));
+                }
+                name => match persona::find(&repl.settings.answer.personas, name)? {
+                    Some(persona) => {
+                        println!("{}", ui::success(tr!("persona.switched", persona.name, parameters(&persona))));
+                        apply(repl, Some(persona));
+                    }
+                    None => println!("{}", ui::warning(tr!("persona.unknown", name.trim_matches('"'), repl.settings.answer.personas))),
+                },
+            }
+            Ok(())
+        })
+    }
+}
+
+pub struct Personas;
+
+impl Command for Personas {
+    fn name(&self) -> &'static str {
+        "/personas"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.personas.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.personas.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, _args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let personas = persona::load_all(&repl.settings.answer.personas)?;
+            if personas.is_empty() {
+                println!("{}", ui::status(tr!("persona.empty", repl.settings.answer.personas)));
+                return Ok(());
+            }
+            let active = repl.profile.persona.as_ref().map(|persona| persona.file.as_str());
+            println!("{}", ui::heading(tr!("persona.heading", repl.settings.answer.personas)));
+            for persona in &personas {
+                let marker = if active == Some(persona.file.as_str()) { "*" } else { " " };
+                let prompt: String = persona.prompt.chars().take(80).collect();
+                let ellipsis = if persona.prompt.chars().count() > 80 { "…" } else { "" };
+                println!("{} {} [{}] {}", marker, ui::code(&persona.name), persona.file, ui::status(parameters(persona)));
+                println!("    {}{}", prompt.replace('\n', " "), ellipsis);
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,69 @@
+use super::{Command, CommandFuture};
+use crate::api::{estimate_tokens, ChatMessage};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{layout, references, review_messages, ui};
+
+pub struct Preview;
+
+// Every message of a request in full, with a header line each
+fn print_messages(heading: String, messages: &[ChatMessage]) {
+    let tokens: u32 = messages.iter().map(|message| estimate_tokens(&message.content)).sum();
+    println!("\n{}", ui::heading(tr!("preview.request", heading, messages.len(), tokens)));
+    for (i, message) in messages.iter().enumerate() {
+        println!("{}", ui::status(tr!("preview.message", i + 1, message.role, estimate_tokens(&message.content))));
+        println!("{}", layout::prefix_lines(&message.content, "  "));
+    }
+}
+
+impl Command for Preview {
+    fn name(&self) -> &'static str {
+        "/preview"
+    }
+
+    fn args(&self) -> &'static str {
+        "[QUESTION]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.preview.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.preview.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            // The question is assembled as the main loop does it: @path
+            // references inlined, then a waiting /quote in front
+            let question = if args.is_empty() {
+                tr!("preview.question_placeholder").to_string()
+            } else {
+                references::expand(args, &repl.settings.references)?.question
+            };
+            let question = match &repl.quote {
+                Some(quote) => format!("{}\n\n{}", quote, question),
+                None => question,
+            };
+
+            let mut answer_messages = repl.session.context_messages(&repl.profile.answer_system_prompt(&repl.settings.answer));
+            answer_messages.push(ChatMessage::user(&question));
+            let answerers: Vec<String> = match &repl.ensemble {
+                Some(ensemble) => ensemble.answerers.iter().map(|answerer| format!("{} ({})", answerer.name, answerer.model)).collect(),
+                None => vec![format!("{} ({})", repl.answerer.name, repl.answerer.model)],
+            };
+            print_messages(tr!("preview.answerer", answerers.join(", ")), &answer_messages);
+
+            let review = review_messages(&repl.profile, &question, tr!("preview.answer_placeholder"), false, &repl.focus, repl.settings.review.strictness);
+            let mut reviewers = vec![format!("{} ({})", repl.reviewer.name, repl.reviewer.model)];
+            if let Some(panel) = &repl.panel {
+                reviewers.extend(panel.reviewers.iter().map(|reviewer| format!("{} ({})", reviewer.name, reviewer.model)));
+            }
+            print_messages(tr!("preview.reviewer", reviewers.join(", ")), &review);
+
+            println!("\n{}", ui::status(tr!("preview.not_shown")));
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::Context;
+
+use super::{Command, CommandFuture};
+use crate::config::{AiConfig, Settings};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct Profile;
+
+impl Command for Profile {
+    fn name(&self) -> &'static str {
+        "/profile"
+    }
+
+    fn args(&self) -> &'static str {
+        "[NAME]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.profile.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.profile.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        Settings::load().map(|s| s.profile_names()).unwrap_or_default()
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if args.is_empty() {
+                let names = repl.settings.profile_names();
+                println!(
+                    "{}",
+                    ui::status(tr!(
+                        "profile.current",
+                        repl.profile.name,
+                        repl.answerer.name,
+                        repl.answerer.model,
+                        repl.reviewer.name,
+                        repl.reviewer.model
+                    ))
+                );
+                if names.is_empty() {
+                    println!("{}", ui::status(tr!("profile.none")));
+                } else {
+                    println!("{}", ui::status(tr!("profile.hint", names.join(", "))));
+                }
+                return Ok(());
+            }
+            let mut profile = repl.settings.profile(Some(args))?;
+            // A /persona stays on across profiles
+            profile.persona = repl.profile.persona.clone();
+            let (answerer, reviewer) = AiConfig::pair(&repl.settings, &profile).context("Failed to switch profile")?;
+            repl.answerer = answerer;
+            repl.reviewer = reviewer;
+            repl.profile = profile;
+            println!(
+                "{}",
+                ui::success(format!(
+                    "✓ {}",
+                    tr!(
+                        "profile.current",
+                        repl.profile.name,
+                        repl.answerer.name,
+                        repl.answerer.model,
+                        repl.reviewer.name,
+                        repl.reviewer.model
+                    )
+                ))
+            );
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{generate_filename, github, render_session_markdown, screen_outbound, ui};
+
+pub struct Publish;
+
+// The session's markdown without its YAML frontmatter, which a discussion would
+// show as plain text
+fn without_frontmatter(markdown: &str) -> &str {
+    markdown
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.split_once("\n---\n"))
+        .map_or(markdown, |(_, body)| body.trim_start())
+}
+
+impl Command for Publish {
+    fn name(&self) -> &'static str {
+        "/publish"
+    }
+
+    fn args(&self) -> &'static str {
+        "gist | discussion OWNER/REPO [CATEGORY]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.publish.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.publish.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        vec!["gist".to_string(), "discussion".to_string()]
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let words: Vec<&str> = args.split_whitespace().collect();
+            let target = match words.as_slice() {
+                ["gist"] => None,
+                ["discussion", repo] => Some((*repo, None)),
+                ["discussion", repo, category @ ..] if !category.is_empty() => Some((*repo, Some(category.join(" ")))),
+                _ => {
+                    println!("{}", ui::warning(tr!("github.usage")));
+                    return Ok(());
+                }
+            };
+            if repl.session.is_empty() {
+                println!("{}", ui::warning(tr!("save.empty")));
+                return Ok(());
+            }
+            let token = github::token()?;
+            let markdown = render_session_markdown(&repl.session, &repl.answerer, &repl.reviewer);
+            // Published text leaves the machine like a request does
+            let Some(markdown) = screen_outbound(Some(&mut repl.rl), markdown) else {
+                return Ok(());
+            };
+            let question = repl.session.first_question().unwrap_or("conversation");
+            let title: String = question.lines().next().unwrap_or_default().chars().take(80).collect();
+            println!("{}", ui::status(tr!("github.sending")));
+            let url = match target {
+                None => {
+                    let filename = generate_filename(&repl.session.start_time, question);
+                    github::create_gist(&repl.client, &token, &filename, &tr!("github.description", title), &markdown).await?
+                }
+                Some((repo, category)) => github::create_discussion(&repl.client, &token, repo, category.as_deref(), &title, without_frontmatter(&markdown)).await?,
+            };
+            println!("{}", ui::success(tr!("github.published", url)));
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use super::{Command, CommandFuture};
+use crate::codeblocks;
+use crate::i18n::tr;
+use crate::references::fence_for;
+use crate::repl::Repl;
+use crate::ui;
+
+// The text put in front of the next question: the whole answer as a
+// blockquote, or one code block as it was written
+fn quote_answer(round: usize, answer: &str) -> String {
+    let quoted: Vec<String> = answer.trim().lines().map(|line| format!("> {}", line).trim_end().to_string()).collect();
+    format!("Regarding your answer in round {}:\n\n{}", round, quoted.join("\n"))
+}
+
+fn quote_block(round: usize, number: usize, block: &codeblocks::CodeBlock) -> String {
+    let fence = fence_for(&block.code);
+    format!(
+        "Regarding code block {} of your answer in round {}:\n\n{}{}\n{}\n{}",
+        number, round, fence, block.language, block.code, fence
+    )
+}
+
+pub struct Quote;
+
+impl Command for Quote {
+    fn name(&self) -> &'static str {
+        "/quote"
+    }
+
+    fn args(&self) -> &'static str {
+        "[N [BLOCK]] | off"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.quote.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.quote.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        vec!["off".to_string()]
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if args.eq_ignore_ascii_case("off") {
+                repl.quote = None;
+                println!("{}", ui::success(tr!("quote.off")));
+                return Ok(());
+            }
+            let numbers: Result<Vec<usize>, _> = args.split_whitespace().map(str::parse::<usize>).collect();
+            let (round, block) = match numbers.as_deref() {
+                Ok([]) => (repl.session.last_round(), None),
+                Ok([round]) => (*round, None),
+                Ok([round, block]) if *block > 0 => (*round, Some(*block)),
+                _ => {
+                    println!("{}", ui::warning(tr!("quote.usage")));
+                    return Ok(());
+                }
+            };
+            let Some(turn) = repl.session.turn(round) else {
+                println!("{}", ui::warning(tr!("quote.no_round", round)));
+                return Ok(());
+            };
+            let (quote, lines) = match block {
+                None => (quote_answer(round, &turn.moonshot_answer), turn.moonshot_answer.trim().lines().count()),
+                Some(number) => {
+                    let blocks = codeblocks::extract(&turn.moonshot_answer);
+                    match blocks.get(number - 1) {
+                        Some(code) => (quote_block(round, number, code), code.code.lines().count()),
+                        None => {
+                            println!("{}", ui::warning(tr!("quote.no_block", round, blocks.len())));
+                            return Ok(());
+                        }
+                    }
+                }
+            };
+            repl.quote = Some(quote);
+            let message = match block {
+                Some(number) => tr!("quote.block", number, round, lines),
+                None => tr!("quote.answer", round, lines),
+            };
+            println!("{}", ui::success(message));
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,57 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{pager, reasoning, ui};
+
+pub struct Reasoning;
+
+impl Command for Reasoning {
+    fn name(&self) -> &'static str {
+        "/reasoning"
+    }
+
+    fn args(&self) -> &'static str {
+        "[N]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.reasoning.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.reasoning.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let round = if args.is_empty() {
+                repl.session.last_round()
+            } else {
+                match args.parse::<usize>() {
+                    Ok(round) => round,
+                    Err(_) => {
+                        println!("{}", ui::warning(tr!("reasoning.usage")));
+                        return Ok(());
+                    }
+                }
+            };
+            let Some(turn) = repl.session.turn(round) else {
+                println!("{}", ui::warning(tr!("reasoning.no_round", round)));
+                return Ok(());
+            };
+            let Some(trace) = &turn.review_reasoning else {
+                println!("{}", ui::status(tr!("reasoning.none", round)));
+                return Ok(());
+            };
+            let text = reasoning::full_block(trace);
+            if repl.pager_enabled && !pager::fits_terminal(&text) {
+                if let Err(e) = pager::page(&text, &repl.settings.pager) {
+                    eprintln!("{}", ui::error(tr!("error.pager", format!("{:#}", e))));
+                }
+            } else {
+                println!("{}", text);
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,53 @@
+use super::{round_arg, Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{review_turn, ui};
+
+pub struct ReviewRetry;
+
+impl Command for ReviewRetry {
+    fn name(&self) -> &'static str {
+        "/review-retry"
+    }
+
+    fn args(&self) -> &'static str {
+        "[N]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.review_retry.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.review_retry.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let rounds = match round_arg(args) {
+                Ok(None) => repl.session.pending_reviews(),
+                Ok(Some(round)) => vec![round],
+                Err(_) => {
+                    println!("{}", ui::warning(tr!("review_retry.usage")));
+                    return Ok(());
+                }
+            };
+            if rounds.is_empty() {
+                println!("{}", ui::success(tr!("review_retry.none")));
+            }
+            for round in rounds {
+                match repl.session.turn_mut(round) {
+                    Some(turn) => {
+                        println!("{}", ui::status(tr!("review_retry.retrying", round)));
+                        if review_turn(&repl.client, &repl.reviewer, repl.summarizer.as_ref(), &repl.profile, &repl.settings.tools, turn, true).await {
+                            repl.session.mark_dirty();
+                            repl.scrollback.invalidate(round);
+                        }
+                    }
+                    None => println!("{}", ui::warning(tr!("review_retry.no_round", round))),
+                }
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,33 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{save_and_report, ui};
+
+pub struct Save;
+
+impl Command for Save {
+    fn name(&self) -> &'static str {
+        "/save"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.save.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.save.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if !args.is_empty() {
+                println!("{}", ui::warning(tr!("save.usage")));
+            } else if repl.session.is_empty() {
+                println!("{}", ui::warning(tr!("save.empty")));
+            } else {
+                save_and_report(&mut repl.session, &repl.answerer, &repl.reviewer);
+            }
+            Ok(())
+        })
+    }
+}
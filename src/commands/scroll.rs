@@ -0,0 +1,117 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{pager, round_block, ui};
+
+// Which way /prev and /next move from the round shown last
+#[derive(Clone, Copy)]
+enum Direction {
+    Back,
+    Forward,
+}
+
+// Show round N (or the one before / after the round shown last) again, through
+// the pager in pager mode when it doesn't fit on screen
+fn show(repl: &mut Repl, args: &str, direction: Direction) {
+    let rounds = repl.session.rounds();
+    let Some(&latest) = rounds.last() else {
+        println!("{}", ui::status(tr!("scroll.empty")));
+        return;
+    };
+    let round = if args.is_empty() {
+        // Nothing shown yet: the latest round is still on screen
+        let from = repl.scrollback.viewing.unwrap_or(latest);
+        let step = match direction {
+            Direction::Back => rounds.iter().rev().find(|&&round| round < from),
+            Direction::Forward => rounds.iter().find(|&&round| round > from),
+        };
+        match (step, direction) {
+            (Some(&round), _) => round,
+            (None, Direction::Back) => {
+                println!("{}", ui::status(tr!("scroll.first")));
+                return;
+            }
+            (None, Direction::Forward) => {
+                println!("{}", ui::status(tr!("scroll.latest")));
+                return;
+            }
+        }
+    } else {
+        match args.parse::<usize>() {
+            Ok(round) => round,
+            Err(_) => {
+                println!("{}", ui::warning(tr!("scroll.usage")));
+                return;
+            }
+        }
+    };
+
+    let (answerer, reviewer) = (&repl.answerer.name, &repl.reviewer.name);
+    let session = &repl.session;
+    let Some(text) = repl
+        .scrollback
+        .get_or_render(round, || session.turn(round).map(|turn| round_block(turn, answerer, reviewer)))
+    else {
+        println!("{}", ui::warning(tr!("scroll.no_round", round)));
+        return;
+    };
+    if repl.pager_enabled && !pager::fits_terminal(text) {
+        if let Err(e) = pager::page(text, &repl.settings.pager) {
+            eprintln!("{}", ui::error(tr!("error.pager", format!("{:#}", e))));
+        }
+    } else {
+        println!("{}", text);
+    }
+    println!("{}", ui::status(tr!("scroll.position", round, latest)));
+    repl.scrollback.viewing = Some(round);
+}
+
+pub struct Prev;
+
+impl Command for Prev {
+    fn name(&self) -> &'static str {
+        "/prev"
+    }
+
+    fn args(&self) -> &'static str {
+        "[N]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.prev.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.prev.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            show(repl, args, Direction::Back);
+            Ok(())
+        })
+    }
+}
+
+pub struct Next;
+
+impl Command for Next {
+    fn name(&self) -> &'static str {
+        "/next"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.next.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.next.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            show(repl, args, Direction::Forward);
+            Ok(())
+        })
+    }
+}
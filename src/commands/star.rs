@@ -0,0 +1,93 @@
+use anyhow::Context;
+
+use super::{round_arg, Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{save_starred_digest, ui};
+
+pub struct Star;
+
+impl Command for Star {
+    fn name(&self) -> &'static str {
+        "/star"
+    }
+
+    fn args(&self) -> &'static str {
+        "[N]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.star.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.star.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let Ok(round) = round_arg(args) else {
+                println!("{}", ui::warning(tr!("star.usage")));
+                return Ok(());
+            };
+            match repl.session.toggle_star(round) {
+                Some((round, true)) => println!("{}", ui::success(tr!("star.starred", round))),
+                Some((round, false)) => println!("{}", ui::status(tr!("star.unstarred", round))),
+                None => println!("{}", ui::warning(tr!("star.no_round"))),
+            }
+            Ok(())
+        })
+    }
+}
+
+pub struct Starred;
+
+impl Command for Starred {
+    fn name(&self) -> &'static str {
+        "/starred"
+    }
+
+    fn args(&self) -> &'static str {
+        "[export]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.starred.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.starred.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        vec!["export".to_string()]
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let session = &repl.session;
+            let export = args.eq_ignore_ascii_case("export");
+            if session.starred_turns().next().is_none() {
+                let message = tr!("starred.empty");
+                if export {
+                    println!("{}", ui::warning(format!("⚠ {}", message)));
+                } else {
+                    println!("{}", ui::status(message));
+                }
+                return Ok(());
+            }
+            if export {
+                let filepath = save_starred_digest(session, &repl.answerer, &repl.reviewer)
+                    .context("Failed to save best answers")?;
+                println!("{}", ui::success(tr!("starred.saved", filepath.display())));
+                return Ok(());
+            }
+            for turn in session.starred_turns() {
+                let preview: String = turn.user_question.chars().take(60).collect();
+                println!("  {} {}", ui::warning(tr!("starred.item", turn.round)), preview);
+            }
+            println!("{}", ui::status(tr!("starred.hint")));
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,34 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::stats::{self, RoundCounts};
+use crate::{ui, usage};
+
+pub struct Stats;
+
+impl Command for Stats {
+    fn name(&self) -> &'static str {
+        "/stats"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.stats.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.stats.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, _args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let mut counts = RoundCounts::default();
+            for turn in &repl.session.turns {
+                counts.add(turn);
+            }
+            println!("{}", ui::heading(tr!("stats.session_heading", repl.session.start_time)));
+            // Requests are counted since aivsai started, also across /load
+            stats::print_summary(&counts, &usage::run_rows(), &repl.settings);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use super::{Command, CommandFuture};
+use crate::config::Strictness;
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+// "paranoid (temperature 0.2)" for the current level
+fn describe(strictness: Strictness) -> String {
+    match strictness.temperature() {
+        Some(temperature) => tr!("strict.level_temperature", strictness.name(), temperature),
+        None => strictness.name().to_string(),
+    }
+}
+
+pub struct Strict;
+
+impl Command for Strict {
+    fn name(&self) -> &'static str {
+        "/strict"
+    }
+
+    fn args(&self) -> &'static str {
+        "[low|medium|paranoid]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.strict.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.strict.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        Strictness::NAMES.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if args.is_empty() {
+                println!("{}", ui::status(tr!("strict.current", describe(repl.settings.review.strictness))));
+                return Ok(());
+            }
+            let Some(strictness) = Strictness::parse(args) else {
+                println!("{}", ui::warning(tr!("strict.usage")));
+                return Ok(());
+            };
+            repl.settings.review.strictness = strictness;
+            repl.reviewer.temperature = strictness.temperature();
+            println!("{}", ui::success(tr!("strict.switched", describe(strictness))));
+            Ok(())
+        })
+    }
+}
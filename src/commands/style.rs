@@ -0,0 +1,111 @@
+use super::{Command, CommandFuture};
+use crate::config::{AnswerLength, AnswerStyle};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+// "short, 600 tokens max" for the current `[answer]` constraints
+fn describe(repl: &Repl) -> String {
+    let answer = &repl.settings.answer;
+    let length = answer.length.map_or(tr!("style.none"), AnswerLength::name);
+    let style = answer.style.map_or(tr!("style.none"), AnswerStyle::name);
+    let limit = answer.token_limit().map_or(tr!("style.no_limit").to_string(), |n| n.to_string());
+    tr!("style.current", length, style, limit)
+}
+
+// After a change: the answerer's token cap follows the length, unless the
+// persona sets its own
+fn applied(repl: &mut Repl) {
+    repl.answerer.max_tokens = repl.profile.answer_token_limit(&repl.settings.answer);
+    println!("{}", ui::success(describe(repl)));
+}
+
+pub struct Length;
+
+impl Command for Length {
+    fn name(&self) -> &'static str {
+        "/length"
+    }
+
+    fn args(&self) -> &'static str {
+        "[short|medium|long|off]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.length.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.length.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        AnswerLength::NAMES.iter().chain(&["off"]).map(|name| name.to_string()).collect()
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            repl.settings.answer.length = match args {
+                "" => {
+                    println!("{}", ui::status(describe(repl)));
+                    return Ok(());
+                }
+                "off" => None,
+                name => match AnswerLength::parse(name) {
+                    Some(length) => Some(length),
+                    None => {
+                        println!("{}", ui::warning(tr!("style.length_usage")));
+                        return Ok(());
+                    }
+                },
+            };
+            applied(repl);
+            Ok(())
+        })
+    }
+}
+
+pub struct Style;
+
+impl Command for Style {
+    fn name(&self) -> &'static str {
+        "/style"
+    }
+
+    fn args(&self) -> &'static str {
+        "[bullets|prose|code|off]"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.style.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.style.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        AnswerStyle::NAMES.iter().chain(&["off"]).map(|name| name.to_string()).collect()
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            repl.settings.answer.style = match args {
+                "" => {
+                    println!("{}", ui::status(describe(repl)));
+                    return Ok(());
+                }
+                "off" => None,
+                name => match AnswerStyle::parse(name) {
+                    Some(style) => Some(style),
+                    None => {
+                        println!("{}", ui::warning(tr!("style.style_usage")));
+                        return Ok(());
+                    }
+                },
+            };
+            applied(repl);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::ui;
+
+pub struct Tag;
+
+impl Command for Tag {
+    fn name(&self) -> &'static str {
+        "/tag"
+    }
+
+    fn args(&self) -> &'static str {
+        "[NAME|-NAME]..."
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.tag.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.tag.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let mut changed = false;
+            for word in args.split_whitespace() {
+                let tags = &mut repl.session.tags;
+                match word.strip_prefix('-') {
+                    Some(name) => {
+                        let before = tags.len();
+                        tags.retain(|t| !t.eq_ignore_ascii_case(name));
+                        changed |= tags.len() != before;
+                    }
+                    None if !tags.iter().any(|t| t.eq_ignore_ascii_case(word)) => {
+                        tags.push(word.to_string());
+                        changed = true;
+                    }
+                    None => {}
+                }
+            }
+            if changed {
+                repl.session.mark_dirty();
+            }
+            if repl.session.tags.is_empty() {
+                println!("{}", ui::status(tr!("tag.none")));
+            } else {
+                println!("{}", ui::status(tr!("tag.list", repl.session.tags.join(", "))));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::Context;
+
+use super::{Command, CommandFuture};
+use crate::api::call_ai_api;
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::session::ConversationTurn;
+use crate::translate::{self, TranslationCheck};
+use crate::{answer_block, layout, review_turn, screen_outbound, ui};
+
+pub struct Translate;
+
+impl Command for Translate {
+    fn name(&self) -> &'static str {
+        "/translate"
+    }
+
+    fn args(&self) -> &'static str {
+        "[--to LANG] TEXT"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.translate.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.translate.details")
+    }
+
+    fn completions(&self) -> Vec<String> {
+        vec!["--to".to_string()]
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let (target, text) = match args.strip_prefix("--to") {
+                Some(rest) => match rest.trim_start().split_once(char::is_whitespace) {
+                    Some((target, text)) => (target.to_string(), text.trim()),
+                    None => ("".to_string(), ""),
+                },
+                None => (translate::default_target(args).to_string(), args),
+            };
+            if text.is_empty() || target.is_empty() {
+                println!("{}", ui::warning(tr!("translate.usage")));
+                return Ok(());
+            }
+            let Some(text) = screen_outbound(Some(&mut repl.rl), text.to_string()) else {
+                return Ok(());
+            };
+            let text = text.as_str();
+
+            let translation = call_ai_api(&repl.client, &repl.answerer, translate::answer_messages(text, &target))
+                .await
+                .context(format!("{} could not translate the text", repl.answerer.name))?;
+            println!("{}", answer_block(&repl.answerer.name, &translation));
+
+            repl.round_counter += 1;
+            let mut turn = ConversationTurn::new(repl.round_counter, text.to_string(), translation, &repl.answerer.model);
+            turn.translation = Some(TranslationCheck { target, rows: Vec::new() });
+            let reviewed = review_turn(&repl.client, &repl.reviewer, repl.summarizer.as_ref(), &repl.profile, &repl.settings.tools, &mut turn, true).await;
+            repl.session.add_turn(turn);
+
+            ui::notice(ui::status(format!("\n{}", layout::rule())));
+            if reviewed {
+                ui::notice(ui::status(tr!("round.completed", repl.round_counter)));
+            } else {
+                println!("{}", ui::warning(tr!("round.saved_unreviewed", repl.round_counter)));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use super::{Command, CommandFuture};
+use crate::i18n::tr;
+use crate::repl::Repl;
+use crate::{ui, update_recovery_snapshot};
+
+pub struct Undo;
+
+impl Command for Undo {
+    fn name(&self) -> &'static str {
+        "/undo"
+    }
+
+    fn summary(&self) -> &'static str {
+        tr!("command.undo.summary")
+    }
+
+    fn details(&self) -> &'static str {
+        tr!("command.undo.details")
+    }
+
+    fn run<'a>(&'a self, repl: &'a mut Repl, _args: &'a str) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let was_saved = !repl.session.has_unsaved_changes();
+            let Some(turn) = repl.session.pop_turn() else {
+                println!("{}", ui::warning(tr!("undo.empty")));
+                return Ok(());
+            };
+            if repl.session.is_empty() {
+                // Nothing left to save or recover
+                repl.session.mark_saved();
+            }
+            repl.round_counter = repl.session.last_round();
+            repl.scrollback.invalidate(turn.round);
+            update_recovery_snapshot(&repl.session, &repl.answerer, &repl.reviewer);
+            let question: String = turn.user_question.chars().take(60).collect();
+            println!("{}", ui::success(tr!("undo.removed", turn.round, question.replace('\n', " "))));
+            if was_saved {
+                println!("{}", ui::status(tr!("undo.saved_copy")));
+            }
+            Ok(())
+        })
+    }
+}
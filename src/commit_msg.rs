@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+use crate::api::{self, complete, ChatMessage};
+use crate::config::{AiConfig, Profile, Settings};
+use crate::i18n::tr;
+use crate::references::truncate_to_tokens;
+use crate::{layout, screen_outbound, ui};
+
+// Large diffs are cut off so the request stays within the model's context
+const MAX_DIFF_TOKENS: u32 = 12_000;
+
+const DRAFT_PROMPT: &str = "You write git commit messages that follow the Conventional Commits specification.";
+const CRITIQUE_PROMPT: &str =
+    "You are a meticulous reviewer of git commit messages. You check them against the Conventional Commits rules and against the diff they describe.";
+
+fn staged_diff() -> Result<String> {
+    let output = Command::new("git")
+        .args(["diff", "--cached", "--no-color", "--no-ext-diff"])
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!("git diff failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Contents of the last fenced code block in `text`
+fn last_fenced_block(text: &str) -> Option<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            match current.take() {
+                Some(lines) => blocks.push(lines.join("\n")),
+                None => current = Some(Vec::new()),
+            }
+        } else if let Some(lines) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    blocks.into_iter().rev().find(|block| !block.trim().is_empty())
+}
+
+// Strip a fence the model may have wrapped its draft in
+fn unfence(text: &str) -> String {
+    last_fenced_block(text).unwrap_or_else(|| text.to_string()).trim().to_string()
+}
+
+// `aivsai commit-msg`: the answerer drafts a message for the staged diff, the
+// reviewer critiques it and settles on the final message, which goes to stdout.
+// Everything else is a notice, so `aivsai -q commit-msg` prints only the message.
+pub async fn run(args: &[String], settings: &Settings, profile: &Profile) -> Result<()> {
+    if !args.is_empty() {
+        anyhow::bail!("Usage: aivsai commit-msg (works on the staged changes)");
+    }
+    let diff = staged_diff()?;
+    if diff.trim().is_empty() {
+        anyhow::bail!("Nothing is staged. Stage changes with `git add` first.");
+    }
+    let Some(diff) = screen_outbound(None, diff) else {
+        return Ok(());
+    };
+    let shown = truncate_to_tokens(&diff, MAX_DIFF_TOKENS);
+    let cut_note = if shown.len() < diff.len() { "\n(The diff was cut off here because it is long.)" } else { "" };
+
+    let client = api::client();
+    let (answerer, reviewer) = AiConfig::pair(settings, profile)?;
+
+    let draft_request = format!(
+        "Write a commit message for this staged diff. Use `type(scope): summary` (imperative mood, at most 72 characters), then a blank line and a short body explaining what changed and why, if the change needs one. Reply with the commit message only.\n\n```diff\n{}\n```{}",
+        shown, cut_note
+    );
+    let draft = complete(&client, &answerer, vec![ChatMessage::system(DRAFT_PROMPT), ChatMessage::user(draft_request)])
+        .await
+        .context(format!("{} could not draft a message", answerer.name))?;
+    let draft = unfence(&draft.content);
+    ui::notice(ui::answerer(format!("\n{}", tr!("commit_msg.draft_header", answerer.name))));
+    ui::notice(layout::wrap_for_terminal(&draft));
+
+    let critique_request = format!(
+        "Another assistant drafted this commit message:\n\n```text\n{}\n```\n\nfor this staged diff:\n\n```diff\n{}\n```{}\n\nCheck the type and scope, the summary length and mood, and whether the message matches what the diff actually does. Write your critique in {}. Then give the final commit message, corrected if needed, in a single ```text block at the end.",
+        draft,
+        shown,
+        cut_note,
+        profile.review_language()
+    );
+    let critique = complete(&client, &reviewer, vec![ChatMessage::system(CRITIQUE_PROMPT), ChatMessage::user(critique_request)])
+        .await
+        .context(format!("{} could not review the draft", reviewer.name))?;
+    ui::notice(ui::reviewer(format!("\n{}", tr!("round.review_header", reviewer.name))));
+    ui::notice(layout::wrap_for_terminal(&critique.content));
+
+    let final_message = match last_fenced_block(&critique.content) {
+        Some(message) => message.trim().to_string(),
+        None => {
+            ui::notice(ui::warning(tr!("commit_msg.no_final")));
+            draft
+        }
+    };
+    ui::notice(ui::heading(format!("\n{}", tr!("commit_msg.final_header"))));
+    println!("{}", final_message);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::i18n::tr;
+use crate::recall;
+use crate::session::{ConversationSession, ConversationTurn};
+use crate::{layout, ui};
+
+// Answer comparison for re-asked questions: after `/model` switches the
+// answerer, asking a question of this session again offers a diff against the
+// earlier model's answer. Lines are compared after `recall::normalize`, so
+// changes in whitespace, punctuation or case don't count as differences.
+
+// Unchanged lines kept around a change; longer unchanged runs are folded
+const CONTEXT_LINES: usize = 1;
+
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+// The latest earlier round of the session with the same question, answered by
+// another model
+pub fn previous_answer<'a>(session: &'a ConversationSession, question: &str, model: &str) -> Option<&'a ConversationTurn> {
+    let question = recall::normalize(question);
+    session
+        .turns
+        .iter()
+        .rev()
+        .find(|turn| turn.answer_model != model && !turn.answer_model.is_empty() && recall::normalize(&turn.user_question) == question)
+}
+
+// Line diff by longest common subsequence; blank lines are left out
+fn diff<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<(&str, String)> = old.lines().map(|line| (line, recall::normalize(line))).filter(|(_, key)| !key.is_empty()).collect();
+    let new: Vec<(&str, String)> = new.lines().map(|line| (line, recall::normalize(line))).filter(|(_, key)| !key.is_empty()).collect();
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i].1 == new[j].1 { common[i + 1][j + 1] + 1 } else { common[i + 1][j].max(common[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j, mut lines) = (0, 0, Vec::new());
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i].1 == new[j].1 {
+            lines.push(Line::Same(new[j].0));
+            (i, j) = (i + 1, j + 1);
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            lines.push(Line::Added(new[j].0));
+            j += 1;
+        } else {
+            lines.push(Line::Removed(old[i].0));
+            i += 1;
+        }
+    }
+    lines
+}
+
+// The diff of the earlier answer against the new one: removed lines in red,
+// added in green, unchanged stretches folded
+pub fn terminal_block(earlier: &ConversationTurn, model: &str, answer: &str) -> String {
+    let lines = diff(&earlier.moonshot_answer, answer);
+    let (removed, added) = lines.iter().fold((0, 0), |(r, a), line| match line {
+        Line::Removed(_) => (r + 1, a),
+        Line::Added(_) => (r, a + 1),
+        Line::Same(_) => (r, a),
+    });
+    let mut block = format!("\n{}\n", ui::heading(tr!("compare.heading", earlier.answer_model, earlier.round, model)));
+    if removed + added == 0 {
+        block.push_str(&ui::success(tr!("compare.same")).to_string());
+        return block;
+    }
+    let near_change = |index: usize| {
+        let start = index.saturating_sub(CONTEXT_LINES);
+        lines[start..(index + CONTEXT_LINES + 1).min(lines.len())].iter().any(|line| !matches!(line, Line::Same(_)))
+    };
+    let mut folded = 0;
+    for (index, line) in lines.iter().enumerate() {
+        match line {
+            Line::Same(_) if !near_change(index) => {
+                folded += 1;
+                continue;
+            }
+            _ if folded > 0 => {
+                block.push_str(&format!("{}\n", ui::status(tr!("compare.folded", folded))));
+                folded = 0;
+            }
+            _ => {}
+        }
+        let shown = match line {
+            Line::Same(text) => ui::status(layout::prefix_lines(text, "  ")).to_string(),
+            Line::Removed(text) => ui::error(layout::prefix_lines(text, "- ")).to_string(),
+            Line::Added(text) => ui::success(layout::prefix_lines(text, "+ ")).to_string(),
+        };
+        block.push_str(&format!("{}\n", shown));
+    }
+    if folded > 0 {
+        block.push_str(&format!("{}\n", ui::status(tr!("compare.folded", folded))));
+    }
+    block.push_str(&ui::status(tr!("compare.summary", removed, added)).to_string());
+    block
+}
@@ -0,0 +1,106 @@
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::borrow::Cow;
+
+use crate::commands;
+use crate::ui;
+
+pub type ReplEditor = Editor<ReplHelper, DefaultHistory>;
+
+// Tab completion and inline hints for slash commands and their arguments
+pub struct ReplHelper;
+
+impl ReplHelper {
+    // (start of the word being completed, matching candidates)
+    fn candidates(line: &str) -> (usize, Vec<String>) {
+        if !line.starts_with('/') {
+            return (0, Vec::new());
+        }
+        match line.split_once(' ') {
+            None => (0, commands::names().filter(|c| c.starts_with(line)).map(str::to_string).collect()),
+            Some((command, arg)) => {
+                let start = command.len() + 1 + (arg.len() - arg.trim_start().len());
+                let arg = arg.trim_start();
+                let matches = commands::find(command).map(|c| c.completions()).unwrap_or_default().into_iter().filter(|c| c.starts_with(arg)).collect();
+                (start, matches)
+            }
+        }
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, matches) = Self::candidates(&line[..pos]);
+        let pairs = matches
+            .into_iter()
+            .map(|m| Pair {
+                display: m.clone(),
+                replacement: m,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+
+    // Grey suffix of the single/first match while typing a command
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+        let (start, matches) = Self::candidates(line);
+        let typed = &line[start..];
+        let first = matches.into_iter().next()?;
+        (first.len() > typed.len()).then(|| first[typed.len()..].to_string())
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(ui::status(hint).to_string())
+    }
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Closest known command to a mistyped one, if it's close enough to be a typo
+pub fn suggest_command(input: &str) -> Option<&'static str> {
+    let typed = input.split_whitespace().next()?.to_lowercase();
+    commands::names()
+        .map(|c| (edit_distance(&typed, c), c))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+// Input that looks like a slash command (as opposed to a question that starts with a path)
+pub fn is_command_like(input: &str) -> bool {
+    input
+        .split_whitespace()
+        .next()
+        .is_some_and(|word| word.len() > 1 && word.starts_with('/') && !word[1..].contains('/'))
+}
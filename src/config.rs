@@ -0,0 +1,1566 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::api;
+use crate::archive::{find_project_dir, PROJECT_SETTINGS_FILE};
+use crate::glossary;
+use crate::i18n::tr;
+use crate::keys::ApiKeys;
+use crate::persona::Persona;
+use crate::rate_limit::RateLimiter;
+use crate::ui;
+
+// Optional settings file (TOML) living next to the key file. A `.aivsai.toml` in
+// the project directory is merged over it, table by table. E.g.:
+//
+//   [retry]
+//   max_attempts = 4
+//
+//   [providers.moonshot]
+//   requests_per_minute = 20
+//   tokens_per_minute = 32000
+//   max_concurrent = 2              # requests in flight at once (e.g. pipelined `batch` stages)
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub retry: RetrySettings,
+    pub network: NetworkSettings,
+    pub providers: HashMap<String, ProviderSettings>,
+    pub usage: UsageSettings,
+    pub answer: AnswerSettings,
+    pub theme: ThemeSettings,
+    pub layout: LayoutSettings,
+    pub pager: PagerSettings,
+    pub notify: NotifySettings,
+    pub archive: ArchiveSettings,
+    pub references: ReferenceSettings,
+    pub summary: SummarySettings,
+    pub i18n: I18nSettings,
+    pub redact: RedactSettings,
+    pub guard: GuardSettings,
+    pub recall: RecallSettings,
+    pub review: ReviewSettings,
+    pub panel: PanelSettings,
+    pub ensemble: EnsembleSettings,
+    pub memory: MemorySettings,
+    pub glossary: GlossarySettings,
+    pub tools: ToolSettings,
+    pub plugins: PluginSettings,
+    pub webhooks: Vec<WebhookSettings>,
+    pub bot: BotSettings,
+    pub bridge: BridgeSettings,
+    pub quota: QuotaSettings,
+    pub share: ShareSettings,
+    pub anki: AnkiSettings,
+    pub pdf: PdfSettings,
+    pub context: ContextSettings,
+    pub telemetry: TelemetrySettings,
+    #[serde(rename = "profile")]
+    pub profiles: HashMap<String, Profile>,
+}
+
+pub const DEFAULT_ANSWER_PROMPT: &str = "You are a helpful AI assistant.";
+pub const DEFAULT_REVIEW_PROMPT: &str =
+    "You are an expert technical reviewer. Your goal is to verify the accuracy and quality of answers provided by other AI models.";
+
+// Named bundles of providers, models, prompts and language, picked with
+// `--profile NAME` or `/profile NAME` (`[profile.default]` applies when none is
+// given). Unset fields keep the defaults: Moonshot answers, DeepSeek reviews in Chinese.
+//
+//   [profile.coding]
+//   answerer = "deepseek"          # provider that answers: "moonshot", "deepseek" or a [providers.NAME]
+//   reviewer = "moonshot"
+//   answer_model = "deepseek-chat"
+//   review_model = "moonshot-v1-32k"
+//   answer_prompt = "You are a senior Rust engineer."
+//   review_prompt = "You are a strict code reviewer."
+//   language = "English"           # language of answers and reviews
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Profile {
+    #[serde(skip)]
+    pub name: String,
+    pub answerer: Option<String>,
+    pub reviewer: Option<String>,
+    pub answer_model: Option<String>,
+    pub review_model: Option<String>,
+    pub answer_prompt: Option<String>,
+    pub review_prompt: Option<String>,
+    pub language: Option<String>,
+    // Set by `/persona`; its prompt replaces `answer_prompt`
+    #[serde(skip)]
+    pub persona: Option<Persona>,
+}
+
+impl Profile {
+    // The answerer's system prompt, with `[answer]` length and style asks and
+    // the `[glossary]` appended
+    pub fn answer_system_prompt(&self, answer: &AnswerSettings) -> String {
+        let prompt = match &self.persona {
+            Some(persona) => persona.prompt.as_str(),
+            None => self.answer_prompt.as_deref().unwrap_or(DEFAULT_ANSWER_PROMPT),
+        };
+        let prompt = match &self.language {
+            Some(language) => format!("{} Answer in {}.", prompt, language),
+            None => prompt.to_string(),
+        };
+        let constraints: Vec<&str> = [answer.length.map(AnswerLength::instruction), answer.style.map(AnswerStyle::instruction)].into_iter().flatten().collect();
+        let prompt = if constraints.is_empty() {
+            prompt
+        } else {
+            format!("{} {}", prompt, constraints.join(" "))
+        };
+        prompt + &glossary::answer_instruction()
+    }
+
+    // `max_tokens` for the answerer: the persona's, else `[answer]`'s
+    pub fn answer_token_limit(&self, answer: &AnswerSettings) -> Option<u32> {
+        self.persona.as_ref().and_then(|persona| persona.max_tokens).or(answer.token_limit())
+    }
+
+    pub fn review_system_prompt(&self) -> String {
+        let prompt = self.review_prompt.as_deref().unwrap_or(DEFAULT_REVIEW_PROMPT);
+        format!("{} You must output your review in {}.{}", prompt, self.review_language(), glossary::review_instruction())
+    }
+
+    pub fn review_language(&self) -> &str {
+        self.language.as_deref().unwrap_or("Chinese")
+    }
+}
+
+// How the answering model is called:
+//
+//   [answer]
+//   stream = false     # wait for the whole answer instead of streaming it
+//   render = false     # leave streamed code blocks and tables as raw markdown
+//   length = "short"   # target length: short, medium or long (default: none)
+//   style = "bullets"  # bullets, prose, or code (code only)
+//   max_tokens = 800   # cap on answer tokens (default: set by `length`)
+//   auto_continue = 2  # times an answer the provider cut off is continued (0 = never)
+//   personas = "~/.aivsai/personas"   # directory of `/persona` definitions (see `persona`)
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct AnswerSettings {
+    pub stream: bool,
+    pub render: bool,
+    pub length: Option<AnswerLength>,
+    pub style: Option<AnswerStyle>,
+    pub max_tokens: Option<u32>,
+    pub auto_continue: usize,
+    pub personas: String,
+}
+
+impl Default for AnswerSettings {
+    fn default() -> Self {
+        Self {
+            stream: true,
+            render: true,
+            length: None,
+            style: None,
+            max_tokens: None,
+            auto_continue: 2,
+            personas: "~/.aivsai/personas".to_string(),
+        }
+    }
+}
+
+impl AnswerSettings {
+    // `max_tokens` for the answerer's requests, if capped
+    pub fn token_limit(&self) -> Option<u32> {
+        self.max_tokens.or(self.length.and_then(AnswerLength::max_tokens))
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnswerLength {
+    Short,
+    Medium,
+    Long,
+}
+
+impl AnswerLength {
+    pub const NAMES: [&'static str; 3] = ["short", "medium", "long"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "short" => Some(Self::Short),
+            "medium" => Some(Self::Medium),
+            "long" => Some(Self::Long),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    fn instruction(self) -> &'static str {
+        match self {
+            Self::Short => "Keep the answer short: at most about 150 words, only what is needed.",
+            Self::Medium => "Keep the answer concise: at most about 400 words.",
+            Self::Long => "Answer thoroughly; length is not a concern.",
+        }
+    }
+
+    // With some headroom, so answers end on their own rather than being cut
+    fn max_tokens(self) -> Option<u32> {
+        match self {
+            Self::Short => Some(600),
+            Self::Medium => Some(1500),
+            Self::Long => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnswerStyle {
+    Bullets,
+    Prose,
+    Code,
+}
+
+impl AnswerStyle {
+    pub const NAMES: [&'static str; 3] = ["bullets", "prose", "code"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "bullets" => Some(Self::Bullets),
+            "prose" => Some(Self::Prose),
+            "code" => Some(Self::Code),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    fn instruction(self) -> &'static str {
+        match self {
+            Self::Bullets => "Format the answer as concise bullet points rather than paragraphs.",
+            Self::Prose => "Write the answer as prose paragraphs, without bullet lists.",
+            Self::Code => "Reply with code only: fenced code blocks, with no explanation beyond brief comments in the code.",
+        }
+    }
+}
+
+// Terminal wrapping for answers and reviews, and optional wrapping of saved files:
+//
+//   [layout]
+//   wrap = true          # wrap to the terminal width
+//   max_width = 100      # never wider than this, even on wide terminals
+//   export_width = 0     # wrap "> " quoted blocks in saved markdown (0 = keep lines as-is)
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct LayoutSettings {
+    pub wrap: bool,
+    pub max_width: usize,
+    pub export_width: usize,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            wrap: true,
+            max_width: 100,
+            export_width: 0,
+        }
+    }
+}
+
+// Show rounds taller than the terminal through a pager (toggle at runtime with /pager):
+//
+//   [pager]
+//   enabled = true
+//   command = "less -R"   # defaults to $PAGER, then `less -R`
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PagerSettings {
+    pub enabled: bool,
+    pub command: Option<String>,
+}
+
+// Let the user know when a slow round finishes (both off by default):
+//
+//   [notify]
+//   bell = true          # terminal bell
+//   desktop = true       # needs a build with `--features desktop-notifications`
+//   min_seconds = 10     # only for rounds that took at least this long
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct NotifySettings {
+    pub bell: bool,
+    pub desktop: bool,
+    pub min_seconds: u64,
+}
+
+impl Default for NotifySettings {
+    fn default() -> Self {
+        Self {
+            bell: false,
+            desktop: false,
+            min_seconds: 10,
+        }
+    }
+}
+
+// Files mentioned as `@src/main.rs` in a question are inlined after it, up to a
+// total token budget (larger files are cut off at a line break). Input piped into
+// `aivsai ask` has its own budget; longer input keeps its start and end:
+//
+//   [references]
+//   enabled = true
+//   max_tokens = 8000
+//   stdin_max_tokens = 8000
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ReferenceSettings {
+    pub enabled: bool,
+    pub max_tokens: u32,
+    pub stdin_max_tokens: u32,
+}
+
+impl Default for ReferenceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_tokens: 8000,
+            stdin_max_tokens: 8000,
+        }
+    }
+}
+
+// Before a question is sent, saved sessions are searched for the same (or a very
+// similar) question, and the earlier reviewed answer is offered instead:
+//
+//   [recall]
+//   enabled = true
+//   threshold = 0.9   # 0..1 similarity; 1.0 = only identical questions
+//   semantic_threshold = 0.92   # also match by meaning, through the [memory] embeddings; unset = off
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct RecallSettings {
+    pub enabled: bool,
+    pub threshold: f64,
+    pub semantic_threshold: Option<f32>,
+}
+
+impl Default for RecallSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.9,
+            semantic_threshold: None,
+        }
+    }
+}
+
+// A folder of exemplary reviews, one or two of which are shown to the reviewer
+// as examples of the expected format and depth (see `exemplars`):
+//
+//   [review]
+//   examples = "~/.aivsai/reviews"   # *.md / *.txt files; unset = no examples
+//   count = 2                        # examples per review, 1 or 2
+//   select = "similar"               # "similar" (closest to the question) or "round-robin"
+//
+// and how the reasoning trace of a reasoning reviewer (deepseek-reasoner) is
+// handled (see `reasoning`):
+//
+//   reasoning = "fold"               # "fold" (a few dimmed lines), "full" or "hide"
+//   save_reasoning = false           # keep it in saved sessions and exports; it can be huge
+//
+// Token caps, like `[answer] max_tokens` for answers (default: uncapped):
+//
+//   max_tokens = 800                 # reviews, panel reviews and ensemble rankings
+//   revision_max_tokens = 400        # requests reworking a review: panel consolidation, English summaries
+//
+// and how hard the reviewer looks (`/strict` changes it for the session):
+//
+//   strictness = "medium"            # "low" (sanity check), "medium" or "paranoid" (line-by-line audit)
+//
+// Reviewing in the background (`/background` toggles it for the session): the
+// prompt comes back once the answer is in, and the review is shown when done.
+//
+//   background = false
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ReviewSettings {
+    pub examples: Option<String>,
+    pub count: usize,
+    pub select: ExampleSelection,
+    pub reasoning: ReasoningDisplay,
+    pub save_reasoning: bool,
+    pub max_tokens: Option<u32>,
+    pub revision_max_tokens: Option<u32>,
+    pub strictness: Strictness,
+    pub background: bool,
+}
+
+impl Default for ReviewSettings {
+    fn default() -> Self {
+        Self {
+            examples: None,
+            count: 2,
+            select: ExampleSelection::Similar,
+            reasoning: ReasoningDisplay::Fold,
+            save_reasoning: false,
+            max_tokens: None,
+            revision_max_tokens: None,
+            strictness: Strictness::Medium,
+            background: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Strictness {
+    Low,
+    #[default]
+    Medium,
+    Paranoid,
+}
+
+impl Strictness {
+    pub const NAMES: [&'static str; 3] = ["low", "medium", "paranoid"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "paranoid" => Some(Self::Paranoid),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    // Appended to the review request; medium is the plain review
+    pub fn instruction(self) -> &'static str {
+        match self {
+            Self::Low => "\n\nThis is a quick sanity check: only point out factual errors, hallucinations and bugs that make the answer wrong. Skip style, wording and minor omissions, and keep the review short.",
+            Self::Medium => "",
+            Self::Paranoid => "\n\nAudit the answer line by line: check every claim and every line of code, including edge cases, error handling, security, performance and outdated information. Point out even small inaccuracies, ambiguities and omissions, and only give `VERDICT: clean` if you find nothing at all.",
+        }
+    }
+
+    // Reviewer temperature (None: the provider default)
+    pub fn temperature(self) -> Option<f32> {
+        match self {
+            Self::Low => Some(0.8),
+            Self::Medium => None,
+            Self::Paranoid => Some(0.2),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningDisplay {
+    #[default]
+    Fold,
+    Full,
+    Hide,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExampleSelection {
+    #[default]
+    Similar,
+    RoundRobin,
+}
+
+// Answerer ensemble for the interactive session: every question goes to all of
+// these answerers at once, the reviewer ranks their answers and the best one
+// becomes the round's answer (see `ensemble`). Ensemble answers aren't streamed
+// and don't use tools.
+//
+//   [ensemble]
+//   answerers = ["moonshot", "deepseek", "ollama:qwen2.5:14b"]   # 2 or 3
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct EnsembleSettings {
+    pub answerers: Vec<String>,
+}
+
+// Extra reviewers that review every answer in the interactive session next to
+// the main one, all at the same time (see `panel`):
+//
+//   [panel]
+//   reviewers = ["moonshot", "ollama:qwen2.5:14b"]   # provider keys, or PROVIDER:MODEL
+//   consolidator = "deepseek"        # optional; merges all the reviews into one
+//   layout = "side-by-side"          # or "stacked"
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PanelSettings {
+    pub reviewers: Vec<String>,
+    pub consolidator: Option<String>,
+    pub layout: PanelLayout,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PanelLayout {
+    #[default]
+    SideBySide,
+    Stacked,
+}
+
+// Long-term memory: reviewed rounds of saved sessions are embedded through an
+// OpenAI-compatible `/embeddings` endpoint, and the closest ones are passed to
+// the answerer with each new question:
+//
+//   [memory]
+//   enabled = true
+//   provider = "ollama"              # a provider key; its chat URL's /chat/completions becomes /embeddings
+//   model = "nomic-embed-text"
+//   url = "http://localhost:11434/v1/embeddings"   # optional, overrides the derived URL
+//   top_k = 3
+//   min_score = 0.75                 # cosine similarity
+//   max_tokens = 1500                # budget for the recalled rounds
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct MemorySettings {
+    pub enabled: bool,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub url: Option<String>,
+    pub top_k: usize,
+    pub min_score: f32,
+    pub max_tokens: u32,
+}
+
+impl Default for MemorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: None,
+            model: None,
+            url: None,
+            top_k: 3,
+            min_score: 0.75,
+            max_tokens: 1500,
+        }
+    }
+}
+
+// A terminology glossary given to both models (see `glossary`):
+//
+//   [glossary]
+//   file = "~/.config/aivsai/glossary.toml"
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct GlossarySettings {
+    pub file: Option<String>,
+}
+
+// Functions the answerer may call in the interactive session (answers are then
+// not streamed). `shell` always asks before running anything:
+//
+//   [tools]
+//   enabled = true
+//   allow = ["calculator", "read_file", "shell"]
+//   max_output_tokens = 4000   # per tool result
+//   shell_timeout_secs = 60    # `shell` commands are killed after this
+//
+//   [[tools.external]]         # any executable speaking the protocol in `tools`
+//   name = "dict"
+//   description = "Look up a word in the team glossary."
+//   command = ["python3", "tools/dict.py"]
+//   parameters = { type = "object", properties = { word = { type = "string" } }, required = ["word"] }
+//   review = true              # offer it to the reviewer as well
+//   timeout_secs = 30
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ToolSettings {
+    pub enabled: bool,
+    pub allow: Vec<String>,
+    pub max_output_tokens: u32,
+    pub shell_timeout_secs: u64,
+    pub external: Vec<ExternalTool>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ExternalTool {
+    pub name: String,
+    pub description: String,
+    // Program and arguments, run in the project directory
+    pub command: Vec<String>,
+    // JSON Schema of the arguments; any object when left out
+    pub parameters: Option<serde_json::Value>,
+    pub review: bool,
+    pub timeout_secs: u64,
+}
+
+impl Default for ExternalTool {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            description: String::new(),
+            command: Vec::new(),
+            parameters: None,
+            review: false,
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl Default for ToolSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow: vec!["calculator".to_string(), "read_file".to_string(), "shell".to_string()],
+            max_output_tokens: 4000,
+            shell_timeout_secs: 60,
+            external: Vec::new(),
+        }
+    }
+}
+
+// WebAssembly plugins hooked into each round (`plugins` feature; see `plugins`):
+//
+//   [plugins]
+//   paths = ["plugins/no-secrets.wasm", "plugins/score.wasm"]
+//   fuel = 1000000000   # instruction budget per hook call
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct PluginSettings {
+    pub paths: Vec<String>,
+    #[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+    pub fuel: u64,
+}
+
+impl Default for PluginSettings {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            fuel: 1_000_000_000,
+        }
+    }
+}
+
+// JSON POSTed after each round and/or save, e.g. to a Slack or Feishu relay
+// (payloads are described in `webhook`):
+//
+//   [[webhooks]]
+//   url = "https://relay.example.com/aivsai"
+//   events = ["round", "save"]
+//   headers = { Authorization = "Bearer ..." }
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub headers: HashMap<String, String>,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            events: vec![WebhookEvent::Round, WebhookEvent::Save],
+            headers: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+    Round,
+    Save,
+}
+
+// Flashcards from `/export anki` (see `anki`):
+//
+//   [anki]
+//   deck = "AIvsAI"               # deck the cards are imported into; `/export anki DECK` overrides it
+//   tags = ["aivsai"]             # added to every card, besides the session's /tag labels
+//   include_unreviewed = false    # also make cards of rounds without a review
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct AnkiSettings {
+    pub deck: String,
+    pub tags: Vec<String>,
+    pub include_unreviewed: bool,
+}
+
+impl Default for AnkiSettings {
+    fn default() -> Self {
+        Self {
+            deck: "AIvsAI".to_string(),
+            tags: vec!["aivsai".to_string()],
+            include_unreviewed: false,
+        }
+    }
+}
+
+// `/export pdf` (see `pdf`): the session is rendered as HTML and handed to an
+// external converter. `{input}` and `{output}` in the command are replaced by
+// the HTML and PDF paths.
+//
+//   [pdf]
+//   command = "weasyprint {input} {output}"   # or "wkhtmltopdf --encoding utf-8 {input} {output}",
+//                                             # "chromium --headless --no-pdf-header-footer --print-to-pdf={output} {input}"
+//   fonts = ["Noto Sans CJK SC", "Source Han Sans SC", "PingFang SC", "Microsoft YaHei"]   # for Chinese text, first installed wins
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct PdfSettings {
+    pub command: String,
+    pub fonts: Vec<String>,
+}
+
+impl Default for PdfSettings {
+    fn default() -> Self {
+        Self {
+            command: "weasyprint {input} {output}".to_string(),
+            fonts: ["Noto Sans CJK SC", "Source Han Sans SC", "PingFang SC", "Microsoft YaHei"].map(str::to_string).to_vec(),
+        }
+    }
+}
+
+// `aivsai bot` (platform secrets come from the environment; see `bot`):
+//
+//   [bot]
+//   listen = "127.0.0.1:8787"
+//   path = "/"                                       # callback URL path
+//   feishu_base_url = "https://open.larksuite.com"   # Lark outside China
+//   metrics = true                                   # Prometheus metrics at /metrics (see `metrics`)
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct BotSettings {
+    pub listen: String,
+    pub path: String,
+    pub feishu_base_url: String,
+    pub metrics: bool,
+}
+
+impl Default for BotSettings {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:8787".to_string(),
+            path: "/".to_string(),
+            feishu_base_url: "https://open.feishu.cn".to_string(),
+            metrics: false,
+        }
+    }
+}
+
+// `aivsai bridge` (tokens come from the environment; see `bridge`):
+//
+//   [bridge]
+//   listen = "127.0.0.1:8788"   # Discord interactions endpoint
+//   path = "/"
+//   edit_interval_ms = 1500     # how often a streaming answer's message is edited
+//   allow = ["123456789"]       # Telegram chat / Discord channel ids (default: any)
+//   metrics = true              # Prometheus metrics at /metrics on the Discord listener
+//   per_user = false            # one conversation shared by a group chat, not one per member
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct BridgeSettings {
+    pub listen: String,
+    pub path: String,
+    pub edit_interval_ms: u64,
+    pub allow: Vec<String>,
+    pub metrics: bool,
+    pub per_user: bool,
+}
+
+impl Default for BridgeSettings {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:8788".to_string(),
+            path: "/".to_string(),
+            edit_interval_ms: 1500,
+            allow: Vec::new(),
+            metrics: false,
+            per_user: true,
+        }
+    }
+}
+
+// Daily token quotas per chat user in `bot` and `bridge` (see `quota`):
+//
+//   [quota]
+//   daily_tokens = 200000
+//   users = { "U024BE7LH" = 1000000 }
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct QuotaSettings {
+    pub daily_tokens: Option<u64>,
+    pub users: HashMap<String, u64>,
+}
+
+// Read-only session links from `aivsai share` (see `share`):
+//
+//   [share]
+//   base_url = "https://bot.example.com"   # where the bot/bridge listener is reachable
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ShareSettings {
+    pub base_url: String,
+}
+
+// A short English TL;DR under reviews written in Chinese, from an extra (cheap) call:
+//
+//   [summary]
+//   english = true
+//   provider = "deepseek"      # who writes it (default: deepseek)
+//   model = "deepseek-chat"    # default: that provider's default model
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SummarySettings {
+    pub english: bool,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+// Language of the interface (banners, prompts, statuses, export headings). Without
+// a locale it follows LC_ALL / LC_MESSAGES / LANG; zh-CN and en-US are built in:
+//
+//   [i18n]
+//   locale = "zh-CN"
+//   catalog = "~/.aivsai-ja.toml"   # messages for any other locale, missing keys fall back to English
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct I18nSettings {
+    pub locale: Option<String>,
+    pub catalog: Option<String>,
+}
+
+// Secrets and personal data found in outbound text (questions, attached files,
+// piped input, diffs) are masked before sending; see `redact` for the built-in patterns:
+//
+//   [redact]
+//   mode = "confirm"          # "mask" (default), "confirm" (ask first in the REPL) or "off"
+//   disable = ["email"]       # built-in patterns to skip
+//
+//   [redact.patterns]         # extra regexes; with a capture group only the group is masked
+//   employee_id = "EMP-[0-9]{6}"
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RedactSettings {
+    pub mode: RedactMode,
+    pub disable: Vec<String>,
+    pub patterns: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactMode {
+    #[default]
+    Mask,
+    Confirm,
+    Off,
+}
+
+// Moderation of questions before they are sent and of answers before they are
+// shown or exported: local rules, and optionally a provider's OpenAI-compatible
+// `/moderations` endpoint (see `guard`):
+//
+//   [guard]
+//   mode = "flag"                    # "off" (default), "flag" (warn and mark the round) or "block"
+//   keywords = ["make a bomb"]       # case-insensitive
+//   provider = "openai"              # optional; its chat URL's /chat/completions becomes /moderations
+//   model = "omni-moderation-latest" # optional
+//   url = "https://api.openai.com/v1/moderations"   # optional, overrides the derived URL
+//
+//   [guard.rules]                    # regexes, by name
+//   weapons = "(?i)\\b(?:pipe bomb|nerve agent)s?\\b"
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct GuardSettings {
+    pub mode: GuardMode,
+    pub keywords: Vec<String>,
+    pub rules: HashMap<String, String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardMode {
+    #[default]
+    Off,
+    Flag,
+    Block,
+}
+
+// Where saved conversations (and the usage ledger) go, relative to the project
+// directory; mostly useful in a project's .aivsai.toml:
+//
+//   [archive]
+//   dir = "docs/ai-conversations"
+//
+// and, with a build that has `--features encryption`, whether saved files are
+// encrypted with age:
+//
+//   encryption = "passphrase"   # or "key"
+//   key_file = "~/.config/aivsai/key.txt"   # age identity, for "key"
+//
+// and whether /save writes one markdown file per session or appends to a daily
+// journal (`2024-06-12.md`, one header per session; the JSON copy for /load is
+// still kept per session):
+//
+//   save = "journal"            # default "session"
+//
+// and whether each session keeps an append-only event log (see `events`):
+//
+//   events = true
+//
+// and whether exports mark each round with the models that wrote it (see
+// `provenance`):
+//
+//   provenance = true
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ArchiveSettings {
+    pub dir: Option<String>,
+    pub encryption: Option<EncryptionMode>,
+    pub key_file: Option<String>,
+    pub retention: RetentionSettings,
+    pub save: SaveMode,
+    pub events: bool,
+    pub provenance: bool,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SaveMode {
+    #[default]
+    Session,
+    Journal,
+}
+
+// Default policy for `aivsai sessions prune`, optionally applied after every save:
+//
+//   [archive.retention]
+//   older_than = "90d"    # days ("d") or weeks ("w") since the session was last saved
+//   keep_starred = true   # never delete sessions with a starred round
+//   auto = true           # prune after each /save
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RetentionSettings {
+    pub older_than: Option<String>,
+    pub keep_starred: bool,
+    pub auto: bool,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EncryptionMode {
+    Passphrase,
+    Key,
+}
+
+// Output styles per role: a color name ("blue", "bright_black"), a hex color
+// ("#005f87") and/or modifiers (bold, dimmed, italic, underline), or "plain":
+//
+//   [theme]
+//   answerer = "#005f87 bold"
+//   status = "bright_black"
+//   code_theme = "InspiredGitHub"   # syntax highlighting theme for code blocks, or "none"
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub user: String,
+    pub answerer: String,
+    pub reviewer: String,
+    pub heading: String,
+    pub status: String,
+    pub success: String,
+    pub warning: String,
+    pub error: String,
+    pub code: String,
+    pub code_theme: String,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            user: "plain".to_string(),
+            answerer: "blue bold".to_string(),
+            reviewer: "magenta bold".to_string(),
+            heading: "cyan bold".to_string(),
+            status: "dimmed".to_string(),
+            success: "green".to_string(),
+            warning: "yellow".to_string(),
+            error: "red".to_string(),
+            code: "green".to_string(),
+            code_theme: "base16-ocean.dark".to_string(),
+        }
+    }
+}
+
+// Per-provider limits and overrides. A table for any other name with a
+// `base_url` adds an OpenAI-compatible provider, e.g. a local Ollama:
+//
+//   [providers.ollama]
+//   base_url = "http://localhost:11434/v1/chat/completions"
+//   model = "qwen2.5:14b"
+//   api_key_env = "OLLAMA_API_KEY"   # optional; local servers usually need no key
+//
+// Several keys for one provider (any provider, built-in ones included) raise
+// its effective rate limit; usage is recorded per key:
+//
+//   [providers.deepseek]
+//   api_key_envs = ["DEEPSEEK_API_KEY", "DEEPSEEK_API_KEY_2"]
+//   rotation = "round-robin"         # or "on-429": stay on a key until it is rate-limited
+//   requests_per_minute = 120        # limits apply to the provider, so raise them to match
+//
+// Enterprise accounts and API gateways may need extra headers or query
+// parameters on every request; "env:NAME" takes a value from the environment:
+//
+//   [providers.openai]
+//   organization = "org-123"         # sent as OpenAI-Organization
+//   project = "proj_456"             # sent as OpenAI-Project
+//   headers = { "X-Gateway-Key" = "env:GATEWAY_KEY" }
+//   query = { "api-version" = "2024-06-01" }
+//   compress_requests = true         # gzip large request bodies; only for servers that accept it
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ProviderSettings {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+    pub max_concurrent: Option<usize>,
+    pub base_url: Option<String>,
+    pub model: Option<String>,
+    pub name: Option<String>,
+    pub api_key_env: Option<String>,
+    pub api_key_envs: Vec<String>,
+    pub rotation: KeyRotation,
+    pub organization: Option<String>,
+    pub project: Option<String>,
+    pub headers: HashMap<String, String>,
+    pub query: HashMap<String, String>,
+    pub compress_requests: bool,
+}
+
+impl ProviderSettings {
+    // `headers` plus the organization/project ones, sorted by name, with
+    // "env:NAME" values looked up
+    fn extra_headers(&self, key: &str) -> Result<Vec<(String, String)>> {
+        let mut headers: Vec<(String, String)> = self.headers.iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+        if let Some(organization) = &self.organization {
+            headers.push(("OpenAI-Organization".to_string(), organization.clone()));
+        }
+        if let Some(project) = &self.project {
+            headers.push(("OpenAI-Project".to_string(), project.clone()));
+        }
+        headers.sort();
+        headers
+            .into_iter()
+            .map(|(name, value)| Ok((name, resolve_env(key, &value)?)))
+            .collect()
+    }
+
+    fn query_params(&self, key: &str) -> Result<Vec<(String, String)>> {
+        let mut query: Vec<(String, String)> = self.query.iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+        query.sort();
+        query.into_iter().map(|(name, value)| Ok((name, resolve_env(key, &value)?))).collect()
+    }
+}
+
+fn resolve_env(provider: &str, value: &str) -> Result<String> {
+    match value.strip_prefix("env:") {
+        Some(var) => env::var(var).context(format!("[providers.{}] needs {} to be set", provider, var)),
+        None => Ok(value.to_string()),
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum KeyRotation {
+    #[default]
+    #[serde(rename = "round-robin")]
+    RoundRobin,
+    #[serde(rename = "on-429")]
+    On429,
+}
+
+// The HTTP client shared by all provider requests. Connections are kept open
+// between rounds so later requests skip the TCP and TLS handshakes:
+//
+//   [network]
+//   pool_max_idle_per_host = 4
+//   pool_idle_timeout_secs = 90      # close pooled connections idle this long
+//   tcp_keepalive_secs = 60
+//   http2 = "auto"                   # "auto" (negotiated over TLS), "prior-knowledge" (h2c) or "off"
+//   http2_keepalive_secs = 30        # pings that keep an idle HTTP/2 connection alive; 0 = off
+//   connect_timeout_secs = 10
+//   compress_min_bytes = 32768       # gzip bodies at least this big for providers with compress_requests
+//
+// Responses are always accepted gzip- or brotli-compressed.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_secs: u64,
+    pub tcp_keepalive_secs: u64,
+    pub http2: Http2Mode,
+    pub http2_keepalive_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub compress_min_bytes: usize,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: 4,
+            pool_idle_timeout_secs: 90,
+            tcp_keepalive_secs: 60,
+            http2: Http2Mode::Auto,
+            http2_keepalive_secs: 30,
+            connect_timeout_secs: 10,
+            compress_min_bytes: 32 * 1024,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Http2Mode {
+    #[default]
+    Auto,
+    PriorKnowledge,
+    Off,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct RetrySettings {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay_ms: 1000,
+            max_delay_ms: 30_000,
+        }
+    }
+}
+
+// Prices per million tokens, used by `aivsai usage` to estimate cost:
+//
+//   [usage]
+//   currency = "CNY"
+//
+//   [usage.pricing."deepseek-chat"]
+//   input_per_million = 2.0
+//   output_per_million = 8.0
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct UsageSettings {
+    pub currency: String,
+    pub pricing: HashMap<String, ModelPrice>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl Default for UsageSettings {
+    fn default() -> Self {
+        Self {
+            currency: "CNY".to_string(),
+            pricing: HashMap::new(),
+        }
+    }
+}
+
+impl UsageSettings {
+    // Configured price for a model, falling back to built-in list prices (CNY)
+    pub fn price_for(&self, model: &str) -> Option<ModelPrice> {
+        if let Some(price) = self.pricing.get(model) {
+            return Some(price.clone());
+        }
+        let (input, output) = match model {
+            "moonshot-v1-8k" => (12.0, 12.0),
+            "moonshot-v1-32k" => (24.0, 24.0),
+            "moonshot-v1-128k" => (60.0, 60.0),
+            "deepseek-chat" => (2.0, 8.0),
+            "deepseek-reasoner" => (4.0, 16.0),
+            _ => return None,
+        };
+        Some(ModelPrice {
+            input_per_million: input,
+            output_per_million: output,
+        })
+    }
+}
+
+// The context meter in front of the `User >` prompt (see `meter`):
+//
+//   [context]
+//   meter = true                        # false hides it
+//
+//   [context.windows]                   # context window in tokens, for models not built in
+//   "qwen2.5:14b" = 32768
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ContextSettings {
+    pub meter: bool,
+    pub windows: HashMap<String, u32>,
+}
+
+impl Default for ContextSettings {
+    fn default() -> Self {
+        Self {
+            meter: true,
+            windows: HashMap::new(),
+        }
+    }
+}
+
+impl ContextSettings {
+    // Configured context window of a model, falling back to the built-in ones
+    pub fn window_for(&self, model: &str) -> Option<u32> {
+        if let Some(&window) = self.windows.get(model) {
+            return Some(window);
+        }
+        match model {
+            "moonshot-v1-8k" => Some(8_192),
+            "moonshot-v1-32k" => Some(32_768),
+            "moonshot-v1-128k" => Some(131_072),
+            "deepseek-chat" | "deepseek-reasoner" => Some(65_536),
+            _ => None,
+        }
+    }
+}
+
+// Traces of the pipeline stages for an OTLP collector (see `telemetry`); needs
+// a build with `--features otlp`:
+//
+//   [telemetry]
+//   otlp_endpoint = "http://localhost:4317"
+//   service_name = "aivsai-bot"        # default "aivsai"
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: "aivsai".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn get_settings_path() -> Result<PathBuf> {
+        let home = env::var("HOME").context("Could not find HOME environment variable")?;
+        Ok(std::path::Path::new(&home).join(".ai_vs_ai.toml"))
+    }
+
+    // The current project's .aivsai.toml, if there is one
+    pub fn project_settings_path() -> Option<PathBuf> {
+        let path = find_project_dir().ok()?.join(PROJECT_SETTINGS_FILE);
+        path.exists().then_some(path)
+    }
+
+    pub fn load() -> Result<Self> {
+        let mut merged = toml::Table::new();
+        let global = Some(Self::get_settings_path()?).filter(|path| path.exists());
+        for path in global.into_iter().chain(Self::project_settings_path()) {
+            let text = std::fs::read_to_string(&path)
+                .context(format!("Failed to read settings file at {:?}", path))?;
+            let table: toml::Table = toml::from_str(&text).context(format!("Invalid settings file at {:?}", path))?;
+            // Check each file on its own so errors point at the right one
+            toml::Value::Table(table.clone())
+                .try_into::<Settings>()
+                .context(format!("Invalid settings file at {:?}", path))?;
+            merge_tables(&mut merged, table);
+        }
+        toml::Value::Table(merged).try_into().context("Invalid settings")
+    }
+
+    pub fn provider(&self, key: &str) -> ProviderSettings {
+        self.providers.get(key).cloned().unwrap_or_default()
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    // The named profile; without a name `[profile.default]`, or the built-in defaults
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile> {
+        let key = name.unwrap_or("default");
+        let mut profile = match self.profiles.get(key) {
+            Some(profile) => profile.clone(),
+            None if name.is_none() => Profile::default(),
+            None => anyhow::bail!("Unknown profile {:?} (configured: {})", key, self.profile_names().join(", ")),
+        };
+        profile.name = key.to_string();
+        Ok(profile)
+    }
+}
+
+// Overlay `top` onto `base`, merging nested tables key by key
+fn merge_tables(base: &mut toml::Table, top: toml::Table) {
+    for (key, value) in top {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(top_table)) => merge_tables(base_table, top_table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+// Clones share the key rotation and the rate limiter, so a copy handed to a
+// background task still counts against the provider's budget
+#[derive(Clone)]
+pub struct AiConfig {
+    pub keys: Arc<ApiKeys>,
+    pub base_url: String,
+    pub model: String,
+    pub name: String,
+    pub retry: RetrySettings,
+    pub limiter: Arc<RateLimiter>,
+    // Sent as `max_tokens` when set: the token cap of the role (`[answer]` /
+    // `[review]` max_tokens)
+    pub max_tokens: Option<u32>,
+    // Sampling temperature (a `/persona` may set it); None = the default
+    pub temperature: Option<f32>,
+    // Follow-up requests allowed when a response is cut off by the provider's
+    // own length limit (`[answer] auto_continue`; 0 for other roles)
+    pub auto_continue: usize,
+    // Added to every request (`[providers.KEY] headers`, `query`, organization, project)
+    pub headers: Vec<(String, String)>,
+    pub query: Vec<(String, String)>,
+    // Request bodies of at least this many bytes are sent gzip-compressed
+    pub compress_over: Option<usize>,
+}
+
+impl AiConfig {
+    pub fn get_config_path() -> Result<std::path::PathBuf> {
+        let home = env::var("HOME").context("Could not find HOME environment variable")?;
+        let config_path = std::path::Path::new(&home).join(".ai_vs_ai_config");
+        Ok(config_path)
+    }
+
+    fn get_api_key(env_var: &str, provider_name: &str) -> Result<String> {
+        // 1. Try to get from environment (loaded from config file)
+        if let Ok(key) = env::var(env_var) {
+            if !key.is_empty() {
+                return Ok(key);
+            }
+        }
+
+        // 2. Prompt user using standard io (not rustyline, as this is one-time setup)
+        print!("{}", tr!("prompt.api_key", provider_name));
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let trimmed = input.trim().to_string();
+        if trimmed.is_empty() {
+            anyhow::bail!("API Key for {} cannot be empty", provider_name);
+        }
+
+        // 3. Persist to global config file
+        let config_path = Self::get_config_path()?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config_path)
+            .context(format!("Failed to open config file at {:?}", config_path))?;
+
+        writeln!(file, "{}={}", env_var, trimmed)?;
+        println!("{}", ui::status(tr!("config.key_saved", env_var, config_path.display())));
+
+        // Also set it in the current process environment so subsequent calls work
+        env::set_var(env_var, &trimmed);
+
+        Ok(trimmed)
+    }
+
+    // Apply `[providers.KEY]` overrides and limits. The destination is checked
+    // against --local-only before any API key is asked for; `api_key_envs`
+    // replaces the usual single key.
+    fn build(
+        settings: &Settings,
+        key: &str,
+        name: &str,
+        base_url: &str,
+        model: &str,
+        api_key: impl FnOnce() -> Result<String>,
+    ) -> Result<Self> {
+        let provider = settings.provider(key);
+        let base_url = provider.base_url.clone().unwrap_or_else(|| base_url.to_string());
+        api::check_destination(name, &base_url)?;
+        let keys = if provider.api_key_envs.is_empty() {
+            ApiKeys::single(api_key()?)
+        } else {
+            ApiKeys::from_env(key, &provider.api_key_envs, provider.rotation)?
+        };
+        Ok(Self {
+            headers: provider.extra_headers(key)?,
+            query: provider.query_params(key)?,
+            compress_over: provider.compress_requests.then_some(settings.network.compress_min_bytes),
+            keys: Arc::new(keys),
+            base_url,
+            model: provider.model.unwrap_or_else(|| model.to_string()),
+            name: name.to_string(),
+            retry: settings.retry.clone(),
+            limiter: Arc::new(RateLimiter::new(provider.requests_per_minute, provider.tokens_per_minute, provider.max_concurrent)),
+            max_tokens: None,
+            temperature: None,
+            auto_continue: 0,
+        })
+    }
+
+    pub fn moonshot(settings: &Settings) -> Result<Self> {
+        Self::build(
+            settings,
+            "moonshot",
+            "Moonshot AI",
+            "https://api.moonshot.cn/v1/chat/completions",
+            "moonshot-v1-8k",
+            || Self::get_api_key("MOONSHOT_API_KEY", "Moonshot AI"),
+        )
+    }
+
+    pub fn deepseek(settings: &Settings) -> Result<Self> {
+        Self::build(
+            settings,
+            "deepseek",
+            "DeepSeek AI",
+            "https://api.deepseek.com/chat/completions",
+            "deepseek-chat",
+            || Self::get_api_key("DEEPSEEK_API_KEY", "DeepSeek AI"),
+        )
+    }
+
+    // A provider defined entirely in settings (`[providers.NAME]` with a base_url)
+    fn custom(settings: &Settings, key: &str) -> Result<Self> {
+        let custom = settings.provider(key);
+        if custom.base_url.is_none() {
+            anyhow::bail!(
+                "Unknown provider {:?} (available: moonshot, deepseek, or a [providers.NAME] table with base_url)",
+                key
+            );
+        }
+        let model = custom.model.clone().context(format!("[providers.{}] needs a model", key))?;
+        let name = custom.name.clone().unwrap_or_else(|| key.to_string());
+        Self::build(settings, key, &name, "", &model, || {
+            Ok(custom.api_key_env.as_deref().and_then(|var| env::var(var).ok()).unwrap_or_default())
+        })
+    }
+
+    pub fn provider(settings: &Settings, key: &str) -> Result<Self> {
+        match key {
+            "moonshot" => Self::moonshot(settings),
+            "deepseek" => Self::deepseek(settings),
+            other => Self::custom(settings, other),
+        }
+    }
+
+    // Provider for English review summaries, when `[summary] english` is on
+    // A provider key, or PROVIDER:MODEL for another model of the provider
+    // ("ollama:qwen2.5:14b" is the ollama provider with model qwen2.5:14b)
+    pub fn from_spec(settings: &Settings, spec: &str) -> Result<Self> {
+        let (provider, model) = match spec.split_once(':') {
+            Some((provider, model)) => (provider, Some(model)),
+            None => (spec, None),
+        };
+        let mut config = Self::provider(settings, provider)?;
+        if let Some(model) = model {
+            config.model = model.to_string();
+        }
+        Ok(config)
+    }
+
+    pub fn summarizer(settings: &Settings) -> Result<Option<Self>> {
+        let summary = &settings.summary;
+        if !summary.english {
+            return Ok(None);
+        }
+        let mut config = Self::provider(settings, summary.provider.as_deref().unwrap_or("deepseek"))?;
+        if let Some(model) = &summary.model {
+            config.model = model.clone();
+        }
+        config.max_tokens = settings.review.revision_max_tokens;
+        Ok(Some(config))
+    }
+
+    // Embeddings endpoint for `[memory]`, when enabled
+    pub fn embedder(settings: &Settings) -> Result<Option<Self>> {
+        let memory = &settings.memory;
+        if !memory.enabled {
+            return Ok(None);
+        }
+        let provider = memory.provider.as_deref().context("[memory] needs a provider with an embeddings endpoint")?;
+        let mut config = Self::provider(settings, provider)?;
+        config.base_url = match &memory.url {
+            Some(url) => url.clone(),
+            None => match config.base_url.strip_suffix("/chat/completions") {
+                Some(root) => format!("{}/embeddings", root),
+                None => anyhow::bail!("[memory] can't derive an embeddings URL from {}; set [memory] url", config.base_url),
+            },
+        };
+        api::check_destination(&config.name, &config.base_url)?;
+        config.model = memory.model.clone().context("[memory] needs an embedding model")?;
+        Ok(Some(config))
+    }
+
+    // `[guard]` moderation endpoint, if a provider is set
+    pub fn moderator(settings: &Settings) -> Result<Option<Self>> {
+        let guard = &settings.guard;
+        let Some(provider) = guard.provider.as_deref() else {
+            return Ok(None);
+        };
+        let mut config = Self::provider(settings, provider)?;
+        config.base_url = match &guard.url {
+            Some(url) => url.clone(),
+            None => match config.base_url.strip_suffix("/chat/completions") {
+                Some(root) => format!("{}/moderations", root),
+                None => anyhow::bail!("[guard] can't derive a moderations URL from {}; set [guard] url", config.base_url),
+            },
+        };
+        api::check_destination(&config.name, &config.base_url)?;
+        // Empty: the endpoint's default model
+        config.model = guard.model.clone().unwrap_or_default();
+        Ok(Some(config))
+    }
+
+    // The (answerer, reviewer) pair a profile asks for
+    pub fn pair(settings: &Settings, profile: &Profile) -> Result<(Self, Self)> {
+        let mut answerer = Self::provider(settings, profile.answerer.as_deref().unwrap_or("moonshot"))?;
+        let mut reviewer = Self::provider(settings, profile.reviewer.as_deref().unwrap_or("deepseek"))?;
+        if let Some(model) = &profile.answer_model {
+            answerer.model = model.clone();
+        }
+        if let Some(model) = &profile.review_model {
+            reviewer.model = model.clone();
+        }
+        answerer.max_tokens = profile.answer_token_limit(&settings.answer);
+        answerer.temperature = profile.persona.as_ref().and_then(|persona| persona.temperature);
+        answerer.auto_continue = settings.answer.auto_continue;
+        reviewer.max_tokens = settings.review.max_tokens;
+        reviewer.temperature = settings.review.strictness.temperature();
+        Ok((answerer, reviewer))
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+#[cfg(feature = "encryption")]
+use anyhow::Context;
+#[cfg(feature = "encryption")]
+use std::sync::OnceLock;
+
+use crate::config::ArchiveSettings;
+#[cfg(feature = "encryption")]
+use crate::config::EncryptionMode;
+#[cfg(feature = "encryption")]
+use crate::i18n::tr;
+
+// Optional encryption of saved conversations with age (https://age-encryption.org),
+// so the files can also be opened with the `age` command-line tool:
+//
+//   age -d conversations/2025-03-01_14-30-25_question.md.age
+//
+// Encrypted files get an extra `.age` extension. The passphrase comes from
+// $AIVSAI_PASSPHRASE or is asked for once at startup; a key file holds an age
+// identity (`age-keygen -o key.txt`).
+
+pub const EXTENSION: &str = "age";
+
+pub fn is_encrypted_name(name: &str) -> bool {
+    name.strip_suffix(EXTENSION).is_some_and(|rest| rest.ends_with('.'))
+}
+
+#[cfg(feature = "encryption")]
+enum Secret {
+    Passphrase(age::secrecy::SecretString),
+    Identity(age::x25519::Identity),
+}
+
+#[cfg(feature = "encryption")]
+static SECRET: OnceLock<Secret> = OnceLock::new();
+
+// Passphrase asked for to read an old encrypted file while encryption is off;
+// kept apart so it never turns encryption on for new saves
+#[cfg(feature = "encryption")]
+static READ_SECRET: OnceLock<Secret> = OnceLock::new();
+
+#[cfg(feature = "encryption")]
+fn ask_passphrase() -> Result<age::secrecy::SecretString> {
+    use std::io::IsTerminal;
+    if let Ok(passphrase) = std::env::var("AIVSAI_PASSPHRASE") {
+        return Ok(passphrase.into());
+    }
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Encrypted archive: set AIVSAI_PASSPHRASE when stdin is not a terminal");
+    }
+    let passphrase = rpassword::prompt_password(tr!("crypto.passphrase")).context("Failed to read passphrase")?;
+    if passphrase.is_empty() {
+        anyhow::bail!("The archive passphrase cannot be empty");
+    }
+    Ok(passphrase.into())
+}
+
+#[cfg(feature = "encryption")]
+fn read_identity(path: &str) -> Result<age::x25519::Identity> {
+    let path = match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => std::path::Path::new(&home).join(rest),
+        _ => std::path::PathBuf::from(path),
+    };
+    let text = std::fs::read_to_string(&path).context(format!("Failed to read key file {:?}", path))?;
+    let key = text
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("AGE-SECRET-KEY-"))
+        .context(format!("No AGE-SECRET-KEY line in {:?}", path))?;
+    key.parse().map_err(|e| anyhow::anyhow!("Invalid age identity in {:?}: {}", path, e))
+}
+
+#[cfg(feature = "encryption")]
+fn load_secret(settings: &ArchiveSettings) -> Result<Secret> {
+    match (settings.encryption, &settings.key_file) {
+        (Some(EncryptionMode::Key), Some(path)) => Ok(Secret::Identity(read_identity(path)?)),
+        (Some(EncryptionMode::Key), None) => anyhow::bail!("[archive] encryption = \"key\" needs key_file"),
+        _ => Ok(Secret::Passphrase(ask_passphrase()?)),
+    }
+}
+
+// With encryption on, get the passphrase or key now so later saves (and the
+// crash-recovery hooks) never have to prompt
+#[cfg(feature = "encryption")]
+pub fn init(settings: &ArchiveSettings) -> Result<()> {
+    if settings.encryption.is_some() {
+        let _ = SECRET.set(load_secret(settings)?);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn init(settings: &ArchiveSettings) -> Result<()> {
+    if settings.encryption.is_some() || settings.key_file.is_some() {
+        anyhow::bail!("[archive] encryption needs a build with `--features encryption`");
+    }
+    Ok(())
+}
+
+#[cfg(feature = "encryption")]
+pub fn enabled() -> bool {
+    SECRET.get().is_some()
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn enabled() -> bool {
+    false
+}
+
+#[cfg(feature = "encryption")]
+pub fn encrypt(plaintext: &[u8]) -> Result<Vec<u8>> {
+    let encrypted = match SECRET.get().context("Archive encryption is not set up")? {
+        Secret::Passphrase(passphrase) => age::encrypt(&age::scrypt::Recipient::new(passphrase.clone()), plaintext),
+        Secret::Identity(identity) => age::encrypt(&identity.to_public(), plaintext),
+    };
+    encrypted.context("Failed to encrypt")
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn encrypt(_plaintext: &[u8]) -> Result<Vec<u8>> {
+    anyhow::bail!("Encryption needs a build with `--features encryption`")
+}
+
+// Decrypt an `.age` file; without a configured secret (encryption since turned
+// off) the passphrase is asked for the first time it is needed
+#[cfg(feature = "encryption")]
+pub fn decrypt(ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let secret = match SECRET.get().or(READ_SECRET.get()) {
+        Some(secret) => secret,
+        None => {
+            let secret = Secret::Passphrase(ask_passphrase()?);
+            READ_SECRET.get_or_init(|| secret)
+        }
+    };
+    let decrypted = match secret {
+        Secret::Passphrase(passphrase) => age::decrypt(&age::scrypt::Identity::new(passphrase.clone()), ciphertext),
+        Secret::Identity(identity) => age::decrypt(identity, ciphertext),
+    };
+    decrypted.context("Failed to decrypt (wrong passphrase or key?)")
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn decrypt(_ciphertext: &[u8]) -> Result<Vec<u8>> {
+    anyhow::bail!("This file is encrypted; reading it needs a build with `--features encryption`")
+}
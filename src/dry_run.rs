@@ -0,0 +1,107 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::api::estimate_tokens;
+
+// `--dry-run`: provider requests are answered on the spot with canned responses,
+// so prompts, pipelines, exports and the terminal UI can be tried without
+// network, keys or tokens. Responses are deterministic: an answer echoes the
+// request, a review also ends with the `SCORES:` and `VERDICT:` lines its prompt
+// asks for, moderation passes everything, and embeddings are letter counts.
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    DRY_RUN.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+// Characters of the question quoted back in a canned answer
+const ECHO_CHARS: usize = 200;
+
+// Dimensions of a canned embedding
+const DIMENSIONS: usize = 16;
+
+fn chat_content(request: &Value) -> String {
+    let messages = request["messages"].as_array().cloned().unwrap_or_default();
+    let prompt: String = messages.iter().filter_map(|message| message["content"].as_str()).collect::<Vec<_>>().join("\n");
+    let question = messages
+        .iter()
+        .rev()
+        .find(|message| message["role"] == "user")
+        .and_then(|message| message["content"].as_str())
+        .unwrap_or_default();
+    let mut echo: String = question.chars().take(ECHO_CHARS).collect();
+    if question.chars().count() > ECHO_CHARS {
+        echo.push('…');
+    }
+    let mut content = format!(
+        "[dry run] {} would respond here ({} messages, about {} tokens).\n\n> {}",
+        request["model"].as_str().unwrap_or_default(),
+        messages.len(),
+        estimate_tokens(&prompt),
+        echo.replace('\n', "\n> ")
+    );
+    if prompt.contains("SCORES:") {
+        content.push_str("\n\nSCORES: accuracy=7 completeness=7 clarity=7 code=n/a");
+    }
+    if prompt.contains("VERDICT:") {
+        content.push_str("\nVERDICT: clean");
+    }
+    content
+}
+
+fn usage(request: &Value, content: &str) -> Value {
+    json!({
+        "prompt_tokens": estimate_tokens(&request.to_string()),
+        "completion_tokens": estimate_tokens(content),
+    })
+}
+
+fn embedding(text: &str) -> Vec<f32> {
+    let mut vector = [0.0f32; DIMENSIONS];
+    for c in text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase) {
+        vector[c as usize % DIMENSIONS] += 1.0;
+    }
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt().max(1.0);
+    vector.iter().map(|x| x / norm).collect()
+}
+
+// The body a provider might have sent for `request`: a chat completion (as
+// server-sent events when it asked to stream), a moderation result or embeddings
+pub fn respond(request: &impl Serialize) -> Vec<String> {
+    let request = serde_json::to_value(request).unwrap_or(Value::Null);
+    if request.get("messages").is_none() {
+        let body = match &request["input"] {
+            Value::Array(inputs) => {
+                let data: Vec<Value> = inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(index, input)| json!({ "embedding": embedding(input.as_str().unwrap_or_default()), "index": index }))
+                    .collect();
+                json!({ "data": data, "usage": usage(&request, "") })
+            }
+            _ => json!({ "results": [{ "flagged": false, "categories": {} }] }),
+        };
+        return vec![body.to_string()];
+    }
+    let content = chat_content(&request);
+    if request["stream"].as_bool() != Some(true) {
+        let body = json!({
+            "choices": [{ "message": { "role": "assistant", "content": content }, "finish_reason": "stop" }],
+            "usage": usage(&request, &content),
+        });
+        return vec![body.to_string()];
+    }
+    let mut events: Vec<String> = content
+        .split_inclusive(' ')
+        .map(|word| format!("data: {}\n\n", json!({ "choices": [{ "delta": { "content": word } }] })))
+        .collect();
+    let last = json!({ "choices": [{ "delta": {}, "finish_reason": "stop" }], "usage": usage(&request, &content) });
+    events.push(format!("data: {}\n\ndata: [DONE]\n\n", last));
+    events
+}
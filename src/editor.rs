@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+use crate::recovery;
+
+// Editing text in the user's editor ($VISUAL, then $EDITOR, then vi). The
+// text goes through a temporary file that is removed again afterwards, since
+// it may be the very content being redacted.
+
+fn editor_command() -> Vec<String> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    // e.g. "code --wait"
+    editor.split_whitespace().map(str::to_string).collect()
+}
+
+// `text` after the user edited it, or None if the editor left it unchanged
+pub fn edit(text: &str, name: &str) -> Result<Option<String>> {
+    let path = std::env::temp_dir().join(format!("aivsai-{}-{}.md", name, std::process::id()));
+    std::fs::write(&path, text).context(format!("Failed to write {:?}", path))?;
+    let command = editor_command();
+    let status = {
+        // Ctrl+C goes to the editor
+        let _claim = recovery::claim_interrupts();
+        Command::new(&command[0]).args(&command[1..]).arg(&path).status()
+    };
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+    let status = status.context(format!("Failed to start editor {:?} (set $EDITOR)", command[0]))?;
+    if !status.success() {
+        anyhow::bail!("Editor {:?} exited with {}", command[0], status);
+    }
+    let edited = edited.context(format!("Failed to read back {:?}", path))?;
+    let edited = edited.trim_end().to_string();
+    Ok((edited != text.trim_end()).then_some(edited))
+}
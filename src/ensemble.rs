@@ -0,0 +1,183 @@
+use anyhow::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::api::{complete, ChatMessage, Finish};
+use crate::config::{AiConfig, Profile, Settings};
+use crate::i18n::tr;
+use crate::session::ConversationTurn;
+use crate::{layout, ui, usage};
+
+// Answerer ensemble: two or three answerers answer the same question at once,
+// the reviewer ranks their answers with a justification, and the top-ranked one
+// becomes the answer of the round. The others are kept with the round so
+// exports show what was chosen from.
+
+// One answerer's answer and its place in the ranking (1 = chosen)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Candidate {
+    pub answerer: String,
+    pub model: String,
+    pub answer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish: Option<Finish>,
+}
+
+pub struct Ensemble {
+    // Shared with the tasks that run the answers
+    pub answerers: Vec<Arc<AiConfig>>,
+}
+
+impl Ensemble {
+    // The `[ensemble]` of the settings, if it names any answerers
+    pub fn from_settings(settings: &Settings) -> Result<Option<Self>> {
+        let answerers = &settings.ensemble.answerers;
+        if answerers.is_empty() {
+            return Ok(None);
+        }
+        if !(2..=3).contains(&answerers.len()) {
+            anyhow::bail!("[ensemble] answerers must name 2 or 3 answerers, not {}", answerers.len());
+        }
+        Ok(Some(Self {
+            answerers: answerers
+                .iter()
+                .map(|spec| {
+                    let answerer = AiConfig::from_spec(settings, spec)?;
+                    Ok(Arc::new(AiConfig {
+                        max_tokens: settings.answer.token_limit(),
+                        auto_continue: settings.answer.auto_continue,
+                        ..answerer
+                    }))
+                })
+                .collect::<Result<_>>()?,
+        }))
+    }
+}
+
+// Letter an answer is shown to the reviewer under
+fn letter(index: usize) -> char {
+    (b'A' + index as u8) as char
+}
+
+// Every answerer's answer to the same request, run concurrently. Answerers that
+// fail are left out with a warning.
+pub async fn answer_all(client: &Client, answerers: &[Arc<AiConfig>], messages: &[ChatMessage]) -> Vec<Candidate> {
+    let tasks: Vec<_> = answerers
+        .iter()
+        .map(|answerer| {
+            let (client, answerer, messages) = (client.clone(), Arc::clone(answerer), messages.to_vec());
+            usage::spawn(async move { complete(&client, &answerer, messages).await })
+        })
+        .collect();
+    let mut candidates = Vec::new();
+    for (answerer, task) in answerers.iter().zip(tasks) {
+        let result = match usage::joined(task).await {
+            Ok(result) => result,
+            Err(e) => Err(anyhow::anyhow!("answer task failed: {}", e)),
+        };
+        match result {
+            Ok(completion) => candidates.push(Candidate {
+                answerer: answerer.name.clone(),
+                model: answerer.model.clone(),
+                answer: completion.content,
+                rank: None,
+                finish: completion.finish,
+            }),
+            Err(e) => ui::notice(ui::warning(tr!("error.provider", answerer.name, e))),
+        }
+    }
+    candidates
+}
+
+// The order in a `RANKING: B > A > C` line, as candidate indexes
+fn parse_ranking(text: &str, count: usize) -> Option<Vec<usize>> {
+    let line = text.lines().rev().find_map(|line| {
+        let line = line.trim().trim_matches(|c| c == '`' || c == '*').trim();
+        line.get(..8).filter(|head| head.eq_ignore_ascii_case("ranking:")).map(|_| &line[8..])
+    })?;
+    let mut order = Vec::new();
+    for c in line.chars().filter(|c| c.is_ascii_alphabetic()) {
+        let index = (c.to_ascii_uppercase() as u8).checked_sub(b'A')? as usize;
+        if index < count && !order.contains(&index) {
+            order.push(index);
+        }
+    }
+    (!order.is_empty()).then_some(order)
+}
+
+// Have the reviewer rank the candidates, filling in their ranks. Returns the
+// reviewer's justification. Candidates the ranking leaves out come last, in
+// answerer order; when there is no ranking line at all the first answer wins.
+pub async fn rank(client: &Client, reviewer: &AiConfig, profile: &Profile, question: &str, candidates: &mut [Candidate]) -> Result<String> {
+    let answers: String = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| format!("\n\n--- Answer {} ---\n{}", letter(i), candidate.answer))
+        .collect();
+    let letters: Vec<String> = (0..candidates.len()).map(|i| letter(i).to_string()).collect();
+    let prompt = format!(
+        "The user asked: \"{}\"\n\nSeveral AI assistants answered independently:{}\n\nRank these answers from best to worst by correctness first, then completeness and clarity. Justify the ranking briefly, naming the errors or omissions that decided it. Write the justification in {}.\n\nEnd with one final line in exactly this form, with the keyword in English: `RANKING: {}` (best first).",
+        question,
+        answers,
+        profile.review_language(),
+        letters.join(" > ")
+    );
+    let messages = vec![ChatMessage::system(profile.review_system_prompt()), ChatMessage::user(prompt)];
+    let justification = complete(client, reviewer, messages).await?.content;
+    let mut order = parse_ranking(&justification, candidates.len()).unwrap_or_default();
+    for i in 0..candidates.len() {
+        if !order.contains(&i) {
+            order.push(i);
+        }
+    }
+    for (place, index) in order.into_iter().enumerate() {
+        candidates[index].rank = Some(place + 1);
+    }
+    Ok(justification)
+}
+
+// The top-ranked candidate; the first one when they aren't ranked
+pub fn winner(candidates: &[Candidate]) -> Option<&Candidate> {
+    candidates.iter().min_by_key(|candidate| candidate.rank.unwrap_or(usize::MAX))
+}
+
+// Ranking table and justification for the terminal
+pub fn terminal_block(candidates: &[Candidate], ranking: Option<&str>) -> String {
+    let mut ranked: Vec<&Candidate> = candidates.iter().collect();
+    ranked.sort_by_key(|candidate| candidate.rank.unwrap_or(usize::MAX));
+    let rows: Vec<Vec<String>> = ranked
+        .iter()
+        .map(|candidate| {
+            vec![
+                candidate.rank.map_or("-".to_string(), |rank| rank.to_string()),
+                format!("{} ({})", candidate.answerer, candidate.model),
+                tr!("ensemble.chars", candidate.answer.chars().count()),
+            ]
+        })
+        .collect();
+    let table = layout::table(&[tr!("ensemble.rank"), tr!("ensemble.answerer"), tr!("ensemble.length")], &rows);
+    match ranking {
+        Some(justification) => format!("\n{}\n{}\n{}", ui::heading(tr!("ensemble.heading")), table, ui::status(layout::wrap_for_terminal(justification.trim()))),
+        None => format!("\n{}\n{}", ui::heading(tr!("ensemble.heading")), table),
+    }
+}
+
+// Every candidate and the ranking for Markdown exports, collapsed under the answer
+pub fn export_block(turn: &ConversationTurn) -> String {
+    if turn.candidates.is_empty() {
+        return String::new();
+    }
+    let mut block = format!("\n\n<details><summary>{}</summary>\n", tr!("ensemble.heading"));
+    if let Some(justification) = &turn.ranking {
+        block.push_str(&format!("\n{}\n", justification.trim()));
+    }
+    for candidate in &turn.candidates {
+        let rank = candidate.rank.map_or(String::new(), |rank| format!("#{} ", rank));
+        block.push_str(&format!("\n#### {}{} ({})\n\n{}\n", rank, candidate.answerer, candidate.model, candidate.answer.trim()));
+    }
+    block.push_str("\n</details>");
+    block
+}
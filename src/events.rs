@@ -0,0 +1,80 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::archive::conversations_dir;
+use crate::config::ArchiveSettings;
+use crate::crypto;
+
+// `[archive] events = true`: an audit trail per session in
+// `conversations/events/<session start>.jsonl`, one JSON object per line with a
+// timestamp and an `event` field:
+//
+//   question   a question as sent (`round`, `text`)
+//   command    a REPL slash command (`command`, `args`)
+//   request    a request to a provider, with the full body (`provider`, `body`)
+//   retry      a failed attempt about to be retried (`provider`, `attempt`, `reason`, `delay_ms`)
+//   response   what came back (`provider`, `content`, `finish_reason`, tokens, `latency_ms`)
+//   failure    a request that failed for good (`provider`, `error`)
+//
+// Lines are only ever appended, so nothing written is lost in a crash. The REPL,
+// `ask` and `batch` write it. It holds every prompt in plain text, so it can't be
+// combined with archive encryption.
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// The log of the session being worked on
+static CURRENT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+pub fn init(settings: &ArchiveSettings) -> Result<()> {
+    if settings.events && crypto::enabled() {
+        anyhow::bail!("[archive] events can't be used with encryption: the event log is written in plain text");
+    }
+    ENABLED.store(settings.events, Ordering::Relaxed);
+    Ok(())
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// Log to the session that started at `start_time` from now on; called whenever
+// the session may have changed (/new, /load, /branch)
+pub fn follow(start_time: &str) {
+    if !enabled() {
+        return;
+    }
+    let name = format!("{}.jsonl", start_time.replace(' ', "_").replace(':', "-"));
+    let path = conversations_dir().ok().map(|dir| dir.join("events").join(name));
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+// Append an event; `fields` is a JSON object merged into the line. Failures are
+// ignored: the audit trail must never break a chat.
+pub fn log(event: &str, fields: Value) {
+    if !enabled() {
+        return;
+    }
+    let mut line = json!({
+        "time": chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+        "event": event,
+    });
+    if let (Some(line), Value::Object(fields)) = (line.as_object_mut(), fields) {
+        line.extend(fields);
+    }
+    let current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(path) = current.as_ref() {
+        let _ = append(path, &line);
+    }
+}
+
+fn append(path: &Path, line: &Value) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use crate::config::{ExampleSelection, ReviewSettings};
+use crate::recall;
+
+// Few-shot examples for the reviewer: a folder of reviews written the way they
+// should be, one or two of which are added to the reviewer's system prompt to
+// keep the format and depth of reviews steady. A file may start with a
+// `## Question` section; similar selection compares that with the question
+// being reviewed (the whole file when there is none).
+
+struct Example {
+    text: String,
+    pairs: HashSet<(char, char)>,
+}
+
+struct Library {
+    examples: Vec<Example>,
+    count: usize,
+    select: ExampleSelection,
+}
+
+static LIBRARY: OnceLock<Library> = OnceLock::new();
+
+// Where round-robin selection continues
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+// The text under a "Question" heading, up to the next heading
+fn question_section(text: &str) -> Option<String> {
+    let mut lines = text.lines().skip_while(|line| {
+        let heading = line.trim_start_matches('#');
+        heading.len() == line.len() || !heading.trim().eq_ignore_ascii_case("question")
+    });
+    lines.next()?;
+    let section: Vec<&str> = lines.take_while(|line| !line.starts_with('#')).collect();
+    Some(section.join("\n"))
+}
+
+fn load(dir: &str) -> Result<Vec<Example>> {
+    let dir = match (dir.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => std::path::Path::new(&home).join(rest),
+        _ => PathBuf::from(dir),
+    };
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .context(format!("Failed to read [review] examples folder {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md" || ext == "txt"))
+        .collect();
+    paths.sort();
+    let mut examples = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path).context(format!("Failed to read review example {:?}", path))?;
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        let key = question_section(&text).unwrap_or_else(|| text.clone());
+        examples.push(Example {
+            pairs: recall::pairs(&recall::normalize(&key)),
+            text,
+        });
+    }
+    if examples.is_empty() {
+        anyhow::bail!("No .md or .txt review examples in {:?}", dir);
+    }
+    Ok(examples)
+}
+
+pub fn init(settings: &ReviewSettings) -> Result<()> {
+    let Some(dir) = &settings.examples else {
+        return Ok(());
+    };
+    if !(1..=2).contains(&settings.count) {
+        anyhow::bail!("[review] count must be 1 or 2, not {}", settings.count);
+    }
+    let _ = LIBRARY.set(Library {
+        examples: load(dir)?,
+        count: settings.count,
+        select: settings.select,
+    });
+    Ok(())
+}
+
+fn pick<'a>(library: &'a Library, question: &str) -> Vec<&'a Example> {
+    let count = library.count.min(library.examples.len());
+    match library.select {
+        ExampleSelection::Similar => {
+            let pairs = recall::pairs(&recall::normalize(question));
+            let mut scored: Vec<(f64, &Example)> = library.examples.iter().map(|example| (recall::dice(&pairs, &example.pairs), example)).collect();
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+            scored.into_iter().take(count).map(|(_, example)| example).collect()
+        }
+        ExampleSelection::RoundRobin => {
+            let start = NEXT.fetch_add(count, Ordering::Relaxed);
+            (0..count).map(|i| &library.examples[(start + i) % library.examples.len()]).collect()
+        }
+    }
+}
+
+// The reviewer's system prompt with the examples chosen for `question`
+// appended, or unchanged when no examples folder is configured
+pub fn system_prompt(prompt: String, question: &str) -> String {
+    let Some(library) = LIBRARY.get() else {
+        return prompt;
+    };
+    let mut prompt = format!(
+        "{}\n\nBelow are examples of good reviews. Follow their format and depth; they are about other questions, so do not repeat their content.",
+        prompt
+    );
+    for (i, example) in pick(library, question).into_iter().enumerate() {
+        prompt.push_str(&format!("\n\n--- Example review {} ---\n{}", i + 1, example.text));
+    }
+    prompt
+}
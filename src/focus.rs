@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+// Aspects the reviewer can be told to concentrate on with `/focus`. Each has a
+// weight from 1 to 3 (`/focus security=3`) that sets how strongly it is asked for.
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Aspect {
+    Security,
+    Performance,
+    Facts,
+}
+
+impl Aspect {
+    pub const NAMES: [&'static str; 3] = ["security", "performance", "facts"];
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "security" => Some(Self::Security),
+            "performance" | "perf" => Some(Self::Performance),
+            "facts" | "fact" => Some(Self::Facts),
+            _ => None,
+        }
+    }
+
+    fn subject(self) -> &'static str {
+        match self {
+            Self::Security => "security: injection, unsafe input handling, secrets, unsafe defaults and other vulnerabilities",
+            Self::Performance => "performance: algorithmic complexity, needless allocations or copies, blocking calls and scalability",
+            Self::Facts => "factual accuracy: check every claim, number, API name and version, and flag anything unverifiable",
+        }
+    }
+}
+
+pub const MAX_WEIGHT: u8 = 3;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct Focus {
+    pub aspect: Aspect,
+    pub weight: u8,
+}
+
+impl Focus {
+    // "security" or "security=2"
+    pub fn parse(text: &str) -> Option<Self> {
+        let (name, weight) = match text.split_once('=') {
+            Some((name, weight)) => (name, weight.parse::<u8>().ok().filter(|w| (1..=MAX_WEIGHT).contains(w))?),
+            None => (text, 1),
+        };
+        Some(Self { aspect: Aspect::parse(name)?, weight })
+    }
+
+    // "security", "security×2"
+    pub fn label(&self) -> String {
+        if self.weight == 1 {
+            self.aspect.name().to_string()
+        } else {
+            format!("{}×{}", self.aspect.name(), self.weight)
+        }
+    }
+}
+
+// Add or re-weight `focus` in `focuses`
+pub fn stack(focuses: &mut Vec<Focus>, focus: Focus) {
+    match focuses.iter_mut().find(|f| f.aspect == focus.aspect) {
+        Some(existing) => existing.weight = focus.weight,
+        None => focuses.push(focus),
+    }
+}
+
+// Appended to the review request; empty without focuses. Heavier ones come first.
+pub fn instruction(focuses: &[Focus]) -> String {
+    let mut focuses = focuses.to_vec();
+    focuses.sort_by_key(|focus| std::cmp::Reverse(focus.weight));
+    let lines: Vec<String> = focuses
+        .iter()
+        .map(|focus| {
+            let emphasis = match focus.weight {
+                1 => "Pay particular attention to",
+                2 => "Concentrate strongly on",
+                _ => "Make this the main subject of your review, above everything else:",
+            };
+            format!("- {} {}.", emphasis, focus.aspect.subject())
+        })
+        .collect();
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!("\n\nThe user asked for this review to focus on:\n{}", lines.join("\n"))
+    }
+}
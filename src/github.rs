@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde_json::{json, Map, Value};
+
+use crate::api;
+
+// Sharing a session on GitHub (`/publish`): as a secret gist, or as a discussion
+// in a repository. The token comes from GITHUB_TOKEN (or GH_TOKEN, as the gh CLI
+// uses); gists need the `gist` scope, discussions write access to the repository.
+
+const GITHUB_API: &str = "https://api.github.com";
+
+pub fn token() -> Result<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|token| !token.trim().is_empty()))
+        .context("Set GITHUB_TOKEN (or GH_TOKEN) to publish to GitHub")
+}
+
+async fn post(client: &Client, token: &str, path: &str, body: &Value) -> Result<Value> {
+    api::check_destination("GitHub", GITHUB_API)?;
+    let response = client
+        .post(format!("{}{}", GITHUB_API, path))
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "aivsai")
+        .json(body)
+        .send()
+        .await
+        .context("Failed to reach GitHub")?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        anyhow::bail!("HTTP {} from GitHub: {}", status, body.get("message").unwrap_or(&body));
+    }
+    Ok(body)
+}
+
+// A GraphQL request; errors in the response fail it
+async fn graphql(client: &Client, token: &str, query: &str, variables: Value) -> Result<Value> {
+    let body = post(client, token, "/graphql", &json!({ "query": query, "variables": variables })).await?;
+    if let Some(errors) = body.get("errors").and_then(Value::as_array).filter(|errors| !errors.is_empty()) {
+        let messages: Vec<&str> = errors.iter().filter_map(|error| error.get("message").and_then(Value::as_str)).collect();
+        anyhow::bail!("GitHub: {}", messages.join("; "));
+    }
+    Ok(body.get("data").cloned().unwrap_or_default())
+}
+
+// A secret gist with one markdown file; returns its URL
+pub async fn create_gist(client: &Client, token: &str, filename: &str, description: &str, content: &str) -> Result<String> {
+    let mut files = Map::new();
+    files.insert(filename.to_string(), json!({ "content": content }));
+    let gist = post(client, token, "/gists", &json!({ "description": description, "public": false, "files": files })).await?;
+    gist.get("html_url").and_then(Value::as_str).map(str::to_string).context("GitHub returned a gist without a URL")
+}
+
+const REPOSITORY_QUERY: &str = "query($owner: String!, $name: String!) {
+  repository(owner: $owner, name: $name) { id hasDiscussionsEnabled discussionCategories(first: 50) { nodes { id name } } }
+}";
+
+const CREATE_DISCUSSION: &str = "mutation($repository: ID!, $category: ID!, $title: String!, $body: String!) {
+  createDiscussion(input: { repositoryId: $repository, categoryId: $category, title: $title, body: $body }) { discussion { url } }
+}";
+
+// A discussion in `repo` (OWNER/NAME) under `category` (by name; default
+// "General", else the first one); returns its URL
+pub async fn create_discussion(client: &Client, token: &str, repo: &str, category: Option<&str>, title: &str, body: &str) -> Result<String> {
+    let (owner, name) = repo.split_once('/').filter(|(owner, name)| !owner.is_empty() && !name.is_empty()).context("The repository should be OWNER/NAME")?;
+    let data = graphql(client, token, REPOSITORY_QUERY, json!({ "owner": owner, "name": name })).await?;
+    let repository = data.get("repository").filter(|repository| !repository.is_null()).context(format!("No repository {} (or the token can't see it)", repo))?;
+    if repository.get("hasDiscussionsEnabled").and_then(Value::as_bool) != Some(true) {
+        anyhow::bail!("Discussions are not enabled in {}", repo);
+    }
+    let categories = repository.pointer("/discussionCategories/nodes").and_then(Value::as_array).cloned().unwrap_or_default();
+    let named = |wanted: &str| categories.iter().find(|node| node.get("name").and_then(Value::as_str).is_some_and(|name| name.eq_ignore_ascii_case(wanted)));
+    let chosen = match category {
+        Some(wanted) => named(wanted).context(format!("{} has no discussion category {:?}", repo, wanted))?,
+        None => named("General").or(categories.first()).context(format!("{} has no discussion categories", repo))?,
+    };
+    let variables = json!({
+        "repository": repository.get("id").cloned().unwrap_or_default(),
+        "category": chosen.get("id").cloned().unwrap_or_default(),
+        "title": title,
+        "body": body,
+    });
+    let created = graphql(client, token, CREATE_DISCUSSION, variables).await?;
+    created.pointer("/createDiscussion/discussion/url").and_then(Value::as_str).map(str::to_string).context("GitHub returned a discussion without a URL")
+}
@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::config::GlossarySettings;
+
+// A terminology glossary for consistent (often bilingual) technical writing: a
+// TOML file of terms and how they should be translated or used,
+//
+//   "borrow checker" = "借用检查器"
+//   lifetime = "生命周期 (not 寿命)"
+//   crate = "keep as \"crate\", don't translate"
+//
+// given to the answerer as terms to use, and to the reviewer as terms to hold
+// the answer to, with any deviation reported as an issue.
+
+// (term, preferred translation / definition), sorted by term
+static TERMS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+fn load(file: &str) -> Result<Vec<(String, String)>> {
+    let path = match (file.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => std::path::Path::new(&home).join(rest),
+        _ => PathBuf::from(file),
+    };
+    let text = std::fs::read_to_string(&path).context(format!("Failed to read [glossary] file {:?}", path))?;
+    let table: toml::Table = toml::from_str(&text).context(format!("{:?} is not a TOML glossary", path))?;
+    let mut terms = Vec::new();
+    for (term, value) in table {
+        let Some(preferred) = value.as_str() else {
+            anyhow::bail!("Glossary term {:?} in {:?} must map to a string", term, path);
+        };
+        terms.push((term, preferred.trim().to_string()));
+    }
+    if terms.is_empty() {
+        anyhow::bail!("No terms in glossary {:?}", path);
+    }
+    Ok(terms)
+}
+
+pub fn init(settings: &GlossarySettings) -> Result<()> {
+    if let Some(file) = &settings.file {
+        let _ = TERMS.set(load(file)?);
+    }
+    Ok(())
+}
+
+fn list(terms: &[(String, String)]) -> String {
+    terms.iter().map(|(term, preferred)| format!("\n- {}: {}", term, preferred)).collect()
+}
+
+// Appended to the answerer's system prompt; empty without a glossary
+pub fn answer_instruction() -> String {
+    match TERMS.get() {
+        Some(terms) => format!("\n\nUse these terms exactly as the glossary says (term: preferred translation or usage):{}", list(terms)),
+        None => String::new(),
+    }
+}
+
+// Appended to the reviewer's system prompt; empty without a glossary
+pub fn review_instruction() -> String {
+    match TERMS.get() {
+        Some(terms) => format!(
+            "\n\nThe user keeps a terminology glossary (term: preferred translation or usage):{}\nCheck the answer against it and list every place it uses, translates or defines one of these terms differently, as a terminology issue quoting the wording and the glossary's.",
+            list(terms)
+        ),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::Client;
+use std::sync::OnceLock;
+
+use crate::api;
+use crate::config::{AiConfig, GuardMode, Settings};
+use crate::i18n::tr;
+use crate::ui;
+
+// Guardrail pass: questions before they are sent and answers before they are
+// shown or saved are checked against the `[guard]` policy (keywords, regex
+// rules and, with a provider set, its `/moderations` endpoint). "flag" warns
+// and marks the round, which exports then note; "block" stops the question or
+// withholds the answer, so nothing blocked reaches the screen or an export.
+
+struct Rule {
+    name: String,
+    regex: Regex,
+}
+
+struct Policy {
+    mode: GuardMode,
+    rules: Vec<Rule>,
+    moderator: Option<AiConfig>,
+}
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+pub fn init(settings: &Settings) -> Result<()> {
+    let guard = &settings.guard;
+    if guard.mode == GuardMode::Off {
+        return Ok(());
+    }
+    let mut rules: Vec<Rule> = guard
+        .keywords
+        .iter()
+        .map(|keyword| Rule {
+            name: format!("\"{}\"", keyword),
+            regex: Regex::new(&format!("(?i){}", regex::escape(keyword))).expect("escaped keyword compiles"),
+        })
+        .collect();
+    let mut custom: Vec<(&String, &String)> = guard.rules.iter().collect();
+    custom.sort();
+    for (name, pattern) in custom {
+        let regex = Regex::new(pattern).context(format!("Invalid [guard.rules] {}", name))?;
+        rules.push(Rule { name: name.clone(), regex });
+    }
+    let _ = POLICY.set(Policy {
+        mode: guard.mode,
+        rules,
+        moderator: AiConfig::moderator(settings)?,
+    });
+    Ok(())
+}
+
+pub fn mode() -> GuardMode {
+    POLICY.get().map_or(GuardMode::Off, |policy| policy.mode)
+}
+
+// Blocking has to see the whole answer before any of it is shown, so answers
+// aren't streamed in "block" mode
+pub fn holds_answers() -> bool {
+    mode() == GuardMode::Block
+}
+
+// Rules and moderation categories `text` trips; empty if it passes. When the
+// moderation request fails the local rules still apply.
+pub async fn check(client: &Client, text: &str) -> Vec<String> {
+    let Some(policy) = POLICY.get() else {
+        return Vec::new();
+    };
+    let mut flags: Vec<String> = policy.rules.iter().filter(|rule| rule.regex.is_match(text)).map(|rule| rule.name.clone()).collect();
+    if let Some(moderator) = &policy.moderator {
+        match api::moderate(client, moderator, text).await {
+            Ok(categories) => flags.extend(categories),
+            Err(e) => ui::notice(ui::warning(tr!("guard.unavailable", format!("{:#}", e)))),
+        }
+    }
+    flags
+}
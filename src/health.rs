@@ -0,0 +1,70 @@
+use reqwest::Client;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::api;
+use crate::config::AiConfig;
+use crate::http::{Request, Response};
+
+// Probes for Kubernetes, Compose and load balancers, served by `bot` and the
+// Discord `bridge` next to their callback path:
+//
+//   /healthz   200 as soon as the process is up with its configuration loaded
+//   /readyz    200 when the answerer's and reviewer's endpoints can be reached,
+//              503 naming the ones that can't
+//
+// Any HTTP response counts as reachable, even an error status: the probe checks
+// the network path, not keys or quotas, and costs no tokens. The readiness
+// result is reused for a few seconds so frequent probes don't become a stream
+// of requests to the providers.
+
+pub const LIVENESS: &str = "/healthz";
+pub const READINESS: &str = "/readyz";
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+const REUSE: Duration = Duration::from_secs(10);
+
+// When the providers were last probed, and the ones that couldn't be reached
+static LAST: Mutex<Option<(Instant, Vec<String>)>> = Mutex::new(None);
+
+async fn unreachable(client: &Client, roles: &[&AiConfig]) -> Vec<String> {
+    // Replayed and dry runs never leave the machine
+    if api::offline() {
+        return Vec::new();
+    }
+    let mut failed = Vec::new();
+    for config in roles {
+        let reached = match api::check_destination(&config.name, &config.base_url) {
+            Ok(()) => client.get(&config.base_url).timeout(TIMEOUT).send().await.map_err(anyhow::Error::from),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = reached {
+            failed.push(format!("{} ({:#})", config.name, e));
+        }
+    }
+    failed
+}
+
+// The probe's response, or None if `request` isn't for a probe
+pub async fn respond(request: &Request, client: &Client, roles: &[&AiConfig]) -> Option<Response> {
+    if request.path == LIVENESS {
+        return Some(Response::text(200, "ok"));
+    }
+    if request.path != READINESS {
+        return None;
+    }
+    let cached = LAST.lock().unwrap_or_else(|e| e.into_inner()).clone().filter(|(at, _)| at.elapsed() < REUSE);
+    let failed = match cached {
+        Some((_, failed)) => failed,
+        None => {
+            let failed = unreachable(client, roles).await;
+            *LAST.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), failed.clone()));
+            failed
+        }
+    };
+    if failed.is_empty() {
+        Some(Response::text(200, "ready"))
+    } else {
+        Some(Response::text(503, format!("unreachable: {}", failed.join(", "))))
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+use crate::codeblocks;
+use crate::layout;
+use crate::ui;
+
+// Syntax highlighting of fenced code blocks by their language tag, with
+// syntect's bundled syntaxes and themes: 24-bit color in the terminal and inline
+// styles in published HTML. `[theme] code_theme = "none"` turns it off, leaving
+// terminal code in the plain `code` style. Blocks without a known language are
+// left alone.
+
+// None when highlighting is off
+static THEME: OnceLock<Option<Theme>> = OnceLock::new();
+
+pub fn init(code_theme: &str) -> Result<()> {
+    let theme = if code_theme.is_empty() || code_theme.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        let mut themes = ThemeSet::load_defaults().themes;
+        let names = themes.keys().cloned().collect::<Vec<_>>().join(", ");
+        match themes.remove(code_theme) {
+            Some(theme) => Some(theme),
+            None => anyhow::bail!("Unknown [theme] code_theme {:?} (available: {}, none)", code_theme, names),
+        }
+    };
+    let _ = THEME.set(theme);
+    Ok(())
+}
+
+fn theme() -> Option<&'static Theme> {
+    THEME.get().and_then(Option::as_ref)
+}
+
+// Loaded on first use; it takes a moment
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+// "rust", "rs", "Python", ... as written after the fence
+fn syntax(language: &str) -> Option<&'static SyntaxReference> {
+    let token = language.split_whitespace().next()?;
+    syntaxes().find_syntax_by_token(token)
+}
+
+// Code with terminal color escapes, if colors are on and the language is known
+pub fn terminal(code: &str, language: &str) -> Option<String> {
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return None;
+    }
+    let (theme, syntax) = (theme()?, syntax(language)?);
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut out = String::new();
+    for line in LinesWithEndings::from(code) {
+        let ranges = highlighter.highlight_line(line, syntaxes()).ok()?;
+        out.push_str(&as_24_bit_terminal_escaped(&ranges, false));
+    }
+    out.push_str("\x1b[0m");
+    Some(out)
+}
+
+// A `<pre>` with inline styles, if the language is known
+pub fn html(code: &str, language: &str) -> Option<String> {
+    syntect::html::highlighted_html_for_string(code, syntaxes(), syntax(language)?, theme()?).ok()
+}
+
+// Wrap markdown for the terminal, number its fenced code blocks ([1], [2], ...
+// as `/copy` and friends count them) and highlight them
+pub fn terminal_markdown(text: &str) -> String {
+    let wrapped = layout::wrap_for_terminal(text);
+    let mut out: Vec<String> = Vec::new();
+    let mut number = 0;
+    // Fence, language and lines of the open code block
+    let mut block: Option<(String, String, Vec<&str>)> = None;
+    for line in wrapped.lines() {
+        match block.take() {
+            Some((fence, language, lines)) if codeblocks::closes(line, &fence) => {
+                if !lines.is_empty() {
+                    let code = lines.join("\n");
+                    out.push(terminal(&code, &language).unwrap_or(code));
+                }
+                out.push(line.to_string());
+            }
+            Some((fence, language, mut lines)) => {
+                lines.push(line);
+                block = Some((fence, language, lines));
+            }
+            None => match codeblocks::opening_fence(line) {
+                Some(fence) => {
+                    number += 1;
+                    let language = line.trim_start()[fence.len()..].trim().to_string();
+                    out.push(format!("{}  {}", line, ui::status(format!("[{}]", number))));
+                    block = Some((fence, language, Vec::new()));
+                }
+                None => out.push(line.to_string()),
+            },
+        }
+    }
+    // An unclosed block stays as it is
+    if let Some((_, _, lines)) = block {
+        out.extend(lines.into_iter().map(str::to_string));
+    }
+    if wrapped.ends_with('\n') {
+        out.push(String::new());
+    }
+    out.join("\n")
+}
@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::i18n::tr;
+use crate::ui;
+
+// A deliberately small HTTP/1.1 server for callbacks from chat platforms: one
+// request per connection, no TLS (run it behind a reverse proxy for HTTPS), no
+// chunked request bodies. Good enough for JSON webhooks, not a general server.
+
+const MAX_HEADER: usize = 16 * 1024;
+const MAX_BODY: usize = 1024 * 1024;
+// For the whole request to arrive
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct Request {
+    pub method: String,
+    // Without the query string
+    pub path: String,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    // Header value by case-insensitive name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> Result<Value> {
+        serde_json::from_slice(&self.body).context("Request body is not JSON")
+    }
+}
+
+pub struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(value: &Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    pub fn html(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body: body.into_bytes(),
+        }
+    }
+
+    pub fn text(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.into().into_bytes(),
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Error",
+    }
+}
+
+fn parse_head(head: &str) -> Result<Request> {
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("Malformed request line {:?}", request_line);
+    };
+    let path = target.split('?').next().unwrap_or_default();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        headers,
+        body: Vec::new(),
+    })
+}
+
+// Read one request; Err(status) when it should be refused
+async fn read_request(stream: &mut TcpStream) -> Result<Request, u16> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_HEADER {
+            return Err(413);
+        }
+        let read = stream.read(&mut chunk).await.map_err(|_| 400u16)?;
+        if read == 0 {
+            return Err(400);
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut request = parse_head(&head).map_err(|_| 400u16)?;
+    let length: usize = match request.header("content-length") {
+        Some(value) => value.parse().map_err(|_| 400u16)?,
+        None => 0,
+    };
+    if length > MAX_BODY {
+        return Err(413);
+    }
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < length {
+        let read = stream.read(&mut chunk).await.map_err(|_| 400u16)?;
+        if read == 0 {
+            return Err(400);
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(length);
+    request.body = body;
+    Ok(request)
+}
+
+async fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.flush().await
+}
+
+// Accept connections on `addr` forever, answering each request with `handler`
+pub async fn serve<H, F>(addr: &str, handler: H) -> Result<()>
+where
+    H: Fn(Request) -> F + Send + Sync + 'static,
+    F: Future<Output = Response> + Send,
+{
+    let listener = TcpListener::bind(addr).await.context(format!("Failed to listen on {}", addr))?;
+    let handler = Arc::new(handler);
+    loop {
+        let (mut stream, _) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                ui::notice(ui::warning(tr!("http.accept_failed", e)));
+                continue;
+            }
+        };
+        let handler = Arc::clone(&handler);
+        tokio::spawn(async move {
+            let response = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+                Ok(Ok(request)) => handler(request).await,
+                Ok(Err(status)) => Response::text(status, reason(status)),
+                Err(_) => return,
+            };
+            let _ = write_response(&mut stream, &response).await;
+        });
+    }
+}
@@ -0,0 +1,958 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use crate::config::I18nSettings;
+
+// Message catalog for everything the tool prints: banners, prompts, statuses,
+// /help text and the headings in saved files. Prompts sent to the models are not
+// translated; the review language comes from the profile.
+//
+// en-US and zh-CN are built in. Any other locale reads its strings from
+// `[i18n] catalog`, a TOML file of key = "text" pairs (tables nest keys, so
+// `[round] completed = "..."` is the same as `"round.completed" = "..."`);
+// keys it leaves out fall back to English. `{}` marks an argument, filled in order.
+
+type Catalog = HashMap<&'static str, &'static str>;
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(|| built_in(&detect_locale()).into_iter().collect())
+}
+
+// Locale from the environment when `[i18n] locale` isn't set
+fn detect_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .filter(|value| value.to_lowercase().starts_with("zh"))
+        .map_or_else(|| "en-US".to_string(), |_| "zh-CN".to_string())
+}
+
+fn is_chinese(locale: &str) -> bool {
+    let locale = locale.to_lowercase();
+    locale == "zh" || locale.starts_with("zh-") || locale.starts_with("zh_")
+}
+
+// English entries overlaid with the locale's own, where it has them
+fn built_in(locale: &str) -> Vec<(&'static str, &'static str)> {
+    let mut entries = EN_US.to_vec();
+    if is_chinese(locale) {
+        entries.extend_from_slice(ZH_CN);
+    }
+    entries
+}
+
+// Flatten nested tables into dotted keys
+fn flatten(prefix: &str, table: &toml::Table, entries: &mut Vec<(String, String)>) -> Result<()> {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::String(text) => entries.push((key, text.clone())),
+            toml::Value::Table(table) => flatten(&key, table, entries)?,
+            _ => anyhow::bail!("{} must be a string", key),
+        }
+    }
+    Ok(())
+}
+
+fn load_catalog(path: &str) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path).context(format!("Failed to read message catalog {}", path))?;
+    let table: toml::Table = toml::from_str(&content).context(format!("Failed to parse message catalog {}", path))?;
+    let mut entries = Vec::new();
+    flatten("", &table, &mut entries).context(format!("Invalid message catalog {}", path))?;
+    Ok(entries)
+}
+
+pub fn init(settings: &I18nSettings) -> Result<()> {
+    let locale = settings.locale.clone().unwrap_or_else(detect_locale);
+    let mut catalog: Catalog = built_in(&locale).into_iter().collect();
+    match &settings.catalog {
+        Some(path) => {
+            // Loaded once at startup and kept for the life of the process
+            for (key, text) in load_catalog(path)? {
+                catalog.insert(Box::leak(key.into_boxed_str()), Box::leak(text.into_boxed_str()));
+            }
+        }
+        None if !is_chinese(&locale) && !locale.to_lowercase().starts_with("en") => {
+            anyhow::bail!("No built-in messages for locale {:?}; set [i18n] catalog to a message file", locale)
+        }
+        None => {}
+    }
+    let _ = CATALOG.set(catalog);
+    Ok(())
+}
+
+// Text for `key` in the current locale; unknown keys come back as themselves
+pub fn message(key: &'static str) -> &'static str {
+    catalog().get(key).copied().unwrap_or(key)
+}
+
+// Replace each `{}` in `template` with the next argument
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut pieces = template.split("{}");
+    let mut text = pieces.next().unwrap_or_default().to_string();
+    let mut args = args.iter();
+    for piece in pieces {
+        match args.next() {
+            Some(arg) => text.push_str(&arg.to_string()),
+            None => text.push_str("{}"),
+        }
+        text.push_str(piece);
+    }
+    text
+}
+
+// `tr!("key")` looks up a message; `tr!("key", a, b)` also fills in its arguments
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::i18n::message($key)
+    };
+    ($key:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::message($key), &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub(crate) use tr;
+
+const EN_US: &[(&str, &str)] = &[
+    ("review.summary_heading", "TL;DR (English)"),
+    ("export.review_failed", "(Review failed: {})"),
+    ("export.not_reviewed", "(Not reviewed yet)"),
+    ("export.truncated", "(Answer was interrupted and is incomplete)"),
+    ("round.answer_header", "--- {} Answer ---"),
+    ("round.review_header", "--- {} Review ---"),
+    ("review.summary_failed", "⚠ English summary failed: {}"),
+    ("error.provider", "{} Error: {}"),
+    ("export.round_starred", "Round {} (starred)"),
+    ("export.round", "Round {}"),
+    ("export.user_translate", "User (translate to {})"),
+    ("export.user", "User"),
+    ("export.colon", ": "),
+    ("export.best_title", "AIvsAI Best Answers"),
+    ("export.session_title", "AIvsAI Conversation"),
+    ("save.saved", "✓ Conversation saved to: {}"),
+    ("save.rounds", "  Total rounds saved: {}"),
+    ("save.failed", "✗ Failed to save conversation: {}"),
+    ("prompt.save_before_exit", "You have unsaved rounds. Save before exiting? [Y/n] "),
+    ("prompt.discard_unsaved", "You have unsaved rounds. Discard them? [y/N] "),
+    ("prompt.truncated_action", "[R]eview partial answer / [k]eep without review / [d]iscard round? "),
+    ("references.truncated_note", ", truncated"),
+    ("references.attached", "📎 {} (~{} tokens{})"),
+    ("references.skipped", "⚠ Not attached: {}"),
+    ("error.quiet_verbose", "Error: -q and -v cannot be used together"),
+    ("error.generic", "Error: {}"),
+    ("error.config", "Configuration Error: {}"),
+    ("banner.pair", "   AI Pair: {} (Answer) + {} (Review)   "),
+    ("banner.profile", "Profile: {}"),
+    ("banner.project_settings", "Project settings: {}"),
+    ("banner.hint", "Type /help for commands (Tab completes them), exit/quit to leave"),
+    ("prompt.user", "User > "),
+    ("repl.exit_hint", "Use 'exit' or 'quit' to exit."),
+    ("error.read_input", "Error reading input: {}"),
+    ("repl.unknown_command_suggest", "⚠ Unknown command {}. Did you mean {}?"),
+    ("repl.unknown_command", "⚠ Unknown command {}. Type /help to list commands."),
+    ("round.stopped_empty", "⚠ Generation stopped before any answer arrived; round discarded"),
+    ("round.stopped_partial", "⚠ Generation stopped; the partial answer is kept and marked as truncated"),
+    ("round.discarded", "✓ Round {} discarded"),
+    ("round.kept_unreviewed", "Round {} kept without review. Type /review-retry to review it later"),
+    ("error.pager", "Pager Error: {}"),
+    ("round.completed", "Round {} completed. Type /save to save this conversation"),
+    ("round.saved_unreviewed", "Round {} saved without review. Type /review-retry to run the review again"),
+    ("stdin.clipped_note", ", middle left out"),
+    ("stdin.read", "📥 Read {} line(s) from stdin{}"),
+    ("batch.saved", "✓ Batch results saved to: {}"),
+    ("commit_msg.draft_header", "--- {} Draft ---"),
+    ("commit_msg.no_final", "⚠ The review did not include a final message; using the draft"),
+    ("commit_msg.final_header", "--- Suggested commit message ---"),
+    ("usage.range", "{} to {}"),
+    ("usage.since", "since {}"),
+    ("usage.until", "until {}"),
+    ("usage.all_time", "all time"),
+    ("usage.heading", "AIvsAI usage ({})"),
+    ("usage.empty", "No requests recorded for this period."),
+    ("usage.total", "Estimated total: {} {} (prices from [usage.pricing] in ~/.ai_vs_ai.toml)"),
+    ("usage.csv_written", "✓ CSV written to: {}"),
+    ("api.sending", "Sending request to {} (attempt {}/{}, ~{} prompt tokens)"),
+    ("api.rate_limited", "Waited {}s for {} rate limit"),
+    ("api.retry_status", "{} returned {}, retrying in {}s ({}/{})"),
+    ("api.retry_network", "Network error talking to {}, retrying in {}s ({}/{})"),
+    ("api.request_done", "{} ({}): {}s, {} prompt + {} completion tokens"),
+    ("api.request_failed", "{} ({}): failed after {}s"),
+    ("api.thinking", "Thinking ({}) ..."),
+    ("api.thinking_stream", "Thinking ({}) ... (Ctrl+C stops generation)"),
+    ("notify.failed", "⚠ Desktop notification failed: {}"),
+    ("notify.unavailable", "⚠ Desktop notifications need a build with `--features desktop-notifications`"),
+    ("prompt.api_key", "Enter API Key for {}: "),
+    ("config.key_saved", "Saved {} to {}"),
+    ("notify.review_failed", "Review failed - type /review-retry to run it again"),
+    ("notify.title", "AIvsAI: round {} reviewed"),
+    ("recovery.written", "Unsaved conversation written to {}"),
+    ("command.clear.summary", "drop all previous rounds from the model context"),
+    ("command.clear.details", "Cleared rounds are no longer sent to the answering model but are still included when you /save."),
+    ("command.forget.summary", "drop the last N rounds from the model context"),
+    ("command.forget.details", "Like /clear, but only for the N most recent rounds still in context."),
+    ("command.context.summary", "show exactly what the next request will include"),
+    ("command.context.details", "Lists the system prompt and history messages with a rough token estimate for each."),
+    ("command.help.summary", "list commands, or show details for one"),
+    ("command.help.details", "Without an argument lists every command. `/help save` (or `/help /save`) explains a single command."),
+    ("command.load.summary", "list saved sessions, or resume one"),
+    ("command.load.details", "Without a name lists the 10 most recent saved sessions. With a name (Tab completes) replaces the current session; you are asked first if it has unsaved rounds."),
+    ("command.model.summary", "show or switch the answering model"),
+    ("command.model.details", "Without a name shows the answering and reviewing models. With a name switches the answering model for the rest of the session; each round records the model that answered it."),
+    ("command.pager.summary", "page rounds taller than the screen"),
+    ("command.pager.details", "Toggles (or sets) pager mode for this session. The pager is `[pager] command`, then $PAGER, then `less -R`."),
+    ("command.profile.summary", "list profiles, or switch to one"),
+    ("command.profile.details", "Profiles are `[profile.NAME]` tables in ~/.ai_vs_ai.toml bundling providers, models, prompts and language. Switching keeps the session; later rounds use the new pairing."),
+    ("command.review_retry.summary", "re-run failed or skipped reviews"),
+    ("command.review_retry.details", "Without an argument retries every round that has no review; with N retries round N only."),
+    ("command.save.summary", "save the conversation to conversations/"),
+    ("command.save.details", "Writes a markdown file for reading and a .json file that /load can restore. Saving also clears the crash-recovery snapshot."),
+    ("command.star.summary", "toggle a star on the latest round (or round N)"),
+    ("command.star.details", "Starred rounds are highlighted in saved files and can be exported on their own with /starred export."),
+    ("command.starred.summary", "list starred rounds, or save them as a digest"),
+    ("command.starred.details", "`/starred export` writes only the starred rounds to a best-answers markdown file."),
+    ("command.tag.summary", "list, add or remove tags on this session"),
+    ("command.tag.details", "`/tag NAME...` adds tags, `/tag -NAME` removes one. Tags are saved with the session and listed in conversations/INDEX.md."),
+    ("command.force.summary", "send a question without checking the archive for repeats"),
+    ("command.force.details", "Questions that match an earlier reviewed one (`[recall] threshold`) first offer the saved answer; /force skips that check and asks the models again."),
+    ("command.translate.summary", "translate text and have the reviewer check it"),
+    ("command.translate.details", "The answerer translates (Chinese to English, anything else to Chinese, unless --to says otherwise) and the reviewer checks fidelity and terminology sentence by sentence. The result is a source / translation / issues table, on screen and in saved files."),
+    ("help.heading", "Commands:"),
+    ("help.exit", "leave (asks to save unsaved rounds)"),
+    ("help.footer", "Anything else is sent as a question. Type /help NAME for details."),
+    ("help.unknown", "⚠ No such command: {}. Type /help to list commands."),
+    ("context.cleared", "✓ Context cleared ({} turns dropped, still kept for /save)"),
+    ("context.forgot", "✓ Dropped {} turn(s) from context (still kept for /save)"),
+    ("context.forget_usage", "⚠ Usage: /forget N (N = number of recent turns to drop)"),
+    ("context.heading", "Next request to {} will include {} message(s) + your question:"),
+    ("context.tokens", "(~{} tokens)"),
+    ("load.loaded", "✓ Loaded {} round(s); new questions continue from round {}"),
+    ("load.empty", "No saved sessions yet. Use /save first."),
+    ("load.heading", "Saved sessions (newest first):"),
+    ("load.hint", "Type /load NAME to resume one (Tab completes names)"),
+    ("model.current", "Answering: {}  Reviewing: {}"),
+    ("model.hint", "Type /model NAME to switch ({})"),
+    ("model.switched", "✓ {} now answers with {}"),
+    ("pager.usage", "⚠ Usage: /pager [on|off]"),
+    ("pager.on", "✓ Pager on (long rounds open in the pager)"),
+    ("pager.off", "✓ Pager off"),
+    ("profile.current", "Profile {}: {} ({}) answers, {} ({}) reviews"),
+    ("profile.none", "No profiles configured. Add [profile.NAME] tables to ~/.ai_vs_ai.toml"),
+    ("profile.hint", "Type /profile NAME to switch ({})"),
+    ("review_retry.usage", "⚠ Usage: /review-retry [N]"),
+    ("review_retry.none", "✓ No reviews to retry."),
+    ("review_retry.retrying", "Retrying review for round {} ..."),
+    ("review_retry.no_round", "⚠ No round {} in this session"),
+    ("save.empty", "⚠ No conversation to save yet. Ask a question first!"),
+    ("save.usage", "⚠ Usage: /save (the file is named after the first question)"),
+    ("star.usage", "⚠ Usage: /star [N]"),
+    ("star.starred", "⭐ Starred round {}"),
+    ("star.unstarred", "Removed star from round {}"),
+    ("star.no_round", "⚠ No such round to star yet."),
+    ("starred.empty", "No starred rounds yet. Use /star to mark one."),
+    ("starred.saved", "✓ Best answers saved to: {}"),
+    ("starred.item", "⭐ Round {}:"),
+    ("starred.hint", "Type /starred export to save them as a best-answers digest"),
+    ("translate.usage", "⚠ Usage: /translate [--to LANG] TEXT"),
+    ("translate.source", "Source"),
+    ("translate.translation", "Translation ({})"),
+    ("translate.issues", "Issues"),
+    ("verdict.clean", "clean"),
+    ("verdict.minor", "minor issues"),
+    ("verdict.errors", "errors found"),
+    ("verdict.line", "Verdict: {}"),
+    ("verdict.missing", "⚠ The review did not end with a VERDICT line"),
+    ("redact.confirm", "⚠ Possible secrets: {}. [M]ask / [s]end as is / [c]ancel? "),
+    ("redact.cancelled", "Not sent."),
+    ("redact.masked", "🔒 Masked before sending: {}"),
+    ("banner.local_only", "Local-only: requests go to localhost backends only"),
+    ("crypto.passphrase", "Archive passphrase: "),
+    ("tag.none", "No tags. Type /tag NAME to add one."),
+    ("tag.list", "Tags: {}"),
+    ("index.title", "Saved conversations"),
+    ("index.date", "Date"),
+    ("index.session", "Session"),
+    ("index.tags", "Tags"),
+    ("index.models", "Models"),
+    ("index.rounds", "Rounds"),
+    ("index.skipped", "⚠ Left {} out of the index: {}"),
+    ("index.failed", "⚠ The session index was not updated: {}"),
+    ("index.written", "✓ Index written to: {}"),
+    ("publish.back", "← All sessions"),
+    ("publish.search", "Search questions, answers and reviews"),
+    ("publish.tag_heading", "Tag: {}"),
+    ("publish.unencrypted", "⚠ The archive is encrypted, but the published pages are plain HTML"),
+    ("publish.written", "✓ Site written to {} ({} sessions)"),
+    ("prune.unreadable", "⚠ Keeping {}: can't check it for stars ({})"),
+    ("prune.auto", "🗑 Retention: deleted {} old session(s)"),
+    ("prune.failed", "⚠ Retention pruning failed: {}"),
+    ("prune.nothing", "No sessions older than {} to delete."),
+    ("prune.would_delete", "Would delete {} session(s) (dry run):"),
+    ("prune.deleting", "Deleting {} session(s):"),
+    ("prune.deleted", "✓ Deleted {} session(s) ({} files)"),
+    ("recall.found", "↺ Asked before ({}, {}% match): {}"),
+    ("recall.found_semantic", "↺ Asked before in other words ({}, {}% similar): {}"),
+    ("recall.prompt", "[S]how the earlier reviewed answer / [a]sk again? "),
+    ("recall.force_hint", "(Shown from the archive, no tokens spent. Use /force QUESTION to ask anyway.)"),
+    ("force.usage", "⚠ Usage: /force QUESTION"),
+    ("memory.opened", "🧠 Memory: {} earlier round(s) indexed"),
+    ("memory.recalled", "🧠 Recalled {} related earlier round(s)"),
+    ("memory.failed", "⚠ Memory unavailable: {}"),
+    ("tools.called", "🔧 {}({})"),
+    ("tools.failed", "⚠ Tool {} failed: {}"),
+    ("tools.confirm_shell", "The answerer wants to run `{}`. Allow? [y/N] "),
+    ("plugins.failed", "⚠ Plugin {} failed in {}: {}"),
+    ("plugins.blocked", "⚠ Question blocked by plugin {}"),
+    ("plugins.scores", "Plugin scores: {}"),
+    ("webhook.failed", "⚠ Webhook {} failed: {}"),
+    ("http.accept_failed", "⚠ Failed to accept a connection: {}"),
+    ("bot.listening", "🤖 {} bot listening on {} (Ctrl+C to stop)"),
+    ("bot.question", "\n💬 [{}] {}"),
+    ("bot.review_heading", "Review ({})"),
+    ("bot.failed", "Sorry, no answer could be produced for this message."),
+    ("bot.rejected", "⚠ Rejected a request that failed the {} signature check"),
+    ("bot.encrypted", "⚠ Feishu sent an encrypted event; clear the Encrypt Key in the app's event settings"),
+    ("bot.reply_failed", "⚠ Failed to reply on {}: {}"),
+    ("bridge.telegram_ready", "🤖 Telegram bridge running as @{} (Ctrl+C to stop)"),
+    ("bridge.discord_ready", "🤖 Discord bridge listening on {} (Ctrl+C to stop)"),
+    ("bridge.poll_failed", "⚠ Telegram polling failed, retrying: {}"),
+    ("bridge.thinking", "⏳ Thinking…"),
+    ("bridge.help", "Ask a question (on Discord: /ask) and a second model reviews the answer. Earlier rounds in this chat are kept as context.\n/save – save this conversation\n/new – start a new conversation\n/help – this message"),
+    ("bridge.saved", "Saved {} round(s) as {}"),
+    ("bridge.save_failed", "Saving failed: {}"),
+    ("bridge.nothing_to_save", "Nothing to save yet. Ask a question first!"),
+    ("bridge.new", "Started a new conversation."),
+    ("bridge.new_unsaved", "Started a new conversation ({} unsaved round(s) dropped)."),
+    ("bridge.not_allowed", "This chat ({}) is not allowed to use this bot."),
+    ("quota.exceeded", "You've used your daily quota ({} of {} tokens). It resets at midnight."),
+    ("quota.refused", "⚠ User {} is over their daily quota ({} of {} tokens); question refused"),
+    ("share.expires", "Anyone with this link can read the session for {} days"),
+    ("share.failed", "⚠ Couldn't show shared session {}: {}"),
+    ("shield.hijacked", "⚠ The answer contains instructions aimed at the reviewer (\"{}\") and the review may have followed them; read it with care"),
+    ("schedule.next", "⏰ Next run at {} (Ctrl+C to stop)"),
+    ("schedule.saved", "✓ {} of {} question(s) answered, saved to {}"),
+    ("schedule.failed", "⚠ Scheduled run failed: {}"),
+    ("watch.watching", "👀 Watching {} for question files (Ctrl+C to stop)"),
+    ("watch.answered", "✓ {} answered: {}"),
+    ("watch.failed", "⚠ {} failed, moved to failed/: {}"),
+    ("command.prev.summary", "show an earlier round again"),
+    ("command.prev.details", "Shows the round before the one shown last (at first, the one before the latest); with N shows round N. In pager mode long rounds open in the pager."),
+    ("command.next.summary", "show the following round again"),
+    ("command.next.details", "Goes forward again after /prev, one round at a time."),
+    ("scroll.heading", "=== Round {} ==="),
+    ("scroll.position", "Round {} of {} · /prev and /next to move"),
+    ("scroll.empty", "No rounds in this session yet."),
+    ("scroll.first", "Already at the first round."),
+    ("scroll.latest", "Already at the latest round."),
+    ("scroll.usage", "⚠ Usage: /prev [N]"),
+    ("scroll.no_round", "⚠ No round {} in this session"),
+    ("command.quote.summary", "quote an earlier answer into the next question"),
+    ("command.quote.details", "Puts round N's answer (default: the latest) in front of your next question, or with BLOCK only its code block number BLOCK. `/quote off` drops it again."),
+    ("quote.usage", "⚠ Usage: /quote [N [BLOCK]] or /quote off"),
+    ("quote.no_round", "⚠ No round {} in this session"),
+    ("quote.no_block", "⚠ Round {} has {} code block(s)"),
+    ("quote.answer", "✓ Round {}'s answer ({} lines) will be quoted in your next question"),
+    ("quote.block", "✓ Code block {} of round {} ({} lines) will be quoted in your next question"),
+    ("quote.off", "✓ Quote dropped"),
+    ("command.copy.summary", "copy a code block to the clipboard"),
+    ("command.copy.details", "Copies code block BLOCK (the [N] next to its fence) of the latest answer, or of round ROUND. Uses pbcopy, wl-copy, xclip, xsel or clip.exe, or else the terminal."),
+    ("command.write.summary", "save a code block to a file"),
+    ("command.write.details", "Writes code block BLOCK of the latest answer (or of round ROUND) to FILE, asking before overwriting."),
+    ("command.run.summary", "run a code block"),
+    ("command.run.details", "Runs code block BLOCK of the latest answer (or of round ROUND) in the current directory after you confirm: sh/bash, Python, JavaScript, Ruby, Go, or Rust (via rustc). It is not sandboxed."),
+    ("command.check.summary", "cargo check a Rust code block"),
+    ("command.check.details", "Compiles code block BLOCK of the latest answer (or of round ROUND) with `cargo check` in a scratch crate, without running it."),
+    ("code.copy_usage", "⚠ Usage: /copy [ROUND] BLOCK"),
+    ("code.write_usage", "⚠ Usage: /write [ROUND] BLOCK FILE"),
+    ("code.run_usage", "⚠ Usage: /run [ROUND] BLOCK"),
+    ("code.check_usage", "⚠ Usage: /check [ROUND] BLOCK"),
+    ("code.no_round", "⚠ No round {} in this session"),
+    ("code.no_block", "⚠ Round {} has {} code block(s)"),
+    ("code.copied", "✓ Copied {} line(s) ({})"),
+    ("code.written", "✓ Wrote {} line(s) to {}"),
+    ("code.confirm_overwrite", "{} exists. Overwrite? [y/N] "),
+    ("code.cannot_run", "⚠ Don't know how to run \"{}\" code; try /copy or /write"),
+    ("code.confirm_run", "Run this {} code in the current directory? It is not sandboxed. [y/N] "),
+    ("code.checking", "Running cargo check ..."),
+    ("code.finished", "✓ Finished"),
+    ("code.failed", "⚠ Failed ({})"),
+    ("scores.line", "Scores: accuracy {} · completeness {} · clarity {} · code {}"),
+    ("stats.heading", "Review scores by answer model ({})"),
+    ("stats.empty", "No graded reviews recorded yet."),
+    ("stats.chart_heading", "{}: overall score by week"),
+    ("stats.reviews", "({} reviews)"),
+    ("command.stats.summary", "rounds, tokens, cost and review verdicts of this session"),
+    ("command.stats.details", "Shows the session's rounds (reviewed, starred), the review verdicts and average score, and tokens, average latency and estimated cost per provider since aivsai started. `aivsai stats --all` does the same for the whole archive."),
+    ("stats.session_heading", "Session started {}"),
+    ("stats.archive_heading", "All saved sessions ({})"),
+    ("stats.rounds", "Rounds: {} ({} reviewed, {} starred)"),
+    ("stats.verdicts", "Review verdicts: {}"),
+    ("stats.no_verdict", "no verdict {}"),
+    ("stats.average_score", "Average review score: {} over {} graded review(s)"),
+    ("command.length.summary", "set a target answer length"),
+    ("command.length.details", "short (~150 words), medium (~400 words) or long; short and medium also cap the answer's max_tokens. `off` removes the target. Starts from `[answer] length`."),
+    ("command.style.summary", "ask for bullet points, prose, or code only"),
+    ("command.style.details", "bullets, prose, or code (fenced code blocks only, no explanation). `off` leaves the format to the model. Starts from `[answer] style`."),
+    ("style.current", "Answer length: {} · style: {} · max tokens: {}"),
+    ("style.none", "any"),
+    ("style.no_limit", "no limit"),
+    ("style.length_usage", "⚠ Usage: /length [short|medium|long|off]"),
+    ("style.style_usage", "⚠ Usage: /style [bullets|prose|code|off]"),
+    ("command.focus.summary", "make the reviewer concentrate on security, performance or facts"),
+    ("command.focus.details", "Stackable: `/focus security performance=2` adds both, `=2` or `=3` weighs one more heavily. Applies to every review until `/focus off`; without arguments shows the current focus."),
+    ("focus.usage", "⚠ Usage: /focus ASPECT[=WEIGHT] ... or /focus off (aspects: {}; weights 1-{})"),
+    ("focus.current", "✓ Review focus: {}"),
+    ("focus.none", "No review focus set."),
+    ("focus.off", "✓ Review focus cleared"),
+    ("guard.question_flagged", "⚑ Question flagged by [guard]: {} (sending anyway)"),
+    ("guard.question_blocked", "⛔ Question blocked by [guard]: {}"),
+    ("guard.answer_flagged", "⚑ Answer flagged by [guard]: {}"),
+    ("guard.answer_blocked", "⛔ Answer withheld by [guard]: {} (the round was discarded)"),
+    ("guard.flagged_note", "⚑ Flagged by [guard]: {}"),
+    ("guard.unavailable", "⚠ Moderation check failed, only local [guard] rules applied: {}"),
+    ("keys.missing", "⚠ {} is not set; [providers.{}] rotates over the other keys"),
+    ("keys.switching", "↻ {} rate-limited this key; retrying with the next one (attempt {}/{})"),
+    ("command.reasoning.summary", "show the reviewer's full reasoning for round N (default: latest)"),
+    ("command.reasoning.details", "Reasoning reviewers such as deepseek-reasoner think before they review. The trace is shown folded above the review (`[review] reasoning`); this prints all of it. Traces are only kept in saved sessions with `[review] save_reasoning = true`."),
+    ("reasoning.header", "💭 Reasoning ({} lines)"),
+    ("reasoning.folded", "   … {} more lines (/reasoning shows all)"),
+    ("reasoning.usage", "Usage: /reasoning [N]"),
+    ("reasoning.no_round", "No round {} in this session"),
+    ("reasoning.none", "Round {} has no reasoning trace"),
+    ("panel.consolidated", "🧩 Panel consensus"),
+    ("ensemble.answering", "Asking {} answerers..."),
+    ("ensemble.rank_failed", "Ranking the answers failed, keeping the first one: {}"),
+    ("ensemble.heading", "🏆 Answer ranking"),
+    ("ensemble.rank", "Rank"),
+    ("ensemble.answerer", "Answerer"),
+    ("ensemble.length", "Length"),
+    ("ensemble.chars", "{} chars"),
+    ("api.continuing", "{} was cut off; asking it to continue ({}/{})..."),
+    ("api.continue_failed", "⚠ {} failed partway through the response; keeping what arrived: {}"),
+    ("finish.truncated", "answer truncated"),
+    ("finish.filtered", "stopped by the provider's content filter"),
+    ("finish.refused", "provider refused"),
+    ("finish.answer", "⚠️ Answer incomplete: {}"),
+    ("finish.review", "⚠️ Review incomplete: {}"),
+    ("compare.prompt", "You asked {} this in round {}. Compare the answers? [y/N] "),
+    ("compare.heading", "🔀 {} (round {}) → {}"),
+    ("compare.same", "No substantive differences."),
+    ("compare.folded", "  … {} unchanged lines"),
+    ("compare.summary", "{} lines removed, {} added"),
+    ("command.branch.summary", "fork the session at round N (default: the last) into a new one"),
+    ("command.branch.details", "The current session is saved first and stays as it is; the new session starts with its rounds up to N, so you can follow up in another direction. Save it with /save like any session."),
+    ("branch.empty", "Nothing to branch yet."),
+    ("branch.usage", "Usage: /branch [N], where N is a round of this session."),
+    ("branch.created", "Branched from the session of {} at round {}; the new session has {} rounds."),
+    ("export.branched_from", "Branched from the session of {} at round {}"),
+    ("command.undo.summary", "remove the last round"),
+    ("command.undo.details", "Removes the last round from the session, the context sent to the models and the crash-recovery copy, as if it had never been asked. Repeat to remove more."),
+    ("undo.empty", "There is no round to undo."),
+    ("undo.removed", "Removed round {}: {}"),
+    ("undo.saved_copy", "An earlier /save still contains it; /save again for a copy without it."),
+    ("command.edit_turn.summary", "edit round N's answer (or review) in $EDITOR"),
+    ("command.edit_turn.details", "`/edit-turn 3` opens round 3's answer in $VISUAL or $EDITOR (vi if neither is set); `/edit-turn 3 review` opens its review. Trim rambling or redact sensitive content before saving; exports note that the round was edited."),
+    ("edit_turn.usage", "Usage: /edit-turn N [answer|review]"),
+    ("edit_turn.no_round", "There is no round {}."),
+    ("edit_turn.not_reviewed", "Round {} has no review to edit."),
+    ("edit_turn.saved", "Updated the {} of round {}."),
+    ("edit_turn.unchanged", "No changes."),
+    ("export.edited", "(Edited by hand)"),
+    ("command.export.summary", "save only some rounds as Markdown or HTML"),
+    ("command.export.details", "`/export rounds 2-4,7` writes rounds 2, 3, 4 and 7 to a Markdown file in conversations/; add `html` for a self-contained web page instead. `/export anki [DECK]` writes the reviewed rounds as Anki flashcards (File → Import), `/export ipynb` the session as a Jupyter notebook whose Python and shell blocks are code cells, `/export pdf` the session as a PDF through the `[pdf] command` converter."),
+    ("export.usage", "Usage: /export rounds N-M,K [md|html] | anki [DECK] | ipynb | pdf"),
+    ("export.missing_rounds", "This session has no round {}."),
+    ("export.saved", "✓ {} rounds exported to: {}"),
+    ("export.rounds_title", "AIvsAI Selected Rounds"),
+    ("export.rounds_subtitle", "From the session of {}, rounds {}"),
+    ("anki.empty", "No reviewed rounds to make cards of (set [anki] include_unreviewed to include the others)."),
+    ("anki.saved", "✓ {} cards for deck \"{}\" saved to: {} (import it with File → Import in Anki)"),
+    ("notebook.saved", "✓ Notebook with {} code cells saved to: {}"),
+    ("pdf.converting", "Converting the session to PDF..."),
+    ("pdf.saved", "✓ PDF saved to: {}"),
+    ("batch.results_written", "✓ Results table written to: {}"),
+    ("vcr.write_failed", "Could not add a response to the cassette {}: {}"),
+    ("banner.recording", "Recording provider responses to the cassette (--record)"),
+    ("banner.replaying", "Replaying provider responses from the cassette; nothing is sent (--replay)"),
+    ("banner.dry_run", "Dry run: providers are not called, every response is canned (--dry-run)"),
+    ("command.preview.summary", "show the exact messages the next round will send"),
+    ("command.preview.details", "Prints, in full, the messages the next question would send to the answerer (system prompt, history in context, the question with @path files and a waiting /quote) and the review request, with the reviewer's focus. Give the question to see it assembled; nothing is sent."),
+    ("preview.question_placeholder", "(your next question)"),
+    ("preview.answer_placeholder", "(the answer)"),
+    ("preview.answerer", "To the answerer: {}"),
+    ("preview.reviewer", "To the reviewer: {}"),
+    ("preview.request", "{} · {} messages, about {} tokens"),
+    ("preview.message", "[{}] {} · about {} tokens"),
+    ("preview.not_shown", "Added only when the question is sent: background from memory, plugin and redaction changes to the question."),
+    ("command.persona.summary", "answer as a named persona (system prompt and parameters)"),
+    ("command.persona.details", "`/persona \"senior Rust reviewer\"` swaps the answerer's system prompt, and the temperature and token cap if the persona sets them, for the rest of the session; `/persona off` goes back to the profile's prompt. Personas are TOML files in `[answer] personas` (~/.aivsai/personas); `/personas` lists them."),
+    ("command.personas.summary", "list the personas /persona can switch to"),
+    ("command.personas.details", "Lists the persona files in `[answer] personas` with their name, parameters and the start of their prompt; the active one is marked with *."),
+    ("persona.current", "Persona: {} {}"),
+    ("persona.none", "No persona; the profile's answer prompt is used. /personas lists the available ones."),
+    ("persona.off", "✓ Persona off; answering with the profile's prompt again"),
+    ("persona.switched", "✓ Answering as {} {}"),
+    ("persona.unknown", "No persona \"{}\" in {} (see /personas)"),
+    ("persona.empty", "No personas in {} yet: add NAME.toml files with name, prompt and optionally temperature and max_tokens."),
+    ("persona.heading", "Personas in {}"),
+    ("persona.temperature", "temperature {}"),
+    ("persona.max_tokens", "{} tokens max"),
+    ("command.strict.summary", "set how hard the reviewer looks: low, medium or paranoid"),
+    ("command.strict.details", "low is a quick sanity check for real errors at a higher temperature; medium is the regular review; paranoid audits every claim and line of code at a low temperature. Applies to every review until changed; without arguments shows the current level. Starts from `[review] strictness`."),
+    ("strict.usage", "⚠ Usage: /strict [low|medium|paranoid]"),
+    ("strict.current", "Reviewer strictness: {}"),
+    ("strict.switched", "✓ Reviewer strictness: {}"),
+    ("strict.level_temperature", "{} (temperature {})"),
+    ("journal.session", "🗂 Session {} ({} → {})"),
+    ("notion.token_prompt", "Notion integration token: "),
+    ("notion.token_saved", "✓ Notion token stored in the keyring"),
+    ("notion.pushed", "✓ {} → {}"),
+    ("notion.round", "Round {}: {}"),
+    ("notion.question", "💬 Question"),
+    ("notion.answer", "🤖 Answer ({})"),
+    ("notion.review", "🔍 Review ({})"),
+    ("notion.review_failed", "Review failed: {}"),
+    ("notion.no_review", "Not reviewed."),
+    ("command.publish.summary", "share the session as a secret GitHub gist or a repository discussion"),
+    ("command.publish.details", "`/publish gist` posts the session's markdown as a secret gist; `/publish discussion OWNER/REPO [CATEGORY]` opens a discussion with it (category \"General\" by default). Prints the URL. Needs GITHUB_TOKEN (or GH_TOKEN); the text goes through `[redact]` first."),
+    ("github.usage", "⚠ Usage: /publish gist or /publish discussion OWNER/REPO [CATEGORY]"),
+    ("github.sending", "Publishing to GitHub..."),
+    ("github.description", "AIvsAI: {}"),
+    ("github.published", "✓ Published: {}"),
+    ("command.background.summary", "review in the background while you ask the next question"),
+    ("command.background.details", "Toggles (or sets) background reviews for this session: the prompt comes back as soon as the answer is in, and each review is printed and added to its round when it finishes. Exiting waits for reviews still running. Starts from `[review] background`; a `[panel]` always reviews in the foreground."),
+    ("background.usage", "⚠ Usage: /background [on|off]"),
+    ("background.on", "✓ Background reviews on (the prompt returns while the reviewer works)"),
+    ("background.off", "✓ Background reviews off"),
+    ("background.panel", "⚠ With a [panel] configured, rounds are still reviewed in the foreground"),
+    ("background.started", "⏳ Round {} is being reviewed in the background"),
+    ("background.done", "✓ Review of round {} is in"),
+    ("background.waiting", "⏳ Waiting for {} background review(s)..."),
+    ("background.lost", "⚠ The background review of round {} stopped unexpectedly"),
+    ("background.dropped", "⚠ Round {} changed or is gone; its background review was dropped"),
+    ("batch.resuming", "↻ Resuming: {} of {} questions already reviewed"),
+    ("batch.checkpoint_kept", "Progress kept in {}; run the batch again with --resume to ask only the questions that are left"),
+];
+
+const ZH_CN: &[(&str, &str)] = &[
+    ("review.summary_heading", "英文摘要（TL;DR）"),
+    ("export.review_failed", "（审查失败：{}）"),
+    ("export.not_reviewed", "（尚未审查）"),
+    ("export.truncated", "（回答被中断，内容不完整）"),
+    ("round.answer_header", "--- {} 的回答 ---"),
+    ("round.review_header", "--- {} 的审查 ---"),
+    ("review.summary_failed", "⚠ 英文摘要生成失败：{}"),
+    ("error.provider", "{} 出错：{}"),
+    ("export.round_starred", "第 {} 轮（已加星标）"),
+    ("export.round", "第 {} 轮"),
+    ("export.user_translate", "用户（翻译为 {}）"),
+    ("export.user", "用户"),
+    ("export.colon", "："),
+    ("export.best_title", "AIvsAI 精选回答"),
+    ("export.session_title", "AIvsAI 对话记录"),
+    ("save.saved", "✓ 对话已保存到：{}"),
+    ("save.rounds", "  共保存 {} 轮"),
+    ("save.failed", "✗ 保存对话失败：{}"),
+    ("prompt.save_before_exit", "还有未保存的轮次，退出前保存吗？[Y/n] "),
+    ("prompt.discard_unsaved", "还有未保存的轮次，确定丢弃吗？[y/N] "),
+    ("prompt.truncated_action", "审查部分回答 [R] / 保留但不审查 [k] / 丢弃本轮 [d]？"),
+    ("references.truncated_note", "，已截断"),
+    ("references.attached", "📎 {}（约 {} tokens{}）"),
+    ("references.skipped", "⚠ 未附加：{}"),
+    ("error.quiet_verbose", "错误：-q 与 -v 不能同时使用"),
+    ("error.generic", "错误：{}"),
+    ("error.config", "配置错误：{}"),
+    ("banner.pair", "   AI 组合：{}（回答）+ {}（审查）   "),
+    ("banner.profile", "配置档：{}"),
+    ("banner.project_settings", "项目设置：{}"),
+    ("banner.hint", "输入 /help 查看命令（Tab 可补全），exit/quit 退出"),
+    ("prompt.user", "用户 > "),
+    ("repl.exit_hint", "输入 exit 或 quit 退出。"),
+    ("error.read_input", "读取输入出错：{}"),
+    ("repl.unknown_command_suggest", "⚠ 未知命令 {}，是否想输入 {}？"),
+    ("repl.unknown_command", "⚠ 未知命令 {}，输入 /help 查看命令列表。"),
+    ("round.stopped_empty", "⚠ 回答尚未开始就被中断，本轮已丢弃"),
+    ("round.stopped_partial", "⚠ 回答已中断；保留部分内容并标记为不完整"),
+    ("round.discarded", "✓ 已丢弃第 {} 轮"),
+    ("round.kept_unreviewed", "第 {} 轮已保留但未审查。输入 /review-retry 可稍后审查"),
+    ("error.pager", "分页器错误：{}"),
+    ("round.completed", "第 {} 轮完成。输入 /save 保存本次对话"),
+    ("round.saved_unreviewed", "第 {} 轮已记录但审查失败。输入 /review-retry 重新审查"),
+    ("stdin.clipped_note", "，中间部分已省略"),
+    ("stdin.read", "📥 从标准输入读取了 {} 行{}"),
+    ("batch.saved", "✓ 批量结果已保存到：{}"),
+    ("commit_msg.draft_header", "--- {} 的草稿 ---"),
+    ("commit_msg.no_final", "⚠ 审查中没有给出最终提交信息，改用草稿"),
+    ("commit_msg.final_header", "--- 建议的提交信息 ---"),
+    ("usage.range", "{} 至 {}"),
+    ("usage.since", "{} 起"),
+    ("usage.until", "截至 {}"),
+    ("usage.all_time", "全部时间"),
+    ("usage.heading", "AIvsAI 用量（{}）"),
+    ("usage.empty", "该时间段内没有请求记录。"),
+    ("usage.total", "预估总费用：{} {}（价格取自 ~/.ai_vs_ai.toml 的 [usage.pricing]）"),
+    ("usage.csv_written", "✓ CSV 已写入：{}"),
+    ("api.sending", "正在向 {} 发送请求（第 {}/{} 次，约 {} 个提示 tokens）"),
+    ("api.rate_limited", "等待了 {} 秒（{} 限流）"),
+    ("api.retry_status", "{} 返回 {}，{} 秒后重试（{}/{}）"),
+    ("api.retry_network", "与 {} 通信时网络出错，{} 秒后重试（{}/{}）"),
+    ("api.request_done", "{}（{}）：{} 秒，提示 {} + 生成 {} tokens"),
+    ("api.request_failed", "{}（{}）：{} 秒后失败"),
+    ("api.thinking", "思考中（{}）..."),
+    ("api.thinking_stream", "思考中（{}）...（Ctrl+C 停止生成）"),
+    ("notify.failed", "⚠ 桌面通知发送失败：{}"),
+    ("notify.unavailable", "⚠ 桌面通知需要使用 `--features desktop-notifications` 构建"),
+    ("prompt.api_key", "请输入 {} 的 API Key："),
+    ("config.key_saved", "已将 {} 保存到 {}"),
+    ("notify.review_failed", "审查失败——输入 /review-retry 重新审查"),
+    ("notify.title", "AIvsAI：第 {} 轮审查完成"),
+    ("recovery.written", "未保存的对话已写入 {}"),
+    ("command.clear.summary", "清空模型上下文中之前的所有轮次"),
+    ("command.clear.details", "清空的轮次不再发送给回答模型，但 /save 时仍会保存。"),
+    ("command.forget.summary", "从模型上下文中移除最近 N 轮"),
+    ("command.forget.details", "与 /clear 类似，但只移除上下文中最近的 N 轮。"),
+    ("command.context.summary", "显示下一次请求会包含的全部内容"),
+    ("command.context.details", "列出系统提示词和历史消息，并粗略估算每条消息的 token 数。"),
+    ("command.help.summary", "列出所有命令，或显示某个命令的详情"),
+    ("command.help.details", "不带参数时列出所有命令。`/help save`（或 `/help /save`）说明单个命令。"),
+    ("command.load.summary", "列出已保存的会话，或恢复其中一个"),
+    ("command.load.details", "不带名称时列出最近保存的 10 个会话。带名称（可用 Tab 补全）时替换当前会话；如有未保存的轮次会先询问。"),
+    ("command.model.summary", "查看或切换回答模型"),
+    ("command.model.details", "不带名称时显示回答和审查模型。带名称时在本次会话剩余部分切换回答模型；每一轮都会记录回答它的模型。"),
+    ("command.pager.summary", "超过一屏的轮次使用分页器显示"),
+    ("command.pager.details", "切换（或设置）本次会话的分页模式。分页器依次取 `[pager] command`、$PAGER、`less -R`。"),
+    ("command.profile.summary", "列出配置档，或切换到其中一个"),
+    ("command.profile.details", "配置档是 ~/.ai_vs_ai.toml 中的 `[profile.NAME]` 表，组合了服务商、模型、提示词和语言。切换后会话保留，之后的轮次使用新的组合。"),
+    ("command.review_retry.summary", "重新运行失败或跳过的审查"),
+    ("command.review_retry.details", "不带参数时重试所有没有审查结果的轮次；带 N 时只重试第 N 轮。"),
+    ("command.save.summary", "将对话保存到 conversations/"),
+    ("command.save.details", "写入一个便于阅读的 markdown 文件和一个可供 /load 恢复的 .json 文件。保存后会清除崩溃恢复快照。"),
+    ("command.star.summary", "为最新一轮（或第 N 轮）切换星标"),
+    ("command.star.details", "加星标的轮次在保存的文件中会突出显示，并可用 /starred export 单独导出。"),
+    ("command.starred.summary", "列出加星标的轮次，或保存为精选摘要"),
+    ("command.starred.details", "`/starred export` 只将加星标的轮次写入精选回答 markdown 文件。"),
+    ("command.tag.summary", "列出、添加或删除本会话的标签"),
+    ("command.tag.details", "`/tag NAME...` 添加标签，`/tag -NAME` 删除标签。标签随会话保存，并列在 conversations/INDEX.md 中。"),
+    ("command.force.summary", "发送问题，不检查存档中是否问过"),
+    ("command.force.details", "与之前审查过的问题相同或相似（`[recall] threshold`）时，会先提供已保存的回答；/force 跳过此检查，重新向模型提问。"),
+    ("command.translate.summary", "翻译文本并由审查方检查译文"),
+    ("command.translate.details", "回答方负责翻译（中文译为英文，其他语言译为中文，除非用 --to 指定），审查方逐句检查准确性和术语。结果是一张原文 / 译文 / 问题对照表，会显示在屏幕上并写入保存的文件。"),
+    ("help.heading", "命令："),
+    ("help.exit", "退出（有未保存的轮次时会询问是否保存）"),
+    ("help.footer", "其他输入都会作为问题发送。输入 /help NAME 查看详情。"),
+    ("help.unknown", "⚠ 没有这个命令：{}。输入 /help 查看命令列表。"),
+    ("context.cleared", "✓ 上下文已清空（移除 {} 轮，/save 时仍会保存）"),
+    ("context.forgot", "✓ 已从上下文中移除 {} 轮（/save 时仍会保存）"),
+    ("context.forget_usage", "⚠ 用法：/forget N（N 为要移除的最近轮数）"),
+    ("context.heading", "下一次发给 {} 的请求将包含 {} 条消息 + 你的问题："),
+    ("context.tokens", "（约 {} tokens）"),
+    ("load.loaded", "✓ 已加载 {} 轮；新问题从第 {} 轮继续"),
+    ("load.empty", "还没有保存的会话，请先使用 /save。"),
+    ("load.heading", "已保存的会话（最新的在前）："),
+    ("load.hint", "输入 /load NAME 恢复会话（Tab 可补全名称）"),
+    ("model.current", "回答：{}  审查：{}"),
+    ("model.hint", "输入 /model NAME 切换（{}）"),
+    ("model.switched", "✓ {} 现在使用 {} 回答"),
+    ("pager.usage", "⚠ 用法：/pager [on|off]"),
+    ("pager.on", "✓ 分页器已开启（较长的轮次会在分页器中打开）"),
+    ("pager.off", "✓ 分页器已关闭"),
+    ("profile.current", "配置档 {}：{}（{}）回答，{}（{}）审查"),
+    ("profile.none", "尚未配置任何配置档。请在 ~/.ai_vs_ai.toml 中添加 [profile.NAME] 表"),
+    ("profile.hint", "输入 /profile NAME 切换（{}）"),
+    ("review_retry.usage", "⚠ 用法：/review-retry [N]"),
+    ("review_retry.none", "✓ 没有需要重试的审查。"),
+    ("review_retry.retrying", "正在重新审查第 {} 轮 ..."),
+    ("review_retry.no_round", "⚠ 本次会话中没有第 {} 轮"),
+    ("save.empty", "⚠ 还没有可保存的对话，先提个问题吧！"),
+    ("save.usage", "⚠ 用法：/save（文件名取自第一个问题）"),
+    ("star.usage", "⚠ 用法：/star [N]"),
+    ("star.starred", "⭐ 已为第 {} 轮加星标"),
+    ("star.unstarred", "已取消第 {} 轮的星标"),
+    ("star.no_round", "⚠ 还没有可以加星标的轮次。"),
+    ("starred.empty", "还没有加星标的轮次。使用 /star 标记一轮。"),
+    ("starred.saved", "✓ 精选回答已保存到：{}"),
+    ("starred.item", "⭐ 第 {} 轮："),
+    ("starred.hint", "输入 /starred export 将它们保存为精选回答摘要"),
+    ("translate.usage", "⚠ 用法：/translate [--to LANG] TEXT"),
+    ("translate.source", "原文"),
+    ("translate.translation", "译文（{}）"),
+    ("translate.issues", "问题"),
+    ("verdict.clean", "没有问题"),
+    ("verdict.minor", "有小问题"),
+    ("verdict.errors", "发现错误"),
+    ("verdict.line", "结论：{}"),
+    ("verdict.missing", "⚠ 审查末尾没有 VERDICT 结论行"),
+    ("redact.confirm", "⚠ 可能包含敏感信息：{}。遮盖 [M] / 原样发送 [s] / 取消 [c]？"),
+    ("redact.cancelled", "已取消发送。"),
+    ("redact.masked", "🔒 发送前已遮盖：{}"),
+    ("banner.local_only", "仅本地模式：请求只发往本机后端"),
+    ("crypto.passphrase", "存档密码："),
+    ("tag.none", "没有标签。输入 /tag NAME 添加。"),
+    ("tag.list", "标签：{}"),
+    ("index.title", "已保存的对话"),
+    ("index.date", "日期"),
+    ("index.session", "会话"),
+    ("index.tags", "标签"),
+    ("index.models", "模型"),
+    ("index.rounds", "轮数"),
+    ("index.skipped", "⚠ 未将 {} 列入索引：{}"),
+    ("index.failed", "⚠ 会话索引未更新：{}"),
+    ("index.written", "✓ 索引已写入：{}"),
+    ("publish.back", "← 全部会话"),
+    ("publish.search", "搜索问题、回答和审查"),
+    ("publish.tag_heading", "标签：{}"),
+    ("publish.unencrypted", "⚠ 存档已加密，但发布的页面是未加密的 HTML"),
+    ("publish.written", "✓ 网站已生成到 {}（{} 个会话）"),
+    ("prune.unreadable", "⚠ 保留 {}：无法检查其星标（{}）"),
+    ("prune.auto", "🗑 保留策略：已删除 {} 个旧会话"),
+    ("prune.failed", "⚠ 按保留策略清理失败：{}"),
+    ("prune.nothing", "没有早于 {} 的会话需要删除。"),
+    ("prune.would_delete", "将删除 {} 个会话（试运行）："),
+    ("prune.deleting", "正在删除 {} 个会话："),
+    ("prune.deleted", "✓ 已删除 {} 个会话（{} 个文件）"),
+    ("recall.found", "↺ 之前问过（{}，相似度 {}%）：{}"),
+    ("recall.found_semantic", "↺ 之前问过意思相同的问题（{}，语义相似度 {}%）：{}"),
+    ("recall.prompt", "显示之前审查过的回答 [S] / 重新提问 [a]？"),
+    ("recall.force_hint", "（来自存档，未消耗 token。使用 /force QUESTION 强制重新提问。）"),
+    ("force.usage", "⚠ 用法：/force QUESTION"),
+    ("memory.opened", "🧠 记忆：已索引 {} 个历史轮次"),
+    ("memory.recalled", "🧠 调取了 {} 个相关的历史轮次"),
+    ("memory.failed", "⚠ 记忆不可用：{}"),
+    ("tools.called", "🔧 {}({})"),
+    ("tools.failed", "⚠ 工具 {} 执行失败：{}"),
+    ("tools.confirm_shell", "回答方想要运行 `{}`，是否允许？[y/N] "),
+    ("plugins.failed", "⚠ 插件 {} 在 {} 中出错：{}"),
+    ("plugins.blocked", "⚠ 问题被插件拦截：{}"),
+    ("plugins.scores", "插件评分：{}"),
+    ("webhook.failed", "⚠ Webhook {} 调用失败：{}"),
+    ("http.accept_failed", "⚠ 接受连接失败：{}"),
+    ("bot.listening", "🤖 {} 机器人正在监听 {}（Ctrl+C 停止）"),
+    ("bot.question", "\n💬 [{}] {}"),
+    ("bot.review_heading", "审查（{}）"),
+    ("bot.failed", "抱歉，这条消息没能得到回答。"),
+    ("bot.rejected", "⚠ 已拒绝一个未通过 {} 签名校验的请求"),
+    ("bot.encrypted", "⚠ 飞书发送了加密事件；请在应用的事件订阅设置中清空 Encrypt Key"),
+    ("bot.reply_failed", "⚠ 在 {} 上回复失败：{}"),
+    ("bridge.telegram_ready", "🤖 Telegram 桥接已启动，机器人为 @{}（Ctrl+C 停止）"),
+    ("bridge.discord_ready", "🤖 Discord 桥接正在监听 {}（Ctrl+C 停止）"),
+    ("bridge.poll_failed", "⚠ Telegram 拉取消息失败，稍后重试：{}"),
+    ("bridge.thinking", "⏳ 思考中…"),
+    ("bridge.help", "发送问题（Discord 上用 /ask），回答会由第二个模型审查。本聊天中之前的轮次会作为上下文。\n/save – 保存本次对话\n/new – 开始新对话\n/help – 显示本说明"),
+    ("bridge.saved", "已保存 {} 轮，文件为 {}"),
+    ("bridge.save_failed", "保存失败：{}"),
+    ("bridge.nothing_to_save", "还没有可保存的对话，先提个问题吧！"),
+    ("bridge.new", "已开始新对话。"),
+    ("bridge.new_unsaved", "已开始新对话（丢弃了 {} 轮未保存的内容）。"),
+    ("bridge.not_allowed", "此聊天（{}）无权使用本机器人。"),
+    ("quota.exceeded", "你今天的额度已用完（已用 {} / {} 个 token），午夜后重置。"),
+    ("quota.refused", "⚠ 用户 {} 已超出今日额度（{} / {} 个 token），问题已拒绝"),
+    ("share.expires", "持有此链接的人可在 {} 天内查看该会话"),
+    ("share.failed", "⚠ 无法显示分享的会话 {}：{}"),
+    ("shield.hijacked", "⚠ 回答中含有针对审查者的指令（“{}”），审查可能受其影响，请谨慎阅读"),
+    ("schedule.next", "⏰ 下次运行时间：{}（Ctrl+C 停止）"),
+    ("schedule.saved", "✓ 已回答 {}/{} 个问题，保存到 {}"),
+    ("schedule.failed", "⚠ 定时运行失败：{}"),
+    ("watch.watching", "👀 正在监视 {} 中的问题文件（Ctrl+C 停止）"),
+    ("watch.answered", "✓ {} 已回答：{}"),
+    ("watch.failed", "⚠ {} 处理失败，已移到 failed/：{}"),
+    ("command.prev.summary", "重新显示之前的轮次"),
+    ("command.prev.details", "显示上次所显示轮次的前一轮（第一次使用时为最新一轮的前一轮）；带 N 时显示第 N 轮。分页模式下较长的轮次使用分页器显示。"),
+    ("command.next.summary", "重新显示之后的轮次"),
+    ("command.next.details", "在 /prev 之后逐轮向后翻看。"),
+    ("scroll.heading", "=== 第 {} 轮 ==="),
+    ("scroll.position", "第 {} 轮，共 {} 轮 · 用 /prev 和 /next 翻看"),
+    ("scroll.empty", "本次会话还没有任何轮次。"),
+    ("scroll.first", "已经是第一轮。"),
+    ("scroll.latest", "已经是最新一轮。"),
+    ("scroll.usage", "⚠ 用法：/prev [N]"),
+    ("scroll.no_round", "⚠ 本次会话中没有第 {} 轮"),
+    ("command.quote.summary", "在下一个问题中引用之前的回答"),
+    ("command.quote.details", "把第 N 轮的回答（默认最新一轮）放在下一个问题前面；带 BLOCK 时只引用其中第 BLOCK 个代码块。`/quote off` 取消引用。"),
+    ("quote.usage", "⚠ 用法：/quote [N [BLOCK]] 或 /quote off"),
+    ("quote.no_round", "⚠ 本次会话中没有第 {} 轮"),
+    ("quote.no_block", "⚠ 第 {} 轮共有 {} 个代码块"),
+    ("quote.answer", "✓ 下一个问题将引用第 {} 轮的回答（{} 行）"),
+    ("quote.block", "✓ 下一个问题将引用代码块 {}（第 {} 轮，{} 行）"),
+    ("quote.off", "✓ 已取消引用"),
+    ("command.copy.summary", "复制代码块到剪贴板"),
+    ("command.copy.details", "复制最新回答（或第 ROUND 轮回答）中的第 BLOCK 个代码块（即代码块开头标注的 [N]）。依次尝试 pbcopy、wl-copy、xclip、xsel、clip.exe，都没有时通过终端复制。"),
+    ("command.write.summary", "把代码块保存到文件"),
+    ("command.write.details", "把最新回答（或第 ROUND 轮回答）中的第 BLOCK 个代码块写入 FILE，覆盖已有文件前会询问。"),
+    ("command.run.summary", "运行代码块"),
+    ("command.run.details", "确认后在当前目录运行最新回答（或第 ROUND 轮回答）中的第 BLOCK 个代码块：支持 sh/bash、Python、JavaScript、Ruby、Go，以及 Rust（通过 rustc）。不在沙箱中运行。"),
+    ("command.check.summary", "用 cargo check 检查 Rust 代码块"),
+    ("command.check.details", "在临时 crate 中用 `cargo check` 编译最新回答（或第 ROUND 轮回答）中的第 BLOCK 个代码块，不会运行它。"),
+    ("code.copy_usage", "⚠ 用法：/copy [ROUND] BLOCK"),
+    ("code.write_usage", "⚠ 用法：/write [ROUND] BLOCK FILE"),
+    ("code.run_usage", "⚠ 用法：/run [ROUND] BLOCK"),
+    ("code.check_usage", "⚠ 用法：/check [ROUND] BLOCK"),
+    ("code.no_round", "⚠ 本次会话中没有第 {} 轮"),
+    ("code.no_block", "⚠ 第 {} 轮共有 {} 个代码块"),
+    ("code.copied", "✓ 已复制 {} 行（{}）"),
+    ("code.written", "✓ 已将 {} 行写入 {}"),
+    ("code.confirm_overwrite", "{} 已存在，是否覆盖？[y/N] "),
+    ("code.cannot_run", "⚠ 不知道如何运行 {} 代码；可以用 /copy 或 /write"),
+    ("code.confirm_run", "在当前目录运行这段 {} 代码？它不在沙箱中运行。[y/N] "),
+    ("code.checking", "正在运行 cargo check ..."),
+    ("code.finished", "✓ 完成"),
+    ("code.failed", "⚠ 失败（{}）"),
+    ("scores.line", "评分：准确性 {} · 完整性 {} · 清晰度 {} · 代码 {}"),
+    ("stats.heading", "按回答模型统计的审查评分（{}）"),
+    ("stats.empty", "还没有记录任何带评分的审查。"),
+    ("stats.chart_heading", "{}：每周综合评分"),
+    ("stats.reviews", "（{} 次审查）"),
+    ("command.stats.summary", "本次会话的轮数、token、费用和审查结论"),
+    ("command.stats.details", "显示本次会话的轮数（已审查、已加星）、审查结论分布和平均评分，以及自 aivsai 启动以来各服务商的 token 用量、平均延迟和估算费用。`aivsai stats --all` 对整个存档做同样的统计。"),
+    ("stats.session_heading", "会话开始于 {}"),
+    ("stats.archive_heading", "全部已保存会话（{} 个）"),
+    ("stats.rounds", "轮数：{}（已审查 {}，已加星 {}）"),
+    ("stats.verdicts", "审查结论：{}"),
+    ("stats.no_verdict", "无结论 {}"),
+    ("stats.average_score", "平均审查评分：{}（共 {} 次评分）"),
+    ("command.length.summary", "设置回答的目标长度"),
+    ("command.length.details", "short（约 150 词）、medium（约 400 词）或 long；short 和 medium 同时限制回答的 max_tokens。`off` 取消目标长度。初始值取自 `[answer] length`。"),
+    ("command.style.summary", "要求使用要点列表、段落或只输出代码"),
+    ("command.style.details", "bullets、prose 或 code（只输出代码块，不做解释）。`off` 由模型自行决定格式。初始值取自 `[answer] style`。"),
+    ("style.current", "回答长度：{} · 格式：{} · 最大 token：{}"),
+    ("style.none", "不限"),
+    ("style.no_limit", "不限"),
+    ("style.length_usage", "⚠ 用法：/length [short|medium|long|off]"),
+    ("style.style_usage", "⚠ 用法：/style [bullets|prose|code|off]"),
+    ("command.focus.summary", "让审查方重点关注安全、性能或事实"),
+    ("command.focus.details", "可叠加：`/focus security performance=2` 同时添加两项，`=2` 或 `=3` 加重某一项的权重。在 `/focus off` 之前对每次审查生效；不带参数时显示当前重点。"),
+    ("focus.usage", "⚠ 用法：/focus ASPECT[=WEIGHT] ... 或 /focus off（可选：{}；权重 1-{}）"),
+    ("focus.current", "✓ 审查重点：{}"),
+    ("focus.none", "尚未设置审查重点。"),
+    ("focus.off", "✓ 已清除审查重点"),
+    ("guard.question_flagged", "⚑ 问题被 [guard] 标记：{}（仍会发送）"),
+    ("guard.question_blocked", "⛔ 问题被 [guard] 拦截：{}"),
+    ("guard.answer_flagged", "⚑ 回答被 [guard] 标记：{}"),
+    ("guard.answer_blocked", "⛔ 回答被 [guard] 拦截：{}（本轮已丢弃）"),
+    ("guard.flagged_note", "⚑ 被 [guard] 标记：{}"),
+    ("guard.unavailable", "⚠ 内容审核请求失败，仅应用本地 [guard] 规则：{}"),
+    ("keys.missing", "⚠ 未设置 {}；[providers.{}] 将在其余密钥间轮换"),
+    ("keys.switching", "↻ {} 对当前密钥限流，改用下一个密钥重试（第 {}/{} 次）"),
+    ("command.reasoning.summary", "显示第 N 轮审查方的完整推理过程（默认最新一轮）"),
+    ("command.reasoning.details", "deepseek-reasoner 等推理模型会先思考再审查。推理过程默认折叠显示在审查上方（`[review] reasoning`），此命令显示全部内容。只有设置 `[review] save_reasoning = true` 时才会保存到会话文件中。"),
+    ("reasoning.header", "💭 推理过程（{} 行）"),
+    ("reasoning.folded", "   … 还有 {} 行（/reasoning 显示全部）"),
+    ("reasoning.usage", "用法：/reasoning [N]"),
+    ("reasoning.no_round", "本次会话中没有第 {} 轮"),
+    ("reasoning.none", "第 {} 轮没有推理过程"),
+    ("panel.consolidated", "🧩 综合审查"),
+    ("ensemble.answering", "正在询问 {} 个回答者..."),
+    ("ensemble.rank_failed", "答案排名失败，保留第一个答案：{}"),
+    ("ensemble.heading", "🏆 答案排名"),
+    ("ensemble.rank", "名次"),
+    ("ensemble.answerer", "回答者"),
+    ("ensemble.length", "长度"),
+    ("ensemble.chars", "{} 字"),
+    ("api.continuing", "{} 的回复被截断，正在请求继续（{}/{}）..."),
+    ("api.continue_failed", "⚠ {} 在回复中途失败，保留已收到的内容：{}"),
+    ("finish.truncated", "回答被截断"),
+    ("finish.filtered", "被服务商的内容过滤器拦截"),
+    ("finish.refused", "服务商拒绝回答"),
+    ("finish.answer", "⚠️ 回答不完整：{}"),
+    ("finish.review", "⚠️ 审查不完整：{}"),
+    ("compare.prompt", "曾向 {} 提过这个问题（第 {} 轮）。比较两个回答？[y/N] "),
+    ("compare.heading", "🔀 {}（第 {} 轮）→ {}"),
+    ("compare.same", "没有实质性差异。"),
+    ("compare.folded", "  … {} 行未变"),
+    ("compare.summary", "删除 {} 行，新增 {} 行"),
+    ("command.branch.summary", "在第 N 轮（默认最后一轮）把会话分叉为新会话"),
+    ("command.branch.details", "先保存当前会话并保持不变；新会话包含到第 N 轮为止的内容，可以朝另一个方向继续追问。新会话和其他会话一样用 /save 保存。"),
+    ("branch.empty", "还没有可以分叉的内容。"),
+    ("branch.usage", "用法：/branch [N]，N 为本会话中的轮次。"),
+    ("branch.created", "已从 {} 的会话第 {} 轮分叉；新会话有 {} 轮。"),
+    ("export.branched_from", "分叉自 {} 的会话第 {} 轮"),
+    ("command.undo.summary", "删除最后一轮"),
+    ("command.undo.details", "从会话、发送给模型的上下文和崩溃恢复副本中删除最后一轮，就像从未问过一样。重复使用可删除更多轮。"),
+    ("undo.empty", "没有可以撤销的轮次。"),
+    ("undo.removed", "已删除第 {} 轮：{}"),
+    ("undo.saved_copy", "之前 /save 的文件中仍包含这一轮；再次 /save 可保存不含它的副本。"),
+    ("command.edit_turn.summary", "在 $EDITOR 中编辑第 N 轮的回答（或审查）"),
+    ("command.edit_turn.details", "`/edit-turn 3` 在 $VISUAL 或 $EDITOR（都未设置时用 vi）中打开第 3 轮的回答；`/edit-turn 3 review` 打开它的审查。可在保存前删减冗长内容或隐去敏感信息；导出会注明该轮经过编辑。"),
+    ("edit_turn.usage", "用法：/edit-turn N [answer|review]"),
+    ("edit_turn.no_round", "没有第 {} 轮。"),
+    ("edit_turn.not_reviewed", "第 {} 轮没有可编辑的审查。"),
+    ("edit_turn.saved", "已更新 {}（第 {} 轮）。"),
+    ("edit_turn.unchanged", "没有改动。"),
+    ("export.edited", "（经手动编辑）"),
+    ("command.export.summary", "只把部分轮次导出为 Markdown 或 HTML"),
+    ("command.export.details", "`/export rounds 2-4,7` 把第 2、3、4、7 轮写入 conversations/ 中的 Markdown 文件；加上 `html` 则生成独立的网页。`/export anki [DECK]` 把已审查的轮次导出为 Anki 卡片（文件 → 导入），`/export ipynb` 把会话导出为 Jupyter 笔记本，其中 Python 和 shell 代码块成为代码单元，`/export pdf` 通过 `[pdf] command` 转换器把会话导出为 PDF。"),
+    ("export.usage", "用法：/export rounds N-M,K [md|html] | anki [DECK] | ipynb | pdf"),
+    ("export.missing_rounds", "本会话没有第 {} 轮。"),
+    ("export.saved", "✓ 已导出 {} 轮到：{}"),
+    ("export.rounds_title", "AIvsAI 精选轮次"),
+    ("export.rounds_subtitle", "来自 {} 的会话，第 {} 轮"),
+    ("anki.empty", "没有已审查的轮次可制成卡片（设置 [anki] include_unreviewed 可包含其他轮次）。"),
+    ("anki.saved", "✓ 已保存 {} 张卡片（牌组「{}」）到：{}（在 Anki 中用 文件 → 导入）"),
+    ("notebook.saved", "✓ 含 {} 个代码单元的笔记本已保存到：{}"),
+    ("pdf.converting", "正在把会话转换为 PDF……"),
+    ("pdf.saved", "✓ PDF 已保存到：{}"),
+    ("batch.results_written", "✓ 结果表已写入：{}"),
+    ("vcr.write_failed", "无法把响应写入录制文件 {}：{}"),
+    ("banner.recording", "正在把服务商响应录制到文件（--record）"),
+    ("banner.replaying", "正在从录制文件回放服务商响应，不会发送任何请求（--replay）"),
+    ("banner.dry_run", "演练模式：不调用服务商，所有响应均为预设内容（--dry-run）"),
+    ("command.preview.summary", "显示下一轮将发送的完整消息"),
+    ("command.preview.details", "完整打印下一个问题会发给回答模型的消息（系统提示词、上下文中的历史、带 @path 文件和待用 /quote 的问题）以及审查请求（含审查重点）。给出问题即可看到组装结果；不会发送任何内容。"),
+    ("preview.question_placeholder", "（你的下一个问题）"),
+    ("preview.answer_placeholder", "（回答）"),
+    ("preview.answerer", "发给回答模型：{}"),
+    ("preview.reviewer", "发给审查模型：{}"),
+    ("preview.request", "{} · {} 条消息，约 {} tokens"),
+    ("preview.message", "[{}] {} · 约 {} tokens"),
+    ("preview.not_shown", "仅在真正发送时加入：记忆中的背景信息，以及插件和脱敏对问题的修改。"),
+    ("command.persona.summary", "以指定角色回答（系统提示词和参数）"),
+    ("command.persona.details", "`/persona \"senior Rust reviewer\"` 在本次会话剩余时间内替换回答模型的系统提示词，以及角色设定的温度和 token 上限；`/persona off` 恢复配置档的提示词。角色是 `[answer] personas`（~/.aivsai/personas）中的 TOML 文件；`/personas` 列出它们。"),
+    ("command.personas.summary", "列出 /persona 可切换的角色"),
+    ("command.personas.details", "列出 `[answer] personas` 中的角色文件及其名称、参数和提示词开头；当前角色以 * 标出。"),
+    ("persona.current", "当前角色：{} {}"),
+    ("persona.none", "未使用角色，使用配置档的回答提示词。/personas 可列出可用角色。"),
+    ("persona.off", "✓ 已关闭角色，恢复使用配置档的提示词"),
+    ("persona.switched", "✓ 现在以 {} 的身份回答 {}"),
+    ("persona.unknown", "没有名为“{}”的角色（目录 {}，见 /personas）"),
+    ("persona.empty", "{} 中还没有角色：添加包含 name、prompt 以及可选 temperature、max_tokens 的 NAME.toml 文件。"),
+    ("persona.heading", "{} 中的角色"),
+    ("persona.temperature", "温度 {}"),
+    ("persona.max_tokens", "最多 {} tokens"),
+    ("command.strict.summary", "设置审查的严格程度：low、medium 或 paranoid"),
+    ("command.strict.details", "low 只做快速检查、只指出真正的错误，温度较高；medium 为常规审查；paranoid 逐条检查每个论断和每行代码，温度较低。在再次修改之前对每次审查生效；不带参数时显示当前级别。初始值取自 `[review] strictness`。"),
+    ("strict.usage", "⚠ 用法：/strict [low|medium|paranoid]"),
+    ("strict.current", "审查严格程度：{}"),
+    ("strict.switched", "✓ 审查严格程度：{}"),
+    ("strict.level_temperature", "{}（温度 {}）"),
+    ("journal.session", "🗂 会话 {}（{} → {}）"),
+    ("notion.token_prompt", "Notion 集成令牌："),
+    ("notion.token_saved", "✓ Notion 令牌已存入系统钥匙串"),
+    ("notion.pushed", "✓ {} → {}"),
+    ("notion.round", "第 {} 轮：{}"),
+    ("notion.question", "💬 问题"),
+    ("notion.answer", "🤖 回答（{}）"),
+    ("notion.review", "🔍 审查（{}）"),
+    ("notion.review_failed", "审查失败：{}"),
+    ("notion.no_review", "未审查。"),
+    ("command.publish.summary", "将会话发布为私密 GitHub Gist 或仓库讨论"),
+    ("command.publish.details", "`/publish gist` 将会话的 markdown 发布为私密 Gist；`/publish discussion OWNER/REPO [CATEGORY]` 用它创建一个讨论（默认分类 \"General\"）。完成后显示链接。需要 GITHUB_TOKEN（或 GH_TOKEN）；内容会先经过 `[redact]` 过滤。"),
+    ("github.usage", "⚠ 用法：/publish gist 或 /publish discussion OWNER/REPO [CATEGORY]"),
+    ("github.sending", "正在发布到 GitHub..."),
+    ("github.description", "AIvsAI：{}"),
+    ("github.published", "✓ 已发布：{}"),
+    ("command.background.summary", "在后台审查，同时可以提出下一个问题"),
+    ("command.background.details", "切换（或设置）本次会话的后台审查：回答一到提示符就返回，每条审查完成后会显示并补入对应的轮次。退出时会等待仍在进行的审查。初始值取自 `[review] background`；配置了 `[panel]` 时始终在前台审查。"),
+    ("background.usage", "⚠ 用法：/background [on|off]"),
+    ("background.on", "✓ 后台审查已开启（审查进行时提示符即返回）"),
+    ("background.off", "✓ 后台审查已关闭"),
+    ("background.panel", "⚠ 已配置 [panel]，轮次仍在前台审查"),
+    ("background.started", "⏳ 第 {} 轮正在后台审查"),
+    ("background.done", "✓ 第 {} 轮的审查已完成"),
+    ("background.waiting", "⏳ 正在等待 {} 个后台审查完成..."),
+    ("background.lost", "⚠ 第 {} 轮的后台审查意外中止"),
+    ("background.dropped", "⚠ 第 {} 轮已更改或已不存在，其后台审查已丢弃"),
+    ("batch.resuming", "↻ 继续上次的运行：{} / {} 个问题已审查"),
+    ("batch.checkpoint_kept", "进度已保存在 {}；使用 --resume 再次运行，只会提出剩下的问题"),
+];
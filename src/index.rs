@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::archive::{self, conversations_dir, saved_sessions};
+use crate::i18n::tr;
+use crate::session::ConversationSession;
+use crate::ui;
+
+// conversations/INDEX.md (for people) and INDEX.json (for tools) list every
+// saved session. Both are rebuilt after each save and by `aivsai reindex`;
+// sessions whose file hasn't changed are taken from the previous manifest, so
+// an encrypted archive isn't decrypted file by file on every save.
+
+pub const INDEX_MARKDOWN: &str = "INDEX.md";
+pub const INDEX_JSON: &str = "INDEX.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IndexEntry {
+    // Session JSON file, relative to the conversations directory
+    pub file: String,
+    // Markdown export next to it
+    pub markdown: String,
+    pub date: String,
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub models: Vec<String>,
+    pub rounds: usize,
+    #[serde(default)]
+    pub starred: usize,
+    // Modification time of `file` when this entry was built (seconds since the epoch)
+    pub modified: u64,
+}
+
+fn modified(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+// `x.json` → `x.md`, `x.json.age` → `x.md.age`
+fn markdown_name(file: &str) -> String {
+    match file.strip_suffix(".json.age") {
+        Some(stem) => format!("{}.md.age", stem),
+        None => format!("{}.md", file.strip_suffix(".json").unwrap_or(file)),
+    }
+}
+
+// Index line for a loaded session (`modified` is left for the caller to fill in)
+pub fn entry(file: &str, session: &ConversationSession) -> IndexEntry {
+    let title: String = session.first_question().unwrap_or_default().lines().next().unwrap_or_default().chars().take(80).collect();
+    let mut models: Vec<String> = Vec::new();
+    for turn in &session.turns {
+        for model in [&turn.answer_model, &turn.review_model] {
+            if !model.is_empty() && !models.contains(model) {
+                models.push(model.clone());
+            }
+        }
+    }
+    IndexEntry {
+        file: file.to_string(),
+        markdown: markdown_name(file),
+        date: session.start_time.clone(),
+        title,
+        tags: session.tags.clone(),
+        models,
+        rounds: session.len(),
+        starred: session.starred_turns().count(),
+        modified: 0,
+    }
+}
+
+fn previous_manifest(dir: &Path) -> Vec<IndexEntry> {
+    [INDEX_JSON.to_string(), format!("{}.age", INDEX_JSON)]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+        .and_then(|path| archive::read_file(&path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn render_markdown(entries: &[IndexEntry]) -> String {
+    let mut lines = vec![
+        format!("# {}", tr!("index.title")),
+        String::new(),
+        format!(
+            "| {} | {} | {} | {} | {} |",
+            tr!("index.date"),
+            tr!("index.session"),
+            tr!("index.tags"),
+            tr!("index.models"),
+            tr!("index.rounds")
+        ),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ];
+    for entry in entries {
+        let title = if entry.title.is_empty() { entry.file.clone() } else { entry.title.clone() };
+        let rounds = if entry.starred > 0 { format!("{} (⭐ {})", entry.rounds, entry.starred) } else { entry.rounds.to_string() };
+        lines.push(format!(
+            "| {} | [{}](<{}>) | {} | {} | {} |",
+            entry.date,
+            markdown_cell(&title),
+            entry.markdown,
+            markdown_cell(&entry.tags.join(", ")),
+            entry.models.join(", "),
+            rounds
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+// Every saved session, newest first, reusing unchanged entries from the last manifest
+fn entries(dir: &Path) -> Vec<IndexEntry> {
+    let previous = previous_manifest(dir);
+    let mut entries = Vec::new();
+    for file in saved_sessions() {
+        let path = dir.join(&file);
+        let stamp = modified(&path);
+        if let Some(known) = previous.iter().find(|e| e.file == file && e.modified == stamp) {
+            entries.push(known.clone());
+            continue;
+        }
+        match ConversationSession::load(&path) {
+            Ok(session) => {
+                let mut entry = entry(&file, &session);
+                // Sessions saved to a daily journal link to it
+                let journal = archive::written_path(Path::new(&archive::journal_name(&session.start_time)));
+                if !dir.join(&entry.markdown).exists() && dir.join(&journal).exists() {
+                    entry.markdown = journal.to_string_lossy().into_owned();
+                }
+                entries.push(IndexEntry { modified: stamp, ..entry })
+            }
+            Err(e) => ui::notice(ui::warning(tr!("index.skipped", file, format!("{:#}", e)))),
+        }
+    }
+    entries
+}
+
+// Rewrite INDEX.md and INDEX.json; returns the path of INDEX.md
+pub fn rebuild() -> Result<PathBuf> {
+    let dir = conversations_dir()?;
+    let entries = entries(&dir);
+    let manifest = serde_json::to_string_pretty(&entries)?;
+    archive::write_file(&dir.join(INDEX_JSON), &manifest).context("Failed to write the session manifest")?;
+    archive::write_file(&dir.join(INDEX_MARKDOWN), &render_markdown(&entries)).context("Failed to write the session index")
+}
+
+// `aivsai reindex`
+pub fn run_reindex(args: &[String]) -> Result<()> {
+    if !args.is_empty() {
+        anyhow::bail!("Usage: aivsai reindex (rebuilds INDEX.md and INDEX.json in the conversations directory)");
+    }
+    let path = rebuild()?;
+    ui::notice(ui::success(tr!("index.written", path.display())));
+    Ok(())
+}
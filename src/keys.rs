@@ -0,0 +1,110 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::KeyRotation;
+use crate::i18n::tr;
+use crate::{api, ui};
+
+// The API keys of one provider. With several (`api_key_envs`), requests rotate
+// between them, and a key that gets a 429 rests for the time the provider asked
+// while the others carry on. Keys are known by their environment variable, which
+// is what the usage ledger records; the key itself is never written anywhere.
+
+struct Key {
+    env: String,
+    value: String,
+    paused_until: Mutex<Option<Instant>>,
+}
+
+impl Key {
+    fn paused(&self, now: Instant) -> bool {
+        self.paused_until.lock().unwrap_or_else(|e| e.into_inner()).is_some_and(|until| until > now)
+    }
+}
+
+pub struct ApiKeys {
+    keys: Vec<Key>,
+    rotation: KeyRotation,
+    // Next key for round-robin; the current one for on-429
+    next: AtomicUsize,
+}
+
+impl ApiKeys {
+    pub fn single(value: String) -> Self {
+        Self::new(vec![(String::new(), value)], KeyRotation::RoundRobin)
+    }
+
+    fn new(keys: Vec<(String, String)>, rotation: KeyRotation) -> Self {
+        Self {
+            keys: keys
+                .into_iter()
+                .map(|(env, value)| Key {
+                    env,
+                    value,
+                    paused_until: Mutex::new(None),
+                })
+                .collect(),
+            rotation,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    // The keys in `envs` that are set; unset ones are skipped with a warning
+    pub fn from_env(provider: &str, envs: &[String], rotation: KeyRotation) -> Result<Self> {
+        // Replayed and canned responses need no key
+        if api::offline() {
+            return Ok(Self::new(envs.iter().map(|env| (env.clone(), String::new())).collect(), rotation));
+        }
+        let mut keys = Vec::new();
+        for env in envs {
+            match std::env::var(env) {
+                Ok(value) if !value.is_empty() => keys.push((env.clone(), value)),
+                _ => ui::notice(ui::warning(tr!("keys.missing", env, provider))),
+            }
+        }
+        if keys.is_empty() {
+            anyhow::bail!("None of the api_key_envs of [providers.{}] is set", provider);
+        }
+        Ok(Self::new(keys, rotation))
+    }
+
+    // Key for the next request: the next one in turn (round-robin) or the
+    // current one (on-429), skipping keys that are resting after a 429
+    pub fn pick(&self) -> usize {
+        let count = self.keys.len();
+        if count == 1 {
+            return 0;
+        }
+        let start = match self.rotation {
+            KeyRotation::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed),
+            KeyRotation::On429 => self.next.load(Ordering::Relaxed),
+        };
+        let now = Instant::now();
+        (0..count).map(|i| (start + i) % count).find(|&i| !self.keys[i].paused(now)).unwrap_or(start % count)
+    }
+
+    pub fn value(&self, index: usize) -> &str {
+        &self.keys[index].value
+    }
+
+    // Environment variable of the key, for the usage ledger; None with a single key
+    pub fn label(&self, index: usize) -> Option<String> {
+        (self.keys.len() > 1).then(|| self.keys[index].env.clone())
+    }
+
+    // A 429 on key `index`: rest it for `delay` and move on. True if another
+    // key can take the retry right away.
+    pub fn rate_limited(&self, index: usize, delay: Duration) -> bool {
+        if self.keys.len() == 1 {
+            return false;
+        }
+        let now = Instant::now();
+        *self.keys[index].paused_until.lock().unwrap_or_else(|e| e.into_inner()) = Some(now + delay);
+        if self.rotation == KeyRotation::On429 {
+            self.next.store((index + 1) % self.keys.len(), Ordering::Relaxed);
+        }
+        self.keys.iter().any(|key| !key.paused(now))
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::OnceLock;
+use unicode_width::UnicodeWidthChar;
+
+use crate::config::LayoutSettings;
+
+// Display-width-aware wrapping for answers and reviews. Widths come from
+// unicode-width so CJK characters count as two columns; English words are
+// never split, CJK text may break between any two characters, and fenced
+// code blocks and tables are left untouched.
+
+static LAYOUT: OnceLock<LayoutSettings> = OnceLock::new();
+
+fn settings() -> &'static LayoutSettings {
+    LAYOUT.get_or_init(LayoutSettings::default)
+}
+
+pub fn init(settings: &LayoutSettings) {
+    let _ = LAYOUT.set(settings.clone());
+}
+
+// Columns available for terminal output; None when wrapping is off or stdout isn't a terminal
+pub fn terminal_width() -> Option<usize> {
+    let settings = settings();
+    if !settings.wrap {
+        return None;
+    }
+    let (terminal_size::Width(columns), _) = terminal_size::terminal_size()?;
+    Some((columns as usize).min(settings.max_width).max(20))
+}
+
+// Horizontal rule that never runs past the edge of the terminal
+pub fn rule() -> String {
+    "-".repeat(terminal_width().map_or(42, |w| w.min(42)))
+}
+
+// Wrap a complete block of text for the terminal
+pub fn wrap_for_terminal(text: &str) -> String {
+    match terminal_width() {
+        Some(width) => wrap(text, width),
+        None => text.to_string(),
+    }
+}
+
+// Prefix every line (e.g. "> " for markdown quotes), wrapping first if
+// `[layout] export_width` is set. Blank lines get the prefix without trailing spaces.
+pub fn prefix_lines(content: &str, prefix: &str) -> String {
+    let export_width = settings().export_width;
+    let wrapped;
+    let content = if export_width > 0 {
+        wrapped = wrap(content, export_width.saturating_sub(prefix.chars().count()).max(20));
+        &wrapped
+    } else {
+        content
+    };
+    content
+        .lines()
+        .map(|line| if line.trim().is_empty() { prefix.trim_end().to_string() } else { format!("{}{}", prefix, line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut wrapper = Wrapper::new(width);
+    let mut out = wrapper.push(text);
+    out.push_str(&wrapper.finish());
+    out
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+// Break `line` so no piece is wider than `width` (for words too long to wrap)
+fn split_to_width(line: &str, width: usize) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    let mut column = 0;
+    for c in line.chars() {
+        let w = char_width(c);
+        if column + w > width && column > 0 {
+            pieces.push(String::new());
+            column = 0;
+        }
+        pieces.last_mut().expect("never empty").push(c);
+        column += w;
+    }
+    pieces
+}
+
+fn table_row(cells: &[String], width: usize) -> Vec<String> {
+    let wrapped: Vec<Vec<String>> = cells
+        .iter()
+        .map(|cell| wrap(cell, width).lines().flat_map(|line| split_to_width(line, width)).collect())
+        .collect();
+    let height = wrapped.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    (0..height)
+        .map(|i| {
+            let parts: Vec<String> = wrapped
+                .iter()
+                .map(|lines| {
+                    let line = lines.get(i).map_or("", String::as_str);
+                    format!(" {}{} ", line, " ".repeat(width.saturating_sub(text_width(line))))
+                })
+                .collect();
+            format!("│{}│", parts.join("│"))
+        })
+        .collect()
+}
+
+// Box-drawn table whose columns share the terminal width; cells wrap inside their column
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let total = terminal_width().unwrap_or(settings().max_width);
+    let columns = headers.len().max(1);
+    let width = (total.saturating_sub(3 * columns + 1) / columns).max(8);
+    let border = |left: &str, middle: &str, right: &str| {
+        format!("{}{}{}", left, vec!["─".repeat(width + 2); columns].join(middle), right)
+    };
+
+    let mut lines = vec![border("┌", "┬", "┐")];
+    let headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    lines.extend(table_row(&headers, width));
+    for row in rows {
+        lines.push(border("├", "┼", "┤"));
+        lines.extend(table_row(row, width));
+    }
+    lines.push(border("└", "┴", "┘"));
+    lines.join("\n")
+}
+
+// CJK punctuation that must not start a line
+fn no_break_before(c: char) -> bool {
+    "，。、；：？！）」』》】〉…".contains(c)
+}
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+// Incremental wrapper, so streamed answers can be wrapped as they arrive
+pub struct Wrapper {
+    width: usize,
+    column: usize,
+    // Current source line so far (for list indentation and code-fence detection)
+    line: String,
+    // Pending run of narrow non-space characters, kept together when wrapping
+    word: String,
+    word_width: usize,
+    pending_space: bool,
+    in_code: bool,
+}
+
+impl Wrapper {
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            column: 0,
+            line: String::new(),
+            word: String::new(),
+            word_width: 0,
+            pending_space: false,
+            in_code: false,
+        }
+    }
+
+    // Wrapper for terminal output, or None when wrapping is off
+    pub fn for_terminal() -> Option<Self> {
+        terminal_width().map(Self::new)
+    }
+
+    // Feed more text; returns what can be printed now
+    pub fn push(&mut self, text: &str) -> String {
+        let mut out = String::new();
+        for c in text.chars() {
+            self.push_char(c, &mut out);
+        }
+        out
+    }
+
+    // Flush whatever is still buffered
+    pub fn finish(&mut self) -> String {
+        let mut out = String::new();
+        self.flush_word(&mut out);
+        out
+    }
+
+    // Indentation for continuation lines: the line's own indent plus any list marker
+    fn hanging_indent(&self) -> usize {
+        let trimmed = self.line.trim_start();
+        let indent = self.line.len() - trimmed.len();
+        let marker = if ["- ", "* ", "+ ", "> "].iter().any(|m| trimmed.starts_with(m)) {
+            2
+        } else {
+            let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits > 0 && trimmed[digits..].starts_with(". ") {
+                digits + 2
+            } else {
+                0
+            }
+        };
+        (indent + marker).min(self.width / 2)
+    }
+
+    fn break_line(&mut self, out: &mut String) {
+        let indent = self.hanging_indent();
+        out.push('\n');
+        out.push_str(&" ".repeat(indent));
+        self.column = indent;
+        self.pending_space = false;
+    }
+
+    fn emit_space(&mut self, out: &mut String) {
+        if self.pending_space {
+            out.push(' ');
+            self.column += 1;
+            self.pending_space = false;
+        }
+    }
+
+    fn flush_word(&mut self, out: &mut String) {
+        if self.word.is_empty() {
+            self.emit_space(out);
+            return;
+        }
+        let space = usize::from(self.pending_space);
+        if self.column + space + self.word_width > self.width && self.column > self.hanging_indent() {
+            self.break_line(out);
+        } else {
+            self.emit_space(out);
+        }
+        out.push_str(&self.word);
+        self.column += self.word_width;
+        self.word.clear();
+        self.word_width = 0;
+    }
+
+    fn push_char(&mut self, c: char, out: &mut String) {
+        if c == '\n' {
+            self.flush_word(out);
+            out.push('\n');
+            if self.line.trim_start().starts_with("```") {
+                self.in_code = !self.in_code;
+            }
+            self.line.clear();
+            self.column = 0;
+            self.pending_space = false;
+            return;
+        }
+
+        // Code, tables and leading indentation pass through untouched
+        let at_indent = self.line.chars().all(char::is_whitespace);
+        self.line.push(c);
+        let verbatim = self.in_code || ["```", "|"].iter().any(|p| self.line.trim_start().starts_with(p));
+        if verbatim || (at_indent && c.is_whitespace()) {
+            self.flush_word(out);
+            out.push(c);
+            self.column += char_width(c).max(1);
+            return;
+        }
+
+        if c == ' ' || c == '\t' {
+            self.flush_word(out);
+            self.pending_space = true;
+            return;
+        }
+
+        // Narrow characters (Latin letters, digits, ASCII punctuation) build up words
+        let w = char_width(c);
+        if w < 2 {
+            self.word.push(c);
+            self.word_width += w;
+            return;
+        }
+        self.flush_word(out);
+        if no_break_before(c) {
+            // Keep punctuation attached to what precedes it, even if it overflows
+            self.emit_space(out);
+            out.push(c);
+            self.column += w;
+            return;
+        }
+
+        // Wide characters can break anywhere
+        if self.column + w > self.width && self.column > self.hanging_indent() {
+            self.break_line(out);
+        }
+        self.emit_space(out);
+        out.push(c);
+        self.column += w;
+    }
+}
@@ -1,315 +1,883 @@
+mod anki;
+mod api;
+mod archive;
+mod background;
+mod bot;
+mod bridge;
+mod codeblocks;
+mod commands;
+mod commit_msg;
+mod compare;
+mod completion;
+mod config;
+mod crypto;
+mod dry_run;
+mod editor;
+mod ensemble;
+mod events;
+mod exemplars;
+mod github;
+mod focus;
+mod glossary;
+mod guard;
+mod health;
+mod highlight;
+mod http;
+mod i18n;
+mod index;
+mod keys;
+mod layout;
+mod memory;
+mod meter;
+mod metrics;
+mod notebook;
+mod notify;
+mod notion;
+mod pager;
+mod panel;
+mod pdf;
+mod persona;
+mod pipeline;
+mod plugins;
+mod provenance;
+mod publish;
+mod quota;
+mod recall;
+mod rate_limit;
+mod reasoning;
+mod recovery;
+mod redact;
+mod references;
+mod render;
+mod repl;
+mod retention;
+mod schedule;
+mod session;
+mod share;
+mod shield;
+mod slug;
+mod stats;
+mod stdio;
+mod telemetry;
+mod tools;
+mod translate;
+mod ui;
+mod usage;
+mod vcr;
+mod verdict;
+mod watch;
+mod webhook;
+
 use anyhow::{Context, Result};
-use colored::*;
 use reqwest::Client;
-use rustyline::DefaultEditor;
-use serde::{Deserialize, Serialize};
 use std::env;
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use api::{call_ai_api, complete, complete_with_tools, stream_ai_api, ChatMessage};
+use archive::{conversations_dir, conversations_path};
+use completion::{ReplEditor, ReplHelper};
+use ensemble::Ensemble;
+use config::{AiConfig, GuardMode, Profile, RedactMode, Settings, Strictness, ToolSettings};
+use focus::Focus;
+use i18n::tr;
+use memory::Memory;
+use panel::Panel;
+use repl::Repl;
+use session::{ConversationSession, ConversationTurn, RoundMeta};
+
+// Generate filename from timestamp and first question
+fn generate_filename(start_time: &str, question: &str) -> String {
+    // Session start for the filename: YYYY-MM-DD_HH-MM-SS, so every export of a
+    // session shares its prefix
+    let filename_timestamp = match chrono::NaiveDateTime::parse_from_str(start_time, "%Y-%m-%d %H:%M:%S") {
+        Ok(started) => started.format("%Y-%m-%d_%H-%M-%S").to_string(),
+        Err(_) => chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string(),
+    };
 
-// Define structures for OpenAI-compatible API requests/responses
-#[derive(Serialize)]
-struct ChatMessage {
-    role: String,
-    content: String,
+    format!("{}_{}.md", filename_timestamp, slug::slug(question))
 }
 
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<ChatMessage>,
-    temperature: f32,
+// `filename`, or NAME_2.md, NAME_3.md... when another session (started the same
+// second with the same question, e.g. a second /branch) was saved under it;
+// this session's own earlier save is replaced
+fn session_filename(dir: &Path, session: &ConversationSession, filename: String) -> String {
+    let stem = filename.trim_end_matches(".md").to_string();
+    for n in 1.. {
+        let candidate = if n == 1 { filename.clone() } else { format!("{}_{}.md", stem, n) };
+        let json = dir.join(&candidate).with_extension("json");
+        let encrypted = PathBuf::from(format!("{}.{}", json.display(), crypto::EXTENSION));
+        let saved = [json, encrypted].into_iter().find(|path| path.exists());
+        match saved {
+            None => return candidate,
+            Some(path) if ConversationSession::load(&path).is_ok_and(|saved| saved.id == session.id) => return candidate,
+            Some(_) => {}
+        }
+    }
+    unreachable!("some numbered name is free")
 }
 
-#[derive(Deserialize)]
-struct ChatChoice {
-    message: MessageContent,
+// Review block for the export; failed reviews are kept as a visible placeholder
+fn format_review_for_export(turn: &ConversationTurn) -> String {
+    match (&turn.deepseek_review, &turn.review_error) {
+        (Some(review), _) => {
+            let body = match &turn.translation {
+                Some(check) if !check.rows.is_empty() => {
+                    let table = translate::render_markdown(check);
+                    let notes = translate::notes(review);
+                    layout::prefix_lines(format!("{}\n\n{}", table, notes).trim_end(), "> ")
+                }
+                _ => layout::prefix_lines(review, "> "),
+            };
+            let body = match turn.review_finish {
+                Some(finish) => format!("{}\n>\n> *{}*", body, tr!("finish.review", finish.label())),
+                None => body,
+            };
+            let body = match turn.review_reasoning.as_deref().filter(|_| reasoning::saved()) {
+                Some(trace) => format!("{}\n>\n{}", reasoning::export_block(trace), body),
+                None => body,
+            };
+            let body = match &turn.review_summary {
+                Some(summary) => format!("{}\n>\n> **{}**\n>\n{}", body, tr!("review.summary_heading"), layout::prefix_lines(summary, "> ")),
+                None => body,
+            };
+            let body = match &turn.scores {
+                Some(scores) => format!("{}\n>\n> *{}*", body, scores.summary()),
+                None => body,
+            };
+            let body = match plugins::score_line(&turn.plugin_scores) {
+                Some(scores) => format!("{}\n>\n> *{}*", body, scores),
+                None => body,
+            };
+            format!("{}{}", body, panel::export_block(turn))
+        }
+        (None, Some(error)) => format!("> *{}*{}", tr!("export.review_failed", error), panel::export_block(turn)),
+        (None, None) => format!("> *{}*", tr!("export.not_reviewed")),
+    }
 }
 
-#[derive(Deserialize)]
-struct MessageContent {
-    content: String,
+// Answer block for the export, noting when the user stopped generation early
+// or edited the round, when the provider cut it short and what `[guard]` flagged, followed by the `[ensemble]` candidates
+fn format_answer_for_export(turn: &ConversationTurn) -> String {
+    let answer = layout::prefix_lines(&turn.moonshot_answer, "> ");
+    let answer = if turn.truncated {
+        format!("{}\n>\n> *{}*", answer, tr!("export.truncated"))
+    } else {
+        answer
+    };
+    let answer = if turn.edited {
+        format!("{}\n>\n> *{}*", answer, tr!("export.edited"))
+    } else {
+        answer
+    };
+    let answer = match turn.answer_finish {
+        Some(finish) => format!("{}\n>\n> *{}*", answer, tr!("finish.answer", finish.label())),
+        None => answer,
+    };
+    let answer = if turn.guard_flags.is_empty() {
+        answer
+    } else {
+        format!("{}\n>\n> *{}*", answer, tr!("guard.flagged_note", turn.guard_flags.join(", ")))
+    };
+    format!("{}{}", answer, ensemble::export_block(turn))
 }
 
-#[derive(Deserialize)]
-struct ChatResponse {
-    choices: Vec<ChatChoice>,
+// Messages asking the reviewer to check an answer to the given question
+fn review_messages(profile: &Profile, question: &str, answer: &str, truncated: bool, focuses: &[Focus], strictness: Strictness) -> Vec<ChatMessage> {
+    let truncation_note = if truncated {
+        "\n\nNote: the user stopped this answer before it finished, so it is incomplete. Review what is there and do not count the missing ending as an error."
+    } else {
+        ""
+    };
+    let review_prompt = format!(
+        "The user asked:\n{}\n\nAnother AI assistant provided the following answer:\n{}{}{}\n\nPlease review this answer. Point out any errors, hallucinations, or missing information. If the code is provided, check for bugs. If the answer is perfect, verify it.{}{}\n\nIMPORTANT: Please provide your review entirely in {}.{}{}",
+        shield::quote("question", question),
+        shield::quote("answer", answer),
+        truncation_note,
+        shield::RULES,
+        strictness.instruction(),
+        focus::instruction(focuses),
+        profile.review_language(),
+        verdict::RUBRIC,
+        verdict::INSTRUCTION
+    );
+
+    vec![ChatMessage::system(exemplars::system_prompt(profile.review_system_prompt(), question)), ChatMessage::user(review_prompt)]
 }
 
-struct AiConfig {
-    api_key: String,
-    base_url: String,
-    model: String,
-    name: String,
+// The review request for a stored turn: a translation check or a regular review
+fn turn_review_messages(profile: &Profile, turn: &ConversationTurn) -> Vec<ChatMessage> {
+    match &turn.translation {
+        Some(check) => translate::review_messages(profile, &turn.user_question, &turn.moonshot_answer, &check.target),
+        None => review_messages(profile, &turn.user_question, &turn.moonshot_answer, turn.truncated, &turn.review_focus, turn.review_strictness.unwrap_or_default()),
+    }
 }
 
-// Structure to hold a single conversation turn
-struct ConversationTurn {
-    user_question: String,
-    moonshot_answer: String,
-    deepseek_review: String,
-    _timestamp: String,
-    round: usize, // 第几轮对话
+// Terminal rendering of an answer / review, header included
+fn answer_block(name: &str, answer: &str) -> String {
+    format!("\n{}\n{}", ui::answerer(tr!("round.answer_header", name)), highlight::terminal_markdown(answer))
 }
 
-impl ConversationTurn {
-    fn new(round: usize, user_question: String, moonshot_answer: String, deepseek_review: String) -> Self {
-        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-        Self {
-            round,
-            user_question,
-            moonshot_answer,
-            deepseek_review,
-            _timestamp: timestamp,
-        }
+fn review_block(name: &str, review: &str) -> String {
+    format!("\n{}\n{}", ui::reviewer(tr!("round.review_header", name)), highlight::terminal_markdown(review))
+}
+
+// Review block for a stored turn; translation checks show their side-by-side
+// table, and `[panel]` reviews are shown next to the main one
+fn turn_review_block(name: &str, turn: &ConversationTurn) -> Option<String> {
+    let review = turn.deepseek_review.as_deref()?;
+    let block = match &turn.translation {
+        _ if !turn.panel_reviews.is_empty() => panel::terminal_block(name, review, turn),
+        Some(check) if !check.rows.is_empty() => format!(
+            "\n{}\n{}\n{}",
+            ui::reviewer(tr!("round.review_header", name)),
+            translate::render_terminal(check),
+            highlight::terminal_markdown(&translate::notes(review))
+        ),
+        _ => review_block(name, review),
+    };
+    let block = match turn.review_reasoning.as_deref().and_then(reasoning::terminal_block) {
+        Some(reasoning) => format!("\n{}{}", reasoning, block),
+        None => block,
+    };
+    let block = match &turn.review_summary {
+        Some(summary) => format!("{}\n\n{}\n{}", block, ui::heading(tr!("review.summary_heading")), layout::wrap_for_terminal(summary)),
+        None => block,
+    };
+    let block = match turn.verdict {
+        Some(severity) => format!("{}\n\n{}", block, ui::status(tr!("verdict.line", severity.label()))),
+        None => block,
+    };
+    let block = match &turn.scores {
+        Some(scores) => format!("{}\n{}", block, ui::status(scores.summary())),
+        None => block,
+    };
+    Some(match plugins::score_line(&turn.plugin_scores) {
+        Some(scores) => format!("{}\n{}", block, ui::status(scores)),
+        None => block,
+    })
+}
+
+// A whole stored round for re-display: question, answer and review. Rounds
+// saved before models were recorded fall back to the current ones.
+fn round_block(turn: &ConversationTurn, answerer: &str, reviewer: &str) -> String {
+    let answer_model = if turn.answer_model.is_empty() { answerer } else { &turn.answer_model };
+    let review_model = if turn.review_model.is_empty() { reviewer } else { &turn.review_model };
+    let mut flags = if turn.guard_flags.is_empty() {
+        String::new()
+    } else {
+        format!("\n{}", ui::warning(tr!("guard.flagged_note", turn.guard_flags.join(", "))))
+    };
+    if let Some(finish) = turn.answer_finish {
+        flags.push_str(&format!("\n{}", ui::warning(tr!("finish.answer", finish.label()))));
     }
+    let ranking = if turn.candidates.is_empty() {
+        String::new()
+    } else {
+        ensemble::terminal_block(&turn.candidates, turn.ranking.as_deref())
+    };
+    format!(
+        "{}\n{}{}{}{}{}",
+        ui::heading(tr!("scroll.heading", turn.round)),
+        layout::wrap_for_terminal(&turn.user_question),
+        ranking,
+        answer_block(answer_model, &turn.moonshot_answer),
+        flags,
+        turn_review_block(review_model, turn).unwrap_or_default()
+    )
 }
 
-// Structure to hold the entire conversation session
-struct ConversationSession {
-    turns: Vec<ConversationTurn>,
-    start_time: String,
+// Short English TL;DR of a review written in Chinese
+async fn summarize_review(client: &Client, summarizer: &AiConfig, review: &str) -> Result<String> {
+    let messages = vec![
+        ChatMessage::system("You summarize technical reviews for readers who don't read Chinese."),
+        ChatMessage::user(format!(
+            "Summarize this review in English in at most three short bullet points. Keep its verdict and the most important corrections.\n\n{}",
+            review
+        )),
+    ];
+    call_ai_api(client, summarizer, messages).await
 }
 
-impl ConversationSession {
-    fn new() -> Self {
-        let start_time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-        Self {
-            turns: Vec::new(),
-            start_time,
+// Run the reviewer for a stored turn, filling in its review or recording the failure.
+// With `echo` off the review is not printed (the caller shows it, e.g. in the pager).
+async fn review_turn(
+    client: &Client,
+    reviewer: &AiConfig,
+    summarizer: Option<&AiConfig>,
+    profile: &Profile,
+    tools: &ToolSettings,
+    turn: &mut ConversationTurn,
+    echo: bool,
+) -> bool {
+    let messages = turn_review_messages(profile, turn);
+    let offered = if tools.enabled { tools::review_specs(tools) } else { Vec::new() };
+    let reviewed = if offered.is_empty() {
+        complete(client, reviewer, messages).await
+    } else {
+        // Review tools are external ones only, none of which asks for confirmation
+        complete_with_tools(client, reviewer, messages, &offered, |call| tools::run(call, &offered, tools, &mut |_| false)).await
+    };
+    match reviewed {
+        Ok(completion) => {
+            let review = completion.content;
+            turn.review_reasoning = completion.reasoning;
+            turn.review_finish = completion.finish;
+            if let Some(finish) = completion.finish {
+                ui::notice(ui::warning(tr!("finish.review", finish.label())));
+            }
+            let (review, scores) = plugins::on_review(&turn.user_question, &turn.moonshot_answer, review);
+            turn.plugin_scores = scores;
+            if let Some(check) = turn.translation.as_mut() {
+                check.rows = translate::parse_table(&review);
+            }
+            turn.review_summary = None;
+            if let Some(summarizer) = summarizer.filter(|_| turn.translation.is_none() && translate::is_mostly_chinese(&review)) {
+                match summarize_review(client, summarizer, &review).await {
+                    Ok(summary) => turn.review_summary = Some(summary),
+                    Err(e) => ui::notice(ui::warning(tr!("review.summary_failed", e))),
+                }
+            }
+            turn.verdict = verdict::parse(&review);
+            turn.scores = verdict::parse_scores(&review);
+            if let Some(phrase) = shield::hijacked(&turn.moonshot_answer, &review, turn.verdict.is_some()) {
+                ui::notice(ui::warning(tr!("shield.hijacked", phrase)));
+            }
+            turn.deepseek_review = Some(review);
+            turn.review_model = reviewer.model.clone();
+            turn.review_error = None;
+            stats::record(turn);
+            if echo {
+                println!("{}", turn_review_block(&reviewer.name, turn).unwrap_or_default());
+            }
+            true
+        }
+        Err(e) => {
+            eprintln!("{}", ui::error(tr!("error.provider", reviewer.name, e)));
+            turn.review_error = Some(e.to_string());
+            false
         }
     }
+}
 
-    fn add_turn(&mut self, turn: ConversationTurn) {
-        self.turns.push(turn);
+// `round_meta` frontmatter entries for the rounds that recorded it; each
+// request is a JSON object, which YAML reads as a flow mapping
+fn round_meta_frontmatter<'a>(turns: impl IntoIterator<Item = &'a ConversationTurn>) -> String {
+    let mut yaml = String::new();
+    let (mut prompt_total, mut completion_total) = (0, 0);
+    // Only a total over rounds that all have a cost, in one currency
+    let mut cost_total = Some((0.0, None::<&str>));
+    for turn in turns {
+        let Some(meta) = &turn.meta else {
+            continue;
+        };
+        let (prompt, completion) = meta.tokens();
+        prompt_total += prompt;
+        completion_total += completion;
+        cost_total = match (cost_total, meta.cost) {
+            (Some((total, currency)), Some(cost)) if currency.is_none_or(|c| c == meta.currency) => Some((total + cost, Some(meta.currency.as_str()))),
+            _ => None,
+        };
+        yaml.push_str(&format!("  - round: {}\n    duration_ms: {}\n    prompt_tokens: {}\n    completion_tokens: {}\n", turn.round, meta.duration_ms, prompt, completion));
+        if let Some(cost) = meta.cost {
+            yaml.push_str(&format!("    cost: {:.6}\n    currency: {}\n", cost, meta.currency));
+        }
+        yaml.push_str("    requests:\n");
+        for request in &meta.requests {
+            yaml.push_str(&format!("      - {}\n", serde_json::to_string(request).unwrap_or_default()));
+        }
     }
-
-    fn is_empty(&self) -> bool {
-        self.turns.is_empty()
+    if yaml.is_empty() {
+        return yaml;
     }
-
-    fn len(&self) -> usize {
-        self.turns.len()
+    let mut totals = format!("prompt_tokens: {}\ncompletion_tokens: {}\n", prompt_total, completion_total);
+    if let Some((cost, Some(currency))) = cost_total {
+        totals.push_str(&format!("cost: {:.6}\ncurrency: {}\n", cost, currency));
     }
+    format!("{}round_meta:\n{}", totals, yaml)
+}
 
-    fn first_question(&self) -> Option<&str> {
-        self.turns.first().map(|t| t.user_question.as_str())
+// "1234 + 567 tokens · 0.0123 CNY": what a round used, if recorded
+fn turn_cost(meta: &RoundMeta) -> String {
+    let (prompt, completion) = meta.tokens();
+    match meta.cost {
+        Some(cost) => format!("{} + {} tokens · {:.4} {}", prompt, completion, cost, meta.currency),
+        None => format!("{} + {} tokens", prompt, completion),
     }
 }
 
-impl AiConfig {
-    fn get_config_path() -> Result<std::path::PathBuf> {
-        let home = env::var("HOME").context("Could not find HOME environment variable")?;
-        let config_path = std::path::Path::new(&home).join(".ai_vs_ai_config");
-        Ok(config_path)
+// "2024-06-01 12:00:03 · 5.2s · 1234 + 567 tokens · 0.0123 CNY": when the round
+// was asked and, if recorded, how long it took and what it cost
+fn turn_time(turn: &ConversationTurn) -> String {
+    match &turn.meta {
+        Some(meta) => format!("{} · {:.1}s · {}", turn.timestamp, meta.duration_ms as f64 / 1000.0, turn_cost(meta)),
+        None => turn.timestamp.clone(),
     }
+}
 
-    fn get_api_key(env_var: &str, provider_name: &str) -> Result<String> {
-        // 1. Try to get from environment (loaded from config file)
-        if let Ok(key) = env::var(env_var) {
-            if !key.is_empty() {
-                return Ok(key);
-            }
-        }
+// Render a single round as markdown; starred rounds get a highlighted heading
+fn render_turn_markdown(turn: &ConversationTurn, answerer: &AiConfig, reviewer: &AiConfig) -> String {
+    let heading = if turn.starred {
+        format!("## ⭐ {}", tr!("export.round_starred", turn.round))
+    } else {
+        format!("## {}", tr!("export.round", turn.round))
+    };
+    format!(r#"{}
+{}
+*🕒 {}*
 
-        // 2. Prompt user using standard io (not rustyline, as this is one-time setup)
-        print!("Enter API Key for {}: ", provider_name);
-        io::stdout().flush()?;
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let trimmed = input.trim().to_string();
-        if trimmed.is_empty() {
-            anyhow::bail!("API Key for {} cannot be empty", provider_name);
-        }
+> 💬 **{}**{}{}
 
-        // 3. Persist to global config file
-        let config_path = Self::get_config_path()?;
-        let mut file = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&config_path)
-            .context(format!("Failed to open config file at {:?}", config_path))?;
-        
-        writeln!(file, "{}={}", env_var, trimmed)?;
-        println!("{}", format!("Saved {} to {:?}", env_var, config_path).dimmed());
+---
 
-        // Also set it in the current process environment so subsequent calls work
-        env::set_var(env_var, &trimmed);
+> 🤖 **{}** ({})
+> 
+{}
 
-        Ok(trimmed)
-    }
+---
 
-    fn moonshot() -> Result<Self> {
-        Ok(Self {
-            api_key: Self::get_api_key("MOONSHOT_API_KEY", "Moonshot AI")?,
-            base_url: "https://api.moonshot.cn/v1/chat/completions".to_string(),
-            model: "moonshot-v1-8k".to_string(),
-            name: "Moonshot AI".to_string(),
-        })
-    }
+> 🔍 **{}** ({})
+> 
+{}
 
-    fn deepseek() -> Result<Self> {
-        Ok(Self {
-            api_key: Self::get_api_key("DEEPSEEK_API_KEY", "DeepSeek AI")?,
-            base_url: "https://api.deepseek.com/chat/completions".to_string(),
-            model: "deepseek-chat".to_string(),
-            name: "DeepSeek AI".to_string(),
-        })
-    }
+---
+
+"#,
+        heading,
+        provenance::comment(turn),
+        turn_time(turn),
+        match &turn.translation {
+            Some(check) => tr!("export.user_translate", check.target),
+            None => tr!("export.user").to_string(),
+        },
+        tr!("export.colon"),
+        turn.user_question,
+        answerer.name,
+        if turn.answer_model.is_empty() { &answerer.model } else { &turn.answer_model },
+        format_answer_for_export(turn),
+        reviewer.name,
+        reviewer.model,
+        format_review_for_export(turn),
+    )
 }
 
-async fn call_ai_api(client: &Client, config: &AiConfig, messages: Vec<ChatMessage>) -> Result<String> {
-    println!("{}", format!("Thinking ({}) ...", config.name).dimmed());
+// Save only the starred rounds as a "best answers" digest
+fn save_starred_digest(
+    session: &ConversationSession,
+    answerer: &AiConfig,
+    reviewer: &AiConfig,
+) -> Result<PathBuf> {
+    let conversations_dir = conversations_dir()?;
+    let filepath = conversations_dir.join(generate_filename(&session.start_time, "best_answers"));
 
-    let request_body = ChatRequest {
-        model: config.model.clone(),
-        messages,
-        temperature: 0.7,
-    };
+    let mut content = format!(r#"---
+session_start: {}
+starred_rounds: {}
+answer_model: {}
+review_model: {}
+{}---
 
-    let response = client
-        .post(&config.base_url)
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .context(format!("Failed to send request to {}", config.name))?;
+# {}
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(anyhow::anyhow!("API Error from {}: {}", config.name, error_text));
+"#, session.start_time, session.starred_turns().count(), answerer.model, reviewer.model, round_meta_frontmatter(session.starred_turns()), tr!("export.best_title"));
+    for turn in session.starred_turns() {
+        content.push_str(&render_turn_markdown(turn, answerer, reviewer));
     }
 
-    let chat_response: ChatResponse = response
-        .json()
-        .await
-        .context(format!("Failed to parse response from {}", config.name))?;
+    archive::write_file(&filepath, &content).context("Failed to write best answers digest")
+}
+
+// Save the given rounds of the session (`/export rounds`) as Markdown or a
+// self-contained HTML page; `label` is the round list, e.g. "2-4,7"
+fn save_rounds_export(
+    session: &ConversationSession,
+    turns: &[&ConversationTurn],
+    label: &str,
+    html: bool,
+    answerer: &AiConfig,
+    reviewer: &AiConfig,
+) -> Result<PathBuf> {
+    let filepath = conversations_dir()?.join(generate_filename(&session.start_time, &format!("rounds_{}", label)));
+    let subtitle = tr!("export.rounds_subtitle", session.start_time, label);
+    let content = if html {
+        publish::rounds_page(tr!("export.rounds_title"), &subtitle, turns)
+    } else {
+        let mut content = format!(r#"---
+session_start: {}
+rounds: {}
+answer_model: {}
+review_model: {}
+{}---
+
+# {}
 
-    chat_response
-        .choices
-        .first()
-        .map(|c| c.message.content.clone())
-        .ok_or_else(|| anyhow::anyhow!("No choices returned from {}", config.name))
+*{}*
+
+"#, session.start_time, label, answerer.model, reviewer.model, round_meta_frontmatter(turns.iter().copied()), tr!("export.rounds_title"), subtitle);
+        for turn in turns {
+            content.push_str(&render_turn_markdown(turn, answerer, reviewer));
+        }
+        content
+    };
+    let filepath = if html { filepath.with_extension("html") } else { filepath };
+    archive::write_file(&filepath, &content).context("Failed to write the exported rounds")
 }
 
-// Generate filename from timestamp and first question
-fn generate_filename(_start_time: &str, question: &str) -> String {
-    // Extract first 20 chars of question, remove punctuation, replace spaces with underscores
-    let summary: String = question
-        .chars()
-        .take(20)
-        .map(|c| {
-            if c.is_ascii_punctuation() || c.is_ascii_whitespace() {
-                '_'
-            } else {
-                c
-            }
-        })
-        .collect();
+// Render entire conversation session as markdown
+fn render_session_markdown(
+    session: &ConversationSession,
+    answerer: &AiConfig,
+    reviewer: &AiConfig,
+) -> String {
+    // Build markdown content
+    let mut content = format!(r#"---
+session_start: {}
+total_rounds: {}
+answer_model: {}
+review_model: {}
+{}---
+
+# {}
+
+"#, session.start_time, session.len(), answerer.model, reviewer.model, round_meta_frontmatter(&session.turns), tr!("export.session_title"));
+    if let Some(parent) = &session.branched_from {
+        content.push_str(&format!("*{}*\n\n", tr!("export.branched_from", parent.session, parent.round)));
+    }
     
-    // Format timestamp for filename: YYYY-MM-DD_HH-MM-SS
-    let filename_timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+    // Add each turn
+    for turn in &session.turns {
+        content.push_str(&render_turn_markdown(turn, answerer, reviewer));
+    }
     
-    format!("{}_{}.md", filename_timestamp, summary)
+    content
 }
 
-// Format content with proper line prefixing
-fn format_content_with_prefix(content: &str, prefix: &str) -> String {
-    content.lines().map(|line| format!("{}{}", prefix, line)).collect::<Vec<_>>().join("\n")
+// Save entire conversation session to markdown file
+fn save_conversation_session(
+    session: &ConversationSession,
+    answerer: &AiConfig,
+    reviewer: &AiConfig,
+) -> Result<PathBuf> {
+    // Generate filename using first question
+    let first_question = session.first_question().unwrap_or("conversation");
+    let filename = session_filename(&conversations_dir()?, session, generate_filename(&session.start_time, first_question));
+    if archive::journal() {
+        return save_to_journal(session, answerer, reviewer, &filename);
+    }
+    save_session_as(session, answerer, reviewer, &filename)
 }
 
-// Find project directory by looking for Cargo.toml in current dir or parents
-fn find_project_dir() -> Result<PathBuf> {
-    let mut current_dir = env::current_dir()
-        .context("Failed to get current directory")?;
-    
-    loop {
-        // Check if Cargo.toml exists in current directory
-        let cargo_toml = current_dir.join("Cargo.toml");
-        if cargo_toml.exists() {
-            return Ok(current_dir);
+// `[archive] save = "journal"`: append the rounds that aren't in the session's
+// daily journal yet, under a header for the session the first time. Hidden
+// markers tell which rounds are already there; the journal is append-only, so
+// rounds edited after they were written keep their first version.
+fn save_to_journal(session: &ConversationSession, answerer: &AiConfig, reviewer: &AiConfig, filename: &str) -> Result<PathBuf> {
+    let conversations_dir = conversations_dir()?;
+    let journal = conversations_dir.join(archive::journal_name(&session.start_time));
+    let existing = archive::written_path(&journal);
+    let mut content = if existing.exists() { archive::read_file(&existing)? } else { String::new() };
+
+    let header = format!("<!-- aivsai session {} -->", session.id);
+    if !content.contains(&header) {
+        content.push_str(&format!("{}\n# {}\n\n", header, tr!("journal.session", session.start_time, answerer.model, reviewer.model)));
+        if let Some(parent) = &session.branched_from {
+            content.push_str(&format!("*{}*\n\n", tr!("export.branched_from", parent.session, parent.round)));
         }
-        
-        // Try parent directory
-        match current_dir.parent() {
-            Some(parent) => current_dir = parent.to_path_buf(),
-            None => break,
+    }
+    for turn in &session.turns {
+        let marker = format!("<!-- aivsai session {} round {} -->", session.id, turn.round);
+        if !content.contains(&marker) {
+            content.push_str(&format!("{}\n{}", marker, render_turn_markdown(turn, answerer, reviewer)));
         }
     }
-    
-    // If no Cargo.toml found, fall back to current directory
-    env::current_dir().context("Failed to get current directory")
+    let written = archive::write_file(&journal, &content).context("Failed to write the journal")?;
+    finish_save(session, &conversations_dir.join(filename).with_extension("json"), &written)?;
+    Ok(written)
 }
 
-// Save entire conversation session to markdown file
-fn save_conversation_session(
+// Save to conversations/`filename` (and its JSON copy), replacing any earlier save there
+fn save_session_as(
     session: &ConversationSession,
-    moonshot_model: &str,
-    deepseek_model: &str,
+    answerer: &AiConfig,
+    reviewer: &AiConfig,
+    filename: &str,
 ) -> Result<PathBuf> {
-    // Find project directory and create conversations subdirectory
-    let project_dir = find_project_dir()?;
-    let conversations_dir = project_dir.join("conversations");
+    let conversations_dir = conversations_dir()?;
+    let filepath = conversations_dir.join(filename);
     
-    // Create conversations directory if it doesn't exist
-    if !conversations_dir.exists() {
-        std::fs::create_dir_all(&conversations_dir)
-            .context("Failed to create conversations directory")?;
+    // Write to file
+    let (content, extra_files) = plugins::transform_export(session, render_session_markdown(session, answerer, reviewer));
+    let written = archive::write_file(&filepath, &content)
+        .context("Failed to write conversation file")?;
+    // Plugin exports go next to it as NAME.<file name>
+    let stem = filepath.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    for (name, text) in extra_files {
+        let Some(name) = Path::new(&name).file_name() else {
+            continue;
+        };
+        let path = conversations_dir.join(format!("{}.{}", stem, name.to_string_lossy()));
+        archive::write_file(&path, &text).context(format!("Failed to write {:?}", path))?;
     }
-    
-    // Generate filename using first question
-    let first_question = session.first_question().unwrap_or("conversation");
-    let filename = generate_filename(&session.start_time, first_question);
-    let filepath = conversations_dir.join(&filename);
-    
-    // Build markdown content
-    let mut content = format!(r#"---
-session_start: {}
-total_rounds: {}
-moonshot_model: {}
-deepseek_model: {}
----
+    finish_save(session, &filepath.with_extension("json"), &written)?;
+    Ok(written)
+}
 
-# AIvsAI 对话记录
+// After the markdown is written: the machine-readable copy for /load, then
+// webhooks, retention and the index
+fn finish_save(session: &ConversationSession, json: &Path, written: &Path) -> Result<()> {
+    session.write_json(json)?;
+    webhook::session_saved(session, written);
+    retention::auto_prune();
+    // The index is a convenience; a failure to update it doesn't fail the save
+    if let Err(e) = index::rebuild() {
+        ui::notice(ui::warning(tr!("index.failed", format!("{:#}", e))));
+    }
+    Ok(())
+}
 
-"#, session.start_time, session.len(), moonshot_model, deepseek_model);
-    
-    // Add each turn
-    for turn in &session.turns {
-        content.push_str(&format!(r#"## 第 {} 轮
+// Save the session and report the outcome; returns true on success
+fn save_and_report(session: &mut ConversationSession, answerer: &AiConfig, reviewer: &AiConfig) -> bool {
+    match save_conversation_session(session, answerer, reviewer) {
+        Ok(filepath) => {
+            session.mark_saved();
+            recovery::update(None);
+            println!("{}", ui::success(tr!("save.saved", filepath.display())));
+            println!("{}", ui::status(tr!("save.rounds", session.len())));
+            true
+        }
+        Err(e) => {
+            eprintln!("{}", ui::error(tr!("save.failed", e)));
+            false
+        }
+    }
+}
 
-> 💬 **用户**：{}
+// Ask whether to save unsaved turns before exiting; returns false if the user
+// cancels (Ctrl+C) or saving failed, in which case the REPL keeps running
+fn confirm_exit(rl: &mut ReplEditor, session: &mut ConversationSession, answerer: &AiConfig, reviewer: &AiConfig) -> bool {
+    if !session.has_unsaved_changes() {
+        return true;
+    }
 
----
+    let answer = match rl.readline(tr!("prompt.save_before_exit")) {
+        Ok(line) => line.trim().to_lowercase(),
+        Err(rustyline::error::ReadlineError::Interrupted) => return false,
+        Err(_) => "n".to_string(),
+    };
 
-> 🤖 **Moonshot** ({})
-> 
-{}
+    if answer.is_empty() || answer == "y" || answer == "yes" {
+        return save_and_report(session, answerer, reviewer);
+    }
+    true
+}
 
----
+// Ask before the answerer's `shell` tool runs a command; defaults to no
+fn confirm_command(rl: &mut ReplEditor, command: &str) -> bool {
+    match rl.readline(&tr!("tools.confirm_shell", command)) {
+        Ok(line) => matches!(line.trim().to_lowercase().as_str(), "y" | "yes"),
+        Err(_) => false,
+    }
+}
 
-> 🔍 **DeepSeek** ({})
-> 
-{}
+// Confirm throwing away unsaved rounds (e.g. before /load); defaults to no
+fn confirm_discard(rl: &mut ReplEditor) -> bool {
+    match rl.readline(tr!("prompt.discard_unsaved")) {
+        Ok(answer) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+        Err(_) => false,
+    }
+}
 
----
+enum TruncatedAction {
+    Review,
+    Keep,
+    Discard,
+}
 
-"#,
-            turn.round,
-            turn.user_question,
-            moonshot_model,
-            format_content_with_prefix(&turn.moonshot_answer, "> "),
-            deepseek_model,
-            format_content_with_prefix(&turn.deepseek_review, "> "),
-        ));
+// Ask what to do with an answer cut short by Ctrl+C; reviewing it is the default
+fn ask_truncated_action(rl: &mut ReplEditor) -> TruncatedAction {
+    match rl.readline(tr!("prompt.truncated_action")) {
+        Ok(answer) => match answer.trim().to_lowercase().as_str() {
+            "k" | "keep" => TruncatedAction::Keep,
+            "d" | "discard" => TruncatedAction::Discard,
+            _ => TruncatedAction::Review,
+        },
+        Err(_) => TruncatedAction::Review,
     }
-    
-    // Write to file
-    std::fs::write(&filepath, content)
-        .context("Failed to write conversation file")?;
-    
-    Ok(filepath)
+}
+
+// List the files attached to a question and the references that were left out
+fn report_attachments(expanded: &references::Expanded) {
+    for attachment in &expanded.attachments {
+        let note = if attachment.truncated { tr!("references.truncated_note") } else { "" };
+        ui::notice(ui::status(tr!("references.attached", attachment.path, attachment.tokens, note)));
+    }
+    for skipped in &expanded.skipped {
+        ui::notice(ui::warning(tr!("references.skipped", skipped)));
+    }
+}
+
+// Run outbound text through the `[redact]` filter; None if the user cancels sending.
+// Outside the REPL (`rl` is None) "confirm" falls back to masking.
+fn screen_outbound(rl: Option<&mut ReplEditor>, text: String) -> Option<String> {
+    let mode = redact::mode();
+    if mode == RedactMode::Off {
+        return Some(text);
+    }
+    let redacted = redact::scan(&text);
+    if redacted.is_clean() {
+        return Some(text);
+    }
+    if let (RedactMode::Confirm, Some(rl)) = (mode, rl) {
+        let answer = match rl.readline(&tr!("redact.confirm", redacted.summary())) {
+            Ok(line) => line.trim().to_lowercase(),
+            Err(_) => "c".to_string(),
+        };
+        match answer.as_str() {
+            "s" | "send" => return Some(text),
+            "c" | "cancel" => {
+                println!("{}", ui::status(tr!("redact.cancelled")));
+                return None;
+            }
+            _ => {}
+        }
+    }
+    ui::notice(ui::warning(tr!("redact.masked", redacted.summary())));
+    Some(redacted.text)
+}
+
+// Offer the reviewed answer to an earlier, matching question instead of asking
+// again; true if the question should not be sent
+async fn offer_recall(repl: &mut Repl, question: &str) -> bool {
+    if !repl.settings.recall.enabled {
+        return false;
+    }
+    let archive = repl.recall.get_or_insert_with(recall::Archive::load);
+    // Same words first; then, with `semantic_threshold`, the same meaning
+    let (turn, found) = match archive.find(question, repl.settings.recall.threshold) {
+        Some(hit) => (hit.past.turn.clone(), tr!("recall.found", hit.past.date, (hit.similarity * 100.0).round(), recalled_question(&hit.past.turn))),
+        None => match semantic_recall(repl, question).await {
+            Some((date, turn, similarity)) => {
+                let found = tr!("recall.found_semantic", date, (similarity * 100.0).round(), recalled_question(&turn));
+                (turn, found)
+            }
+            None => return false,
+        },
+    };
+    let turn = &turn;
+    println!("{}", ui::status(found));
+    let answer = match repl.rl.readline(tr!("recall.prompt")) {
+        Ok(line) => line.trim().to_lowercase(),
+        Err(_) => {
+            println!("{}", ui::status(tr!("redact.cancelled")));
+            return true;
+        }
+    };
+    if answer == "a" || answer == "ask" {
+        return false;
+    }
+    println!("{}", answer_block(&turn.answer_model, &turn.moonshot_answer));
+    // Rounds saved before review models were recorded fall back to the current reviewer
+    let reviewer = if turn.review_model.is_empty() { &repl.reviewer.name } else { &turn.review_model };
+    println!("{}", turn_review_block(reviewer, turn).unwrap_or_default());
+    ui::notice(ui::status(tr!("recall.force_hint")));
+    true
+}
+
+fn recalled_question(turn: &ConversationTurn) -> String {
+    turn.user_question.lines().next().unwrap_or_default().chars().take(80).collect()
+}
+
+// The earlier reviewed round whose question means the same, by `[memory]`
+// embeddings: its session start, the round and the similarity
+async fn semantic_recall(repl: &mut Repl, question: &str) -> Option<(String, ConversationTurn, f64)> {
+    let threshold = repl.settings.recall.semantic_threshold?;
+    open_memory(repl).await;
+    let (memory, embedder) = (repl.memory.as_ref()?, repl.embedder.as_ref()?);
+    let asked = match memory.asked_before(&repl.client, embedder, question, threshold).await {
+        Ok(asked) => asked?,
+        Err(e) => {
+            ui::notice(ui::warning(tr!("memory.failed", format!("{:#}", e))));
+            return None;
+        }
+    };
+    let session = archive::session_file(&asked.file).and_then(|path| ConversationSession::load(&path)).ok()?;
+    let turn = session.turns.into_iter().find(|turn| turn.round == asked.round)?;
+    Some((asked.date, turn, f64::from(asked.similarity)))
+}
+
+// Open the `[memory]` index the first time it is needed; failures only warn
+async fn open_memory(repl: &mut Repl) {
+    let Some(embedder) = repl.embedder.as_ref() else {
+        return;
+    };
+    if repl.memory.is_none() {
+        let memory = match Memory::open(&repl.client, embedder).await {
+            Ok(memory) => {
+                ui::notice(ui::status(tr!("memory.opened", memory.rounds())));
+                memory
+            }
+            Err(e) => {
+                ui::notice(ui::warning(tr!("memory.failed", format!("{:#}", e))));
+                Memory::default()
+            }
+        };
+        repl.memory = Some(memory);
+    }
+}
+
+// Earlier rounds related to the question, from `[memory]`; failures only warn
+async fn recall_memory(repl: &mut Repl, question: &str) -> Option<ChatMessage> {
+    open_memory(repl).await;
+    let (memory, embedder) = (repl.memory.as_ref()?, repl.embedder.as_ref()?);
+    match memory.recall(&repl.client, embedder, &repl.settings.memory, question).await {
+        Ok(Some((rounds, background))) => {
+            ui::notice(ui::status(tr!("memory.recalled", rounds)));
+            Some(background)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            ui::notice(ui::warning(tr!("memory.failed", format!("{:#}", e))));
+            None
+        }
+    }
+}
+
+// Keep the crash-recovery snapshot in sync with the in-memory session
+fn update_recovery_snapshot(session: &ConversationSession, answerer: &AiConfig, reviewer: &AiConfig) {
+    if session.has_unsaved_changes() {
+        recovery::update(Some(render_session_markdown(session, answerer, reviewer)));
+    } else {
+        recovery::update(None);
+    }
+}
+
+// Report a config error, exiting with `code` unless it is 0
+fn config_error(e: anyhow::Error, code: i32) -> Result<()> {
+    eprintln!("{}", ui::error(tr!("error.config", format!("{:#}", e))));
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+// Remove every occurrence of a global flag from the arguments; true if it was present
+fn take_flag(args: &mut Vec<String>, names: &[&str]) -> bool {
+    let before = args.len();
+    args.retain(|a| !names.contains(&a.as_str()));
+    args.len() != before
+}
+
+// Remove a global `--name VALUE` option from the arguments
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+    let Some(i) = args.iter().position(|a| a == name) else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        anyhow::bail!("{} needs a value", name);
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
 }
 
 #[tokio::main]
@@ -322,153 +890,621 @@ async fn main() -> Result<()> {
         }
     }
 
-    let client = Client::new();
+    // Global flags may appear anywhere; the rest is the subcommand and its options
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let no_color_flag = take_flag(&mut args, &["--no-color"]);
+    if ui::color_disabled(no_color_flag) {
+        ui::disable_color();
+    }
+    if take_flag(&mut args, &["--local-only"]) {
+        api::set_local_only();
+    }
+    match (take_flag(&mut args, &["-q", "--quiet"]), take_flag(&mut args, &["-v", "--verbose"])) {
+        (true, true) => {
+            eprintln!("{}", ui::error(tr!("error.quiet_verbose")));
+            std::process::exit(1);
+        }
+        (true, false) => ui::set_verbosity(ui::Verbosity::Quiet),
+        (false, true) => ui::set_verbosity(ui::Verbosity::Verbose),
+        (false, false) => {}
+    }
+    let profile_name = match take_option(&mut args, "--profile") {
+        Ok(name) => name,
+        Err(e) => {
+            eprintln!("{}", ui::error(tr!("error.generic", e)));
+            std::process::exit(1);
+        }
+    };
+    let dry_run = take_flag(&mut args, &["--dry-run"]);
+    let stdio_mode = take_flag(&mut args, &["--stdio"]);
+    let fast_forward = take_flag(&mut args, &["--fast-forward"]);
+    let cassette = match (take_option(&mut args, "--record"), take_option(&mut args, "--replay")) {
+        (Ok(Some(_)), Ok(Some(_))) => Err(anyhow::anyhow!("--record and --replay can't be used together")),
+        (Ok(Some(_)), _) | (_, Ok(Some(_))) if dry_run => Err(anyhow::anyhow!("--dry-run can't be used with --record or --replay")),
+        (Ok(Some(path)), Ok(None)) => vcr::record(Path::new(&path)),
+        (Ok(None), Ok(Some(path))) => vcr::replay(Path::new(&path), fast_forward),
+        (Ok(None), Ok(None)) => Ok(()),
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    };
+    if let Err(e) = cassette {
+        eprintln!("{}", ui::error(tr!("error.generic", format!("{:#}", e))));
+        std::process::exit(1);
+    }
+    if dry_run {
+        dry_run::enable();
+    }
 
-    println!("{}", "==========================================".cyan().bold());
-    println!("{}", "   AI Pair: Moonshot (Answer) + DeepSeek (Review)   ".cyan().bold());
-    println!("{}", "==========================================".cyan().bold());
-    println!("{}", "Commands: /save = save conversation, exit/quit = exit".dimmed());
+    // Scripted runs must not exit 0 on a broken config; `ask` / `batch` fail
+    // with their "no verdict" code. The interactive REPL just stops.
+    let config_failure = match args.first().map(String::as_str) {
+        Some("ask" | "batch") => verdict::EXIT_FAILED,
+        Some(_) => 1,
+        None if stdio_mode => 1,
+        None => 0,
+    };
 
-    // Check configuration early
-    let moonshot_config = match AiConfig::moonshot() {
-        Ok(c) => c,
+    // Load optional settings file
+    let settings = match Settings::load() {
+        Ok(s) => s,
         Err(e) => {
-            eprintln!("{}", format!("Configuration Error: {}", e).red());
-            return Ok(());
+            return config_error(e, config_failure);
+        }
+    };
+    layout::init(&settings.layout);
+    archive::init(&settings.archive);
+    if let Err(e) = crypto::init(&settings.archive) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = events::init(&settings.archive) {
+        return config_error(e, config_failure);
+    }
+    provenance::init(&settings.archive);
+    if let Err(e) = retention::init(&settings.archive.retention) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = i18n::init(&settings.i18n) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = plugins::init(&settings.plugins) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = telemetry::init(&settings.telemetry) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = webhook::init(&settings.webhooks) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = tools::validate(&settings.tools) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = api::init_client(&settings.network) {
+        return config_error(e, config_failure);
+    }
+    reasoning::init(&settings.review);
+    if let Err(e) = exemplars::init(&settings.review) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = glossary::init(&settings.glossary) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = redact::init(&settings.redact) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = guard::init(&settings) {
+        return config_error(e, config_failure);
+    }
+    if let Err(e) = ui::init(&settings.theme) {
+        return config_error(e, config_failure);
+    }
+    let profile = match settings.profile(profile_name.as_deref()) {
+        Ok(p) => p,
+        Err(e) => {
+            return config_error(e, config_failure);
         }
     };
 
-    let deepseek_config = match AiConfig::deepseek() {
-        Ok(c) => c,
+    // Editor plugins drive the pipeline over JSON-RPC instead of the REPL
+    if stdio_mode {
+        let result = stdio::run(&settings, &profile).await;
+        webhook::flush().await;
+        telemetry::flush().await;
+        if let Err(e) = result {
+            eprintln!("{}", ui::error(tr!("error.generic", format!("{:#}", e))));
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Subcommands that don't need the interactive session
+    if let Some(command) = args.first() {
+        // `ask` and `batch` exit with the review verdict; see `verdict`
+        let (result, failure_code) = match command.as_str() {
+            "ask" => (pipeline::run_ask(&args[1..], &settings, &profile).await.map(Some), verdict::EXIT_FAILED),
+            "batch" => (pipeline::run_batch(&args[1..], &settings, &profile).await.map(Some), verdict::EXIT_FAILED),
+            "bot" => (bot::run(&args[1..], &settings, &profile).await.map(|()| None), 1),
+            "bridge" => (bridge::run(&args[1..], &settings, &profile).await.map(|()| None), 1),
+            "commit-msg" => (commit_msg::run(&args[1..], &settings, &profile).await.map(|()| None), 1),
+            "export" => (notion::run_export(&args[1..]).await.map(|()| None), 1),
+            "usage" => (usage::run_report(&args[1..], &settings).map(|()| None), 1),
+            "publish" => (publish::run(&args[1..]).map(|()| None), 1),
+            "reindex" => (index::run_reindex(&args[1..]).map(|()| None), 1),
+            "schedule" => (schedule::run(&args[1..], &settings, &profile).await.map(|()| None), 1),
+            "sessions" => (retention::run_sessions(&args[1..]).map(|()| None), 1),
+            "share" => (share::run(&args[1..], &settings.share).map(|()| None), 1),
+            "stats" => (stats::run_report(&args[1..], &settings).map(|()| None), 1),
+            "watch" => (watch::run(&args[1..], &settings, &profile).await.map(|()| None), 1),
+            other => (Err(anyhow::anyhow!("Unknown command: {} (available: ask, batch, bot, bridge, commit-msg, export, publish, reindex, schedule, sessions, share, stats, usage, watch)", other)), 1),
+        };
+        webhook::flush().await;
+        telemetry::flush().await;
+        match result {
+            Ok(Some(severity)) => std::process::exit(severity.exit_code()),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("{}", ui::error(tr!("error.generic", format!("{:#}", e))));
+                std::process::exit(failure_code);
+            }
+        }
+        return Ok(());
+    }
+
+    let client = api::client();
+
+    // Check configuration early
+    let roles = AiConfig::pair(&settings, &profile)
+        .and_then(|pair| Ok((pair, AiConfig::summarizer(&settings)?, AiConfig::embedder(&settings)?, Panel::from_settings(&settings)?, Ensemble::from_settings(&settings)?)));
+    let ((answerer, reviewer), summarizer, embedder, panel, ensemble) = match roles {
+        Ok(roles) => roles,
         Err(e) => {
-            eprintln!("{}", format!("Configuration Error: {}", e).red());
+            eprintln!("{}", ui::error(tr!("error.config", e)));
             return Ok(());
         }
     };
 
+    ui::notice(ui::heading("=========================================="));
+    ui::notice(ui::heading(tr!("banner.pair", answerer.name, reviewer.name)));
+    ui::notice(ui::heading("=========================================="));
+    if profile_name.is_some() {
+        ui::notice(ui::status(tr!("banner.profile", profile.name)));
+    }
+    if let Some(path) = Settings::project_settings_path() {
+        ui::notice(ui::status(tr!("banner.project_settings", path.display())));
+    }
+    if api::local_only() {
+        ui::notice(ui::status(tr!("banner.local_only")));
+    }
+    if dry_run::enabled() {
+        ui::notice(ui::status(tr!("banner.dry_run")));
+    }
+    if vcr::recording() {
+        ui::notice(ui::status(tr!("banner.recording")));
+    }
+    if vcr::replaying() {
+        ui::notice(ui::status(tr!("banner.replaying")));
+    }
+    ui::notice(ui::status(tr!("banner.hint")));
+
     // Create rustyline editor for better input handling (supports Chinese characters properly)
-    let mut rl = DefaultEditor::new()?;
-    
+    let mut rl = ReplEditor::new()?;
+    rl.set_helper(Some(ReplHelper));
+
     // Store the entire conversation session
-    let mut session = ConversationSession::new();
-    let mut round_counter: usize = 0;
+    let mut repl = Repl {
+        client,
+        pager_enabled: settings.pager.enabled,
+        settings,
+        answerer,
+        reviewer,
+        summarizer,
+        embedder,
+        ensemble,
+        panel,
+        memory: None,
+        profile,
+        rl,
+        session: ConversationSession::new(),
+        round_counter: 0,
+        recall: None,
+        forced_question: None,
+        quote: None,
+        focus: Vec::new(),
+        scrollback: Default::default(),
+        background: Default::default(),
+    };
+
+    // Flush unsaved rounds to a recovery file if we crash or get killed
+    let stamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    recovery::install(conversations_path().ok().map(|dir| dir.join(format!("recovered_{}.md", stamp))));
 
     loop {
+        background::collect_finished(&mut repl).await;
+        update_recovery_snapshot(&repl.session, &repl.answerer, &repl.reviewer);
+        events::follow(&repl.session.start_time);
+
         // Use rustyline for reading input with proper Unicode support
-        let readline = rl.readline("\nUser > ");
+        let readline = repl.rl.readline(&format!("\n{}{}", meter::prompt_prefix(&repl), ui::user(tr!("prompt.user"))));
         
         let input = match readline {
             Ok(line) => {
                 // Add to history (optional, allows up-arrow to recall previous inputs)
-                let _ = rl.add_history_entry(line.as_str());
+                let _ = repl.rl.add_history_entry(line.as_str());
                 line.trim().to_string()
             }
             Err(rustyline::error::ReadlineError::Interrupted) => {
                 // Handle Ctrl+C
-                println!("{}", "\nUse 'exit' or 'quit' to exit.".dimmed());
+                println!("{}", ui::status(format!("\n{}", tr!("repl.exit_hint"))));
                 continue;
             }
             Err(rustyline::error::ReadlineError::Eof) => {
                 // Handle Ctrl+D
-                break;
+                background::wait_all(&mut repl).await;
+                if confirm_exit(&mut repl.rl, &mut repl.session, &repl.answerer, &repl.reviewer) {
+                    break;
+                }
+                continue;
             }
             Err(err) => {
-                eprintln!("{}", format!("Error reading input: {}", err).red());
+                eprintln!("{}", ui::error(tr!("error.read_input", err)));
                 continue;
             }
         };
 
         if input.eq_ignore_ascii_case("exit") || input.eq_ignore_ascii_case("quit") {
-            break;
+            background::wait_all(&mut repl).await;
+            if confirm_exit(&mut repl.rl, &mut repl.session, &repl.answerer, &repl.reviewer) {
+                break;
+            }
+            continue;
         }
 
+        // Reviews that finished while the prompt was shown go in before the input is handled
+        background::collect_finished(&mut repl).await;
         if input.is_empty() {
             continue;
         }
 
-        // Handle /save command
-        if input.eq_ignore_ascii_case("/save") {
-            if session.is_empty() {
-                println!("{}", "⚠ No conversation to save yet. Ask a question first!".yellow());
-            } else {
-                match save_conversation_session(&session, &moonshot_config.model, &deepseek_config.model) {
-                    Ok(filepath) => {
-                        println!("{}", format!("✓ Conversation saved to: {}", filepath.display()).green());
-                        println!("{}", format!("  Total rounds saved: {}", session.len()).dimmed());
-                    }
-                    Err(e) => {
-                        eprintln!("{}", format!("✗ Failed to save conversation: {}", e).red());
-                    }
+        let mut input = input;
+        let mut forced = false;
+        if commands::dispatch(&mut repl, &input).await {
+            // `/force QUESTION` hands its question back to be sent
+            match repl.forced_question.take() {
+                Some(question) => {
+                    input = question;
+                    forced = true;
                 }
+                None => continue,
+            }
+        }
+
+        // A mistyped command shouldn't be sent to the model as a question
+        if !forced && completion::is_command_like(&input) {
+            let word = input.split_whitespace().next().unwrap_or_default();
+            match completion::suggest_command(&input) {
+                Some(command) => println!("{}", ui::warning(tr!("repl.unknown_command_suggest", word, command))),
+                None => println!("{}", ui::warning(tr!("repl.unknown_command", word))),
             }
             continue;
         }
 
-        // Increment round counter
-        round_counter += 1;
-
-        // --- Step 1: Moonshot Answers ---
-        let moonshot_messages = vec![
-            ChatMessage {
-                role: "system".to_string(),
-                content: "You are a helpful AI assistant.".to_string(),
-            },
-            ChatMessage {
-                role: "user".to_string(),
-                content: input.to_string(),
-            },
-        ];
-
-        let moonshot_answer = match call_ai_api(&client, &moonshot_config, moonshot_messages).await {
-            Ok(ans) => ans,
+        // Inline files referenced with @path so both models see the code
+        let question = match references::expand(&input, &repl.settings.references) {
+            Ok(expanded) => {
+                report_attachments(&expanded);
+                expanded.question
+            }
             Err(e) => {
-                eprintln!("{}", format!("Moonshot Error: {}", e).red());
+                eprintln!("{}", ui::error(format!("✗ {:#}", e)));
                 continue;
             }
         };
-
-        println!("\n{}", "--- Moonshot AI Answer ---".blue().bold());
-        println!("{}", moonshot_answer);
-
-        // --- Step 2: DeepSeek Reviews ---
-        let review_prompt = format!(
-            "The user asked: \"{}\"\n\nAnother AI assistant provided the following answer:\n\"{}\"\n\nPlease review this answer. Point out any errors, hallucinations, or missing information. If the code is provided, check for bugs. If the answer is perfect, verify it.\n\nIMPORTANT: Please provide your review entirely in Chinese.",
-            input, moonshot_answer
-        );
-
-        let deepseek_messages = vec![
-            ChatMessage {
-                role: "system".to_string(),
-                content: "You are an expert technical reviewer. Your goal is to verify the accuracy and quality of answers provided by other AI models. You must output your review in Chinese.".to_string(),
-            },
-            ChatMessage {
-                role: "user".to_string(),
-                content: review_prompt,
-            },
-        ];
-
-        let deepseek_review = match call_ai_api(&client, &deepseek_config, deepseek_messages).await {
-            Ok(ans) => ans,
+        // A /quote waiting for this question goes in front of it (after @path
+        // expansion, so quoted code isn't taken for file references)
+        let question = match &repl.quote {
+            Some(quote) => format!("{}\n\n{}", quote, question),
+            None => question,
+        };
+        let Some(question) = screen_outbound(Some(&mut repl.rl), question) else {
+            continue;
+        };
+        let question = match plugins::on_question(question) {
+            Ok(question) => question,
             Err(e) => {
-                eprintln!("{}", format!("DeepSeek Error: {}", e).red());
+                eprintln!("{}", ui::warning(tr!("plugins.blocked", format!("{:#}", e))));
                 continue;
             }
         };
+        let mut guard_flags = guard::check(&repl.client, &question).await;
+        if !guard_flags.is_empty() {
+            if guard::mode() == GuardMode::Block {
+                println!("{}", ui::warning(tr!("guard.question_blocked", guard_flags.join(", "))));
+                continue;
+            }
+            println!("{}", ui::warning(tr!("guard.question_flagged", guard_flags.join(", "))));
+        }
+        if !forced && offer_recall(&mut repl, &question).await {
+            continue;
+        }
 
-        println!("\n{}", "--- DeepSeek AI Review ---".magenta().bold());
-        println!("{}", deepseek_review);
-        
-        println!("\n{}", "------------------------------------------".dimmed());
-        println!("{}", format!("Round {} completed. Type /save to save this conversation", round_counter).dimmed());
+        // Increment round counter
+        repl.round_counter += 1;
+        repl.scrollback.viewing = None;
+        repl.quote = None;
+        events::log("question", serde_json::json!({ "round": repl.round_counter, "text": question }));
+        let round_started = std::time::Instant::now();
+        let session_start = repl.session.start_time.clone();
+
+        // --- Step 1: The answerer answers ---
+        let mut moonshot_messages = repl.session.context_messages(&repl.profile.answer_system_prompt(&repl.settings.answer));
+        let (recalled, recall_requests) = usage::collect(recall_memory(&mut repl, &question)).await;
+        if let Some(background) = recalled {
+            moonshot_messages.push(background);
+        }
+        moonshot_messages.push(ChatMessage::user(&question));
+
+        let (mut candidates, mut ranking, mut answer_model) = (Vec::new(), None, repl.answerer.model.clone());
+        // This round's requests only: a background review finishing meanwhile
+        // is added to its own round (see `background`)
+        let (answered, answer_requests) = usage::collect(async {
+            if let Some(ensemble) = &repl.ensemble {
+                ui::notice(ui::status(tr!("ensemble.answering", ensemble.answerers.len())));
+                let mut answers = ensemble::answer_all(&repl.client, &ensemble.answerers, &moonshot_messages).await;
+                if answers.is_empty() {
+                    return None;
+                }
+                if answers.len() > 1 {
+                    match ensemble::rank(&repl.client, &repl.reviewer, &repl.profile, &question, &mut answers).await {
+                        Ok(justification) => ranking = Some(justification),
+                        Err(e) => ui::notice(ui::warning(tr!("ensemble.rank_failed", e))),
+                    }
+                }
+                let best = ensemble::winner(&answers).expect("answers are not empty").clone();
+                if !repl.pager_enabled && !guard::holds_answers() {
+                    println!("{}", ensemble::terminal_block(&answers, ranking.as_deref()));
+                    println!("{}", answer_block(&best.answerer, &best.answer));
+                }
+                answer_model = best.model;
+                candidates = answers;
+                Some((best.answer, false, best.finish))
+            } else if repl.settings.tools.enabled {
+                // Tool calls need the whole response, so answers aren't streamed
+                let specs = tools::specs(&repl.settings.tools);
+                let (rl, tool_settings) = (&mut repl.rl, &repl.settings.tools);
+                let answered = complete_with_tools(&repl.client, &repl.answerer, moonshot_messages, &specs, |call| {
+                    tools::run(call, &specs, tool_settings, &mut |command| confirm_command(rl, command))
+                })
+                .await;
+                match answered {
+                    Ok(ans) => {
+                        if !repl.pager_enabled && !guard::holds_answers() {
+                            println!("{}", answer_block(&repl.answerer.name, &ans.content));
+                        }
+                        Some((ans.content, false, ans.finish))
+                    }
+                    Err(e) => {
+                        eprintln!("{}", ui::error(tr!("error.provider", repl.answerer.name, e)));
+                        None
+                    }
+                }
+            } else if repl.settings.answer.stream && !guard::holds_answers() {
+                println!("\n{}", ui::answerer(tr!("round.answer_header", repl.answerer.name)));
+                let mut renderer = render::StreamRenderer::for_terminal(repl.settings.answer.render);
+                let streamed = stream_ai_api(&repl.client, &repl.answerer, moonshot_messages, |delta| {
+                    print!("{}", renderer.push(delta));
+                    let _ = std::io::stdout().flush();
+                })
+                .await;
+                print!("{}", renderer.finish());
+                match streamed {
+                    Ok(answer) => {
+                        println!();
+                        let finish = answer.finish();
+                        Some((answer.content, answer.interrupted, finish))
+                    }
+                    Err(e) => {
+                        eprintln!("{}", ui::error(format!("\n{}", tr!("error.provider", repl.answerer.name, e))));
+                        None
+                    }
+                }
+            } else {
+                match complete(&repl.client, &repl.answerer, moonshot_messages).await {
+                    Ok(ans) => {
+                        // In pager mode the answer is shown together with the review
+                        if !repl.pager_enabled && !guard::holds_answers() {
+                            println!("{}", answer_block(&repl.answerer.name, &ans.content));
+                        }
+                        Some((ans.content, false, ans.finish))
+                    }
+                    Err(e) => {
+                        eprintln!("{}", ui::error(tr!("error.provider", repl.answerer.name, e)));
+                        None
+                    }
+                }
+            }
+        })
+        .await;
+        let mut round_requests = recall_requests;
+        round_requests.extend(answer_requests);
+        let Some((moonshot_answer, truncated, answer_finish)) = answered else {
+            continue;
+        };
+
+        if let Some(finish) = answer_finish {
+            println!("{}", ui::warning(tr!("finish.answer", finish.label())));
+        }
+        let moonshot_answer = plugins::on_answer(&question, moonshot_answer);
+        let answer_flags = guard::check(&repl.client, &moonshot_answer).await;
+        if !answer_flags.is_empty() {
+            if guard::mode() == GuardMode::Block {
+                println!("{}", ui::warning(tr!("guard.answer_blocked", answer_flags.join(", "))));
+                repl.round_counter -= 1;
+                continue;
+            }
+            println!("{}", ui::warning(tr!("guard.answer_flagged", answer_flags.join(", "))));
+        }
+        if guard::holds_answers() && !repl.pager_enabled {
+            println!("{}", answer_block(&repl.answerer.name, &moonshot_answer));
+        }
+        for flag in answer_flags {
+            if !guard_flags.contains(&flag) {
+                guard_flags.push(flag);
+            }
+        }
 
-        // Store the conversation turn
-        session.add_turn(ConversationTurn::new(
-            round_counter,
-            input.to_string(),
-            moonshot_answer,
-            deepseek_review,
-        ));
+        // Ctrl+C while streaming: keep what arrived and let the user decide what to do with it
+        let mut review_partial = true;
+        if truncated {
+            if moonshot_answer.trim().is_empty() {
+                println!("{}", ui::warning(tr!("round.stopped_empty")));
+                repl.round_counter -= 1;
+                continue;
+            }
+            println!("{}", ui::warning(tr!("round.stopped_partial")));
+            match ask_truncated_action(&mut repl.rl) {
+                TruncatedAction::Review => {}
+                TruncatedAction::Keep => review_partial = false,
+                TruncatedAction::Discard => {
+                    println!("{}", ui::success(tr!("round.discarded", repl.round_counter)));
+                    repl.round_counter -= 1;
+                    continue;
+                }
+            }
+        }
+
+        // Same question as an earlier round, answered by another model before a
+        // /model switch: offer to show how the answers differ
+        if let Some(earlier) = compare::previous_answer(&repl.session, &question, &answer_model) {
+            let prompt = tr!("compare.prompt", earlier.answer_model, earlier.round);
+            if let Ok(reply) = repl.rl.readline(&prompt) {
+                if matches!(reply.trim().to_lowercase().as_str(), "y" | "yes") {
+                    println!("{}", compare::terminal_block(earlier, &answer_model, &moonshot_answer));
+                }
+            }
+        }
+
+        // Store the answer right away so a reviewer failure never loses it
+        let mut turn = ConversationTurn::new(repl.round_counter, question, moonshot_answer, &answer_model);
+        turn.truncated = truncated;
+        turn.answer_finish = answer_finish;
+        turn.candidates = candidates;
+        turn.ranking = ranking;
+        turn.review_focus = repl.focus.clone();
+        turn.review_strictness = Some(repl.settings.review.strictness);
+        turn.guard_flags = guard_flags;
+        repl.session.add_turn(turn);
+        update_recovery_snapshot(&repl.session, &repl.answerer, &repl.reviewer);
+
+        if !review_partial {
+            let turn = repl.session.last_turn_mut().expect("turn was just added");
+            turn.review_error = Some("skipped (answer truncated)".to_string());
+            webhook::round_finished(&session_start, turn, usage::Totals::of(&round_requests), round_started.elapsed());
+            turn.meta = Some(RoundMeta::new(round_requests, round_started, &repl.settings.usage));
+            println!("\n{}", ui::status(layout::rule()));
+            println!("{}", ui::warning(tr!("round.kept_unreviewed", repl.round_counter)));
+            continue;
+        }
+
+        // Background review: the prompt comes back now; a `[panel]` reviews in the foreground
+        if repl.settings.review.background && repl.panel.is_none() {
+            // Pager mode holds non-streamed answers back for the review, which comes later now
+            let answer_shown = !repl.pager_enabled || (repl.settings.answer.stream && repl.ensemble.is_none() && !repl.settings.tools.enabled && !guard::holds_answers());
+            let turn = repl.session.last_turn_mut().expect("turn was just added");
+            if !answer_shown {
+                println!("{}", answer_block(&repl.answerer.name, &turn.moonshot_answer));
+            }
+            turn.meta = Some(RoundMeta::new(round_requests, round_started, &repl.settings.usage));
+            background::start(&mut repl, round_started);
+            continue;
+        }
+
+        // --- Step 2: The reviewer reviews ---
+        let turn = repl.session.last_turn_mut().expect("turn was just added");
+        // Fields, not `repl`: `turn` holds `repl.session`
+        let (client, reviewer, summarizer, profile, tools) = (&repl.client, &repl.reviewer, repl.summarizer.as_ref(), &repl.profile, &repl.settings.tools);
+        let (panel, pager_enabled) = (repl.panel.as_ref(), repl.pager_enabled);
+        let (reviewed, review_requests) = usage::collect(async {
+            match panel {
+                // With a panel all reviewers run at once and are shown together
+                Some(panel) => {
+                    let messages = turn_review_messages(profile, turn);
+                    let (reviewed, panel_reviews) = tokio::join!(
+                        review_turn(client, reviewer, summarizer, profile, tools, turn, false),
+                        panel::review_all(client, &panel.reviewers, &messages)
+                    );
+                    turn.panel_reviews = panel_reviews;
+                    if let Some(consolidator) = &panel.consolidator {
+                        match panel::consolidate(client, consolidator, profile, turn).await {
+                            Ok(merged) => turn.consolidated_review = Some(merged),
+                            Err(e) => ui::notice(ui::warning(tr!("error.provider", consolidator.name, e))),
+                        }
+                    }
+                    if !pager_enabled {
+                        println!("{}", turn_review_block(&reviewer.name, turn).unwrap_or_default());
+                    }
+                    reviewed
+                }
+                None => review_turn(client, reviewer, summarizer, profile, tools, turn, !pager_enabled).await,
+            }
+        })
+        .await;
+        round_requests.extend(review_requests);
+
+        notify::round_finished(&repl.settings.notify, repl.round_counter, round_started.elapsed(), turn.deepseek_review.as_deref());
+        webhook::round_finished(&session_start, turn, usage::Totals::of(&round_requests), round_started.elapsed());
+        turn.meta = Some(RoundMeta::new(round_requests, round_started, &repl.settings.usage));
+
+        // Pager mode: the round is shown in one piece, through the pager if it won't fit
+        if repl.pager_enabled {
+            let answer = answer_block(&repl.answerer.name, &turn.moonshot_answer);
+            let review = turn_review_block(&repl.reviewer.name, turn).unwrap_or_default();
+            let round_text = format!("{}{}", answer, review);
+            if !pager::fits_terminal(&round_text) {
+                if let Err(e) = pager::page(&round_text, &repl.settings.pager) {
+                    eprintln!("{}", ui::error(tr!("error.pager", format!("{:#}", e))));
+                }
+            } else if repl.settings.answer.stream {
+                println!("{}", review);
+            } else {
+                println!("{}", round_text);
+            }
+        }
+
+        ui::notice(ui::status(format!("\n{}", layout::rule())));
+        if reviewed {
+            ui::notice(ui::status(tr!("round.completed", repl.round_counter)));
+        } else {
+            println!("{}", ui::warning(tr!("round.saved_unreviewed", repl.round_counter)));
+        }
     }
 
+    webhook::flush().await;
+    telemetry::flush().await;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_under(dir: &Path, session: &ConversationSession) -> String {
+        let filename = session_filename(dir, session, generate_filename(&session.start_time, "same question"));
+        session.write_json(&dir.join(&filename).with_extension("json")).unwrap();
+        filename
+    }
+
+    #[test]
+    fn sessions_started_the_same_second_get_numbered_names() {
+        let dir = std::env::temp_dir().join(format!("aivsai-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = ConversationSession::new();
+        let mut second = first.branch(0);
+        second.start_time = first.start_time.clone();
+
+        let name = saved_under(&dir, &first);
+        assert!(name.ends_with("_same_question.md"));
+        assert_eq!(saved_under(&dir, &second), name.replace(".md", "_2.md"));
+        // A session saved again keeps its own name
+        assert_eq!(saved_under(&dir, &first), name);
+        assert_eq!(saved_under(&dir, &second), name.replace(".md", "_2.md"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crate::api::{self, estimate_tokens, ChatMessage};
+use crate::archive::{self, conversations_dir, saved_sessions};
+use crate::config::{AiConfig, MemorySettings};
+use crate::crypto;
+use crate::session::ConversationSession;
+
+// Cross-session memory: every reviewed round of the saved sessions is embedded
+// once and kept in conversations/memory.json (encrypted like the sessions when
+// `[archive] encryption` is on). Before a question is answered, the rounds
+// closest to it are handed to the answerer as background. The questions are also
+// embedded on their own, for `[recall] semantic_threshold`: a new question that
+// means the same as an earlier one is offered the earlier reviewed answer.
+
+const MEMORY_FILE: &str = "memory.json";
+// Inputs per /embeddings request
+const BATCH: usize = 32;
+// Characters of the answer that are embedded and recalled
+const ANSWER_EXCERPT: usize = 1500;
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    // Session JSON file and round the entry came from
+    file: String,
+    round: usize,
+    date: String,
+    question: String,
+    answer: String,
+    // Reviewer verdict or first review line, so recalled answers carry their caveats
+    #[serde(default)]
+    review: String,
+    vector: Vec<f32>,
+    // The question alone; empty in indexes written before semantic recall
+    #[serde(default)]
+    question_vector: Vec<f32>,
+}
+
+// An earlier round whose question means the same as a new one
+pub struct Asked {
+    pub file: String,
+    pub round: usize,
+    pub date: String,
+    pub similarity: f32,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Memory {
+    // Embedding model the vectors came from; another model means starting over
+    model: String,
+    entries: Vec<Entry>,
+}
+
+fn memory_path() -> Result<PathBuf> {
+    Ok(conversations_dir()?.join(MEMORY_FILE))
+}
+
+fn excerpt(text: &str, chars: usize) -> String {
+    match text.char_indices().nth(chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+impl Memory {
+    fn load(model: &str) -> Self {
+        let saved = memory_path()
+            .ok()
+            .into_iter()
+            .flat_map(|path| [path.clone(), PathBuf::from(format!("{}.{}", path.display(), crypto::EXTENSION))])
+            .find(|path| path.exists())
+            .and_then(|path| archive::read_file(&path).ok())
+            .and_then(|text| serde_json::from_str::<Memory>(&text).ok());
+        match saved {
+            Some(memory) if memory.model == model => memory,
+            _ => Memory {
+                model: model.to_string(),
+                entries: Vec::new(),
+            },
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = memory_path()?;
+        archive::write_file(&path, &serde_json::to_string(self)?).context("Failed to write the memory index")?;
+        // Switching encryption on or off leaves the other variant behind
+        let stale = if crypto::enabled() { path } else { PathBuf::from(format!("{}.{}", path.display(), crypto::EXTENSION)) };
+        let _ = std::fs::remove_file(stale);
+        Ok(())
+    }
+
+    // Load the index and bring it up to date with the saved sessions: embed new
+    // reviewed rounds and forget rounds whose session was deleted
+    pub async fn open(client: &Client, embedder: &AiConfig) -> Result<Self> {
+        let mut memory = Self::load(&embedder.model);
+        let files = saved_sessions();
+        let before = memory.entries.len();
+        memory.entries.retain(|entry| files.contains(&entry.file));
+        let mut changed = memory.entries.len() != before;
+
+        let known: HashSet<(String, usize)> = memory.entries.iter().map(|e| (e.file.clone(), e.round)).collect();
+        let dir = conversations_dir()?;
+        let mut pending = Vec::new();
+        for file in &files {
+            // Encrypted sessions are only read while encryption is on, so this never prompts
+            if crypto::is_encrypted_name(file) && !crypto::enabled() {
+                continue;
+            }
+            let Ok(session) = ConversationSession::load(&dir.join(file)) else {
+                continue;
+            };
+            for turn in session.turns.iter().filter(|t| !known.contains(&(file.clone(), t.round))) {
+                let Some(review) = &turn.deepseek_review else {
+                    continue;
+                };
+                let review = match turn.verdict {
+                    Some(severity) => severity.label().to_string(),
+                    None => excerpt(review.lines().find(|l| !l.trim().is_empty()).unwrap_or_default(), 200),
+                };
+                pending.push(Entry {
+                    file: file.clone(),
+                    round: turn.round,
+                    date: session.start_time.clone(),
+                    question: turn.user_question.clone(),
+                    answer: excerpt(&turn.moonshot_answer, ANSWER_EXCERPT),
+                    review,
+                    vector: Vec::new(),
+                    question_vector: Vec::new(),
+                });
+            }
+        }
+
+        for batch in pending.chunks_mut(BATCH) {
+            let inputs: Vec<String> = batch.iter().map(|e| format!("{}\n\n{}", e.question, e.answer)).collect();
+            let vectors = api::embed(client, embedder, &inputs).await?;
+            for (entry, vector) in batch.iter_mut().zip(vectors) {
+                entry.vector = vector;
+            }
+            changed = true;
+        }
+        memory.entries.extend(pending);
+
+        let unembedded: Vec<usize> = (0..memory.entries.len()).filter(|&i| memory.entries[i].question_vector.is_empty()).collect();
+        for batch in unembedded.chunks(BATCH) {
+            let inputs: Vec<String> = batch.iter().map(|&i| memory.entries[i].question.clone()).collect();
+            let vectors = api::embed(client, embedder, &inputs).await?;
+            for (&i, vector) in batch.iter().zip(vectors) {
+                memory.entries[i].question_vector = vector;
+            }
+            changed = true;
+        }
+        if changed {
+            memory.save()?;
+        }
+        Ok(memory)
+    }
+
+    // Background message with the past rounds closest to `question` (and how many
+    // there are), or None when nothing is similar enough
+    pub async fn recall(
+        &self,
+        client: &Client,
+        embedder: &AiConfig,
+        settings: &MemorySettings,
+        question: &str,
+    ) -> Result<Option<(usize, ChatMessage)>> {
+        if self.entries.is_empty() {
+            return Ok(None);
+        }
+        let query = api::embed(client, embedder, &[question.to_string()]).await?;
+        let Some(query) = query.first() else {
+            return Ok(None);
+        };
+        let mut scored: Vec<(f32, &Entry)> = self
+            .entries
+            .iter()
+            .map(|entry| (cosine(query, &entry.vector), entry))
+            .filter(|(score, _)| *score >= settings.min_score)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut budget = settings.max_tokens;
+        let mut recalled = Vec::new();
+        for (_, entry) in scored.into_iter().take(settings.top_k) {
+            let block = format!(
+                "[{} round {}]\nQ: {}\nA: {}\nReview: {}",
+                entry.date, entry.round, entry.question, entry.answer, entry.review
+            );
+            let tokens = estimate_tokens(&block);
+            if tokens > budget {
+                break;
+            }
+            budget -= tokens;
+            recalled.push(block);
+        }
+        if recalled.is_empty() {
+            return Ok(None);
+        }
+        let message = ChatMessage::system(format!(
+            "Possibly relevant questions and answers from the user's earlier sessions (they may be outdated; use them only if they help):\n\n{}",
+            recalled.join("\n\n")
+        ));
+        Ok(Some((recalled.len(), message)))
+    }
+
+    // The earlier round whose question is most similar to `question`, if at or
+    // above `threshold` (cosine); newest wins a tie
+    pub async fn asked_before(&self, client: &Client, embedder: &AiConfig, question: &str, threshold: f32) -> Result<Option<Asked>> {
+        if self.entries.is_empty() {
+            return Ok(None);
+        }
+        let query = api::embed(client, embedder, &[question.to_string()]).await?;
+        let Some(query) = query.first() else {
+            return Ok(None);
+        };
+        let mut best: Option<(f32, &Entry)> = None;
+        for entry in &self.entries {
+            let similarity = cosine(query, &entry.question_vector);
+            if similarity >= threshold && best.is_none_or(|(b, e)| similarity > b || (similarity == b && entry.date > e.date)) {
+                best = Some((similarity, entry));
+            }
+        }
+        Ok(best.map(|(similarity, entry)| Asked {
+            file: entry.file.clone(),
+            round: entry.round,
+            date: entry.date.clone(),
+            similarity,
+        }))
+    }
+
+    // Number of remembered rounds
+    pub fn rounds(&self) -> usize {
+        self.entries.len()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::api::estimate_tokens;
+use crate::repl::Repl;
+use crate::ui;
+
+// Context meter shown in front of the `User >` prompt: roughly how many tokens
+// the next answer request carries before the question (system prompt and the
+// history still in context) against each answer model's context window. It is
+// worked out again at every prompt, so /clear, /forget, /model and /profile
+// show up right away. Reviews don't carry the history, so only answer models
+// are metered.
+
+// Share of the window from which the meter turns to a warning, then an error
+const WARN_AT: f64 = 0.75;
+const FULL_AT: f64 = 0.9;
+
+// 850, 12.3k, 128k
+fn compact(tokens: u32) -> String {
+    match tokens {
+        0..=999 => tokens.to_string(),
+        1_000..=9_999 => format!("{:.1}k", tokens as f64 / 1000.0),
+        _ => format!("{}k", tokens / 1000),
+    }
+}
+
+// "[1.2k/8k] " in the color of the fullest window; empty when `[context] meter` is off
+pub fn prompt_prefix(repl: &Repl) -> String {
+    let context = &repl.settings.context;
+    if !context.meter {
+        return String::new();
+    }
+    let messages = repl.session.context_messages(&repl.profile.answer_system_prompt(&repl.settings.answer));
+    let tokens: u32 = messages.iter().map(|message| estimate_tokens(&message.content)).sum();
+    let mut models: Vec<&str> = match &repl.ensemble {
+        Some(ensemble) => ensemble.answerers.iter().map(|answerer| answerer.model.as_str()).collect(),
+        None => vec![repl.answerer.model.as_str()],
+    };
+    models.dedup();
+    let mut fullest: f64 = 0.0;
+    let gauges: Vec<String> = models
+        .iter()
+        .map(|model| {
+            let gauge = match context.window_for(model) {
+                Some(window) => {
+                    fullest = fullest.max(tokens as f64 / window as f64);
+                    format!("{}/{}", compact(tokens), compact(window))
+                }
+                None => compact(tokens),
+            };
+            // Name the models only when there are several
+            if models.len() > 1 {
+                format!("{} {}", model, gauge)
+            } else {
+                gauge
+            }
+        })
+        .collect();
+    let meter = format!("[{}]", gauges.join(" · "));
+    let meter = if fullest >= FULL_AT {
+        ui::error(meter)
+    } else if fullest >= WARN_AT {
+        ui::warning(meter)
+    } else {
+        ui::status(meter)
+    };
+    format!("{} ", meter)
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use crate::http::Response;
+use crate::usage::{self, UsageRecord};
+
+// Prometheus metrics of this process's provider requests, served at `/metrics`
+// by `bot` and the Discord `bridge` when their `metrics` setting is on:
+//
+//   aivsai_requests_total{provider,model}              requests sent, failed ones included
+//   aivsai_request_failures_total{provider,model}
+//   aivsai_tokens_total{provider,model,kind}           kind is "prompt" or "completion"
+//   aivsai_request_duration_seconds{provider,model}    histogram of request latency
+//
+// The counters come from the run's usage rows (see `usage::run_rows`); only
+// the latency buckets are kept here.
+
+pub const PATH: &str = "/metrics";
+
+// Upper bounds of the latency buckets, in seconds
+const BUCKETS: &[f64] = &[0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+// Requests per (provider, model) that fell in each bucket, not cumulative
+static LATENCY: Mutex<BTreeMap<(String, String), [u64; BUCKETS.len()]>> = Mutex::new(BTreeMap::new());
+
+pub fn observe(record: &UsageRecord) {
+    let seconds = record.latency_ms as f64 / 1000.0;
+    let Some(bucket) = BUCKETS.iter().position(|&bound| seconds <= bound) else {
+        // Only counted in +Inf, which is the request total
+        return;
+    };
+    let mut latency = LATENCY.lock().unwrap_or_else(|e| e.into_inner());
+    latency.entry((record.provider.clone(), record.model.clone())).or_default()[bucket] += 1;
+}
+
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// The text exposition format
+pub fn render() -> String {
+    let rows = usage::run_rows();
+    let latency = LATENCY.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let labels = |provider: &str, model: &str| format!("provider=\"{}\",model=\"{}\"", label(provider), label(model));
+    let mut out = String::new();
+
+    out.push_str("# HELP aivsai_requests_total Provider requests sent, failed ones included.\n# TYPE aivsai_requests_total counter\n");
+    for ((provider, model), row) in &rows {
+        let _ = writeln!(out, "aivsai_requests_total{{{}}} {}", labels(provider, model), row.requests);
+    }
+    out.push_str("# HELP aivsai_request_failures_total Provider requests that failed after retries.\n# TYPE aivsai_request_failures_total counter\n");
+    for ((provider, model), row) in &rows {
+        let _ = writeln!(out, "aivsai_request_failures_total{{{}}} {}", labels(provider, model), row.errors);
+    }
+    out.push_str("# HELP aivsai_tokens_total Tokens used, as reported by the provider.\n# TYPE aivsai_tokens_total counter\n");
+    for ((provider, model), row) in &rows {
+        let labels = labels(provider, model);
+        let _ = writeln!(out, "aivsai_tokens_total{{{},kind=\"prompt\"}} {}", labels, row.prompt_tokens);
+        let _ = writeln!(out, "aivsai_tokens_total{{{},kind=\"completion\"}} {}", labels, row.completion_tokens);
+    }
+    out.push_str("# HELP aivsai_request_duration_seconds Provider request latency.\n# TYPE aivsai_request_duration_seconds histogram\n");
+    for ((provider, model), row) in &rows {
+        let labels = labels(provider, model);
+        let counts = latency.get(&(provider.clone(), model.clone())).copied().unwrap_or_default();
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(counts) {
+            cumulative += count;
+            let _ = writeln!(out, "aivsai_request_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, bound, cumulative);
+        }
+        let _ = writeln!(out, "aivsai_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, row.requests);
+        let _ = writeln!(out, "aivsai_request_duration_seconds_sum{{{}}} {}", labels, row.latency_ms as f64 / 1000.0);
+        let _ = writeln!(out, "aivsai_request_duration_seconds_count{{{}}} {}", labels, row.requests);
+    }
+    out
+}
+
+pub fn response() -> Response {
+    Response::text(200, render())
+}
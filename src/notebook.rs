@@ -0,0 +1,109 @@
+use serde_json::{json, Value};
+
+use crate::codeblocks::{closes, opening_fence};
+use crate::i18n::tr;
+use crate::session::ConversationSession;
+
+// Jupyter notebook (`.ipynb`, nbformat 4) from a session for `/export ipynb`:
+// questions, answer prose and reviews become Markdown cells, and the answers'
+// Python code blocks become code cells that run as they are. Shell blocks run
+// through the `%%bash` cell magic; blocks in other languages stay in Markdown.
+
+enum Part {
+    Text(String),
+    Code { language: String, code: String },
+}
+
+// Prose and fenced code blocks of an answer, in order
+fn parts(markdown: &str) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut text: Vec<&str> = Vec::new();
+    // Fence, language and lines of the open block
+    let mut open: Option<(String, String, Vec<&str>)> = None;
+    for line in markdown.lines() {
+        match open.take() {
+            Some((fence, language, code)) if closes(line, &fence) => parts.push(Part::Code { language, code: code.join("\n") }),
+            Some((fence, language, mut code)) => {
+                code.push(line);
+                open = Some((fence, language, code));
+            }
+            None => match opening_fence(line) {
+                Some(fence) => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(text.join("\n")));
+                        text.clear();
+                    }
+                    let language = line.trim_start()[fence.len()..].trim().to_lowercase();
+                    open = Some((fence, language, Vec::new()));
+                }
+                None => text.push(line),
+            },
+        }
+    }
+    if let Some((_, language, code)) = open {
+        parts.push(Part::Code { language, code: code.join("\n") });
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text.join("\n")));
+    }
+    parts
+}
+
+// Cell source as nbformat stores it: lines that keep their newline
+fn source(text: &str) -> Value {
+    Value::Array(text.trim_end().split_inclusive('\n').map(Value::from).collect())
+}
+
+fn markdown_cell(text: &str) -> Value {
+    json!({ "cell_type": "markdown", "metadata": {}, "source": source(text) })
+}
+
+fn code_cell(code: &str) -> Value {
+    json!({ "cell_type": "code", "execution_count": Value::Null, "metadata": {}, "outputs": [], "source": source(code) })
+}
+
+// The code of a block as a runnable cell, if the Python kernel can run it
+fn runnable(language: &str, code: &str) -> Option<String> {
+    match language {
+        "python" | "py" | "python3" | "ipython" => Some(code.to_string()),
+        "bash" | "sh" | "shell" => Some(format!("%%bash\n{}", code)),
+        _ => None,
+    }
+}
+
+// The notebook JSON and the number of code cells in it
+pub fn render(session: &ConversationSession) -> (String, usize) {
+    let mut cells = vec![markdown_cell(&format!("# {}\n\n{}", tr!("export.session_title"), session.start_time))];
+    let mut code_cells = 0;
+    for turn in &session.turns {
+        cells.push(markdown_cell(&format!("## {}\n\n💬 **{}**{}{}", tr!("export.round", turn.round), tr!("export.user"), tr!("export.colon"), turn.user_question)));
+        for part in parts(&turn.moonshot_answer) {
+            match part {
+                Part::Text(text) if text.trim().is_empty() => {}
+                Part::Text(text) => cells.push(markdown_cell(&text)),
+                Part::Code { language, code } => match runnable(&language, &code) {
+                    Some(code) => {
+                        cells.push(code_cell(&code));
+                        code_cells += 1;
+                    }
+                    None => cells.push(markdown_cell(&format!("```{}\n{}\n```", language, code))),
+                },
+            }
+        }
+        if let Some(review) = &turn.deepseek_review {
+            let quoted: Vec<String> = review.lines().map(|line| format!("> {}", line)).collect();
+            cells.push(markdown_cell(&format!("> 🔍 **{}**\n>\n{}", turn.review_model, quoted.join("\n"))));
+        }
+    }
+    let notebook = json!({
+        "cells": cells,
+        "metadata": {
+            "kernelspec": { "display_name": "Python 3", "language": "python", "name": "python3" },
+            "language_info": { "name": "python" }
+        },
+        "nbformat": 4,
+        // 4.4: cells need no ids
+        "nbformat_minor": 4
+    });
+    (serde_json::to_string_pretty(&notebook).unwrap_or_default(), code_cells)
+}
@@ -0,0 +1,46 @@
+use std::io::Write;
+use std::time::Duration;
+
+use crate::config::NotifySettings;
+use crate::i18n::tr;
+use crate::ui;
+
+// One-line summary of a review for the notification body
+fn summarize(review: Option<&str>) -> String {
+    let Some(review) = review else {
+        return tr!("notify.review_failed").to_string();
+    };
+    let first_line = review.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("");
+    let summary: String = first_line.chars().take(100).collect();
+    if first_line.chars().count() > 100 {
+        format!("{}…", summary)
+    } else {
+        summary
+    }
+}
+
+#[cfg(feature = "desktop-notifications")]
+fn desktop(title: &str, body: &str) {
+    if let Err(e) = notify_rust::Notification::new().appname("AIvsAI").summary(title).body(body).show() {
+        ui::notice(ui::warning(tr!("notify.failed", e)));
+    }
+}
+
+#[cfg(not(feature = "desktop-notifications"))]
+fn desktop(_title: &str, _body: &str) {
+    ui::notice(ui::warning(tr!("notify.unavailable")));
+}
+
+// Tell the user a round is done, if it took long enough that they may have looked away
+pub fn round_finished(settings: &NotifySettings, round: usize, elapsed: Duration, review: Option<&str>) {
+    if elapsed < Duration::from_secs(settings.min_seconds) {
+        return;
+    }
+    if settings.bell {
+        print!("\x07");
+        let _ = std::io::stdout().flush();
+    }
+    if settings.desktop {
+        desktop(&tr!("notify.title", round), &summarize(review));
+    }
+}
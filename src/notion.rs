@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use reqwest::{Client, Method};
+use serde_json::{json, Map, Value};
+
+use crate::archive::{conversations_dir, saved_sessions, session_file};
+use crate::i18n::tr;
+use crate::session::{ConversationSession, ConversationTurn};
+use crate::{api, index, ui};
+
+// `aivsai export notion --database ID [--all | NAME...]`: saved sessions become
+// pages of a Notion database, titled with the first question, tagged with the
+// session's /tag labels (when the database has a multi-select "Tags" property),
+// one toggle per round holding the answer and the review. The integration token
+// comes from the OS keyring (`--features keyring`, stored with `--save-token`)
+// or NOTION_TOKEN; the database has to be shared with the integration.
+
+const NOTION_API: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "aivsai";
+#[cfg(feature = "keyring")]
+const KEYRING_USER: &str = "notion";
+// Notion caps a text run at 2000 characters, and a request at 100 blocks and
+// 100 runs per block
+const TEXT_LIMIT: usize = 1900;
+const BLOCK_LIMIT: usize = 100;
+
+struct ExportArgs {
+    database: Option<String>,
+    all: bool,
+    save_token: bool,
+    sessions: Vec<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<ExportArgs> {
+    let usage = "Usage: aivsai export notion --database ID [--all | NAME...], or aivsai export notion --save-token";
+    let mut iter = args.iter();
+    if iter.next().map(String::as_str) != Some("notion") {
+        anyhow::bail!("{}", usage);
+    }
+    let mut parsed = ExportArgs {
+        database: None,
+        all: false,
+        save_token: false,
+        sessions: Vec::new(),
+    };
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--database" => parsed.database = Some(iter.next().context("--database needs a database ID")?.clone()),
+            "--all" => parsed.all = true,
+            "--save-token" => parsed.save_token = true,
+            other if other.starts_with("--") => anyhow::bail!("Unknown option for export notion: {}\n{}", other, usage),
+            name => parsed.sessions.push(name.to_string()),
+        }
+    }
+    if !parsed.save_token && parsed.database.is_none() {
+        anyhow::bail!("{}", usage);
+    }
+    Ok(parsed)
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_token() -> Option<String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).ok()?.get_password().ok()
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_token() -> Option<String> {
+    None
+}
+
+// The keyring entry first, then NOTION_TOKEN
+fn token() -> Result<String> {
+    keyring_token()
+        .or_else(|| std::env::var("NOTION_TOKEN").ok())
+        .filter(|token| !token.trim().is_empty())
+        .context("No Notion token: store one with `aivsai export notion --save-token` (needs --features keyring) or set NOTION_TOKEN")
+}
+
+// Read the token from stdin into the keyring
+#[cfg(feature = "keyring")]
+fn save_token() -> Result<()> {
+    eprint!("{}", tr!("notion.token_prompt"));
+    let mut token = String::new();
+    std::io::stdin().read_line(&mut token).context("Failed to read the token")?;
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .and_then(|entry| entry.set_password(token.trim()))
+        .context("Failed to store the token in the keyring")?;
+    ui::notice(ui::success(tr!("notion.token_saved")));
+    Ok(())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn save_token() -> Result<()> {
+    anyhow::bail!("This build has no keyring support; rebuild with --features keyring or set NOTION_TOKEN")
+}
+
+struct Notion {
+    client: Client,
+    token: String,
+}
+
+impl Notion {
+    async fn request(&self, method: Method, path: &str, body: Option<&Value>) -> Result<Value> {
+        api::check_destination("Notion", NOTION_API)?;
+        let mut request = self
+            .client
+            .request(method, format!("{}{}", NOTION_API, path))
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Notion-Version", NOTION_VERSION);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send().await.context("Failed to reach Notion")?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("HTTP {} from Notion: {}", status, body.get("message").unwrap_or(&body));
+        }
+        Ok(body)
+    }
+}
+
+// Names of the database's title property and, if it has one, its multi-select "Tags"
+fn schema(database: &Value) -> Result<(String, Option<String>)> {
+    let properties = database.get("properties").and_then(Value::as_object).context("Notion returned a database without properties")?;
+    let of_type = |kind: &str| properties.iter().find(|(_, property)| property.get("type").and_then(Value::as_str) == Some(kind)).map(|(name, _)| name.clone());
+    let title = of_type("title").context("The Notion database has no title property")?;
+    let tags = properties
+        .iter()
+        .find(|(name, property)| name.eq_ignore_ascii_case("tags") && property.get("type").and_then(Value::as_str) == Some("multi_select"))
+        .map(|(name, _)| name.clone());
+    Ok((title, tags))
+}
+
+// Text runs of at most TEXT_LIMIT characters
+fn runs(text: &str, bold: bool) -> Vec<Value> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(TEXT_LIMIT)
+        .map(|chunk| json!({ "type": "text", "text": { "content": chunk.iter().collect::<String>() }, "annotations": { "bold": bold } }))
+        .collect()
+}
+
+// Paragraph blocks holding `text`, split where a block would get too many runs
+fn paragraphs(text: &str) -> Vec<Value> {
+    runs(text, false)
+        .chunks(BLOCK_LIMIT)
+        .map(|runs| json!({ "object": "block", "type": "paragraph", "paragraph": { "rich_text": runs } }))
+        .collect()
+}
+
+fn label(text: &str) -> Value {
+    json!({ "object": "block", "type": "paragraph", "paragraph": { "rich_text": runs(text, true) } })
+}
+
+// A round as a toggle: the question as its title, answer and review inside
+fn toggle(turn: &ConversationTurn) -> Value {
+    let question: String = turn.user_question.lines().next().unwrap_or_default().chars().take(TEXT_LIMIT).collect();
+    let mut children = Vec::new();
+    // The title holds the first line; longer questions are repeated in full
+    if turn.user_question.contains('\n') {
+        children.push(label(tr!("notion.question")));
+        children.extend(paragraphs(&turn.user_question));
+    }
+    children.push(label(&tr!("notion.answer", turn.answer_model)));
+    children.extend(paragraphs(&turn.moonshot_answer));
+    let review = match (&turn.deepseek_review, &turn.review_error) {
+        (Some(review), _) => review.clone(),
+        (None, Some(error)) => tr!("notion.review_failed", error),
+        (None, None) => tr!("notion.no_review").to_string(),
+    };
+    children.push(label(&tr!("notion.review", turn.review_model)));
+    children.extend(paragraphs(&review));
+    children.truncate(BLOCK_LIMIT);
+    json!({
+        "object": "block",
+        "type": "toggle",
+        "toggle": {
+            "rich_text": runs(&tr!("notion.round", turn.round, question), false),
+            "children": children,
+        },
+    })
+}
+
+// Create the session's page; rounds past the first BLOCK_LIMIT are appended after
+async fn push(notion: &Notion, database: &str, schema: &(String, Option<String>), file: &str, session: &ConversationSession) -> Result<String> {
+    let entry = index::entry(file, session);
+    let title = if entry.title.is_empty() { entry.file.clone() } else { entry.title.clone() };
+    let mut properties = Map::new();
+    properties.insert(schema.0.clone(), json!({ "title": runs(&title, false) }));
+    if let Some(tags) = &schema.1 {
+        let options: Vec<Value> = session.tags.iter().map(|tag| json!({ "name": tag.replace(',', " ") })).collect();
+        properties.insert(tags.clone(), json!({ "multi_select": options }));
+    }
+    let toggles: Vec<Value> = session.turns.iter().map(toggle).collect();
+    let mut batches = toggles.chunks(BLOCK_LIMIT);
+    let page = notion
+        .request(
+            Method::POST,
+            "/pages",
+            Some(&json!({
+                "parent": { "database_id": database },
+                "properties": properties,
+                "children": batches.next().unwrap_or_default(),
+            })),
+        )
+        .await?;
+    let id = page.get("id").and_then(Value::as_str).context("Notion returned a page without an id")?;
+    for batch in batches {
+        notion.request(Method::PATCH, &format!("/blocks/{}/children", id), Some(&json!({ "children": batch }))).await?;
+    }
+    Ok(page.get("url").and_then(Value::as_str).unwrap_or(id).to_string())
+}
+
+pub async fn run_export(args: &[String]) -> Result<()> {
+    let args = parse_args(args)?;
+    if args.save_token {
+        return save_token();
+    }
+    let database = args.database.unwrap_or_default();
+    let files = if args.all {
+        saved_sessions()
+    } else if args.sessions.is_empty() {
+        saved_sessions().into_iter().take(1).collect()
+    } else {
+        args.sessions
+    };
+    if files.is_empty() {
+        anyhow::bail!("No saved sessions in {:?}", conversations_dir()?);
+    }
+
+    let notion = Notion { client: api::client(), token: token()? };
+    let found = notion.request(Method::GET, &format!("/databases/{}", database), None).await.context("Failed to read the Notion database (is it shared with the integration?)")?;
+    let schema = schema(&found)?;
+    for file in &files {
+        let session = session_file(file).and_then(|path| ConversationSession::load(&path)).context(format!("Failed to load session {}", file))?;
+        let url = push(&notion, &database, &schema, file, &session).await.context(format!("Failed to export {} to Notion", file))?;
+        ui::notice(ui::success(tr!("notion.pushed", file, url)));
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::PagerSettings;
+use crate::recovery;
+
+// Whether `text` fits on one screen; always true when stdout isn't a terminal
+pub fn fits_terminal(text: &str) -> bool {
+    match terminal_size::terminal_size() {
+        Some((_, terminal_size::Height(rows))) => text.lines().count() + 2 <= rows as usize,
+        None => true,
+    }
+}
+
+// Pager command: `[pager] command`, then $PAGER, then `less -R` (keeps colors)
+fn pager_command(settings: &PagerSettings) -> String {
+    settings
+        .command
+        .clone()
+        .filter(|c| !c.trim().is_empty())
+        .or_else(|| std::env::var("PAGER").ok().filter(|p| !p.trim().is_empty()))
+        .unwrap_or_else(|| "less -R".to_string())
+}
+
+fn run_pager(text: &str, command: &str) -> Result<()> {
+    let mut parts = command.split_whitespace();
+    let program = parts.next().context("Empty pager command")?;
+    let mut child = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()
+        .context(format!("Failed to start pager: {}", command))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may exit before reading everything (e.g. `q` in less)
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait().context(format!("Pager failed: {}", command))?;
+    Ok(())
+}
+
+// Show text in the pager, falling back to plain printing if it can't be started
+pub fn page(text: &str, settings: &PagerSettings) -> Result<()> {
+    // Ctrl+C belongs to the pager while it is open, not to our exit handler
+    let _claim = recovery::claim_interrupts();
+    let command = pager_command(settings);
+    if let Err(e) = run_pager(text, &command) {
+        println!("{}", text);
+        return Err(e);
+    }
+    Ok(())
+}
@@ -0,0 +1,245 @@
+use anyhow::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+
+use crate::api::{complete, ChatMessage};
+use crate::config::{AiConfig, PanelLayout, Profile, Settings};
+use crate::i18n::tr;
+use crate::session::ConversationTurn;
+use crate::verdict::{self, Severity};
+use crate::{highlight, layout, shield, ui, usage};
+
+// A panel of extra reviewers: each answer is reviewed by the main reviewer and
+// every panel member at the same time, the reviews are shown side by side, and
+// an optional consolidator merges them into one.
+
+// Narrowest column for side-by-side reviews; below it they are stacked
+const MIN_COLUMN: usize = 30;
+
+// One panel member's review of a turn
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PanelReview {
+    pub reviewer: String,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Severity>,
+}
+
+pub struct Panel {
+    // Shared with the tasks that run the reviews
+    pub reviewers: Vec<Arc<AiConfig>>,
+    pub consolidator: Option<AiConfig>,
+}
+
+// `[panel] layout`, for redisplaying stored rounds too
+static LAYOUT: OnceLock<PanelLayout> = OnceLock::new();
+
+impl Panel {
+    // The `[panel]` of the settings, if it names any reviewers
+    pub fn from_settings(settings: &Settings) -> Result<Option<Self>> {
+        let panel = &settings.panel;
+        let _ = LAYOUT.set(panel.layout);
+        if panel.reviewers.is_empty() {
+            return Ok(None);
+        }
+        let member = |spec: &String, max_tokens| {
+            AiConfig::from_spec(settings, spec).map(|config| AiConfig { max_tokens, ..config })
+        };
+        Ok(Some(Self {
+            reviewers: panel.reviewers.iter().map(|spec| member(spec, settings.review.max_tokens).map(Arc::new)).collect::<Result<_>>()?,
+            consolidator: panel.consolidator.as_ref().map(|spec| member(spec, settings.review.revision_max_tokens)).transpose()?,
+        }))
+    }
+}
+
+// Every panel member's review of the same request, run concurrently; in panel order
+pub async fn review_all(client: &Client, reviewers: &[Arc<AiConfig>], messages: &[ChatMessage]) -> Vec<PanelReview> {
+    let tasks: Vec<_> = reviewers
+        .iter()
+        .map(|reviewer| {
+            let (client, reviewer, messages) = (client.clone(), Arc::clone(reviewer), messages.to_vec());
+            usage::spawn(async move { complete(&client, &reviewer, messages).await.map(|completion| completion.content) })
+        })
+        .collect();
+    let mut reviews = Vec::new();
+    for (reviewer, task) in reviewers.iter().zip(tasks) {
+        let result = match usage::joined(task).await {
+            Ok(result) => result,
+            Err(e) => Err(anyhow::anyhow!("review task failed: {}", e)),
+        };
+        reviews.push(match result {
+            Ok(review) => PanelReview {
+                reviewer: reviewer.name.clone(),
+                model: reviewer.model.clone(),
+                verdict: verdict::parse(&review),
+                review: Some(review),
+                error: None,
+            },
+            Err(e) => {
+                ui::notice(ui::warning(tr!("error.provider", reviewer.name, e)));
+                PanelReview {
+                    reviewer: reviewer.name.clone(),
+                    model: reviewer.model.clone(),
+                    review: None,
+                    error: Some(e.to_string()),
+                    verdict: None,
+                }
+            }
+        });
+    }
+    reviews
+}
+
+// Merge the main review and the panel's into one review
+pub async fn consolidate(client: &Client, consolidator: &AiConfig, profile: &Profile, turn: &ConversationTurn) -> Result<String> {
+    let mut reviews = String::new();
+    let main = turn.deepseek_review.iter().map(|review| (turn.review_model.as_str(), review));
+    let panel = turn.panel_reviews.iter().filter_map(|p| p.review.as_ref().map(|review| (p.model.as_str(), review)));
+    for (i, (model, review)) in main.chain(panel).enumerate() {
+        reviews.push_str(&format!("\n\n--- Review {} ({}) ---\n{}", i + 1, model, review));
+    }
+    let prompt = format!(
+        "The user asked:\n{}\n\nAn AI assistant answered:\n{}{}\n\nSeveral reviewers checked this answer independently:{}\n\nMerge their reviews into one: list each distinct issue once, say where the reviewers disagree and which side is right, and leave out repetition. Write the merged review in {}.{}",
+        shield::quote("question", &turn.user_question),
+        shield::quote("answer", &turn.moonshot_answer),
+        shield::RULES,
+        reviews,
+        profile.review_language(),
+        verdict::INSTRUCTION
+    );
+    let messages = vec![ChatMessage::system(profile.review_system_prompt()), ChatMessage::user(prompt)];
+    Ok(complete(client, consolidator, messages).await?.content)
+}
+
+fn header(name: &str, model: &str) -> String {
+    format!("{} ({})", name, model)
+}
+
+// The main review next to the panel's (or under each other when the terminal
+// is too narrow or `[panel] layout = "stacked"`), then the consolidated review
+pub fn terminal_block(name: &str, review: &str, turn: &ConversationTurn) -> String {
+    let model = if turn.review_model.is_empty() { "?" } else { &turn.review_model };
+    let mut columns = vec![(header(name, model), review.to_string())];
+    for panel in &turn.panel_reviews {
+        let text = match (&panel.review, &panel.error) {
+            (Some(review), _) => review.clone(),
+            (None, error) => tr!("export.review_failed", error.as_deref().unwrap_or_default()),
+        };
+        columns.push((header(&panel.reviewer, &panel.model), text));
+    }
+    let width = layout::terminal_width().unwrap_or(0);
+    let fits = width.saturating_sub(3 * columns.len() + 1) / columns.len() >= MIN_COLUMN;
+    let side_by_side = LAYOUT.get().is_none_or(|layout| *layout == PanelLayout::SideBySide);
+    let mut block = if side_by_side && fits {
+        let headers: Vec<&str> = columns.iter().map(|(header, _)| header.as_str()).collect();
+        let row: Vec<String> = columns.iter().map(|(_, text)| text.clone()).collect();
+        format!("\n{}", layout::table(&headers, &[row]))
+    } else {
+        columns
+            .iter()
+            .map(|(header, text)| format!("\n{}\n{}", ui::reviewer(tr!("round.review_header", header)), highlight::terminal_markdown(text)))
+            .collect()
+    };
+    if let Some(merged) = &turn.consolidated_review {
+        block.push_str(&format!("\n\n{}\n{}", ui::heading(tr!("panel.consolidated")), highlight::terminal_markdown(merged)));
+    }
+    block
+}
+
+// Panel reviews and the consolidated review for Markdown exports, as quoted
+// blocks following the main review
+pub fn export_block(turn: &ConversationTurn) -> String {
+    let mut block = String::new();
+    for panel in &turn.panel_reviews {
+        let body = match (&panel.review, &panel.error) {
+            (Some(review), _) => layout::prefix_lines(review, "> "),
+            (None, error) => format!("> *{}*", tr!("export.review_failed", error.as_deref().unwrap_or_default())),
+        };
+        block.push_str(&format!("\n\n> 🔍 **{}** ({})\n>\n{}", panel.reviewer, panel.model, body));
+    }
+    if let Some(merged) = &turn.consolidated_review {
+        block.push_str(&format!("\n\n> 🧩 **{}**\n>\n{}", tr!("panel.consolidated"), layout::prefix_lines(merged, "> ")));
+    }
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetrySettings;
+    use crate::keys::ApiKeys;
+    use crate::rate_limit::RateLimiter;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // A provider on localhost answering one request with a canned review
+    async fn canned_provider() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            // Read the headers and the body they announce before answering
+            loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_ascii_lowercase();
+                let Some(end) = text.find("\r\n\r\n") else {
+                    continue;
+                };
+                let length = text
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|length| length.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    break;
+                }
+            }
+            let body = r#"{"choices":[{"message":{"role":"assistant","content":"Correct.\nVERDICT: clean"},"finish_reason":"stop"}],"usage":{"prompt_tokens":12,"completion_tokens":4}}"#;
+            let response = format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body);
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{}/chat/completions", address)
+    }
+
+    fn canned_reviewer(base_url: String) -> Arc<AiConfig> {
+        Arc::new(AiConfig {
+            keys: Arc::new(ApiKeys::single(String::new())),
+            base_url,
+            model: "deepseek-chat".to_string(),
+            name: "DeepSeek AI".to_string(),
+            retry: RetrySettings::default(),
+            limiter: Arc::new(RateLimiter::new(None, None, None)),
+            max_tokens: None,
+            temperature: None,
+            auto_continue: 0,
+            headers: Vec::new(),
+            query: Vec::new(),
+            compress_over: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn panel_requests_count_for_the_chat_user() {
+        let reviewer = canned_reviewer(canned_provider().await);
+        let messages = vec![ChatMessage::user("Is 2 + 2 = 4?")];
+        let before = usage::user_tokens_today("U-panel");
+
+        let (reviews, records) = usage::as_user("U-panel".to_string(), usage::collect(review_all(&Client::new(), &[reviewer], &messages))).await;
+
+        assert_eq!(reviews[0].verdict, Some(Severity::Clean));
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].user.as_deref(), Some("U-panel"));
+        assert!(usage::user_tokens_today("U-panel") > before);
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::archive;
+use crate::config::PdfSettings;
+use crate::i18n::tr;
+use crate::publish;
+use crate::session::ConversationSession;
+
+// `/export pdf`: the session as the same self-contained page `/export rounds
+// ... html` writes, plus print styles, converted by an external HTML → PDF tool
+// (`[pdf] command`). Chinese text needs a CJK font the converter can find, so
+// the configured fonts are listed ahead of the generic families and the page
+// is marked `zh` when it contains Han characters, which keeps converters from
+// picking Japanese glyph variants.
+
+// Added after the page's own style
+const PRINT_STYLE: &str = r#"@page { size: A4; margin: 18mm 16mm; }
+body { max-width: none; margin: 0; padding: 0; font-size: 11pt; }
+pre { white-space: pre-wrap; overflow-wrap: anywhere; }
+.round { break-before: page; }
+.round:first-of-type { break-before: auto; }
+h2, h3 { break-after: avoid; }
+"#;
+
+fn has_han(text: &str) -> bool {
+    text.chars().any(|c| matches!(c, '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{f900}'..='\u{faff}'))
+}
+
+fn font_list(fonts: &[String]) -> String {
+    fonts.iter().map(|font| format!("\"{}\", ", font.replace('"', ""))).collect()
+}
+
+// The HTML handed to the converter
+fn render(session: &ConversationSession, settings: &PdfSettings) -> String {
+    let turns: Vec<_> = session.turns.iter().collect();
+    let page = publish::rounds_page(tr!("export.session_title"), &session.start_time, &turns);
+    let fonts = font_list(&settings.fonts);
+    let style = format!(
+        "{}body {{ font-family: system-ui, {}sans-serif; }}\ncode {{ font-family: ui-monospace, monospace, {}monospace; }}\n",
+        PRINT_STYLE, fonts, fonts
+    );
+    let page = page.replacen("</style>", &format!("{}</style>", style), 1);
+    if has_han(&page) {
+        page.replacen("<html>", "<html lang=\"zh-CN\">", 1)
+    } else {
+        page.replacen("<html>", "<html lang=\"en\">", 1)
+    }
+}
+
+// `[pdf] command` with the paths filled in, split into program and arguments
+fn converter(command: &str, input: &Path, output: &Path) -> Result<Vec<String>> {
+    let words: Vec<String> = command
+        .split_whitespace()
+        .map(|word| word.replace("{input}", &input.to_string_lossy()).replace("{output}", &output.to_string_lossy()))
+        .collect();
+    if words.is_empty() || !command.contains("{input}") || !command.contains("{output}") {
+        anyhow::bail!("[pdf] command must name a program and contain {{input}} and {{output}}: {:?}", command);
+    }
+    Ok(words)
+}
+
+// Convert the session and save the PDF at `path`; returns the path written
+pub fn export(session: &ConversationSession, settings: &PdfSettings, path: &Path) -> Result<PathBuf> {
+    let stem = std::env::temp_dir().join(format!("aivsai-pdf-{}", std::process::id()));
+    let (input, output) = (stem.with_extension("html"), stem.with_extension("pdf"));
+    std::fs::write(&input, render(session, settings)).context(format!("Failed to write {:?}", input))?;
+    let result = converter(&settings.command, &input, &output).and_then(|command| {
+        let run = Command::new(&command[0])
+            .args(&command[1..])
+            .output()
+            .context(format!("Failed to start the PDF converter {:?} (install it or set [pdf] command)", command[0]))?;
+        if !run.status.success() {
+            anyhow::bail!(
+                "PDF converter {:?} exited with {}: {}",
+                command[0],
+                run.status,
+                String::from_utf8_lossy(&run.stderr).trim()
+            );
+        }
+        std::fs::read(&output).context(format!("The PDF converter wrote no {:?}", output))
+    });
+    // The temporary files hold the whole session
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+    archive::write_bytes(path, &result?)
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+// Answerer personas for `/persona NAME`: one TOML file each in `[answer]
+// personas` (~/.aivsai/personas by default), e.g. rust-reviewer.toml:
+//
+//   name = "senior Rust reviewer"    # defaults to the file name
+//   prompt = "You are a senior Rust engineer reviewing code for soundness and idiom."
+//   temperature = 0.3                # optional
+//   max_tokens = 1200                # optional; otherwise `[answer]` length decides
+//
+// The persona's prompt takes the place of the profile's answer prompt until
+// `/persona off`; language, length and style asks still apply.
+#[derive(Deserialize, Clone)]
+pub struct Persona {
+    #[serde(default)]
+    pub name: String,
+    // File name without `.toml`, which works as a name too
+    #[serde(skip)]
+    pub file: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+fn dir(setting: &str) -> PathBuf {
+    match (setting.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => std::path::Path::new(&home).join(rest),
+        _ => PathBuf::from(setting),
+    }
+}
+
+// Every persona in the directory, by name; none if it doesn't exist
+pub fn load_all(setting: &str) -> Result<Vec<Persona>> {
+    let dir = dir(setting);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut personas = Vec::new();
+    let entries = std::fs::read_dir(&dir).context(format!("Failed to read personas from {:?}", dir))?;
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.extension().is_none_or(|ext| ext != "toml") {
+            continue;
+        }
+        let text = std::fs::read_to_string(&path).context(format!("Failed to read persona {:?}", path))?;
+        let mut persona: Persona = toml::from_str(&text).context(format!("Invalid persona {:?}", path))?;
+        persona.file = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        if persona.name.trim().is_empty() {
+            persona.name = persona.file.clone();
+        }
+        personas.push(persona);
+    }
+    personas.sort_by_key(|persona| persona.name.to_lowercase());
+    Ok(personas)
+}
+
+// The persona called `name` or stored as `name`.toml (case-insensitive, quotes optional)
+pub fn find(setting: &str, name: &str) -> Result<Option<Persona>> {
+    let name = name.trim().trim_matches('"');
+    Ok(load_all(setting)?
+        .into_iter()
+        .find(|persona| persona.name.eq_ignore_ascii_case(name) || persona.file.eq_ignore_ascii_case(name)))
+}
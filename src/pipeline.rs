@@ -0,0 +1,724 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::Instrument;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::api::{self, complete, stream_ai_api, ChatMessage, Completion};
+use crate::config::{AiConfig, AnswerSettings, GuardMode, Profile, ReferenceSettings, Settings, Strictness, UsageSettings};
+use crate::i18n::tr;
+use crate::{highlight, layout};
+use crate::session::{ConversationSession, ConversationTurn, RoundMeta};
+use crate::stats;
+use crate::verdict::{self, Severity};
+use crate::usage::{self, Totals, UsageRecord, UsageRow};
+use crate::{archive, crypto, events, guard, shield, webhook};
+use crate::{plugins, references, render_session_markdown, report_attachments, review_messages, save_conversation_session, save_session_as, screen_outbound, summarize_review, translate, ui};
+
+// Non-interactive answer + review runs:
+//
+//   aivsai ask [--json] "question"
+//   cat error.log | aivsai ask "why is this failing?"
+//   aivsai batch [--json] [--csv results.csv] [--error-budget 10%] [--resume] questions.txt
+//
+// With --json, stdout carries one JSON event per line (answer, review, summary,
+// usage, flagged, error, saved) and all human-oriented output moves to stderr.
+//
+// Reviews end with a verdict that sets the exit code (see `verdict`), so a run
+// can gate CI on AI-generated content.
+pub struct Pipeline {
+    client: Client,
+    answerer: AiConfig,
+    reviewer: AiConfig,
+    summarizer: Option<AiConfig>,
+    profile: Profile,
+    answer: AnswerSettings,
+    strictness: Strictness,
+    references: ReferenceSettings,
+    pricing: UsageSettings,
+    json: bool,
+    // Where `--json` events go instead of stdout (`--stdio`)
+    events: Option<UnboundedSender<Value>>,
+}
+
+// A round that has its answer (None if that failed) and waits for the review
+struct Answered {
+    turn: Option<ConversationTurn>,
+    started: Instant,
+    requests: Vec<UsageRecord>,
+    // False when the answer is held back to be printed with its review
+    shown: bool,
+}
+
+impl Pipeline {
+    pub fn new(settings: &Settings, profile: &Profile, json: bool) -> Result<Self> {
+        if json {
+            ui::send_notices_to_stderr();
+        }
+        let (answerer, reviewer) = AiConfig::pair(settings, profile)?;
+        Ok(Self {
+            client: api::client(),
+            answerer,
+            reviewer,
+            summarizer: AiConfig::summarizer(settings)?,
+            profile: profile.clone(),
+            answer: settings.answer.clone(),
+            strictness: settings.review.strictness,
+            references: settings.references.clone(),
+            pricing: settings.usage.clone(),
+            json,
+            events: None,
+        })
+    }
+
+    // Send the events to `events` rather than printing them; nothing else is printed
+    pub fn send_events_to(mut self, events: UnboundedSender<Value>) -> Self {
+        self.json = true;
+        self.events = Some(events);
+        self
+    }
+
+    fn emit(&self, event: Value) {
+        if let Some(events) = &self.events {
+            let _ = events.send(event);
+        } else if self.json {
+            println!("{}", event);
+        }
+    }
+
+    fn emit_usage(&self, round: usize, role: &str, config: &AiConfig, completion: &Completion) {
+        self.emit(json!({
+            "event": "usage",
+            "round": round,
+            "role": role,
+            "provider": config.name,
+            "model": config.model,
+            "prompt_tokens": completion.usage.prompt_tokens,
+            "completion_tokens": completion.usage.completion_tokens,
+            "latency_ms": completion.latency_ms,
+        }));
+    }
+
+    fn emit_error(&self, round: usize, stage: &str, source: &str, error: &anyhow::Error) {
+        self.emit(json!({
+            "event": "error",
+            "round": round,
+            "stage": stage,
+            "message": format!("{:#}", error),
+        }));
+        if !self.json {
+            eprintln!("{}", ui::error(tr!("error.provider", source, format!("{:#}", error))));
+        }
+    }
+
+    // The `[guard]` pass over a question or answer: what it flagged, or None
+    // (reported as an error) when the policy blocks it
+    async fn guard(&self, round: usize, stage: &str, text: &str) -> Option<Vec<String>> {
+        let flags = guard::check(&self.client, text).await;
+        if flags.is_empty() {
+            return Some(flags);
+        }
+        if guard::mode() == GuardMode::Block {
+            let error = anyhow::anyhow!("{} blocked by [guard]: {}", stage, flags.join(", "));
+            self.emit_error(round, "guard", "Guard", &error);
+            return None;
+        }
+        self.emit(json!({
+            "event": "flagged",
+            "round": round,
+            "stage": stage,
+            "flags": flags,
+        }));
+        if !self.json {
+            ui::notice(ui::warning(tr!("guard.flagged_note", flags.join(", "))));
+        }
+        Some(flags)
+    }
+
+    // English TL;DR of a Chinese review, if `[summary] english` is on
+    async fn summarize(&self, round: usize, review: &str) -> Option<String> {
+        let summarizer = self.summarizer.as_ref().filter(|_| translate::is_mostly_chinese(review))?;
+        let span = stage_span("summary", round, summarizer);
+        match summarize_review(&self.client, summarizer, review).instrument(span.clone()).await {
+            Ok(summary) => {
+                self.emit(json!({ "event": "summary", "round": round, "content": summary }));
+                if !self.json {
+                    println!("\n{}", ui::heading(tr!("review.summary_heading")));
+                    println!("{}", layout::wrap_for_terminal(&summary));
+                }
+                Some(summary)
+            }
+            Err(e) => {
+                span.record("otel.status_code", "ERROR");
+                self.emit_error(round, "summary", &summarizer.name, &e);
+                None
+            }
+        }
+    }
+
+    // Answer one question and review it; None if the answer itself failed
+    pub async fn run_round(&self, session_start: &str, round: usize, question: &str) -> Option<ConversationTurn> {
+        self.run_round_with(session_start, round, self.fresh_context(), question, None).await
+    }
+
+    fn fresh_context(&self) -> Vec<ChatMessage> {
+        vec![ChatMessage::system(self.profile.answer_system_prompt(&self.answer))]
+    }
+
+    // `run_round` after earlier messages (`context`, system prompt first),
+    // streaming the answer to `on_delta` when given
+    pub async fn run_round_with(
+        &self,
+        session_start: &str,
+        round: usize,
+        context: Vec<ChatMessage>,
+        question: &str,
+        on_delta: Option<&mut (dyn FnMut(&str) + Send)>,
+    ) -> Option<ConversationTurn> {
+        let answered = self.answer_stage(round, context, question, on_delta, true).await;
+        self.review_stage(session_start, answered).await.0
+    }
+
+    // The answer half of a round, with the requests it made; `show` false holds
+    // the answer back for `review_stage` to print
+    async fn answer_stage(&self, round: usize, context: Vec<ChatMessage>, question: &str, on_delta: Option<&mut (dyn FnMut(&str) + Send)>, show: bool) -> Answered {
+        let started = Instant::now();
+        events::log("question", json!({ "round": round, "text": question }));
+        let span = stage_span("answer", round, &self.answerer);
+        let (turn, requests) = usage::collect(self.answer(round, context, question, on_delta, show)).instrument(span.clone()).await;
+        if turn.is_none() {
+            span.record("otel.status_code", "ERROR");
+        }
+        Answered { turn, started, requests, shown: show }
+    }
+
+    // The review half: reviews the answered round and finishes it. Also returns
+    // every request of the round, failed answers included.
+    async fn review_stage(&self, session_start: &str, answered: Answered) -> (Option<ConversationTurn>, Vec<UsageRecord>) {
+        let Answered { turn, started, mut requests, shown } = answered;
+        let Some(mut turn) = turn else {
+            return (None, requests);
+        };
+        if !shown {
+            self.print_answer(&turn.moonshot_answer);
+        }
+        let span = stage_span("review", turn.round, &self.reviewer);
+        let ((), review_requests) = usage::collect(self.review(&mut turn)).instrument(span.clone()).await;
+        if turn.review_error.is_some() {
+            span.record("otel.status_code", "ERROR");
+        }
+        requests.extend(review_requests);
+        let meta = RoundMeta::new(requests.clone(), started, &self.pricing);
+        webhook::round_finished(session_start, &turn, Totals::of(&requests), started.elapsed());
+        turn.meta = Some(meta);
+        (Some(turn), requests)
+    }
+
+    fn print_answer(&self, answer: &str) {
+        if !self.json {
+            println!("\n{}", ui::answerer(tr!("round.answer_header", self.answerer.name)));
+            println!("{}", highlight::terminal_markdown(answer));
+        }
+    }
+
+    async fn answer(&self, round: usize, context: Vec<ChatMessage>, question: &str, on_delta: Option<&mut (dyn FnMut(&str) + Send)>, show: bool) -> Option<ConversationTurn> {
+        let question = match references::expand(question, &self.references) {
+            Ok(expanded) => {
+                report_attachments(&expanded);
+                let question = screen_outbound(None, expanded.question)?;
+                match plugins::on_question(question) {
+                    Ok(question) => question,
+                    Err(e) => {
+                        self.emit_error(round, "plugin", "Plugin", &e);
+                        return None;
+                    }
+                }
+            }
+            Err(e) => {
+                self.emit_error(round, "references", "Reference", &e);
+                return None;
+            }
+        };
+        let mut guard_flags = match self.guard(round, "question", &question).await {
+            Some(flags) => flags,
+            None => return None,
+        };
+        let question = question.as_str();
+        let mut messages = context;
+        messages.push(ChatMessage::user(question));
+        // Blocking needs the whole answer before any of it is shown
+        let answer = match on_delta.filter(|_| !guard::holds_answers()) {
+            Some(on_delta) => stream_ai_api(&self.client, &self.answerer, messages, on_delta).await.map(|streamed| {
+                let finish = streamed.finish();
+                (streamed.content, finish)
+            }),
+            None => complete(&self.client, &self.answerer, messages).await.map(|answer| {
+                self.emit_usage(round, "answer", &self.answerer, &answer);
+                (answer.content, answer.finish)
+            }),
+        };
+        let (answer, answer_finish) = match answer {
+            Ok((answer, finish)) => (plugins::on_answer(question, answer), finish),
+            Err(e) => {
+                self.emit_error(round, "answer", &self.answerer.name, &e);
+                return None;
+            }
+        };
+        for flag in self.guard(round, "answer", &answer).await? {
+            if !guard_flags.contains(&flag) {
+                guard_flags.push(flag);
+            }
+        }
+        self.emit(json!({
+            "event": "answer",
+            "round": round,
+            "question": question,
+            "model": self.answerer.model,
+            "content": answer,
+            "finish": answer_finish,
+        }));
+        if show {
+            self.print_answer(&answer);
+        }
+        if let Some(finish) = answer_finish {
+            ui::notice(ui::warning(tr!("finish.answer", finish.label())));
+        }
+
+        let mut turn = ConversationTurn::new(round, question.to_string(), answer, &self.answerer.model);
+        turn.guard_flags = guard_flags;
+        turn.answer_finish = answer_finish;
+        turn.review_strictness = Some(self.strictness);
+        Some(turn)
+    }
+
+    async fn review(&self, turn: &mut ConversationTurn) {
+        let round = turn.round;
+        let messages = review_messages(&self.profile, &turn.user_question, &turn.moonshot_answer, false, &[], self.strictness);
+        match complete(&self.client, &self.reviewer, messages).await {
+            Ok(mut review) => {
+                turn.review_reasoning = review.reasoning.take();
+                turn.review_finish = review.finish;
+                (review.content, turn.plugin_scores) = plugins::on_review(&turn.user_question, &turn.moonshot_answer, review.content);
+                turn.verdict = verdict::parse(&review.content);
+                turn.scores = verdict::parse_scores(&review.content);
+                let hijacked = shield::hijacked(&turn.moonshot_answer, &review.content, turn.verdict.is_some());
+                self.emit_usage(round, "review", &self.reviewer, &review);
+                self.emit(json!({
+                    "event": "review",
+                    "round": round,
+                    "model": self.reviewer.model,
+                    "content": review.content,
+                    "verdict": turn.verdict,
+                    "scores": turn.scores,
+                    "finish": turn.review_finish,
+                    "hijacked": hijacked,
+                }));
+                if !self.json {
+                    println!("\n{}", ui::reviewer(tr!("round.review_header", self.reviewer.name)));
+                    println!("{}", highlight::terminal_markdown(&review.content));
+                }
+                if let Some(finish) = turn.review_finish {
+                    ui::notice(ui::warning(tr!("finish.review", finish.label())));
+                }
+                if let Some(scores) = &turn.scores {
+                    ui::notice(ui::status(scores.summary()));
+                }
+                if let Some(scores) = plugins::score_line(&turn.plugin_scores) {
+                    ui::notice(ui::status(scores));
+                }
+                match turn.verdict {
+                    Some(severity) => ui::notice(ui::status(tr!("verdict.line", severity.label()))),
+                    None => ui::notice(ui::warning(tr!("verdict.missing"))),
+                }
+                if let Some(phrase) = &hijacked {
+                    ui::notice(ui::warning(tr!("shield.hijacked", phrase)));
+                }
+                turn.review_summary = self.summarize(round, &review.content).await;
+                turn.deepseek_review = Some(review.content);
+                turn.review_model = self.reviewer.model.clone();
+                stats::record(turn);
+            }
+            Err(e) => {
+                self.emit_error(round, "review", &self.reviewer.name, &e);
+                turn.review_error = Some(e.to_string());
+            }
+        }
+    }
+
+    // The answerer and the reviewer, e.g. for readiness probes
+    pub fn roles(&self) -> [&AiConfig; 2] {
+        [&self.answerer, &self.reviewer]
+    }
+
+    // Save like the REPL's /save, with this pipeline's models in the header
+    pub fn save(&self, session: &ConversationSession) -> Result<PathBuf> {
+        save_conversation_session(session, &self.answerer, &self.reviewer)
+    }
+
+    // The session as saved markdown, with this pipeline's models in the header
+    pub fn render(&self, session: &ConversationSession) -> String {
+        render_session_markdown(session, &self.answerer, &self.reviewer)
+    }
+
+    // Save under a chosen file name in conversations/, replacing an earlier save there
+    pub fn save_as(&self, session: &ConversationSession, filename: &str) -> Result<PathBuf> {
+        save_session_as(session, &self.answerer, &self.reviewer, filename)
+    }
+}
+
+// Pull `--json` out of the argument list
+fn take_json_flag(args: &[String]) -> (bool, Vec<String>) {
+    let json = args.iter().any(|a| a == "--json");
+    (json, args.iter().filter(|a| *a != "--json").cloned().collect())
+}
+
+// Pull `--csv FILE` out of the argument list
+fn take_csv_flag(args: &[String]) -> Result<(Option<PathBuf>, Vec<String>)> {
+    let mut csv = None;
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--csv" {
+            csv = Some(PathBuf::from(iter.next().context("--csv needs a file path")?));
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    Ok((csv, rest))
+}
+
+// `batch --csv`: one row per question, for evaluation spreadsheets
+const RESULTS_HEADER: &str = "round,question,answer_model,review_model,status,verdict,accuracy,completeness,clarity,code,overall,latency_ms,prompt_tokens,completion_tokens,estimated_cost\n";
+
+// Tokens a round used over every model it called, and their cost when all of
+// those models have a price
+fn round_usage(requests: &[UsageRecord], settings: &Settings) -> (UsageRow, Option<f64>) {
+    let mut used = UsageRow::default();
+    for request in requests {
+        used.add(request);
+    }
+    (used, usage::records_cost(requests, &settings.usage))
+}
+
+fn result_row(round: usize, question: &str, turn: Option<&ConversationTurn>, latency_ms: u128, used: &UsageRow, cost: Option<f64>) -> String {
+    let status = match turn {
+        None => "answer_failed",
+        Some(turn) if turn.review_error.is_some() => "review_failed",
+        Some(turn) if turn.verdict.is_none() => "no_verdict",
+        Some(_) => "ok",
+    };
+    let scores = turn.and_then(|turn| turn.scores);
+    let grade = |grade: Option<u8>| grade.map(|g| g.to_string()).unwrap_or_default();
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+        round,
+        usage::csv_field(question),
+        usage::csv_field(turn.map_or("", |turn| turn.answer_model.as_str())),
+        usage::csv_field(turn.map_or("", |turn| turn.review_model.as_str())),
+        status,
+        turn.and_then(|turn| turn.verdict).map(|severity| severity.keyword()).unwrap_or_default(),
+        grade(scores.map(|s| s.accuracy)),
+        grade(scores.map(|s| s.completeness)),
+        grade(scores.map(|s| s.clarity)),
+        grade(scores.and_then(|s| s.code)),
+        scores.map(|s| format!("{:.2}", s.overall())).unwrap_or_default(),
+        latency_ms,
+        used.prompt_tokens,
+        used.completion_tokens,
+        cost.map(|c| format!("{:.6}", c)).unwrap_or_default()
+    )
+}
+
+// Input piped into `ask`, as a fenced block to append to the question
+fn piped_input(budget: u32) -> Result<Option<String>> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(None);
+    }
+    let mut bytes = Vec::new();
+    stdin.read_to_end(&mut bytes).context("Failed to read piped input")?;
+    let text = String::from_utf8_lossy(&bytes);
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let (content, clipped) = references::clip_middle(text.trim_end(), budget);
+    let note = if clipped { ", middle left out" } else { "" };
+    let shown_note = if clipped { tr!("stdin.clipped_note") } else { "" };
+    ui::notice(ui::status(tr!("stdin.read", text.lines().count(), shown_note)));
+    let fence = references::fence_for(&content);
+    Ok(Some(format!("\n\nInput (piped in{}):\n{}text\n{}\n{}", note, fence, content, fence)))
+}
+
+// Questions from a file, one per line, skipping blank lines and # comments
+pub fn read_questions(path: &Path) -> Result<Vec<String>> {
+    let text = std::fs::read_to_string(path).context(format!("Failed to read questions from {:?}", path))?;
+    let questions: Vec<String> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    if questions.is_empty() {
+        anyhow::bail!("No questions found in {:?}", path);
+    }
+    Ok(questions)
+}
+
+// `ask --file PATH [--lines START-END]`
+struct Selection {
+    path: PathBuf,
+    lines: Option<(usize, usize)>,
+}
+
+// Pull `--file PATH` and `--lines START-END` out of the argument list
+fn take_selection(args: &[String]) -> Result<(Option<Selection>, Vec<String>)> {
+    let mut path = None;
+    let mut lines = None;
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--file" => path = Some(PathBuf::from(iter.next().context("--file needs a file path")?)),
+            "--lines" => lines = Some(references::parse_line_range(iter.next().context("--lines needs a range, e.g. 40-90")?)?),
+            _ => rest.push(arg.clone()),
+        }
+    }
+    match path {
+        Some(path) => Ok((Some(Selection { path, lines }), rest)),
+        None if lines.is_some() => anyhow::bail!("--lines needs --file"),
+        None => Ok((None, rest)),
+    }
+}
+
+pub async fn run_ask(args: &[String], settings: &Settings, profile: &Profile) -> Result<Severity> {
+    let (json, rest) = take_json_flag(args);
+    let (selection, rest) = take_selection(&rest)?;
+    let mut question = rest.join(" ");
+    if question.trim().is_empty() {
+        anyhow::bail!("Usage: aivsai ask [--json] [--file PATH [--lines START-END]] \"question\" (input piped to stdin is appended)");
+    }
+    // The selection goes first, so piped input still ends up after it
+    if let Some(selection) = &selection {
+        question.push_str(&references::excerpt(&selection.path, selection.lines, settings.references.max_tokens)?);
+    }
+    if let Some(input) = piped_input(settings.references.stdin_max_tokens)? {
+        question.push_str(&input);
+    }
+
+    let pipeline = Pipeline::new(settings, profile, json)?;
+    let session = ConversationSession::new();
+    events::follow(&session.start_time);
+    let turn = pipeline.run_round(&session.start_time, 1, question.trim()).await.context("No answer was produced")?;
+    if let Some(error) = &turn.review_error {
+        anyhow::bail!("Answer produced but the review failed: {}", error);
+    }
+    turn.verdict.context("The review did not end with a VERDICT line")
+}
+
+// A span for one stage of a round, exported when `[telemetry]` is set up
+fn stage_span(stage: &'static str, round: usize, role: &AiConfig) -> tracing::Span {
+    tracing::info_span!("stage", otel.name = stage, round, provider = %role.name, model = %role.model, otel.status_code = tracing::field::Empty)
+}
+
+async fn optional<T>(future: Option<impl std::future::Future<Output = T>>) -> Option<T> {
+    match future {
+        Some(future) => Some(future.await),
+        None => None,
+    }
+}
+
+// How many rounds of a batch may fail (answer or review errors) before it
+// stops: `--error-budget 5` rounds, or `--error-budget 10%` of the questions
+enum ErrorBudget {
+    Rounds(usize),
+    Percent(f64),
+}
+
+impl ErrorBudget {
+    fn parse(value: &str) -> Result<Self> {
+        let parsed = match value.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f64>().ok().filter(|p| (0.0..=100.0).contains(p)).map(ErrorBudget::Percent),
+            None => value.trim().parse::<usize>().ok().map(ErrorBudget::Rounds),
+        };
+        parsed.context(format!("--error-budget should be a number of rounds or a percentage such as 10%, got {:?}", value))
+    }
+
+    fn allowed(&self, questions: usize) -> usize {
+        match self {
+            ErrorBudget::Rounds(rounds) => *rounds,
+            ErrorBudget::Percent(percent) => (questions as f64 * percent / 100.0).floor() as usize,
+        }
+    }
+}
+
+struct BatchOptions {
+    budget: Option<ErrorBudget>,
+    resume: bool,
+}
+
+// Pull `--error-budget N|P%` and `--resume` out of the argument list
+fn take_batch_options(args: &[String]) -> Result<(BatchOptions, Vec<String>)> {
+    let mut options = BatchOptions { budget: None, resume: false };
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--error-budget" => options.budget = Some(ErrorBudget::parse(iter.next().context("--error-budget needs a count or a percentage")?)?),
+            "--resume" => options.resume = true,
+            _ => rest.push(arg.clone()),
+        }
+    }
+    Ok((options, rest))
+}
+
+// A batch's progress, rewritten after every round so `--resume` can pick up
+// after a crash or a spent error budget: the questions (a changed file can't be
+// resumed) and the session so far. It sits next to the questions file,
+// encrypted like the archive, until every question has been reviewed.
+#[derive(Deserialize)]
+struct Checkpoint {
+    questions: Vec<String>,
+    session: ConversationSession,
+}
+
+fn checkpoint_path(questions: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.checkpoint.json", questions.display()))
+}
+
+// The checkpoint as written, and its copy under the other encryption setting
+fn checkpoint_files(path: &Path) -> [PathBuf; 2] {
+    [path.to_path_buf(), PathBuf::from(format!("{}.{}", path.display(), crypto::EXTENSION))]
+}
+
+fn load_checkpoint(path: &Path) -> Result<Option<Checkpoint>> {
+    let Some(found) = checkpoint_files(path).into_iter().find(|file| file.exists()) else {
+        return Ok(None);
+    };
+    let text = archive::read_file(&found)?;
+    serde_json::from_str(&text).map(Some).context(format!("Invalid batch checkpoint {:?}", found))
+}
+
+fn write_checkpoint(path: &Path, questions: &[String], session: &ConversationSession) -> Result<()> {
+    let checkpoint = json!({ "questions": questions, "session": session });
+    archive::write_file(path, &checkpoint.to_string()).context("Failed to write the batch checkpoint")?;
+    Ok(())
+}
+
+// A CSV row for a round reviewed in an earlier run, from what it recorded
+fn resumed_row(turn: &ConversationTurn, question: &str, settings: &Settings) -> String {
+    let (requests, latency_ms) = turn.meta.as_ref().map(|meta| (meta.requests.as_slice(), meta.duration_ms)).unwrap_or_default();
+    let (used, cost) = round_usage(requests, settings);
+    result_row(turn.round, question, Some(turn), latency_ms as u128, &used, cost)
+}
+
+// Worst verdict across the batch; any round without one fails the run
+pub async fn run_batch(args: &[String], settings: &Settings, profile: &Profile) -> Result<Severity> {
+    let (json, rest) = take_json_flag(args);
+    let (csv_path, rest) = take_csv_flag(&rest)?;
+    let (options, rest) = take_batch_options(&rest)?;
+    let [path] = rest.as_slice() else {
+        anyhow::bail!("Usage: aivsai batch [--json] [--csv RESULTS] [--error-budget N|P%] [--resume] FILE (one question per line, # for comments)");
+    };
+    let path = Path::new(path);
+    let questions = read_questions(path)?;
+    let checkpoint = checkpoint_path(path);
+
+    let pipeline = Pipeline::new(settings, profile, json)?;
+    let mut session = ConversationSession::new();
+    if options.resume {
+        let resumed = load_checkpoint(&checkpoint)?.context(format!("No checkpoint for {:?} to resume from", path))?;
+        if resumed.questions != questions {
+            anyhow::bail!("{:?} changed since the checkpoint was written; run without --resume to start over", path);
+        }
+        session = resumed.session;
+        // Rounds that failed are asked again
+        session.turns.retain(|turn| turn.deepseek_review.is_some());
+        ui::notice(ui::status(tr!("batch.resuming", session.len(), questions.len())));
+    }
+    events::follow(&session.start_time);
+    let pending: Vec<usize> = (1..=questions.len()).filter(|&round| session.turn(round).is_none()).collect();
+    let allowed = options.budget.as_ref().map(|budget| budget.allowed(questions.len()));
+
+    let mut rows = BTreeMap::new();
+    let mut errors = 0;
+    let mut exhausted = false;
+    // Pipelined: question N+1 is answered while question N is reviewed, as far
+    // as the providers' `max_concurrent` allows
+    let (stages, session_start) = (&pipeline, session.start_time.clone());
+    let mut answered: Option<(usize, Answered)> = None;
+    for step in 0..=pending.len() {
+        let reviewing = answered.take();
+        if let Some((round, _)) = &reviewing {
+            ui::notice(ui::heading(format!("\n[{}/{}] {}", round, questions.len(), questions[*round - 1])));
+        }
+        let next = pending.get(step).map(|&round| {
+            let question = &questions[round - 1];
+            async move { (round, stages.answer_stage(round, stages.fresh_context(), question, None, false).await) }
+        });
+        let review = reviewing.map(|(round, answered)| {
+            let session_start = &session_start;
+            async move {
+                let started = answered.started;
+                (round, stages.review_stage(session_start, answered).await, started)
+            }
+        });
+        let (next, reviewed) = tokio::join!(optional(next), optional(review));
+        answered = next;
+        let Some((round, (turn, requests), started)) = reviewed else {
+            continue;
+        };
+        let (used, cost) = round_usage(&requests, settings);
+        rows.insert(round, result_row(round, &questions[round - 1], turn.as_ref(), started.elapsed().as_millis(), &used, cost));
+        if turn.as_ref().is_none_or(|turn| turn.review_error.is_some()) {
+            errors += 1;
+        }
+        if let Some(turn) = turn {
+            session.add_turn(turn);
+        }
+        if let Err(e) = write_checkpoint(&checkpoint, &questions, &session) {
+            ui::notice(ui::warning(format!("{:#}", e)));
+        }
+        if allowed.is_some_and(|allowed| errors > allowed) {
+            exhausted = true;
+            break;
+        }
+    }
+
+    session.turns.sort_by_key(|turn| turn.round);
+    for turn in &session.turns {
+        rows.entry(turn.round).or_insert_with(|| resumed_row(turn, &questions[turn.round - 1], settings));
+    }
+    if !session.is_empty() {
+        let filepath = pipeline.save(&session)?;
+        pipeline.emit(json!({ "event": "saved", "path": filepath.display().to_string() }));
+        ui::notice(ui::success(tr!("batch.saved", filepath.display())));
+    }
+    if let Some(path) = csv_path {
+        let results: String = std::iter::once(RESULTS_HEADER.to_string()).chain(rows.into_values()).collect();
+        // The byte order mark makes Excel read the file as UTF-8
+        std::fs::write(&path, format!("\u{feff}{}", results)).context(format!("Failed to write results to {:?}", path))?;
+        ui::notice(ui::success(tr!("batch.results_written", path.display())));
+    }
+    let reviewed = session.turns.iter().filter(|turn| turn.deepseek_review.is_some()).count();
+    if reviewed == questions.len() {
+        for file in checkpoint_files(&checkpoint) {
+            let _ = std::fs::remove_file(file);
+        }
+    } else {
+        ui::notice(ui::status(tr!("batch.checkpoint_kept", archive::written_path(&checkpoint).display())));
+    }
+
+    if exhausted {
+        anyhow::bail!("Stopped after {} failed round(s), over the error budget of {}; rerun with --resume to continue", errors, allowed.unwrap_or_default());
+    }
+    let failures = questions.len() - session.turns.iter().filter(|turn| turn.verdict.is_some()).count();
+    if failures > 0 {
+        anyhow::bail!("{} of {} question(s) failed or got no verdict", failures, questions.len());
+    }
+    Ok(session.turns.iter().filter_map(|turn| turn.verdict).max().unwrap_or(Severity::Clean))
+}
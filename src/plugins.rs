@@ -0,0 +1,238 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+#[cfg(feature = "plugins")]
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::config::PluginSettings;
+use crate::i18n::tr;
+use crate::session::ConversationSession;
+use crate::ui;
+
+// WebAssembly plugins (`plugins` feature) hooked into each round:
+//
+//   on_question       {"question"}                      → {"question"?, "block"?: reason}
+//   on_answer         {"question", "answer"}            → {"answer"?}
+//   on_review         {"question", "answer", "review"}  → {"review"?, "score"?: number, "note"?}
+//   transform_export  {"markdown", "session"}           → {"markdown"?, "files"?: [{"name", "content"}]}
+//
+// A plugin is a module exporting `memory`, `alloc(len: i32) -> i32` and any of
+// the hooks as `hook(ptr: i32, len: i32) -> i64`. The input JSON is written to
+// memory from `alloc`; the hook returns the output JSON as `ptr << 32 | len`, or
+// 0 to leave things as they are. Plugins get no imports (no files, network or
+// clock) and each call runs on a fuel budget, so a broken one can't hang a round.
+// Plugins run in `[plugins] paths` order, each seeing the previous one's output.
+
+// A number given to a review by an `on_review` hook
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PluginScore {
+    pub plugin: String,
+    pub score: f64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+#[cfg(feature = "plugins")]
+struct Plugin {
+    store: wasmtime::Store<()>,
+    instance: wasmtime::Instance,
+    memory: wasmtime::Memory,
+    alloc: wasmtime::TypedFunc<i32, i32>,
+    fuel: u64,
+}
+
+// Plugin name (file stem) and instance; instances live for the whole run
+#[cfg(feature = "plugins")]
+static PLUGINS: OnceLock<Vec<(String, Mutex<Plugin>)>> = OnceLock::new();
+
+#[cfg(feature = "plugins")]
+fn load(engine: &wasmtime::Engine, path: &str, fuel: u64) -> Result<(String, Plugin)> {
+    let module = wasmtime::Module::from_file(engine, path).context(format!("Failed to load plugin {:?}", path))?;
+    let mut store = wasmtime::Store::new(engine, ());
+    let instance = wasmtime::Instance::new(&mut store, &module, &[]).context(format!("Failed to start plugin {:?} (plugins get no imports)", path))?;
+    let memory = instance.get_memory(&mut store, "memory").context(format!("Plugin {:?} doesn't export `memory`", path))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "alloc")
+        .context(format!("Plugin {:?} doesn't export `alloc(len: i32) -> i32`", path))?;
+    let name = std::path::Path::new(path).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| path.to_string());
+    Ok((
+        name,
+        Plugin {
+            store,
+            instance,
+            memory,
+            alloc,
+            fuel,
+        },
+    ))
+}
+
+#[cfg(feature = "plugins")]
+pub fn init(settings: &PluginSettings) -> Result<()> {
+    if settings.paths.is_empty() {
+        return Ok(());
+    }
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    let engine = wasmtime::Engine::new(&config)?;
+    let mut plugins = Vec::new();
+    for path in &settings.paths {
+        let (name, plugin) = load(&engine, path, settings.fuel)?;
+        plugins.push((name, Mutex::new(plugin)));
+    }
+    let _ = PLUGINS.set(plugins);
+    Ok(())
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn init(settings: &PluginSettings) -> Result<()> {
+    if !settings.paths.is_empty() {
+        anyhow::bail!("[plugins] needs a build with `--features plugins`");
+    }
+    Ok(())
+}
+
+#[cfg(feature = "plugins")]
+fn names() -> Vec<String> {
+    PLUGINS.get().map(|plugins| plugins.iter().map(|(name, _)| name.clone()).collect()).unwrap_or_default()
+}
+
+#[cfg(not(feature = "plugins"))]
+fn names() -> Vec<String> {
+    Vec::new()
+}
+
+// Run `hook` of the plugin at `index`; None when it doesn't export the hook or returned 0
+#[cfg(feature = "plugins")]
+fn call(index: usize, hook: &str, input: &Value) -> Result<Option<Value>> {
+    let Some((name, plugin)) = PLUGINS.get().and_then(|plugins| plugins.get(index)) else {
+        return Ok(None);
+    };
+    let mut guard = plugin.lock().unwrap_or_else(PoisonError::into_inner);
+    let plugin = &mut *guard;
+    let Ok(function) = plugin.instance.get_typed_func::<(i32, i32), i64>(&mut plugin.store, hook) else {
+        return Ok(None);
+    };
+    plugin.store.set_fuel(plugin.fuel)?;
+
+    let bytes = serde_json::to_vec(input)?;
+    let len = i32::try_from(bytes.len()).context("Input too large for a plugin")?;
+    let ptr = plugin.alloc.call(&mut plugin.store, len)?;
+    plugin.memory.write(&mut plugin.store, ptr as u32 as usize, &bytes)?;
+    let packed = function.call(&mut plugin.store, (ptr, len))? as u64;
+    if packed == 0 {
+        return Ok(None);
+    }
+    let mut output = vec![0; (packed & 0xffff_ffff) as usize];
+    plugin.memory.read(&plugin.store, (packed >> 32) as usize, &mut output)?;
+    let output = serde_json::from_slice(&output).context(format!("{} returned invalid JSON from {}", name, hook))?;
+    Ok(Some(output))
+}
+
+#[cfg(not(feature = "plugins"))]
+fn call(_index: usize, _hook: &str, _input: &Value) -> Result<Option<Value>> {
+    Ok(None)
+}
+
+fn text(reply: &Value, key: &str) -> Option<String> {
+    reply.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+// Plugins that fail are skipped with a warning, except in `on_question`
+fn warn(name: &str, hook: &str, error: &anyhow::Error) {
+    ui::notice(ui::warning(tr!("plugins.failed", name, hook, format!("{:#}", error))));
+}
+
+// The question to send, or an error when a plugin blocks it. A failing filter
+// blocks too, so nothing it was meant to stop slips through.
+pub fn on_question(question: String) -> Result<String> {
+    let mut question = question;
+    for (index, name) in names().iter().enumerate() {
+        let Some(reply) = call(index, "on_question", &json!({ "question": question })).context(format!("Plugin {} failed", name))? else {
+            continue;
+        };
+        if let Some(reason) = text(&reply, "block") {
+            anyhow::bail!("{}: {}", name, reason);
+        }
+        if let Some(rewritten) = text(&reply, "question") {
+            question = rewritten;
+        }
+    }
+    Ok(question)
+}
+
+pub fn on_answer(question: &str, answer: String) -> String {
+    let mut answer = answer;
+    for (index, name) in names().iter().enumerate() {
+        match call(index, "on_answer", &json!({ "question": question, "answer": answer })) {
+            Ok(Some(reply)) => answer = text(&reply, "answer").unwrap_or(answer),
+            Ok(None) => {}
+            Err(e) => warn(name, "on_answer", &e),
+        }
+    }
+    answer
+}
+
+// The review after the plugins, and the scores they gave it
+pub fn on_review(question: &str, answer: &str, review: String) -> (String, Vec<PluginScore>) {
+    let mut review = review;
+    let mut scores = Vec::new();
+    for (index, name) in names().iter().enumerate() {
+        match call(index, "on_review", &json!({ "question": question, "answer": answer, "review": review })) {
+            Ok(Some(reply)) => {
+                if let Some(score) = reply.get("score").and_then(Value::as_f64) {
+                    scores.push(PluginScore {
+                        plugin: name.clone(),
+                        score,
+                        note: text(&reply, "note").unwrap_or_default(),
+                    });
+                }
+                review = text(&reply, "review").unwrap_or(review);
+            }
+            Ok(None) => {}
+            Err(e) => warn(name, "on_review", &e),
+        }
+    }
+    (review, scores)
+}
+
+// The session markdown after the plugins, plus extra files (name, content) they
+// want written next to it
+pub fn transform_export(session: &ConversationSession, markdown: String) -> (String, Vec<(String, String)>) {
+    let names = names();
+    if names.is_empty() {
+        return (markdown, Vec::new());
+    }
+    let session = serde_json::to_value(session).unwrap_or_default();
+    let mut markdown = markdown;
+    let mut files = Vec::new();
+    for (index, name) in names.iter().enumerate() {
+        match call(index, "transform_export", &json!({ "markdown": markdown, "session": session })) {
+            Ok(Some(reply)) => {
+                markdown = text(&reply, "markdown").unwrap_or(markdown);
+                for file in reply.get("files").and_then(Value::as_array).into_iter().flatten() {
+                    match (text(file, "name"), text(file, "content")) {
+                        (Some(file_name), Some(content)) => files.push((file_name, content)),
+                        _ => warn(name, "transform_export", &anyhow::anyhow!("files entries need a name and content")),
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn(name, "transform_export", &e),
+        }
+    }
+    (markdown, files)
+}
+
+// "lint 0.8 (two unsupported claims) · style 0.95" for a turn's scores
+pub fn score_line(scores: &[PluginScore]) -> Option<String> {
+    if scores.is_empty() {
+        return None;
+    }
+    let line = scores
+        .iter()
+        .map(|s| if s.note.is_empty() { format!("{} {}", s.plugin, s.score) } else { format!("{} {} ({})", s.plugin, s.score, s.note) })
+        .collect::<Vec<_>>()
+        .join(" · ");
+    Some(tr!("plugins.scores", line))
+}
@@ -0,0 +1,77 @@
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::bot::hex;
+use crate::config::ArchiveSettings;
+use crate::session::ConversationTurn;
+
+// `[archive] provenance = true`: every round in markdown and HTML exports (and
+// `publish` pages) carries an HTML comment saying which model produced which
+// text, invisible when rendered:
+//
+//   <!-- aivsai-provenance {"round":1,"asked":"2024-06-01 12:00:03",
+//        "answer":{"model":"kimi-k2-0905-preview","sha256":"…"},
+//        "review":{"model":"deepseek-chat","sha256":"…"},
+//        "requests":[{"time":"…","provider":"Kimi","model":"…","endpoint":"3f9a…","temperature":0.7,…}]} -->
+//
+// `sha256` is the hash of the answer / review as the model wrote it (as kept in
+// the session's .json), so a consumer can check it wasn't changed; `endpoint` is a hash of the
+// provider's base URL, which tells endpoints apart without revealing them.
+// Requests are listed for rounds that recorded them.
+
+const MARKER: &str = "aivsai-provenance";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn init(settings: &ArchiveSettings) {
+    ENABLED.store(settings.provenance, Ordering::Relaxed);
+}
+
+fn sha256(text: &str) -> String {
+    hex(&Sha256::digest(text.as_bytes()))
+}
+
+// Short hash of a provider base URL, recorded with each request
+pub fn endpoint_hash(base_url: &str) -> String {
+    sha256(base_url.trim_end_matches('/'))[..16].to_string()
+}
+
+fn record(turn: &ConversationTurn) -> Value {
+    let mut record = json!({
+        "round": turn.round,
+        "asked": turn.timestamp,
+        "answer": { "model": turn.answer_model, "sha256": sha256(&turn.moonshot_answer) },
+    });
+    if let Some(review) = &turn.deepseek_review {
+        record["review"] = json!({ "model": turn.review_model, "sha256": sha256(review) });
+    }
+    // Not the chat user or key name: exports may be published
+    if let Some(meta) = &turn.meta {
+        record["requests"] = meta
+            .requests
+            .iter()
+            .map(|request| {
+                json!({
+                    "time": request.timestamp,
+                    "provider": request.provider,
+                    "model": request.model,
+                    "endpoint": request.endpoint,
+                    "temperature": request.temperature,
+                    "max_tokens": request.max_tokens,
+                    "ok": request.ok,
+                })
+            })
+            .collect();
+    }
+    record
+}
+
+// The comment for `turn`, on a line of its own; empty when disabled
+pub fn comment(turn: &ConversationTurn) -> String {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return String::new();
+    }
+    // `--` may not appear inside an HTML comment
+    format!("<!-- {} {} -->\n", MARKER, record(turn).to_string().replace("--", "-\\u002d"))
+}
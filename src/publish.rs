@@ -0,0 +1,321 @@
+use anyhow::{Context, Result};
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::archive::{conversations_dir, saved_sessions};
+use crate::crypto;
+use crate::highlight;
+use crate::i18n::tr;
+use crate::index::{self, IndexEntry};
+use crate::provenance;
+use crate::session::{ConversationSession, ConversationTurn};
+use crate::{format_answer_for_export, format_review_for_export, ui};
+
+// `aivsai publish` renders the saved sessions into a static site:
+//
+//   site/index.html            sessions table, tag list, search box
+//   site/sessions/NAME.html    one page per session
+//   site/tags/TAG.html         sessions with that tag
+//   site/search.json           documents for the search box (lunr-style: id, title, body, ...)
+//
+// Pages use relative links only, so the directory can be served by any static
+// web server (search needs HTTP; browsers block fetch() on file:// pages).
+
+const STYLE: &str = r#"body { font: 16px/1.6 system-ui, sans-serif; max-width: 56rem; margin: 2rem auto; padding: 0 1rem; color: #222; }
+a { color: #005f87; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: .3rem .5rem; border-bottom: 1px solid #ddd; vertical-align: top; }
+pre { background: #f5f5f5; padding: .7rem; overflow-x: auto; }
+code { font-family: ui-monospace, monospace; font-size: .9em; }
+blockquote { margin: 0; padding: 0 1rem; border-left: 4px solid #ccc; }
+.round { margin: 2rem 0; }
+.starred h2 { color: #af5f00; }
+.answer blockquote { border-color: #005f87; }
+.review blockquote { border-color: #875f00; }
+.verdict { display: inline-block; padding: 0 .5rem; border-radius: 4px; background: #eee; }
+.verdict-clean { background: #d7f5d7; }
+.verdict-minor { background: #fdf0c8; }
+.verdict-errors { background: #f9d4d4; }
+.tag { display: inline-block; margin-right: .4rem; }
+#search { width: 100%; padding: .4rem; font-size: 1rem; }
+"#;
+
+const SEARCH_SCRIPT: &str = r#"<script>
+const box = document.getElementById("search"), results = document.getElementById("results");
+let docs = [];
+fetch("search.json").then(r => r.json()).then(d => { docs = d; });
+box.addEventListener("input", () => {
+  const terms = box.value.toLowerCase().split(/\s+/).filter(t => t);
+  results.replaceChildren();
+  if (!terms.length) return;
+  const score = doc => terms.every(t => (doc.title + " " + doc.tags.join(" ") + " " + doc.body).toLowerCase().includes(t))
+    ? terms.reduce((s, t) => s + (doc.title.toLowerCase().includes(t) ? 10 : 0) + (doc.tags.join(" ").toLowerCase().includes(t) ? 5 : 0) + 1, 0) : 0;
+  docs.map(doc => [score(doc), doc]).filter(([s]) => s > 0).sort((a, b) => b[0] - a[0]).slice(0, 20).forEach(([, doc]) => {
+    const item = document.createElement("li"), link = document.createElement("a");
+    link.href = doc.url; link.textContent = doc.title;
+    item.append(link, " — " + doc.date);
+    results.append(item);
+  });
+});
+</script>"#;
+
+struct PublishArgs {
+    out: PathBuf,
+}
+
+fn parse_args(args: &[String]) -> Result<PublishArgs> {
+    let mut parsed = PublishArgs { out: PathBuf::from("site") };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--out" => parsed.out = PathBuf::from(iter.next().context("--out needs a directory")?),
+            other => anyhow::bail!("Unknown option for publish: {} (expected --out DIR)", other),
+        }
+    }
+    Ok(parsed)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Markdown to HTML. Raw HTML in answers is shown as text, never interpreted.
+pub fn markdown_html(markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        other => other,
+    });
+    // Fenced code blocks in a known language become highlighted <pre>s
+    let mut events: Vec<Event> = Vec::new();
+    let mut block: Option<(String, Vec<Event>)> = None;
+    for event in parser {
+        match (block.take(), event) {
+            (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(language)))) => {
+                let start = Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(language.clone())));
+                block = Some((language.to_string(), vec![start]));
+            }
+            (Some((language, mut buffered)), Event::End(TagEnd::CodeBlock)) => {
+                let code: String = buffered
+                    .iter()
+                    .filter_map(|event| match event {
+                        Event::Text(text) => Some(&**text),
+                        _ => None,
+                    })
+                    .collect();
+                match highlight::html(&code, &language) {
+                    Some(highlighted) => events.push(Event::Html(highlighted.into())),
+                    None => {
+                        buffered.push(Event::End(TagEnd::CodeBlock));
+                        events.extend(buffered);
+                    }
+                }
+            }
+            (Some((language, mut buffered)), event) => {
+                buffered.push(event);
+                block = Some((language, buffered));
+            }
+            (None, event) => events.push(event),
+        }
+    }
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+// `2025-03-01_14-30-25_question.json(.age)` → `2025-03-01_14-30-25_question`
+fn page_name(file: &str) -> &str {
+    file.strip_suffix(".age").unwrap_or(file).strip_suffix(".json").unwrap_or(file)
+}
+
+fn tag_slug(tag: &str) -> String {
+    tag.to_lowercase().chars().map(|c| if c.is_alphanumeric() { c } else { '-' }).collect()
+}
+
+fn page(title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"{}style.css\">\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape(title),
+        root,
+        body
+    )
+}
+
+fn title_of(entry: &IndexEntry) -> &str {
+    if entry.title.is_empty() {
+        &entry.file
+    } else {
+        &entry.title
+    }
+}
+
+fn tag_links(tags: &[String], root: &str) -> String {
+    tags.iter()
+        .map(|tag| format!("<a class=\"tag\" href=\"{}tags/{}.html\">#{}</a>", root, tag_slug(tag), escape(tag)))
+        .collect()
+}
+
+// Sessions table shared by the index and tag pages
+fn sessions_table(entries: &[&IndexEntry], root: &str) -> String {
+    let mut rows = format!(
+        "<table>\n<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>\n",
+        tr!("index.date"),
+        tr!("index.session"),
+        tr!("index.tags"),
+        tr!("index.models"),
+        tr!("index.rounds")
+    );
+    for entry in entries {
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td><a href=\"{}sessions/{}.html\">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&entry.date),
+            root,
+            escape(page_name(&entry.file)),
+            escape(title_of(entry)),
+            tag_links(&entry.tags, root),
+            escape(&entry.models.join(", ")),
+            entry.rounds
+        ));
+    }
+    rows + "</table>"
+}
+
+fn turn_html(turn: &ConversationTurn) -> String {
+    let heading = if turn.starred { tr!("export.round_starred", turn.round) } else { tr!("export.round", turn.round) };
+    let asker = match &turn.translation {
+        Some(check) => tr!("export.user_translate", check.target),
+        None => tr!("export.user").to_string(),
+    };
+    let verdict = turn
+        .verdict
+        .map(|severity| format!(" <span class=\"verdict verdict-{}\">{}</span>", severity.keyword(), escape(severity.label())))
+        .unwrap_or_default();
+    format!(
+        "<section class=\"round{}\" id=\"round-{}\">\n<h2>{}</h2>\n{}<div class=\"question\"><h3>{}</h3>\n{}</div>\n<div class=\"answer\"><h3>🤖 {}</h3>\n{}</div>\n<div class=\"review\"><h3>🔍 {}{}</h3>\n{}</div>\n</section>\n",
+        if turn.starred { " starred" } else { "" },
+        turn.round,
+        escape(&heading),
+        provenance::comment(turn),
+        escape(&asker),
+        markdown_html(&turn.user_question),
+        escape(&turn.answer_model),
+        markdown_html(&format_answer_for_export(turn)),
+        escape(&turn.review_model),
+        verdict,
+        markdown_html(&format_review_for_export(turn))
+    )
+}
+
+// A self-contained page of some rounds, for `/export rounds ... html`: the
+// style is inlined since there is no site around it
+pub fn rounds_page(title: &str, subtitle: &str, turns: &[&ConversationTurn]) -> String {
+    let mut body = format!("<h1>{}</h1>\n<p>{}</p>\n", escape(title), escape(subtitle));
+    for turn in turns {
+        body.push_str(&turn_html(turn));
+    }
+    page(title, "", &body).replacen(
+        "<link rel=\"stylesheet\" href=\"style.css\">",
+        &format!("<style>\n{}</style>", STYLE),
+        1,
+    )
+}
+
+fn session_page(entry: &IndexEntry, session: &ConversationSession) -> String {
+    let mut body = format!(
+        "<p><a href=\"../index.html\">{}</a></p>\n<h1>{}</h1>\n<p>{} · {}</p>\n",
+        tr!("publish.back"),
+        escape(title_of(entry)),
+        escape(&entry.date),
+        tag_links(&entry.tags, "../")
+    );
+    for turn in &session.turns {
+        body.push_str(&turn_html(turn));
+    }
+    page(title_of(entry), "../", &body)
+}
+
+// Plain text of a session for search: questions, answers and reviews
+fn search_body(session: &ConversationSession) -> String {
+    session
+        .turns
+        .iter()
+        .flat_map(|turn| [turn.user_question.as_str(), turn.moonshot_answer.as_str(), turn.deepseek_review.as_deref().unwrap_or_default()])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn write(path: &Path, content: &str) -> Result<()> {
+    std::fs::write(path, content).context(format!("Failed to write {:?}", path))
+}
+
+// `aivsai publish [--out DIR]`
+pub fn run(args: &[String]) -> Result<()> {
+    let args = parse_args(args)?;
+    let dir = conversations_dir()?;
+
+    let files = saved_sessions();
+    let mut sessions = Vec::new();
+    for file in &files {
+        match ConversationSession::load(&dir.join(file)) {
+            Ok(session) => sessions.push((index::entry(file, &session), session)),
+            Err(e) => ui::notice(ui::warning(tr!("index.skipped", file, format!("{:#}", e)))),
+        }
+    }
+    if files.iter().any(|file| crypto::is_encrypted_name(file)) {
+        ui::notice(ui::warning(tr!("publish.unencrypted")));
+    }
+
+    for sub in ["sessions", "tags"] {
+        std::fs::create_dir_all(args.out.join(sub)).context(format!("Failed to create {:?}", args.out.join(sub)))?;
+    }
+    write(&args.out.join("style.css"), STYLE)?;
+
+    let mut documents = Vec::new();
+    let mut by_tag: BTreeMap<&str, Vec<&IndexEntry>> = BTreeMap::new();
+    for (entry, session) in &sessions {
+        let name = page_name(&entry.file);
+        write(&args.out.join("sessions").join(format!("{}.html", name)), &session_page(entry, session))?;
+        documents.push(json!({
+            "id": name,
+            "url": format!("sessions/{}.html", name),
+            "title": title_of(entry),
+            "date": entry.date,
+            "tags": entry.tags,
+            "models": entry.models,
+            "body": search_body(session),
+        }));
+        for tag in &entry.tags {
+            by_tag.entry(tag).or_default().push(entry);
+        }
+    }
+    write(&args.out.join("search.json"), &serde_json::to_string(&documents)?)?;
+
+    for (tag, entries) in &by_tag {
+        let body = format!(
+            "<p><a href=\"../index.html\">{}</a></p>\n<h1>{}</h1>\n{}",
+            tr!("publish.back"),
+            escape(&tr!("publish.tag_heading", tag)),
+            sessions_table(entries, "../")
+        );
+        write(&args.out.join("tags").join(format!("{}.html", tag_slug(tag))), &page(&tr!("publish.tag_heading", tag), "../", &body))?;
+    }
+
+    let all: Vec<&IndexEntry> = sessions.iter().map(|(entry, _)| entry).collect();
+    let tags: String = by_tag
+        .iter()
+        .map(|(tag, entries)| format!("<a class=\"tag\" href=\"tags/{}.html\">#{}</a> ({}) ", tag_slug(tag), escape(tag), entries.len()))
+        .collect();
+    let body = format!(
+        "<h1>{}</h1>\n<input id=\"search\" type=\"search\" placeholder=\"{}\">\n<ul id=\"results\"></ul>\n<p>{}</p>\n{}\n{}",
+        tr!("index.title"),
+        escape(tr!("publish.search")),
+        tags,
+        sessions_table(&all, ""),
+        SEARCH_SCRIPT
+    );
+    write(&args.out.join("index.html"), &page(tr!("index.title"), "", &body))?;
+
+    ui::notice(ui::success(tr!("publish.written", args.out.display(), sessions.len())));
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use crate::config::QuotaSettings;
+use crate::usage;
+
+// Daily token quotas per chat user for `bot` and `bridge`, so one member of a
+// shared deployment can't spend the whole team's budget:
+//
+//   [quota]
+//   daily_tokens = 200000                    # prompt + completion tokens per user and day
+//   users = { "U024BE7LH" = 1000000 }        # overrides, by platform user id
+//
+// Requests made for a user are counted under their id in the usage ledger (see
+// `usage::as_user`); the day is the local calendar day.
+
+// The user's quota for today, if they have one
+fn limit(settings: &QuotaSettings, user: &str) -> Option<u64> {
+    settings.users.get(user).copied().or(settings.daily_tokens)
+}
+
+// The quota and the tokens used, when `user` has used up today's quota
+pub fn exceeded(settings: &QuotaSettings, user: &str) -> Option<(u64, u64)> {
+    let limit = limit(settings, user)?;
+    let used = usage::user_tokens_today(user);
+    (used >= limit).then_some((limit, used))
+}
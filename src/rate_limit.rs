@@ -0,0 +1,136 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+// A classic token bucket: holds up to `capacity` units and refills continuously
+// at `refill_per_sec` units per second.
+struct TokenBucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = limit.max(1) as f64;
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    // How long until `amount` units are available (zero if they already are).
+    // Requests larger than the whole bucket are clamped so they can still proceed.
+    fn wait_for(&self, amount: f64) -> Duration {
+        let amount = amount.min(self.capacity);
+        if self.available >= amount {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((amount - self.available) / self.refill_per_sec)
+        }
+    }
+
+    fn take(&mut self, amount: f64) {
+        self.available -= amount.min(self.capacity);
+    }
+
+    fn drain(&mut self) {
+        self.available = 0.0;
+    }
+}
+
+struct LimiterState {
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+    // Set when the provider itself tells us to back off (HTTP 429)
+    paused_until: Option<Instant>,
+}
+
+// Per-provider limiter enforcing requests/min and tokens/min budgets.
+// Callers `acquire` before each request and wait instead of tripping 429s.
+// Requests in flight at once can be capped as well, with `slot`.
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+    slots: Option<Semaphore>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: Option<u32>, tokens_per_minute: Option<u32>, max_concurrent: Option<usize>) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                requests: requests_per_minute.map(TokenBucket::per_minute),
+                tokens: tokens_per_minute.map(TokenBucket::per_minute),
+                paused_until: None,
+            }),
+            slots: max_concurrent.map(|n| Semaphore::new(n.max(1))),
+        }
+    }
+
+    // Wait for one of the `max_concurrent` slots; hold it until the response
+    // has been read. None when concurrency isn't capped.
+    pub async fn slot(&self) -> Option<SemaphorePermit<'_>> {
+        self.slots.as_ref()?.acquire().await.ok()
+    }
+
+    // Wait until one request costing `estimated_tokens` fits in both budgets, then consume it.
+    // Returns how long we waited in total.
+    pub async fn acquire(&self, estimated_tokens: u32) -> Duration {
+        let mut waited = Duration::ZERO;
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+                let mut wait = state
+                    .paused_until
+                    .map(|until| until.saturating_duration_since(now))
+                    .unwrap_or(Duration::ZERO);
+
+                if let Some(bucket) = state.requests.as_mut() {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait_for(1.0));
+                }
+                if let Some(bucket) = state.tokens.as_mut() {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait_for(estimated_tokens as f64));
+                }
+
+                if wait.is_zero() {
+                    state.paused_until = None;
+                    if let Some(bucket) = state.requests.as_mut() {
+                        bucket.take(1.0);
+                    }
+                    if let Some(bucket) = state.tokens.as_mut() {
+                        bucket.take(estimated_tokens as f64);
+                    }
+                }
+                wait
+            };
+
+            if wait.is_zero() {
+                return waited;
+            }
+            tokio::time::sleep(wait).await;
+            waited += wait;
+        }
+    }
+
+    // Called by the retry layer on a 429: block every caller for `delay` and
+    // empty the request bucket so we ramp back up gradually afterwards.
+    pub fn pause_for(&self, delay: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let until = Instant::now() + delay;
+        state.paused_until = Some(state.paused_until.map_or(until, |current| current.max(until)));
+        if let Some(bucket) = state.requests.as_mut() {
+            bucket.drain();
+        }
+    }
+}
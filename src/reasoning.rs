@@ -0,0 +1,78 @@
+use std::sync::OnceLock;
+
+use crate::config::{ReasoningDisplay, ReviewSettings};
+use crate::i18n::tr;
+use crate::{layout, ui};
+
+// Reasoning traces of reasoning reviewers (deepseek-reasoner). The trace is
+// shown dimmed above the review, folded to a few lines by default, and left out
+// of saved sessions and exports unless `[review] save_reasoning` is on: it is
+// often longer than the answer and the review together.
+
+// Lines of a folded trace
+const FOLDED_LINES: usize = 3;
+
+struct Options {
+    display: ReasoningDisplay,
+    save: bool,
+}
+
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+pub fn init(settings: &ReviewSettings) {
+    let _ = OPTIONS.set(Options {
+        display: settings.reasoning,
+        save: settings.save_reasoning,
+    });
+}
+
+fn options() -> &'static Options {
+    OPTIONS.get_or_init(|| Options {
+        display: ReasoningDisplay::Fold,
+        save: false,
+    })
+}
+
+pub fn saved() -> bool {
+    options().save
+}
+
+// serde `skip_serializing_if` for `ConversationTurn::review_reasoning`
+pub fn skip(reasoning: &Option<String>) -> bool {
+    reasoning.is_none() || !saved()
+}
+
+// The whole trace, dimmed, under its header
+pub fn full_block(reasoning: &str) -> String {
+    let reasoning = reasoning.trim();
+    format!(
+        "{}\n{}",
+        ui::status(tr!("reasoning.header", reasoning.lines().count())),
+        ui::status(layout::wrap_for_terminal(reasoning))
+    )
+}
+
+// Terminal block for a trace as `[review] reasoning` asks; None when hidden
+pub fn terminal_block(reasoning: &str) -> Option<String> {
+    let lines: Vec<&str> = reasoning.trim().lines().collect();
+    match options().display {
+        ReasoningDisplay::Hide => None,
+        ReasoningDisplay::Full => Some(full_block(reasoning)),
+        ReasoningDisplay::Fold if lines.len() <= FOLDED_LINES => Some(full_block(reasoning)),
+        ReasoningDisplay::Fold => Some(format!(
+            "{}\n{}\n{}",
+            ui::status(tr!("reasoning.header", lines.len())),
+            ui::status(layout::wrap_for_terminal(&lines[..FOLDED_LINES].join("\n"))),
+            ui::status(tr!("reasoning.folded", lines.len() - FOLDED_LINES))
+        )),
+    }
+}
+
+// Collapsible block for Markdown exports, inside the review's blockquote
+pub fn export_block(reasoning: &str) -> String {
+    format!(
+        "> <details><summary>{}</summary>\n>\n{}\n>\n> </details>",
+        tr!("reasoning.header", reasoning.trim().lines().count()),
+        layout::prefix_lines(reasoning.trim(), "> ")
+    )
+}
@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+
+use crate::archive::{conversations_dir, saved_sessions};
+use crate::crypto;
+use crate::session::{ConversationSession, ConversationTurn};
+
+// Past-answer recall: reviewed rounds from saved sessions, searched for the
+// question about to be sent. Questions are compared after lowercasing and
+// dropping punctuation; near matches are scored by shared character pairs
+// (Dice coefficient), which works for Chinese as well as English.
+
+// Shorter questions only match exactly; a few shared pairs mean nothing there
+const MIN_FUZZY_CHARS: usize = 12;
+
+pub struct Past {
+    // Session start of the session the round came from
+    pub date: String,
+    pub turn: ConversationTurn,
+    normalized: String,
+    pairs: HashSet<(char, char)>,
+}
+
+pub struct Hit<'a> {
+    pub past: &'a Past,
+    // 1.0 for the same question
+    pub similarity: f64,
+}
+
+pub fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+pub fn pairs(normalized: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = normalized.chars().collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+pub fn dice(a: &HashSet<(char, char)>, b: &HashSet<(char, char)>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[derive(Default)]
+pub struct Archive {
+    past: Vec<Past>,
+}
+
+impl Archive {
+    // Reviewed rounds of every saved session. Encrypted sessions are only read
+    // while encryption is on, so recall never asks for a passphrase.
+    pub fn load() -> Self {
+        let Ok(dir) = conversations_dir() else {
+            return Self::default();
+        };
+        let mut past = Vec::new();
+        for file in saved_sessions() {
+            if crypto::is_encrypted_name(&file) && !crypto::enabled() {
+                continue;
+            }
+            let Ok(session) = ConversationSession::load(&dir.join(&file)) else {
+                continue;
+            };
+            for turn in session.turns.into_iter().filter(|turn| turn.deepseek_review.is_some()) {
+                let normalized = normalize(&turn.user_question);
+                past.push(Past {
+                    date: session.start_time.clone(),
+                    pairs: pairs(&normalized),
+                    normalized,
+                    turn,
+                });
+            }
+        }
+        Self { past }
+    }
+
+    // The most similar earlier question at or above `threshold`; newest wins a tie
+    pub fn find(&self, question: &str, threshold: f64) -> Option<Hit<'_>> {
+        let normalized = normalize(question);
+        if normalized.is_empty() {
+            return None;
+        }
+        let question_pairs = pairs(&normalized);
+        let fuzzy = normalized.chars().count() >= MIN_FUZZY_CHARS;
+        let mut best: Option<Hit> = None;
+        for past in &self.past {
+            let similarity = if past.normalized == normalized {
+                1.0
+            } else if fuzzy {
+                dice(&question_pairs, &past.pairs)
+            } else {
+                continue;
+            };
+            if similarity >= threshold && best.as_ref().is_none_or(|b| similarity > b.similarity) {
+                best = Some(Hit { past, similarity });
+            }
+        }
+        best
+    }
+}
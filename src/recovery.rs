@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::watch;
+
+use crate::archive;
+use crate::i18n::tr;
+
+// Last rendered copy of the unsaved session, written out only if the process
+// dies unexpectedly (panic, SIGTERM/SIGHUP, Ctrl+C while a request is running).
+// This module also owns Ctrl+C handling, see `claim_interrupts`.
+struct Recovery {
+    path: PathBuf,
+    content: Option<String>,
+}
+
+static RECOVERY: Mutex<Option<Recovery>> = Mutex::new(None);
+
+// The signal task is the only Ctrl+C listener. While something has claimed
+// interrupts (e.g. a streaming answer), Ctrl+C is forwarded to it instead of exiting.
+static INTERRUPT_CLAIMED: AtomicBool = AtomicBool::new(false);
+static INTERRUPTS: OnceLock<watch::Sender<u64>> = OnceLock::new();
+
+fn interrupts() -> &'static watch::Sender<u64> {
+    INTERRUPTS.get_or_init(|| watch::channel(0).0)
+}
+
+// Held while Ctrl+C should stop the current operation rather than the program
+pub struct InterruptClaim {
+    receiver: watch::Receiver<u64>,
+}
+
+impl InterruptClaim {
+    // Resolves when the user presses Ctrl+C
+    pub async fn interrupted(&mut self) {
+        let _ = self.receiver.changed().await;
+    }
+}
+
+impl Drop for InterruptClaim {
+    fn drop(&mut self) {
+        INTERRUPT_CLAIMED.store(false, Ordering::SeqCst);
+    }
+}
+
+pub fn claim_interrupts() -> InterruptClaim {
+    let receiver = interrupts().subscribe();
+    INTERRUPT_CLAIMED.store(true, Ordering::SeqCst);
+    InterruptClaim { receiver }
+}
+
+// Remember where a recovery file should go (if anywhere) and install the panic/signal hooks
+pub fn install(path: Option<PathBuf>) {
+    if let Some(path) = path {
+        *RECOVERY.lock().unwrap_or_else(|e| e.into_inner()) = Some(Recovery { path, content: None });
+    }
+
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(path) = flush() {
+            eprintln!("{}", tr!("recovery.written", path.display()));
+        }
+        previous_hook(info);
+    }));
+
+    tokio::spawn(async {
+        let signal = wait_for_termination().await;
+        if let Some(path) = flush() {
+            eprintln!("\n{}", tr!("recovery.written", path.display()));
+        }
+        // The shell convention, so supervisors see which signal it was
+        std::process::exit(128 + signal);
+    });
+}
+
+#[cfg(unix)]
+const SIGHUP: i32 = 1;
+const SIGINT: i32 = 2;
+#[cfg(unix)]
+const SIGTERM: i32 = 15;
+
+// Wait for Ctrl+C that nobody has claimed
+async fn unclaimed_ctrl_c() {
+    loop {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return std::future::pending().await;
+        }
+        if !INTERRUPT_CLAIMED.load(Ordering::SeqCst) {
+            return;
+        }
+        interrupts().send_modify(|count| *count += 1);
+    }
+}
+
+// The number of the signal that should end the program
+#[cfg(unix)]
+async fn wait_for_termination() -> i32 {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (Ok(mut term), Ok(mut hup)) = (signal(SignalKind::terminate()), signal(SignalKind::hangup())) else {
+        unclaimed_ctrl_c().await;
+        return SIGINT;
+    };
+    tokio::select! {
+        _ = term.recv() => SIGTERM,
+        _ = hup.recv() => SIGHUP,
+        _ = unclaimed_ctrl_c() => SIGINT,
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_termination() -> i32 {
+    unclaimed_ctrl_c().await;
+    SIGINT
+}
+
+// Replace the snapshot with the latest rendering of the session (None once saved)
+pub fn update(content: Option<String>) {
+    if let Some(recovery) = RECOVERY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        recovery.content = content;
+    }
+}
+
+// Write the snapshot to disk; used from the hooks, so never panics
+fn flush() -> Option<PathBuf> {
+    let guard = RECOVERY.try_lock().ok()?;
+    let recovery = guard.as_ref()?;
+    let content = recovery.content.as_ref()?;
+    if let Some(dir) = recovery.path.parent() {
+        std::fs::create_dir_all(dir).ok()?;
+    }
+    archive::write_file(&recovery.path, content).ok()
+}
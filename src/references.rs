@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::api::estimate_tokens;
+use crate::archive::find_project_dir;
+use crate::config::ReferenceSettings;
+
+// A file pulled into a question with `@path`
+pub struct Attachment {
+    pub path: String,
+    pub tokens: u32,
+    pub truncated: bool,
+}
+
+pub struct Expanded {
+    pub question: String,
+    pub attachments: Vec<Attachment>,
+    // `@path` words that looked like files but didn't resolve, or didn't fit the budget
+    pub skipped: Vec<String>,
+}
+
+// Characters that may follow a reference without being part of the path
+const TRAILING: &[char] = &[',', '.', ';', ':', '!', '?', ')', ']', '"', '\'', '，', '。', '；', '：', '！', '？', '）'];
+
+// Resolve `@path` against the current directory, then the project directory
+fn resolve(reference: &str) -> Option<PathBuf> {
+    let candidates = [
+        Some(PathBuf::from(reference)),
+        find_project_dir().ok().map(|dir| dir.join(reference)),
+    ];
+    candidates.into_iter().flatten().find(|path| path.is_file())
+}
+
+// `@path` references in a question, in order and without duplicates
+fn references(question: &str) -> Vec<(String, PathBuf)> {
+    let mut found: Vec<(String, PathBuf)> = Vec::new();
+    for word in question.split_whitespace() {
+        let Some(reference) = word.strip_prefix('@') else {
+            continue;
+        };
+        let trimmed = reference.trim_end_matches(TRAILING);
+        let Some((name, path)) = [reference, trimmed]
+            .into_iter()
+            .filter(|r| !r.is_empty())
+            .find_map(|r| resolve(r).map(|path| (r.to_string(), path)))
+        else {
+            continue;
+        };
+        if !found.iter().any(|(n, _)| *n == name) {
+            found.push((name, path));
+        }
+    }
+    found
+}
+
+// Words that look like file references but don't point at a readable file
+fn unresolved(question: &str) -> Vec<String> {
+    question
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('@'))
+        .map(|reference| reference.trim_end_matches(TRAILING))
+        .filter(|reference| reference.contains('/') && resolve(reference).is_none())
+        .map(|reference| format!("@{}", reference))
+        .collect()
+}
+
+// Longest prefix of `text`, cut at a line break where possible, that fits in `budget` tokens
+pub fn truncate_to_tokens(text: &str, budget: u32) -> &str {
+    let (mut ascii, mut other) = (0u32, 0u32);
+    let mut end = text.len();
+    for (i, c) in text.char_indices() {
+        if c.is_ascii() {
+            ascii += 1;
+        } else {
+            other += 1;
+        }
+        if ascii.div_ceil(4) + other > budget {
+            end = i;
+            break;
+        }
+    }
+    let cut = &text[..end];
+    match cut.rfind('\n') {
+        Some(line_end) if end < text.len() && line_end > 0 => &cut[..line_end],
+        _ => cut,
+    }
+}
+
+// Longest suffix of `text`, starting at a line break where possible, that fits in `budget` tokens
+fn tail_to_tokens(text: &str, budget: u32) -> &str {
+    let (mut ascii, mut other) = (0u32, 0u32);
+    let mut start = 0;
+    for (i, c) in text.char_indices().rev() {
+        if c.is_ascii() {
+            ascii += 1;
+        } else {
+            other += 1;
+        }
+        if ascii.div_ceil(4) + other > budget {
+            start = i + c.len_utf8();
+            break;
+        }
+    }
+    let cut = &text[start..];
+    match cut.find('\n') {
+        Some(line_start) if start > 0 && line_start + 1 < cut.len() => &cut[line_start + 1..],
+        _ => cut,
+    }
+}
+
+// Fit `text` in `budget` tokens by keeping its start and end (where logs keep the
+// interesting parts) and marking how many lines were left out; true if anything was cut
+pub fn clip_middle(text: &str, budget: u32) -> (String, bool) {
+    if estimate_tokens(text) <= budget {
+        return (text.to_string(), false);
+    }
+    let head = truncate_to_tokens(text, budget / 2);
+    let rest = &text[head.len()..];
+    let tail = tail_to_tokens(rest, budget / 2);
+    let omitted = rest[..rest.len() - tail.len()].lines().filter(|l| !l.is_empty()).count();
+    let clipped = format!("{}\n… [{} lines omitted] …\n{}", head.trim_end(), omitted, tail);
+    (clipped, true)
+}
+
+// A code fence that can't be closed by anything inside `text`
+pub fn fence_for(text: &str) -> &'static str {
+    if text.contains("```") {
+        "````"
+    } else {
+        "```"
+    }
+}
+
+fn fence_language(path: &Path) -> &str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "rs" => "rust",
+        "py" => "python",
+        "js" | "mjs" => "javascript",
+        "ts" => "typescript",
+        "sh" => "bash",
+        "md" => "markdown",
+        "yml" => "yaml",
+        other => other,
+    }
+}
+
+// Parse a `--lines` range, `40-90` or a single line `42` (1-based, inclusive)
+pub fn parse_line_range(range: &str) -> Result<(usize, usize)> {
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let parse = |n: &str| n.trim().parse::<usize>().ok().filter(|n| *n > 0);
+    match (parse(start), parse(end)) {
+        (Some(start), Some(end)) if start <= end => Ok((start, end)),
+        _ => anyhow::bail!("--lines should be START-END (1-based, e.g. 40-90), got {:?}", range),
+    }
+}
+
+// `ask --file PATH [--lines START-END]`: the file, or the lines of it, as a fenced
+// block labelled with the path and range, clipped to `budget` tokens
+pub fn excerpt(path: &Path, lines: Option<(usize, usize)>, budget: u32) -> Result<String> {
+    let content = std::fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+    let (text, label) = match lines {
+        Some((start, end)) => {
+            let total = content.lines().count();
+            if start > total {
+                anyhow::bail!("{} has {} lines; --lines starts at {}", path.display(), total, start);
+            }
+            let end = end.min(total);
+            let text: Vec<&str> = content.lines().skip(start - 1).take(end + 1 - start).collect();
+            (text.join("\n"), format!("{} (lines {}-{})", path.display(), start, end))
+        }
+        None => (content.clone(), path.display().to_string()),
+    };
+    let (text, clipped) = clip_middle(text.trim_end(), budget);
+    let note = if clipped { ", middle left out" } else { "" };
+    let fence = fence_for(&text);
+    Ok(format!("\n\nFile: {}{}\n{}{}\n{}\n{}", label, note, fence, fence_language(path), text, fence))
+}
+
+// Inline the files referenced with `@path`, within the token budget, after the question
+pub fn expand(question: &str, settings: &ReferenceSettings) -> Result<Expanded> {
+    let mut expanded = Expanded {
+        question: question.to_string(),
+        attachments: Vec::new(),
+        skipped: unresolved(question),
+    };
+    if !settings.enabled {
+        return Ok(expanded);
+    }
+
+    let mut remaining = settings.max_tokens;
+    for (name, path) in references(question) {
+        let content = std::fs::read_to_string(&path).context(format!("Failed to read {}", path.display()))?;
+        if remaining == 0 {
+            expanded.skipped.push(format!("@{} (over the {}-token file budget)", name, settings.max_tokens));
+            continue;
+        }
+        let text = truncate_to_tokens(&content, remaining);
+        let truncated = text.len() < content.len();
+        let tokens = estimate_tokens(text);
+        // Whatever a truncated file left over is too little to be useful
+        remaining = if truncated { 0 } else { remaining.saturating_sub(tokens) };
+
+        let fence = fence_for(text);
+        let note = if truncated { " (truncated to fit the token budget)" } else { "" };
+        expanded.question.push_str(&format!(
+            "\n\nFile: {}{}\n{}{}\n{}\n{}",
+            name,
+            note,
+            fence,
+            fence_language(&path),
+            text.trim_end(),
+            fence
+        ));
+        expanded.attachments.push(Attachment {
+            path: name,
+            tokens,
+            truncated,
+        });
+    }
+    Ok(expanded)
+}
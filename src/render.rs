@@ -0,0 +1,240 @@
+use std::io::IsTerminal;
+use unicode_width::UnicodeWidthChar;
+
+use crate::codeblocks::{closes, opening_fence};
+use crate::highlight;
+use crate::layout::{self, Wrapper};
+use crate::ui;
+
+// Streamed answers are printed as they arrive, which leaves code fences and
+// tables on screen as raw markdown. Once such a block is complete, the renderer
+// moves the cursor back up to where the block started, clears the screen below
+// and prints the block again rendered: code highlighted (see `highlight`) between
+// dimmed fences, tables box-drawn. Only on a terminal, and only for blocks whose
+// start is still on screen; anything else stays as it was printed.
+
+enum Block {
+    Code { fence: String, language: String, number: usize, lines: Vec<String> },
+    Table { lines: Vec<String> },
+}
+
+fn is_table_row(line: &str) -> bool {
+    line.trim_start().starts_with('|')
+}
+
+fn cells(row: &str) -> Vec<String> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = row.strip_suffix('|').unwrap_or(row);
+    row.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+// The |---|:--:| line under a table's header
+fn is_separator(row: &str) -> bool {
+    cells(row).iter().all(|cell| !cell.is_empty() && cell.chars().all(|c| matches!(c, '-' | ':' | ' ')))
+}
+
+fn render_code(fence: &str, language: &str, number: usize, lines: &[String]) -> String {
+    let mut out = vec![ui::status(format!("{}{}  [{}]", fence, language, number)).to_string()];
+    match highlight::terminal(&lines.join("\n"), language) {
+        Some(code) if !lines.is_empty() => out.push(code),
+        _ => out.extend(lines.iter().map(|line| ui::code(line).to_string())),
+    }
+    out.push(ui::status(fence).to_string());
+    out.join("\n")
+}
+
+// None when the lines aren't a table after all (no separator under the header)
+fn render_table(lines: &[String]) -> Option<String> {
+    let [header, separator, rows @ ..] = lines else {
+        return None;
+    };
+    if !is_separator(separator) {
+        return None;
+    }
+    let headers = cells(header);
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            let mut cells = cells(row);
+            cells.resize(headers.len(), String::new());
+            cells
+        })
+        .collect();
+    let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+    Some(layout::table(&headers, &rows))
+}
+
+pub struct StreamRenderer {
+    wrapper: Option<Wrapper>,
+    // Terminal columns and rows, when blocks can be redrawn
+    screen: Option<(usize, usize)>,
+    // The source line being received, and what has been printed for it
+    line: String,
+    line_output: String,
+    block: Option<Block>,
+    // Code blocks so far, numbered like `highlight::terminal_markdown` does
+    code_blocks: usize,
+    // Rows the cursor has moved down since the open block (or else the current
+    // line) started, and its column
+    rows: usize,
+    column: usize,
+}
+
+impl StreamRenderer {
+    // Wrapped like other terminal output; `render` off just prints as it comes
+    pub fn for_terminal(render: bool) -> Self {
+        let screen = if render && std::io::stdout().is_terminal() {
+            terminal_size::terminal_size().map(|(width, height)| (width.0 as usize, height.0 as usize))
+        } else {
+            None
+        };
+        Self {
+            wrapper: Wrapper::for_terminal(),
+            screen,
+            line: String::new(),
+            line_output: String::new(),
+            block: None,
+            code_blocks: 0,
+            rows: 0,
+            column: 0,
+        }
+    }
+
+    // Follow the cursor through printed text, including the terminal's own wrapping
+    fn track(&mut self, text: &str) {
+        let Some((columns, _)) = self.screen else {
+            return;
+        };
+        for c in text.chars() {
+            if c == '\n' {
+                self.rows += 1;
+                self.column = 0;
+                continue;
+            }
+            let width = c.width().unwrap_or(0);
+            if self.column + width > columns {
+                self.rows += 1;
+                self.column = 0;
+            }
+            self.column += width;
+        }
+    }
+
+    fn emit(&mut self, text: &str, out: &mut String) {
+        self.track(text);
+        self.line_output.push_str(text);
+        out.push_str(text);
+    }
+
+    // Replace everything printed since the open block started with `rendered`;
+    // false when the block's first line has scrolled off the screen
+    fn redraw(&mut self, rendered: &str, out: &mut String) -> bool {
+        let Some((_, height)) = self.screen else {
+            return false;
+        };
+        if self.rows >= height {
+            return false;
+        }
+        if self.rows > 0 {
+            out.push_str(&format!("\x1b[{}A", self.rows));
+        }
+        out.push_str("\r\x1b[J");
+        out.push_str(rendered);
+        true
+    }
+
+    // Feed more text; returns what to print now
+    pub fn push(&mut self, text: &str) -> String {
+        let mut out = String::new();
+        for c in text.chars() {
+            let printed = match self.wrapper.as_mut() {
+                Some(wrapper) => wrapper.push(c.encode_utf8(&mut [0; 4])),
+                None => c.to_string(),
+            };
+            self.emit(&printed, &mut out);
+            if c == '\n' {
+                let line = std::mem::take(&mut self.line);
+                let line_output = std::mem::take(&mut self.line_output);
+                self.end_line(line, &line_output, true, &mut out);
+            } else {
+                self.line.push(c);
+            }
+        }
+        out
+    }
+
+    // Flush what's still buffered; a table running to the end is complete too
+    pub fn finish(&mut self) -> String {
+        let mut out = String::new();
+        if let Some(rest) = self.wrapper.as_mut().map(Wrapper::finish) {
+            self.emit(&rest, &mut out);
+        }
+        let line = std::mem::take(&mut self.line);
+        let line_output = std::mem::take(&mut self.line_output);
+        if !line.is_empty() {
+            self.end_line(line, &line_output, false, &mut out);
+        }
+        if let Some(Block::Table { lines }) = self.block.take() {
+            if let Some(table) = render_table(&lines) {
+                self.redraw(&table, &mut out);
+            }
+        }
+        out
+    }
+
+    // `line` is complete (`ended`: with its newline), and printed as `line_output`
+    fn end_line(&mut self, line: String, line_output: &str, ended: bool, out: &mut String) {
+        match self.block.take() {
+            Some(Block::Code { fence, language, number, lines }) if closes(&line, &fence) => {
+                let mut rendered = render_code(&fence, &language, number, &lines);
+                if ended {
+                    rendered.push('\n');
+                }
+                self.redraw(&rendered, out);
+                self.rows = 0;
+                self.column = 0;
+            }
+            Some(Block::Code { fence, language, number, mut lines }) => {
+                lines.push(line);
+                self.block = Some(Block::Code { fence, language, number, lines });
+            }
+            Some(Block::Table { mut lines }) if is_table_row(&line) => {
+                lines.push(line);
+                self.block = Some(Block::Table { lines });
+            }
+            // The table ended with the line before this one, which is on screen
+            // already and goes back under the redrawn table
+            Some(Block::Table { lines }) => {
+                let redrawn = render_table(&lines).is_some_and(|table| self.redraw(&format!("{}\n", table), out));
+                if redrawn {
+                    out.push_str(line_output);
+                }
+                self.rows = 0;
+                self.column = 0;
+                self.track(line_output);
+                self.open(line);
+            }
+            None => self.open(line),
+        }
+    }
+
+    // Start a block if `line` opens one; otherwise the next line starts afresh
+    fn open(&mut self, line: String) {
+        if let Some(fence) = opening_fence(&line) {
+            let language = line.trim_start()[fence.len()..].trim().to_string();
+            self.code_blocks += 1;
+            self.block = Some(Block::Code {
+                fence,
+                language,
+                number: self.code_blocks,
+                lines: Vec::new(),
+            });
+        } else if is_table_row(&line) {
+            self.block = Some(Block::Table { lines: vec![line] });
+        } else {
+            self.rows = 0;
+            self.column = 0;
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use reqwest::Client;
+use std::collections::hash_map::{Entry, HashMap};
+
+use crate::background::Background;
+use crate::completion::ReplEditor;
+use crate::config::{AiConfig, Profile, Settings};
+use crate::ensemble::Ensemble;
+use crate::focus::Focus;
+use crate::memory::Memory;
+use crate::panel::Panel;
+use crate::recall;
+use crate::session::ConversationSession;
+
+// State shared by the REPL loop and the slash commands
+pub struct Repl {
+    pub client: Client,
+    pub settings: Settings,
+    pub answerer: AiConfig,
+    pub reviewer: AiConfig,
+    // `[ensemble]` answerers that answer in place of `answerer`, if configured
+    pub ensemble: Option<Ensemble>,
+    // `[panel]` reviewers that review alongside `reviewer`, if configured
+    pub panel: Option<Panel>,
+    // Writes English TL;DRs of Chinese reviews, if enabled
+    pub summarizer: Option<AiConfig>,
+    // Embeddings endpoint for `[memory]`, if enabled
+    pub embedder: Option<AiConfig>,
+    // Cross-session memory, opened (and brought up to date) on the first question
+    pub memory: Option<Memory>,
+    pub profile: Profile,
+    pub rl: ReplEditor,
+    pub session: ConversationSession,
+    pub round_counter: usize,
+    pub pager_enabled: bool,
+    // Saved rounds searched for repeated questions, loaded on the first question
+    pub recall: Option<recall::Archive>,
+    // Set by `/force QUESTION`: send it without the recall check
+    pub forced_question: Option<String>,
+    // Set by `/quote`: put in front of the next question
+    pub quote: Option<String>,
+    // `/focus` aspects for the reviewer, until `/focus off`
+    pub focus: Vec<Focus>,
+    pub scrollback: Scrollback,
+    // Reviews running in the background (`[review] background`)
+    pub background: Background,
+}
+
+// Rounds rendered for /prev and /next, kept so paging back and forth doesn't
+// re-highlight them, and the round those commands showed last
+#[derive(Default)]
+pub struct Scrollback {
+    rendered: HashMap<usize, String>,
+    pub viewing: Option<usize>,
+}
+
+impl Scrollback {
+    pub fn get_or_render(&mut self, round: usize, render: impl FnOnce() -> Option<String>) -> Option<&str> {
+        if let Entry::Vacant(entry) = self.rendered.entry(round) {
+            entry.insert(render()?);
+        }
+        self.rendered.get(&round).map(String::as_str)
+    }
+
+    // Round N changed (e.g. a new review); render it again next time
+    pub fn invalidate(&mut self, round: usize) {
+        self.rendered.remove(&round);
+    }
+
+    // Another session was loaded
+    pub fn clear(&mut self) {
+        self.rendered.clear();
+        self.viewing = None;
+    }
+}
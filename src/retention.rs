@@ -0,0 +1,212 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use crate::archive::{conversations_dir, saved_sessions};
+use crate::config::RetentionSettings;
+use crate::i18n::tr;
+use crate::index;
+use crate::session::ConversationSession;
+use crate::ui;
+
+// Retention for the conversations directory: sessions not saved for longer than
+// `older_than` are deleted (their .json and .md, encrypted or not), optionally
+// sparing sessions with a starred round. The age is the JSON file's modification
+// time, so a session resumed with /load and saved again starts over.
+
+static POLICY: OnceLock<RetentionSettings> = OnceLock::new();
+
+pub fn init(settings: &RetentionSettings) -> Result<()> {
+    if let Some(age) = &settings.older_than {
+        parse_age(age).context("Invalid [archive.retention] older_than")?;
+    } else if settings.auto {
+        anyhow::bail!("[archive.retention] auto = true needs older_than");
+    }
+    let _ = POLICY.set(settings.clone());
+    Ok(())
+}
+
+// "90d" / "12w"; zero is refused, as it would select the whole archive
+fn parse_age(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let invalid = || anyhow::anyhow!("Expected an age like 90d or 12w, got {:?}", text);
+    let (number, unit) = text.split_at(text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len()));
+    let number = number.parse::<u64>().map_err(|_| invalid())?;
+    let days = match unit {
+        "d" => Some(number),
+        "w" => number.checked_mul(7),
+        _ => None,
+    };
+    match days.and_then(|days| days.checked_mul(24 * 60 * 60)) {
+        Some(0) => anyhow::bail!("An age of {:?} would select every session; use at least 1d", text),
+        Some(seconds) => Ok(Duration::from_secs(seconds)),
+        None => Err(invalid()),
+    }
+}
+
+struct Expired {
+    file: String,
+    saved: String,
+    title: String,
+    // The session's files that exist on disk
+    paths: Vec<PathBuf>,
+}
+
+// x.json(.age) and the x.md(.age) saved next to it
+fn session_paths(dir: &Path, file: &str) -> Vec<PathBuf> {
+    let stem = file.strip_suffix(".age").unwrap_or(file).strip_suffix(".json").unwrap_or(file);
+    ["json", "json.age", "md", "md.age"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", stem, ext)))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+fn expired(dir: &Path, max_age: Duration, keep_starred: bool) -> Vec<Expired> {
+    let cutoff = SystemTime::now().checked_sub(max_age).unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut found = Vec::new();
+    for file in saved_sessions() {
+        let path = dir.join(&file);
+        let Ok(saved) = std::fs::metadata(&path).and_then(|m| m.modified()) else {
+            continue;
+        };
+        if saved >= cutoff {
+            continue;
+        }
+        let title = match ConversationSession::load(&path) {
+            Ok(session) if keep_starred && session.starred_turns().next().is_some() => continue,
+            Ok(session) => index::entry(&file, &session).title,
+            // Can't tell whether it has stars, so keep it
+            Err(e) if keep_starred => {
+                ui::notice(ui::warning(tr!("prune.unreadable", file, format!("{:#}", e))));
+                continue;
+            }
+            Err(_) => String::new(),
+        };
+        found.push(Expired {
+            saved: chrono::DateTime::<chrono::Local>::from(saved).format("%Y-%m-%d").to_string(),
+            paths: session_paths(dir, &file),
+            file,
+            title,
+        });
+    }
+    found
+}
+
+fn delete(sessions: &[Expired]) -> Result<usize> {
+    let mut files = 0;
+    for session in sessions {
+        for path in &session.paths {
+            std::fs::remove_file(path).context(format!("Failed to delete {:?}", path))?;
+            files += 1;
+        }
+    }
+    Ok(files)
+}
+
+// Apply `[archive.retention]` after a save when `auto = true`
+pub fn auto_prune() {
+    let Some(policy) = POLICY.get().filter(|p| p.auto) else {
+        return;
+    };
+    let Some(max_age) = policy.older_than.as_deref().and_then(|age| parse_age(age).ok()) else {
+        return;
+    };
+    let Ok(dir) = conversations_dir() else {
+        return;
+    };
+    let sessions = expired(&dir, max_age, policy.keep_starred);
+    match delete(&sessions) {
+        Ok(_) if sessions.is_empty() => {}
+        Ok(_) => ui::notice(ui::status(tr!("prune.auto", sessions.len()))),
+        Err(e) => ui::notice(ui::warning(tr!("prune.failed", format!("{:#}", e)))),
+    }
+}
+
+struct PruneArgs {
+    older_than: Option<String>,
+    keep_starred: bool,
+    dry_run: bool,
+}
+
+fn parse_prune_args(args: &[String]) -> Result<PruneArgs> {
+    let policy = POLICY.get().cloned().unwrap_or_default();
+    let mut parsed = PruneArgs {
+        older_than: policy.older_than,
+        keep_starred: policy.keep_starred,
+        dry_run: false,
+    };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--older-than" => parsed.older_than = Some(iter.next().context("--older-than needs an age, e.g. 90d")?.clone()),
+            "--keep-starred" => parsed.keep_starred = true,
+            "--dry-run" | "-n" => parsed.dry_run = true,
+            other => anyhow::bail!("Unknown option for sessions prune: {} (expected --older-than AGE, --keep-starred, --dry-run)", other),
+        }
+    }
+    Ok(parsed)
+}
+
+// `aivsai sessions prune [--older-than 90d] [--keep-starred] [--dry-run]`
+fn run_prune(args: &[String]) -> Result<()> {
+    let args = parse_prune_args(args)?;
+    let age = args.older_than.context("sessions prune needs --older-than AGE (or [archive.retention] older_than)")?;
+    let max_age = parse_age(&age)?;
+    let dir = conversations_dir()?;
+    let sessions = expired(&dir, max_age, args.keep_starred);
+    if sessions.is_empty() {
+        println!("{}", ui::status(tr!("prune.nothing", age)));
+        return Ok(());
+    }
+
+    println!("{}", ui::heading(if args.dry_run { tr!("prune.would_delete", sessions.len()) } else { tr!("prune.deleting", sessions.len()) }));
+    for session in &sessions {
+        let title = if session.title.is_empty() { &session.file } else { &session.title };
+        println!("  {}  {}", session.saved, title);
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let files = delete(&sessions)?;
+    if let Err(e) = index::rebuild() {
+        ui::notice(ui::warning(tr!("index.failed", format!("{:#}", e))));
+    }
+    println!("{}", ui::success(tr!("prune.deleted", sessions.len(), files)));
+    Ok(())
+}
+
+// `aivsai sessions ...`
+pub fn run_sessions(args: &[String]) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("prune") => run_prune(&args[1..]),
+        Some(other) => anyhow::bail!("Unknown sessions command: {} (available: prune)", other),
+        None => anyhow::bail!("Usage: aivsai sessions prune [--older-than 90d] [--keep-starred] [--dry-run]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_are_days_or_weeks() {
+        assert_eq!(parse_age("90d").unwrap(), Duration::from_secs(90 * 24 * 60 * 60));
+        assert_eq!(parse_age(" 2w ").unwrap(), Duration::from_secs(14 * 24 * 60 * 60));
+    }
+
+    #[test]
+    fn bad_ages_are_errors() {
+        for age in ["", "90", "d", "3m", "-1d", "1.5w", "99999999999999999999d", "213503982334602d", "3000000000000000000w"] {
+            assert!(parse_age(age).is_err(), "{:?}", age);
+        }
+    }
+
+    #[test]
+    fn zero_is_refused() {
+        assert!(parse_age("0d").is_err());
+        assert!(parse_age("0w").is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, Timelike};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::archive;
+use crate::config::{Profile, Settings};
+use crate::i18n::tr;
+use crate::pipeline::{self, Pipeline};
+use crate::session::ConversationSession;
+use crate::ui;
+
+// `aivsai schedule --cron "0 9 * * *" --questions daily.txt`: run a questions
+// file through the answer + review pipeline on a cron schedule (local time) and
+// append each run's rounds to that day's session, e.g.
+// conversations/2025-03-01_schedule_daily.md, to watch answers drift over time.
+//
+// The questions file is re-read on every run, so it can be edited meanwhile.
+// Runs never overlap: times that pass while a run is going are skipped.
+
+// Tag on scheduled sessions, as /tag would add, so they stand out in the index
+const TAG: &str = "scheduled";
+
+// A five-field cron expression: minute hour day-of-month month day-of-week.
+// Each field is a bit set of the values it allows.
+struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    // Either day field starting with `*`: then both must match, otherwise either may
+    any_day: bool,
+}
+
+// `*`, `5`, `1-5`, `*/15`, `0-30/10`, and comma-separated lists of those
+fn parse_field(text: &str, min: u32, max: u32) -> Result<u64> {
+    let mut bits = 0u64;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|&step| step > 0).context(format!("Bad step in {:?}", part))?),
+            None => (part, 1),
+        };
+        let number = |value: &str| value.parse::<u32>().context(format!("Bad value {:?} in {:?}", value, text));
+        let (low, high) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((low, high)) => (number(low)?, number(high)?),
+            // `5/15` means from 5 to the end
+            None if part.contains('/') => (number(range)?, max),
+            None => (number(range)?, number(range)?),
+        };
+        if low < min || high > max || low > high {
+            anyhow::bail!("{:?} is outside {}-{}", part, min, max);
+        }
+        for value in (low..=high).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+impl Cron {
+    fn parse(expression: &str) -> Result<Self> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            anyhow::bail!("Expected 5 cron fields (minute hour day month weekday), got {:?}", expression);
+        };
+        let mut weekdays = parse_field(weekday, 0, 7).context("In the weekday field")?;
+        // 7 is Sunday too
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59).context("In the minute field")?,
+            hours: parse_field(hour, 0, 23).context("In the hour field")?,
+            days: parse_field(day, 1, 31).context("In the day field")?,
+            months: parse_field(month, 1, 12).context("In the month field")?,
+            weekdays,
+            any_day: day.starts_with('*') || weekday.starts_with('*'),
+        })
+    }
+
+    fn date_matches(&self, time: &NaiveDateTime) -> bool {
+        let day = self.days & (1 << time.day()) != 0;
+        let weekday = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+        let date = if self.any_day { day && weekday } else { day || weekday };
+        date && self.months & (1 << time.month()) != 0
+    }
+
+    // The first matching minute after `after`; None if nothing matches within
+    // a few years (e.g. "0 0 30 2 *")
+    fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let now = after.naive_local();
+        let mut time = now.date().and_hms_opt(now.hour(), now.minute(), 0)? + chrono::Duration::minutes(1);
+        let limit = time + chrono::Duration::days(5 * 366);
+        while time < limit {
+            if !self.date_matches(&time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.date().and_hms_opt(time.hour(), 0, 0)? + chrono::Duration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += chrono::Duration::minutes(1);
+            } else {
+                // Skipped by a daylight saving change: try the next minute
+                match time.and_local_timezone(Local).earliest() {
+                    Some(next) => return Some(next),
+                    None => time += chrono::Duration::minutes(1),
+                }
+            }
+        }
+        None
+    }
+}
+
+struct ScheduleArgs {
+    cron: Cron,
+    questions: PathBuf,
+    now: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<ScheduleArgs> {
+    let usage = "Usage: aivsai schedule --cron \"0 9 * * *\" --questions FILE [--now]";
+    let mut cron = None;
+    let mut questions = None;
+    let mut now = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--cron" => cron = Some(Cron::parse(iter.next().context("--cron needs an expression, e.g. \"0 9 * * *\"")?)?),
+            "--questions" => questions = Some(PathBuf::from(iter.next().context("--questions needs a file")?)),
+            "--now" => now = true,
+            other => anyhow::bail!("Unknown option for schedule: {} ({})", other, usage),
+        }
+    }
+    Ok(ScheduleArgs {
+        cron: cron.context(usage)?,
+        questions: questions.context(usage)?,
+        now,
+    })
+}
+
+// "daily" for daily.txt, safe to put in a file name
+fn file_stem(path: &Path) -> String {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    stem.chars().map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect()
+}
+
+// Run every question once and save the rounds into today's session; returns
+// the saved file
+async fn run_once(pipeline: &Pipeline, questions_path: &Path) -> Result<PathBuf> {
+    let questions = pipeline::read_questions(questions_path)?;
+    let name = format!("{}_schedule_{}", Local::now().format("%Y-%m-%d"), file_stem(questions_path));
+    // Carry on with today's session if an earlier run saved one
+    let mut session = match archive::session_file(&name) {
+        Ok(path) => ConversationSession::load(&path)?,
+        Err(_) => ConversationSession::new(),
+    };
+    if !session.tags.iter().any(|tag| tag == TAG) {
+        session.tags.push(TAG.to_string());
+    }
+
+    let mut answered = 0;
+    for (i, question) in questions.iter().enumerate() {
+        ui::notice(ui::heading(format!("\n[{}/{}] {}", i + 1, questions.len(), question)));
+        let round = session.last_round() + 1;
+        if let Some(turn) = pipeline.run_round(&session.start_time, round, question).await {
+            session.add_turn(turn);
+            answered += 1;
+        }
+    }
+    if answered == 0 {
+        anyhow::bail!("None of the {} question(s) got an answer", questions.len());
+    }
+    let path = pipeline.save_as(&session, &format!("{}.md", name))?;
+    ui::notice(ui::success(tr!("schedule.saved", answered, questions.len(), path.display())));
+    Ok(path)
+}
+
+// `aivsai schedule --cron EXPR --questions FILE [--now]`
+pub async fn run(args: &[String], settings: &Settings, profile: &Profile) -> Result<()> {
+    let args = parse_args(args)?;
+    // Fail now rather than at the first run
+    pipeline::read_questions(&args.questions)?;
+    let pipeline = Pipeline::new(settings, profile, false)?;
+
+    if args.now {
+        if let Err(e) = run_once(&pipeline, &args.questions).await {
+            ui::notice(ui::warning(tr!("schedule.failed", format!("{:#}", e))));
+        }
+    }
+    loop {
+        let next = args.cron.next_after(Local::now()).context("The cron expression never fires")?;
+        ui::notice(ui::status(tr!("schedule.next", next.format("%Y-%m-%d %H:%M"))));
+        let wait = next.timestamp().saturating_sub(Local::now().timestamp()).max(0);
+        tokio::time::sleep(Duration::from_secs(wait as u64)).await;
+        if let Err(e) = run_once(&pipeline, &args.questions).await {
+            ui::notice(ui::warning(tr!("schedule.failed", format!("{:#}", e))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn bits(values: &[u32]) -> u64 {
+        values.iter().fold(0, |bits, value| bits | 1 << value)
+    }
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        Cron::parse(expression).unwrap().next_after(after)
+    }
+
+    #[test]
+    fn fields_allow_steps_ranges_and_lists() {
+        assert_eq!(parse_field("*/15", 0, 59).unwrap(), bits(&[0, 15, 30, 45]));
+        assert_eq!(parse_field("5/15", 0, 59).unwrap(), bits(&[5, 20, 35, 50]));
+        assert_eq!(parse_field("1-5", 0, 7).unwrap(), bits(&[1, 2, 3, 4, 5]));
+        assert_eq!(parse_field("0-30/10,45", 0, 59).unwrap(), bits(&[0, 10, 20, 30, 45]));
+    }
+
+    #[test]
+    fn bad_fields_are_errors() {
+        for field in ["60", "*/0", "5-1", "x", "1-"] {
+            assert!(parse_field(field, 0, 59).is_err(), "{:?}", field);
+        }
+        assert!(Cron::parse("0 9 * *").is_err());
+    }
+
+    #[test]
+    fn seven_is_sunday() {
+        // 2025-03-01 is a Saturday
+        assert_eq!(next("0 0 * * 7", at(2025, 3, 1, 12, 0)), Some(at(2025, 3, 2, 0, 0)));
+    }
+
+    #[test]
+    fn runs_at_the_next_matching_minute() {
+        assert_eq!(next("*/15 * * * *", at(2025, 3, 1, 12, 7)), Some(at(2025, 3, 1, 12, 15)));
+        assert_eq!(next("0 9 * * *", at(2025, 3, 1, 9, 0)), Some(at(2025, 3, 2, 9, 0)));
+        assert_eq!(next("@monthly", at(2025, 12, 15, 8, 0)), Some(at(2026, 1, 1, 0, 0)));
+    }
+
+    #[test]
+    fn impossible_dates_never_fire() {
+        assert_eq!(next("0 0 30 2 *", at(2025, 3, 1, 12, 0)), None);
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 13th or any Friday; 2025-03-07 is a Friday, 2025-03-13 a Thursday
+        assert_eq!(next("0 0 13 * 5", at(2025, 3, 1, 12, 0)), Some(at(2025, 3, 7, 0, 0)));
+        assert_eq!(next("0 0 13 * 5", at(2025, 3, 8, 12, 0)), Some(at(2025, 3, 13, 0, 0)));
+        // With one field `*`, only the other one counts
+        assert_eq!(next("0 0 13 * *", at(2025, 3, 1, 12, 0)), Some(at(2025, 3, 13, 0, 0)));
+        assert_eq!(next("0 0 * * 5", at(2025, 3, 8, 12, 0)), Some(at(2025, 3, 14, 0, 0)));
+    }
+}
@@ -0,0 +1,371 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::api::ChatMessage;
+use crate::archive;
+use crate::config::{Strictness, UsageSettings};
+use crate::focus::Focus;
+use crate::api::Finish;
+use crate::ensemble::Candidate;
+use crate::panel::PanelReview;
+use crate::plugins::PluginScore;
+use crate::translate::TranslationCheck;
+use crate::usage::{self, UsageRecord};
+use crate::verdict::{Scores, Severity};
+
+// Structure to hold a single conversation turn
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConversationTurn {
+    pub user_question: String,
+    pub moonshot_answer: String,
+    // None until the reviewer succeeds; `review_error` explains why it is missing
+    pub deepseek_review: Option<String>,
+    pub review_error: Option<String>,
+    // Reasoning trace of a reasoning reviewer; only saved with `[review] save_reasoning`
+    #[serde(default, skip_serializing_if = "crate::reasoning::skip")]
+    pub review_reasoning: Option<String>,
+    // English TL;DR of a Chinese review (`[summary] english`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_summary: Option<String>,
+    // Parsed `VERDICT:` line of the review
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Severity>,
+    // Rubric grades from the review's `SCORES:` line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scores: Option<Scores>,
+    // When the question was asked, local time
+    pub timestamp: String,
+    pub round: usize, // 第几轮对话
+    // Whether this turn is still sent to the models as history (/clear and /forget
+    // drop turns from the context but keep them in the saved conversation)
+    pub in_context: bool,
+    // Marked important with /star; highlighted in exports and the best-answers digest
+    pub starred: bool,
+    // The answer was cut short with Ctrl+C while streaming
+    #[serde(default)]
+    pub truncated: bool,
+    // Model that produced the answer (it can change mid-session with /model)
+    #[serde(default)]
+    pub answer_model: String,
+    // Model that wrote the review (empty until the reviewer succeeds)
+    #[serde(default)]
+    pub review_model: String,
+    // Set for /translate rounds, whose review is a source/translation/issues table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<TranslationCheck>,
+    // Scores given to the review by `on_review` plugins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugin_scores: Vec<PluginScore>,
+    // `/focus` aspects the review was asked to concentrate on (kept for /review-retry)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub review_focus: Vec<Focus>,
+    // `/strict` level the review was asked for (kept for /review-retry; none: medium)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_strictness: Option<Strictness>,
+    // All `[ensemble]` answers with their ranks, and the reviewer's reasons for
+    // the ranking; `moonshot_answer` is the top-ranked one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<Candidate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranking: Option<String>,
+    // Reviews by the `[panel]` reviewers, and their merge by its consolidator
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub panel_reviews: Vec<PanelReview>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consolidated_review: Option<String>,
+    // Answer or review changed by hand with /edit-turn
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub edited: bool,
+    // How the answer / review ended when the provider cut it short or refused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_finish: Option<Finish>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_finish: Option<Finish>,
+    // `[guard]` rules and moderation categories the question or answer tripped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guard_flags: Vec<String>,
+    // Models, parameters, tokens, latency, retries and errors of this round's requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RoundMeta>,
+}
+
+impl ConversationTurn {
+    pub fn new(round: usize, user_question: String, moonshot_answer: String, answer_model: &str) -> Self {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        Self {
+            round,
+            user_question,
+            moonshot_answer,
+            deepseek_review: None,
+            review_error: None,
+            review_reasoning: None,
+            review_summary: None,
+            verdict: None,
+            scores: None,
+            timestamp,
+            in_context: true,
+            starred: false,
+            truncated: false,
+            answer_model: answer_model.to_string(),
+            review_model: String::new(),
+            translation: None,
+            plugin_scores: Vec::new(),
+            review_focus: Vec::new(),
+            review_strictness: None,
+            candidates: Vec::new(),
+            ranking: None,
+            panel_reviews: Vec::new(),
+            consolidated_review: None,
+            edited: false,
+            answer_finish: None,
+            review_finish: None,
+            guard_flags: Vec::new(),
+            meta: None,
+        }
+    }
+
+    // Take the review of `reviewed`, a copy of this round reviewed in the
+    // background; the rest (a /star in the meantime) stays
+    pub fn adopt_review(&mut self, reviewed: ConversationTurn) {
+        self.deepseek_review = reviewed.deepseek_review;
+        self.review_model = reviewed.review_model;
+        self.review_error = reviewed.review_error;
+        self.review_reasoning = reviewed.review_reasoning;
+        self.review_finish = reviewed.review_finish;
+        self.review_summary = reviewed.review_summary;
+        self.plugin_scores = reviewed.plugin_scores;
+        self.translation = reviewed.translation;
+        self.verdict = reviewed.verdict;
+        self.scores = reviewed.scores;
+    }
+
+    pub fn needs_review(&self) -> bool {
+        self.deepseek_review.is_none()
+    }
+}
+
+// What a round ran on, for reproducing it: every provider request it made
+// (answer, review, panel, summary...) as recorded in the usage ledger, and its
+// wall-clock time
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RoundMeta {
+    pub duration_ms: u64,
+    pub requests: Vec<UsageRecord>,
+    // Estimated cost of the requests at the `[usage]` prices of the time; None
+    // when a model has no price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    #[serde(default)]
+    pub currency: String,
+}
+
+impl RoundMeta {
+    // A round that started at `started` and made `requests`
+    pub fn new(requests: Vec<UsageRecord>, started: std::time::Instant, pricing: &UsageSettings) -> Self {
+        Self {
+            duration_ms: started.elapsed().as_millis() as u64,
+            cost: usage::records_cost(&requests, pricing),
+            currency: pricing.currency.clone(),
+            requests,
+        }
+    }
+
+    // Add the requests of a review that finished after the meta was taken
+    pub fn extend(&mut self, requests: Vec<UsageRecord>, started: std::time::Instant, pricing: &UsageSettings) {
+        self.requests.extend(requests);
+        self.duration_ms = started.elapsed().as_millis() as u64;
+        self.cost = usage::records_cost(&self.requests, pricing);
+    }
+
+    // Prompt and completion tokens over the round's requests
+    pub fn tokens(&self) -> (u64, u64) {
+        self.requests.iter().fold((0, 0), |(prompt, completion), request| {
+            (prompt + request.prompt_tokens as u64, completion + request.completion_tokens as u64)
+        })
+    }
+}
+
+// Structure to hold the entire conversation session. Saved as JSON next to the
+// markdown export so /load can restore it.
+#[derive(Serialize, Deserialize)]
+pub struct ConversationSession {
+    pub turns: Vec<ConversationTurn>,
+    pub start_time: String,
+    // Start time down to the nanosecond, telling apart sessions started the
+    // same second; sessions saved without one use their `start_time`
+    #[serde(default)]
+    pub id: String,
+    // Labels added with /tag, listed in conversations/INDEX.md
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // The session and round this one was forked from with /branch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branched_from: Option<BranchPoint>,
+    // Set whenever the session changes after the last /save
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BranchPoint {
+    // `start_time` of the parent session
+    pub session: String,
+    pub round: usize,
+}
+
+impl ConversationSession {
+    pub fn new() -> Self {
+        let now = chrono::Local::now();
+        Self {
+            turns: Vec::new(),
+            start_time: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+            id: now.format("%Y-%m-%d %H:%M:%S%.9f").to_string(),
+            tags: Vec::new(),
+            branched_from: None,
+            dirty: false,
+        }
+    }
+
+    // A new session starting now with this one's rounds up to `round`, to
+    // follow up in another direction; unsaved until the first /save
+    pub fn branch(&self, round: usize) -> Self {
+        Self {
+            turns: self.turns.iter().filter(|turn| turn.round <= round).cloned().collect(),
+            tags: self.tags.clone(),
+            branched_from: Some(BranchPoint {
+                session: self.start_time.clone(),
+                round,
+            }),
+            dirty: true,
+            ..Self::new()
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = archive::read_file(path).context(format!("Failed to read session file {:?}", path))?;
+        let mut session: Self = serde_json::from_str(&text).context(format!("Invalid session file {:?}", path))?;
+        if session.id.is_empty() {
+            session.id = session.start_time.clone();
+        }
+        Ok(session)
+    }
+
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        archive::write_file(path, &json).context(format!("Failed to write session file {:?}", path))?;
+        Ok(())
+    }
+
+    // Highest round number so far, so new rounds continue after a /load
+    pub fn last_round(&self) -> usize {
+        self.turns.iter().map(|t| t.round).max().unwrap_or(0)
+    }
+
+    pub fn add_turn(&mut self, turn: ConversationTurn) {
+        self.turns.push(turn);
+        self.dirty = true;
+    }
+
+    // Drop the last round, e.g. a question asked by mistake; it leaves the
+    // context and the next export with it
+    pub fn pop_turn(&mut self) -> Option<ConversationTurn> {
+        let turn = self.turns.pop()?;
+        self.dirty = true;
+        Some(turn)
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.dirty = false;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.turns.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.turns.len()
+    }
+
+    pub fn first_question(&self) -> Option<&str> {
+        self.turns.first().map(|t| t.user_question.as_str())
+    }
+
+    pub fn last_turn_mut(&mut self) -> Option<&mut ConversationTurn> {
+        self.turns.last_mut()
+    }
+
+    pub fn turn(&self, round: usize) -> Option<&ConversationTurn> {
+        self.turns.iter().find(|t| t.round == round)
+    }
+
+    pub fn turn_mut(&mut self, round: usize) -> Option<&mut ConversationTurn> {
+        self.turns.iter_mut().find(|t| t.round == round)
+    }
+
+    // Round numbers in order
+    pub fn rounds(&self) -> Vec<usize> {
+        let mut rounds: Vec<usize> = self.turns.iter().map(|t| t.round).collect();
+        rounds.sort_unstable();
+        rounds
+    }
+
+    // Toggle the star on round N (or the latest round); returns the round and new state
+    pub fn toggle_star(&mut self, round: Option<usize>) -> Option<(usize, bool)> {
+        let turn = match round {
+            Some(round) => self.turn_mut(round)?,
+            None => self.last_turn_mut()?,
+        };
+        turn.starred = !turn.starred;
+        let result = (turn.round, turn.starred);
+        self.dirty = true;
+        Some(result)
+    }
+
+    pub fn starred_turns(&self) -> impl Iterator<Item = &ConversationTurn> {
+        self.turns.iter().filter(|t| t.starred)
+    }
+
+    // Rounds whose review failed (or never ran) and can be retried
+    pub fn pending_reviews(&self) -> Vec<usize> {
+        self.turns.iter().filter(|t| t.needs_review()).map(|t| t.round).collect()
+    }
+
+    // Drop every turn from the model context (they stay in the archive)
+    pub fn clear_context(&mut self) -> usize {
+        self.forget_last(self.turns.len())
+    }
+
+    // Drop the last `n` turns that are still in context; returns how many were dropped
+    pub fn forget_last(&mut self, n: usize) -> usize {
+        let mut dropped = 0;
+        for turn in self.turns.iter_mut().rev().filter(|t| t.in_context).take(n) {
+            turn.in_context = false;
+            dropped += 1;
+        }
+        dropped
+    }
+
+    pub fn context_turns(&self) -> impl Iterator<Item = &ConversationTurn> {
+        self.turns.iter().filter(|t| t.in_context)
+    }
+
+    // Build the message list for the answering model: system prompt, then the
+    // in-context history as user/assistant pairs. The caller appends the new question.
+    pub fn context_messages(&self, system_prompt: &str) -> Vec<ChatMessage> {
+        let mut messages = vec![ChatMessage::system(system_prompt)];
+        for turn in self.context_turns() {
+            messages.push(ChatMessage::user(&turn.user_question));
+            messages.push(ChatMessage::assistant(&turn.moonshot_answer));
+        }
+        messages
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+
+use crate::archive::{self, conversations_dir, saved_sessions};
+use crate::bot::{hex, hmac_sha256, same, secret, unix_seconds};
+use crate::config::ShareSettings;
+use crate::http::{Request, Response};
+use crate::i18n::tr;
+use crate::index;
+use crate::publish;
+use crate::session::ConversationSession;
+use crate::ui;
+
+// Read-only links to saved sessions, served by the `bot` and Discord `bridge`
+// listeners as the same page `/export html` writes:
+//
+//   aivsai share 2025-03-01_14-30-25_question [--days 7]
+//   https://bot.example.com/share/2025-03-01_14-30-25_question.1741441825.3f9a…
+//
+// The link carries the session name, when it expires and an HMAC-SHA256 of both
+// under $AIVSAI_SHARE_SECRET, so it can't be altered to open another session or
+// to last longer. Without the secret no link is made or served. Changing the
+// secret revokes every link.
+
+const PREFIX: &str = "/share/";
+const SECRET: &str = "AIVSAI_SHARE_SECRET";
+const DEFAULT_DAYS: u64 = 7;
+
+fn signature(key: &str, name: &str, expires: u64) -> String {
+    hex(&hmac_sha256(key.as_bytes(), format!("{}:{}", name, expires).as_bytes()))
+}
+
+// `2025-03-01_14-30-25_question.json(.age)` → `2025-03-01_14-30-25_question`
+fn session_name(file: &str) -> &str {
+    let file = file.strip_suffix(".age").unwrap_or(file);
+    file.strip_suffix(".json").unwrap_or(file)
+}
+
+// The session file a link points to, if its signature holds and it hasn't expired
+fn shared_file(token: &str) -> Option<String> {
+    let key = secret(SECRET).ok()?;
+    let mut parts = token.rsplitn(3, '.');
+    let (signed, expires, name) = (parts.next()?, parts.next()?, parts.next()?);
+    let expires: u64 = expires.parse().ok()?;
+    if expires < unix_seconds() || !same(signature(&key, name, expires).as_bytes(), signed.as_bytes()) {
+        return None;
+    }
+    // Only sessions listed in conversations/, never a path built from the link
+    saved_sessions().into_iter().find(|file| session_name(file) == name)
+}
+
+fn page(file: &str) -> Result<String> {
+    let session = ConversationSession::load(&conversations_dir()?.join(file))?;
+    let entry = index::entry(file, &session);
+    let subtitle = format!("{} · {}", entry.date, entry.models.join(", "));
+    let turns: Vec<_> = session.turns.iter().collect();
+    Ok(publish::rounds_page(&entry.title, &subtitle, &turns))
+}
+
+// The shared page, or None if `request` isn't for one
+pub fn respond(request: &Request) -> Option<Response> {
+    let token = request.path.strip_prefix(PREFIX)?;
+    if request.method != "GET" {
+        return Some(Response::text(405, "GET only"));
+    }
+    let Some(file) = shared_file(token) else {
+        return Some(Response::text(404, "This link is invalid or has expired"));
+    };
+    Some(match page(&file) {
+        Ok(html) => Response::html(html),
+        Err(e) => {
+            ui::notice(ui::warning(tr!("share.failed", file, format!("{:#}", e))));
+            Response::text(404, "This session can't be shown")
+        }
+    })
+}
+
+// `aivsai share NAME [--days N]`
+pub fn run(args: &[String], settings: &ShareSettings) -> Result<()> {
+    let mut name = None;
+    let mut days = DEFAULT_DAYS;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--days" => {
+                days = iter.next().and_then(|value| value.parse().ok()).filter(|&days| days > 0).context("--days needs a number of days")?;
+            }
+            other if name.is_none() && !other.starts_with("--") => name = Some(other),
+            other => anyhow::bail!("Unknown option for share: {} (expected --days N)", other),
+        }
+    }
+    let name = name.context("Usage: aivsai share SESSION [--days N] (a session saved in conversations/)")?;
+    let key = secret(SECRET)?;
+
+    let path = archive::session_file(name)?;
+    let file = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    if !path.starts_with(conversations_dir()?) || !saved_sessions().contains(&file) {
+        anyhow::bail!("Only sessions saved in conversations/ can be shared");
+    }
+    let name = session_name(&file);
+    let expires = unix_seconds() + days * 24 * 60 * 60;
+    let link = format!("{}{}{}.{}.{}", settings.base_url.trim_end_matches('/'), PREFIX, name, expires, signature(&key, name, expires));
+    println!("{}", link);
+    ui::notice(ui::status(tr!("share.expires", days)));
+    Ok(())
+}
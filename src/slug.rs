@@ -0,0 +1,87 @@
+use pinyin::ToPinyin;
+use sha2::{Digest, Sha256};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Longest slug, in graphemes
+const MAX_GRAPHEMES: usize = 40;
+
+// Filename-safe words of a question: letters and digits lowercased, Chinese
+// characters as toneless pinyin ("如何用 async?" → "ru_he_yong_async"), any
+// other run of characters a single `_`. Cut to MAX_GRAPHEMES at a word
+// boundary; a question with nothing usable (only emoji or punctuation) gets a
+// short hash of its text instead, so it still has a distinct name.
+pub fn slug(text: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for grapheme in text.graphemes(true) {
+        let first = grapheme.chars().next().unwrap_or(' ');
+        if let Some(pinyin) = first.to_pinyin() {
+            flush(&mut words, &mut word);
+            words.push(pinyin.plain().to_string());
+        } else if first.is_alphanumeric() {
+            word.push_str(&grapheme.to_lowercase());
+        } else {
+            flush(&mut words, &mut word);
+        }
+    }
+    flush(&mut words, &mut word);
+
+    let mut slug = String::new();
+    let mut length = 0;
+    for word in &words {
+        let graphemes = word.graphemes(true).count();
+        let separator = usize::from(!slug.is_empty());
+        if length + separator + graphemes > MAX_GRAPHEMES {
+            // A single overlong word is cut rather than dropped
+            if slug.is_empty() {
+                slug = word.graphemes(true).take(MAX_GRAPHEMES).collect();
+            }
+            break;
+        }
+        if separator == 1 {
+            slug.push('_');
+        }
+        slug.push_str(word);
+        length += separator + graphemes;
+    }
+    if slug.is_empty() {
+        let digest = Sha256::digest(text.as_bytes());
+        slug = format!("q{}", digest.iter().take(4).map(|byte| format!("{:02x}", byte)).collect::<String>());
+    }
+    slug
+}
+
+fn flush(words: &mut Vec<String>, word: &mut String) {
+    if !word.is_empty() {
+        words.push(std::mem::take(word));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_are_lowercased_and_joined() {
+        assert_eq!(slug("How do I use async/await in Rust?"), "how_do_i_use_async_await_in_rust");
+    }
+
+    #[test]
+    fn chinese_becomes_pinyin() {
+        assert_eq!(slug("如何用 async?"), "ru_he_yong_async");
+    }
+
+    #[test]
+    fn long_questions_are_cut_at_a_word_boundary() {
+        let long = slug("one two three four five six seven eight nine ten eleven");
+        assert_eq!(long, "one_two_three_four_five_six_seven_eight");
+        assert_eq!(slug(&"a".repeat(60)).len(), MAX_GRAPHEMES);
+    }
+
+    #[test]
+    fn questions_without_words_get_a_hash() {
+        let emoji = slug("🚀🚀?");
+        assert!(emoji.starts_with('q') && emoji.len() == 9);
+        assert_ne!(emoji, slug("🎉?"));
+    }
+}
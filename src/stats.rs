@@ -0,0 +1,294 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::archive::{conversations_dir, saved_sessions};
+use crate::config::Settings;
+use crate::i18n::tr;
+use crate::session::{ConversationSession, ConversationTurn};
+use crate::ui;
+use crate::usage::{self, parse_date, UsageRow};
+use crate::verdict::{Scores, Severity};
+
+// One line of `conversations/scores.jsonl`, appended for every review that
+// graded its answer, so `aivsai stats` can follow each answering model over time
+#[derive(Serialize, Deserialize)]
+pub struct ScoreRecord {
+    pub timestamp: String,
+    pub answer_model: String,
+    pub review_model: String,
+    #[serde(flatten)]
+    pub scores: Scores,
+}
+
+fn ledger_path() -> Result<PathBuf> {
+    Ok(conversations_dir()?.join("scores.jsonl"))
+}
+
+fn append_record(record: &ScoreRecord) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(ledger_path()?)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+// Log a reviewed turn's grades, if it has any. Failures are ignored, as for usage.
+pub fn record(turn: &ConversationTurn) {
+    let Some(scores) = turn.scores else {
+        return;
+    };
+    let _ = append_record(&ScoreRecord {
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        answer_model: turn.answer_model.clone(),
+        review_model: turn.review_model.clone(),
+        scores,
+    });
+}
+
+fn load_records() -> Result<Vec<ScoreRecord>> {
+    let path = ledger_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(&path).context(format!("Failed to read score ledger at {:?}", path))?;
+    Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+// Running sums of one model's grades (code only over answers that had code)
+#[derive(Default)]
+struct ScoreRow {
+    reviews: u32,
+    accuracy: u32,
+    completeness: u32,
+    clarity: u32,
+    code: u32,
+    with_code: u32,
+    overall: f64,
+}
+
+impl ScoreRow {
+    fn add(&mut self, scores: &Scores) {
+        self.reviews += 1;
+        self.accuracy += scores.accuracy as u32;
+        self.completeness += scores.completeness as u32;
+        self.clarity += scores.clarity as u32;
+        if let Some(code) = scores.code {
+            self.code += code as u32;
+            self.with_code += 1;
+        }
+        self.overall += scores.overall();
+    }
+
+    fn mean(sum: u32, count: u32) -> String {
+        if count == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}", sum as f64 / count as f64)
+        }
+    }
+
+    fn overall(&self) -> f64 {
+        self.overall / self.reviews as f64
+    }
+}
+
+// Rounds of a session (or of the whole archive) by how their review went
+#[derive(Default)]
+pub struct RoundCounts {
+    rounds: usize,
+    reviewed: usize,
+    starred: usize,
+    severities: BTreeMap<Severity, usize>,
+    // Reviews without a VERDICT line
+    no_verdict: usize,
+    scored: usize,
+    overall: f64,
+}
+
+impl RoundCounts {
+    pub fn add(&mut self, turn: &ConversationTurn) {
+        self.rounds += 1;
+        if turn.starred {
+            self.starred += 1;
+        }
+        if turn.deepseek_review.is_none() {
+            return;
+        }
+        self.reviewed += 1;
+        match turn.verdict {
+            Some(severity) => *self.severities.entry(severity).or_default() += 1,
+            None => self.no_verdict += 1,
+        }
+        if let Some(scores) = &turn.scores {
+            self.scored += 1;
+            self.overall += scores.overall();
+        }
+    }
+}
+
+// Rounds, review verdicts and the token/cost table of `usage` rows; shared by
+// /stats (this session) and `aivsai stats --all` (the archive)
+pub fn print_summary(counts: &RoundCounts, usage: &BTreeMap<(String, String), UsageRow>, settings: &Settings) {
+    println!("{}", tr!("stats.rounds", counts.rounds, counts.reviewed, counts.starred));
+    if counts.reviewed > 0 {
+        let mut verdicts: Vec<String> = counts.severities.iter().map(|(severity, n)| format!("{} {}", severity.label(), n)).collect();
+        if counts.no_verdict > 0 {
+            verdicts.push(tr!("stats.no_verdict", counts.no_verdict));
+        }
+        println!("{}", tr!("stats.verdicts", verdicts.join(" · ")));
+    }
+    if counts.scored > 0 {
+        println!("{}", tr!("stats.average_score", format!("{:.1}", counts.overall / counts.scored as f64), counts.scored));
+    }
+
+    if usage.is_empty() {
+        println!("{}", ui::status(tr!("usage.empty")));
+        return;
+    }
+    let currency = &settings.usage.currency;
+    println!(
+        "\n{:<14} {:<22} {:>8} {:>12} {:>12} {:>11} {:>12}",
+        "Provider", "Model", "Requests", "Prompt tok", "Output tok", "Avg latency", format!("Cost ({})", currency)
+    );
+    let mut total_cost = 0.0;
+    for ((provider, model), row) in usage {
+        let cost = row.cost(settings.usage.price_for(model).as_ref());
+        total_cost += cost.unwrap_or(0.0);
+        println!(
+            "{:<14} {:<22} {:>8} {:>12} {:>12} {:>9}ms {:>12}",
+            provider,
+            model,
+            row.requests,
+            row.prompt_tokens,
+            row.completion_tokens,
+            row.average_latency_ms(),
+            cost.map(|c| format!("{:.4}", c)).unwrap_or_else(|| "n/a".to_string())
+        );
+    }
+    println!("{}", ui::status(tr!("usage.total", format!("{:.4}", total_cost), currency)));
+}
+
+// `stats --all`: every saved session and the whole usage ledger
+fn print_archive(settings: &Settings) -> Result<()> {
+    let dir = conversations_dir()?;
+    let mut counts = RoundCounts::default();
+    let mut sessions = 0;
+    for file in saved_sessions() {
+        let session = match ConversationSession::load(&dir.join(&file)) {
+            Ok(session) => session,
+            Err(e) => {
+                ui::notice(ui::warning(tr!("index.skipped", file, format!("{:#}", e))));
+                continue;
+            }
+        };
+        sessions += 1;
+        for turn in &session.turns {
+            counts.add(turn);
+        }
+    }
+    let mut usage: BTreeMap<(String, String), UsageRow> = BTreeMap::new();
+    for record in usage::load_records()? {
+        usage.entry((record.provider.clone(), record.model.clone())).or_default().add(&record);
+    }
+    println!("{}", ui::heading(tr!("stats.archive_heading", sessions)));
+    print_summary(&counts, &usage, settings);
+    println!();
+    Ok(())
+}
+
+struct StatsArgs {
+    since: Option<String>,
+    until: Option<String>,
+    model: Option<String>,
+    all: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<StatsArgs> {
+    let mut parsed = StatsArgs { since: None, until: None, model: None, all: false };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--since" => parsed.since = Some(parse_date("--since", iter.next())?),
+            "--until" => parsed.until = Some(parse_date("--until", iter.next())?),
+            "--model" => parsed.model = Some(iter.next().context("--model needs a model name")?.clone()),
+            "--all" => parsed.all = true,
+            other => anyhow::bail!("Unknown option for stats: {} (expected --all, --since, --until, --model)", other),
+        }
+    }
+    Ok(parsed)
+}
+
+// "2025-W09" for a "YYYY-MM-DD HH:MM:SS" timestamp
+fn week_of(timestamp: &str) -> Option<String> {
+    let date = chrono::NaiveDate::parse_from_str(timestamp.get(..10)?, "%Y-%m-%d").ok()?;
+    Some(date.format("%G-W%V").to_string())
+}
+
+// Two cells per point, so a 10 fills 20 columns
+fn bar(score: f64) -> String {
+    "█".repeat((score * 2.0).round() as usize)
+}
+
+// `aivsai stats [--all] [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--model NAME]`
+pub fn run_report(args: &[String], settings: &Settings) -> Result<()> {
+    let args = parse_args(args)?;
+    if args.all {
+        print_archive(settings)?;
+    }
+
+    let mut rows: BTreeMap<String, ScoreRow> = BTreeMap::new();
+    let mut weeks: BTreeMap<String, BTreeMap<String, ScoreRow>> = BTreeMap::new();
+    for record in load_records()? {
+        let day = record.timestamp.get(..10).unwrap_or(&record.timestamp);
+        if args.since.as_deref().is_some_and(|since| day < since) || args.until.as_deref().is_some_and(|until| day > until) {
+            continue;
+        }
+        if args.model.as_ref().is_some_and(|model| *model != record.answer_model) {
+            continue;
+        }
+        let model = if record.answer_model.is_empty() { "?".to_string() } else { record.answer_model };
+        rows.entry(model.clone()).or_default().add(&record.scores);
+        if let Some(week) = week_of(&record.timestamp) {
+            weeks.entry(model).or_default().entry(week).or_default().add(&record.scores);
+        }
+    }
+
+    let range = match (&args.since, &args.until) {
+        (Some(since), Some(until)) => tr!("usage.range", since, until),
+        (Some(since), None) => tr!("usage.since", since),
+        (None, Some(until)) => tr!("usage.until", until),
+        (None, None) => tr!("usage.all_time").to_string(),
+    };
+    println!("{}", ui::heading(tr!("stats.heading", range)));
+    if rows.is_empty() {
+        println!("{}", ui::status(tr!("stats.empty")));
+        return Ok(());
+    }
+
+    println!(
+        "{:<24} {:>7} {:>9} {:>12} {:>8} {:>6} {:>8}",
+        "Answer model", "Reviews", "Accuracy", "Completeness", "Clarity", "Code", "Overall"
+    );
+    for (model, row) in &rows {
+        println!(
+            "{:<24} {:>7} {:>9} {:>12} {:>8} {:>6} {:>8.2}",
+            model,
+            row.reviews,
+            ScoreRow::mean(row.accuracy, row.reviews),
+            ScoreRow::mean(row.completeness, row.reviews),
+            ScoreRow::mean(row.clarity, row.reviews),
+            ScoreRow::mean(row.code, row.with_code),
+            row.overall()
+        );
+    }
+
+    // Weekly overall average per model
+    for (model, by_week) in &weeks {
+        println!("\n{}", ui::heading(tr!("stats.chart_heading", model)));
+        for (week, row) in by_week {
+            println!("  {}  {:<20} {:.1}  {}", week, ui::answerer(bar(row.overall())), row.overall(), ui::status(tr!("stats.reviews", row.reviews)));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::JoinHandle;
+
+use crate::config::{Profile, Settings};
+use crate::pipeline::Pipeline;
+use crate::session::ConversationSession;
+use crate::ui;
+
+// `aivsai --stdio`: the answer + review pipeline for editor plugins, as JSON-RPC
+// 2.0 over stdin/stdout with one message per line. Everything for people goes
+// to stderr. Methods:
+//
+//   ask     {"question": "..."}            → {"turn": {...}} once reviewed
+//   stream  {"question": "..."}            → the same, after `delta` notifications
+//                                            {"id": ID, "text": "..."} with the answer
+//   cancel  {"id": ID}                     → true if the request was still running;
+//                                            it fails with code -32800
+//   save    {"filename": "x.md"} (optional) → {"path": "..."}
+//
+// Questions go to one session, in order, with its earlier rounds as context
+// like the REPL. Pipeline events (usage, flagged, error, ...) arrive as `event`
+// notifications.
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+const ROUND_FAILED: i64 = -32000;
+const REQUEST_CANCELLED: i64 = -32800;
+
+struct Server {
+    pipeline: Pipeline,
+    system_prompt: String,
+    session: tokio::sync::Mutex<ConversationSession>,
+    out: UnboundedSender<Value>,
+    // Running ask / stream requests by id
+    running: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+impl Server {
+    fn reply(&self, id: &Value, result: Value) {
+        let _ = self.out.send(json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+    }
+
+    fn fail(&self, id: &Value, code: i64, message: &str) {
+        let _ = self.out.send(json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }));
+    }
+
+    fn handle(self: &Arc<Self>, line: &str) {
+        let Ok(request) = serde_json::from_str::<Value>(line) else {
+            self.fail(&Value::Null, PARSE_ERROR, "Parse error");
+            return;
+        };
+        let id = request.get("id").cloned().unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or_default();
+        match request.get("method").and_then(Value::as_str).unwrap_or_default() {
+            method @ ("ask" | "stream") => {
+                let Some(question) = params.get("question").and_then(Value::as_str).map(str::to_string) else {
+                    self.fail(&id, INVALID_PARAMS, "params.question is required");
+                    return;
+                };
+                self.start(id, question, method == "stream");
+            }
+            "cancel" => {
+                let target = params.get("id").cloned().unwrap_or_default();
+                let running = self.running.lock().unwrap_or_else(PoisonError::into_inner).remove(&target.to_string());
+                let cancelled = running.is_some_and(|task| {
+                    task.abort();
+                    true
+                });
+                if cancelled {
+                    self.fail(&target, REQUEST_CANCELLED, "Request cancelled");
+                }
+                self.reply(&id, json!(cancelled));
+            }
+            "save" => {
+                let server = Arc::clone(self);
+                let filename = params.get("filename").and_then(Value::as_str).map(str::to_string);
+                tokio::spawn(async move {
+                    let mut session = server.session.lock().await;
+                    let saved = match &filename {
+                        Some(filename) => server.pipeline.save_as(&session, filename),
+                        None => server.pipeline.save(&session),
+                    };
+                    match saved {
+                        Ok(path) => {
+                            session.mark_saved();
+                            server.reply(&id, json!({ "path": path.display().to_string() }));
+                        }
+                        Err(e) => server.fail(&id, ROUND_FAILED, &format!("{:#}", e)),
+                    }
+                });
+            }
+            _ => self.fail(&id, METHOD_NOT_FOUND, "Method not found"),
+        }
+    }
+
+    // Run a round in the background so `cancel` can stop it
+    fn start(self: &Arc<Self>, id: Value, question: String, stream: bool) {
+        let server = Arc::clone(self);
+        let key = id.to_string();
+        // Held while spawning, so the task can't remove itself before it is listed
+        let mut running = self.running.lock().unwrap_or_else(PoisonError::into_inner);
+        let task = tokio::spawn(async move {
+            let mut session = server.session.lock().await;
+            let context = session.context_messages(&server.system_prompt);
+            let round = session.last_round() + 1;
+            let start_time = session.start_time.clone();
+            let out = server.out.clone();
+            let delta_id = id.clone();
+            let mut on_delta = move |text: &str| {
+                let _ = out.send(notification("delta", json!({ "id": delta_id, "text": text })));
+            };
+            let on_delta: Option<&mut (dyn FnMut(&str) + Send)> = if stream { Some(&mut on_delta) } else { None };
+            let turn = server.pipeline.run_round_with(&start_time, round, context, &question, on_delta).await;
+            server.running.lock().unwrap_or_else(PoisonError::into_inner).remove(&id.to_string());
+            match turn {
+                Some(turn) => {
+                    server.reply(&id, json!({ "turn": turn }));
+                    session.add_turn(turn);
+                }
+                None => server.fail(&id, ROUND_FAILED, "The round failed; see the preceding error event"),
+            }
+        });
+        running.insert(key, task);
+    }
+}
+
+pub async fn run(settings: &Settings, profile: &Profile) -> Result<()> {
+    let (out, mut outgoing) = mpsc::unbounded_channel::<Value>();
+    let (events, mut pipeline_events) = mpsc::unbounded_channel::<Value>();
+    let server = Arc::new(Server {
+        pipeline: Pipeline::new(settings, profile, true)?.send_events_to(events),
+        system_prompt: profile.answer_system_prompt(&settings.answer),
+        session: tokio::sync::Mutex::new(ConversationSession::new()),
+        out: out.clone(),
+        running: Mutex::new(HashMap::new()),
+    });
+    // One writer, so messages never interleave
+    tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            println!("{}", message);
+        }
+    });
+    tokio::spawn(async move {
+        while let Some(event) = pipeline_events.recv().await {
+            let _ = out.send(notification("event", event));
+        }
+    });
+    ui::notice(ui::status("aivsai --stdio: JSON-RPC on stdin/stdout"));
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if !line.trim().is_empty() {
+            server.handle(&line);
+        }
+    }
+    // The editor closed stdin: finish what is running, then stop
+    let running: Vec<JoinHandle<()>> = server.running.lock().unwrap_or_else(PoisonError::into_inner).drain().map(|(_, task)| task).collect();
+    for task in running {
+        let _ = task.await;
+    }
+    Ok(())
+}
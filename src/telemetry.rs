@@ -0,0 +1,65 @@
+use anyhow::Result;
+#[cfg(feature = "otlp")]
+use anyhow::Context;
+#[cfg(feature = "otlp")]
+use std::sync::OnceLock;
+
+use crate::config::TelemetrySettings;
+
+// The pipeline stages (answer, review, summary) are `tracing` spans with the
+// round, provider and model, marked as errors when the stage failed. With a
+// build that has `--features otlp` and `[telemetry] otlp_endpoint` set, they
+// are exported over OTLP/gRPC, so a `bot` or `bridge` deployment shows per-stage
+// latency and error rates in an existing collector (Jaeger, Tempo, ...).
+// Otherwise no subscriber is installed and the spans cost next to nothing.
+
+#[cfg(feature = "otlp")]
+static PROVIDER: OnceLock<opentelemetry_sdk::trace::TracerProvider> = OnceLock::new();
+
+#[cfg(feature = "otlp")]
+pub fn init(settings: &TelemetrySettings) -> Result<()> {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::WithExportConfig;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let Some(endpoint) = &settings.otlp_endpoint else {
+        return Ok(());
+    };
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .context(format!("Failed to set up the OTLP exporter for {}", endpoint))?;
+    let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(opentelemetry_sdk::Resource::new([opentelemetry::KeyValue::new("service.name", settings.service_name.clone())]))
+        .build();
+    let tracer = provider.tracer("aivsai");
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()
+        .context("Failed to install the trace exporter")?;
+    let _ = PROVIDER.set(provider);
+    Ok(())
+}
+
+#[cfg(not(feature = "otlp"))]
+pub fn init(settings: &TelemetrySettings) -> Result<()> {
+    if settings.otlp_endpoint.is_some() {
+        anyhow::bail!("[telemetry] otlp_endpoint needs a build with `--features otlp`");
+    }
+    Ok(())
+}
+
+// Send the spans still batched up before the process exits
+#[cfg(feature = "otlp")]
+pub async fn flush() {
+    if let Some(provider) = PROVIDER.get().cloned() {
+        // Shutting down blocks until the exporter is done
+        let _ = tokio::task::spawn_blocking(move || provider.shutdown()).await;
+    }
+}
+
+#[cfg(not(feature = "otlp"))]
+pub async fn flush() {}
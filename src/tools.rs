@@ -0,0 +1,467 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::api::{ToolCall, ToolSpec};
+use crate::archive::find_project_dir;
+use crate::config::{ExternalTool, ToolSettings};
+use crate::i18n::tr;
+use crate::redact;
+use crate::references::clip_middle;
+use crate::ui;
+
+// Built-in tools for the answerer. Results go back to the provider, so file
+// contents and command output pass through the `[redact]` filter first; files
+// and commands are confined to the project directory.
+//
+// External tools (`[[tools.external]]`) are executables run once per call in the
+// project directory. They get one JSON object on stdin,
+//
+//   {"name": "dict", "arguments": {"word": "borrow"}}
+//
+// and answer on stdout with {"content": "..."} or {"error": "..."}; output that
+// isn't JSON is used as the result as is. A non-zero exit status is an error.
+
+const BUILT_IN: [&str; 3] = ["calculator", "read_file", "shell"];
+
+// Check `[[tools.external]]` entries before the session starts
+pub fn validate(settings: &ToolSettings) -> Result<()> {
+    let mut seen: Vec<&str> = Vec::new();
+    for tool in &settings.external {
+        let name = tool.name.as_str();
+        // The chat API's limit for function names
+        if name.is_empty() || name.len() > 64 || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            anyhow::bail!("[[tools.external]] name {:?} must be 1-64 letters, digits, '_' or '-'", name);
+        }
+        if BUILT_IN.contains(&name) || seen.contains(&name) {
+            anyhow::bail!("[[tools.external]] name {:?} is already taken", name);
+        }
+        if tool.command.is_empty() {
+            anyhow::bail!("[[tools.external]] {:?} needs a command", name);
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+fn external_spec(tool: &ExternalTool) -> ToolSpec {
+    let parameters = tool.parameters.clone().unwrap_or_else(|| json!({ "type": "object" }));
+    ToolSpec::function(&tool.name, &tool.description, parameters)
+}
+
+// Tools offered to the reviewer: external ones marked `review = true`. Built-ins
+// stay with the answerer, since nobody is asked to confirm a reviewer's command.
+pub fn review_specs(settings: &ToolSettings) -> Vec<ToolSpec> {
+    settings.external.iter().filter(|tool| tool.review).map(external_spec).collect()
+}
+
+pub fn specs(settings: &ToolSettings) -> Vec<ToolSpec> {
+    let all = [
+        ToolSpec::function(
+            "calculator",
+            "Evaluate an arithmetic expression exactly instead of estimating it. Supports + - * / % ^, parentheses, sqrt, abs, ln, log10, exp, sin, cos, tan, pi and e.",
+            json!({
+                "type": "object",
+                "properties": { "expression": { "type": "string", "description": "e.g. (1.07 ^ 10 - 1) * 100" } },
+                "required": ["expression"]
+            }),
+        ),
+        ToolSpec::function(
+            "read_file",
+            "Read a text file from the user's project, optionally only a range of lines.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path relative to the project root" },
+                    "start_line": { "type": "integer", "minimum": 1 },
+                    "end_line": { "type": "integer", "minimum": 1 }
+                },
+                "required": ["path"]
+            }),
+        ),
+        ToolSpec::function(
+            "shell",
+            "Run a shell command in the project root and return its exit status and output. The user is asked to approve every command.",
+            json!({
+                "type": "object",
+                "properties": { "command": { "type": "string" } },
+                "required": ["command"]
+            }),
+        ),
+    ];
+    all.into_iter()
+        .filter(|spec| settings.allow.contains(&spec.function.name))
+        .chain(settings.external.iter().map(external_spec))
+        .collect()
+}
+
+// Run a call and return the text sent back to the model; failures are reported
+// to the model as text too, so it can correct itself. Only tools in `offered`
+// run; `confirm` asks the user.
+pub fn run(call: &ToolCall, offered: &[ToolSpec], settings: &ToolSettings, confirm: &mut dyn FnMut(&str) -> bool) -> String {
+    let name = call.function.name.as_str();
+    let result = serde_json::from_str::<Value>(&call.function.arguments)
+        .context("Arguments are not valid JSON")
+        .and_then(|args| {
+            if !offered.iter().any(|spec| spec.function.name == name) {
+                anyhow::bail!("Unknown tool {:?}", name);
+            }
+            if let Some(tool) = settings.external.iter().find(|tool| tool.name == name) {
+                return external(tool, args);
+            }
+            let text = |key: &str| args.get(key).and_then(Value::as_str).context(format!("Missing string argument {:?}", key));
+            match name {
+                "calculator" => calculator(text("expression")?),
+                "read_file" => read_file(
+                    text("path")?,
+                    args.get("start_line").and_then(Value::as_u64),
+                    args.get("end_line").and_then(Value::as_u64),
+                ),
+                "shell" => shell(text("command")?, settings.shell_timeout_secs, confirm),
+                _ => anyhow::bail!("Unknown tool {:?}", name),
+            }
+        });
+    let output = match result {
+        Ok(output) => {
+            ui::notice(ui::status(tr!("tools.called", name, summarize(&call.function.arguments))));
+            output
+        }
+        Err(e) => {
+            ui::notice(ui::warning(tr!("tools.failed", name, format!("{:#}", e))));
+            format!("Error: {:#}", e)
+        }
+    };
+    let (clipped, _) = clip_middle(&output, settings.max_output_tokens);
+    let redacted = redact::scan(&clipped);
+    if !redacted.is_clean() {
+        ui::notice(ui::warning(tr!("redact.masked", redacted.summary())));
+    }
+    redacted.text
+}
+
+// Arguments as shown in the tool notice
+fn summarize(arguments: &str) -> String {
+    let line: String = arguments.chars().filter(|c| *c != '\n').take(80).collect();
+    if arguments.chars().count() > 80 {
+        format!("{}…", line)
+    } else {
+        line
+    }
+}
+
+// A path inside the project directory (symlinks resolved), or an error
+fn project_path(path: &str) -> Result<PathBuf> {
+    let root = find_project_dir()?.canonicalize()?;
+    let full = root.join(path).canonicalize().context(format!("No such file: {}", path))?;
+    if !full.starts_with(&root) {
+        anyhow::bail!("{} is outside the project directory", path);
+    }
+    Ok(full)
+}
+
+fn read_file(path: &str, start: Option<u64>, end: Option<u64>) -> Result<String> {
+    let full = project_path(path)?;
+    let text = std::fs::read_to_string(&full).context(format!("Can't read {} as text", path))?;
+    if start.is_none() && end.is_none() {
+        return Ok(text);
+    }
+    let start = start.unwrap_or(1).max(1) as usize;
+    let end = end.map_or(usize::MAX, |end| end as usize);
+    Ok(text
+        .lines()
+        .enumerate()
+        .skip(start - 1)
+        .take_while(|(i, _)| *i < end)
+        .map(|(i, line)| format!("{:>5}  {}", i + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn shell(command: &str, timeout_secs: u64, confirm: &mut dyn FnMut(&str) -> bool) -> Result<String> {
+    if !confirm(command) {
+        return Ok("The user declined to run this command.".to_string());
+    }
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(find_project_dir()?)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to start sh")?;
+    let (status, stdout, stderr) = wait_with_timeout(child, "The command", timeout_secs)?;
+    let status = match status.code() {
+        Some(code) => format!("exit status {}", code),
+        None => "killed by a signal".to_string(),
+    };
+    Ok(format!("{}\n--- stdout ---\n{}\n--- stderr ---\n{}", status, stdout, stderr))
+}
+
+fn external(tool: &ExternalTool, arguments: Value) -> Result<String> {
+    let mut child = Command::new(&tool.command[0])
+        .args(&tool.command[1..])
+        .current_dir(find_project_dir()?)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(format!("Failed to start {:?}", tool.command[0]))?;
+
+    let request = json!({ "name": tool.name, "arguments": arguments });
+    if let Some(mut stdin) = child.stdin.take() {
+        // A tool that doesn't read its input closes the pipe early; that's fine
+        let _ = writeln!(stdin, "{}", request);
+    }
+    let (status, stdout, stderr) = wait_with_timeout(child, &tool.name, tool.timeout_secs)?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}: {}", tool.name, status, stderr.trim());
+    }
+
+    match serde_json::from_str::<Value>(stdout.trim()) {
+        Ok(reply) if reply.get("error").is_some() => {
+            anyhow::bail!("{}", reply["error"].as_str().map(str::to_string).unwrap_or_else(|| reply["error"].to_string()))
+        }
+        Ok(reply) => Ok(match reply.get("content") {
+            Some(Value::String(text)) => text.clone(),
+            Some(other) => other.to_string(),
+            None => reply.to_string(),
+        }),
+        Err(_) => Ok(stdout),
+    }
+}
+
+// Wait for a child with piped stdout and stderr, killing it after `timeout_secs`;
+// its exit status and output
+fn wait_with_timeout(mut child: Child, name: &str, timeout_secs: u64) -> Result<(ExitStatus, String, String)> {
+    // Read the pipes on their own threads so a chatty command can't block on a full pipe
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut bytes);
+            }
+            String::from_utf8_lossy(&bytes).into_owned()
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+
+    let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("{} timed out after {}s", name, timeout_secs);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    Ok((status, stdout, stderr))
+}
+
+fn calculator(expression: &str) -> Result<String> {
+    let mut parser = Calc {
+        chars: expression.chars().filter(|c| !c.is_whitespace()).collect(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.expr()?;
+    if parser.pos < parser.chars.len() {
+        anyhow::bail!("Unexpected {:?} at position {}", parser.chars[parser.pos], parser.pos + 1);
+    }
+    if !value.is_finite() {
+        anyhow::bail!("The result is not a finite number");
+    }
+    Ok(value.to_string())
+}
+
+// Deepest nesting of parentheses, signs and powers. Expressions come from the
+// model, and each level is a few stack frames.
+const MAX_DEPTH: usize = 64;
+
+// Recursive-descent arithmetic: expr = term (+|- term)*, term = power (*|/|% power)*,
+// power = unary (^ power)?, unary = -unary | primary
+struct Calc {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Calc {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Parse one level deeper, failing past MAX_DEPTH
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<f64>) -> Result<f64> {
+        if self.depth == MAX_DEPTH {
+            anyhow::bail!("The expression is nested more than {} levels deep", MAX_DEPTH);
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn expr(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.power()?;
+        loop {
+            if self.eat('*') {
+                value *= self.power()?;
+            } else if self.eat('/') {
+                value /= self.power()?;
+            } else if self.eat('%') {
+                value %= self.power()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn power(&mut self) -> Result<f64> {
+        let base = self.unary()?;
+        if self.eat('^') {
+            Ok(base.powf(self.nested(Self::power)?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64> {
+        if self.eat('-') {
+            Ok(-self.nested(Self::unary)?)
+        } else if self.eat('+') {
+            self.nested(Self::unary)
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<f64> {
+        if self.eat('(') {
+            let value = self.nested(Self::expr)?;
+            if !self.eat(')') {
+                anyhow::bail!("Missing ')'");
+            }
+            return Ok(value);
+        }
+        let start = self.pos;
+        if self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                self.pos += 1;
+            }
+            // Exponent: 1.5e3, 2E-4
+            if matches!(self.peek(), Some('e' | 'E')) && self.chars.get(self.pos + 1).is_some_and(|c| c.is_ascii_digit() || *c == '-' || *c == '+') {
+                self.pos += 2;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+            }
+            let number: String = self.chars[start..self.pos].iter().collect();
+            return number.parse::<f64>().context(format!("Invalid number {:?}", number));
+        }
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect::<String>().to_lowercase();
+        match name.as_str() {
+            "" => anyhow::bail!("Expected a number at position {}", start + 1),
+            "pi" => return Ok(std::f64::consts::PI),
+            "e" => return Ok(std::f64::consts::E),
+            _ => {}
+        }
+        if !self.eat('(') {
+            anyhow::bail!("Unknown name {:?}", name);
+        }
+        let arg = self.nested(Self::expr)?;
+        if !self.eat(')') {
+            anyhow::bail!("Missing ')' after {}(", name);
+        }
+        Ok(match name.as_str() {
+            "sqrt" => arg.sqrt(),
+            "abs" => arg.abs(),
+            "ln" => arg.ln(),
+            "log10" | "log" => arg.log10(),
+            "exp" => arg.exp(),
+            "sin" => arg.sin(),
+            "cos" => arg.cos(),
+            "tan" => arg.tan(),
+            other => anyhow::bail!("Unknown function {:?}", other),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calculator_follows_precedence() {
+        assert_eq!(calculator("1 + 2 * 3").unwrap(), "7");
+        assert_eq!(calculator("(1 + 2) * 3").unwrap(), "9");
+        assert_eq!(calculator("2 ^ 3 ^ 2").unwrap(), "512");
+        assert_eq!(calculator("-2 ^ 2").unwrap(), "4");
+        assert_eq!(calculator("7 % 4 - 1.5e1").unwrap(), "-12");
+    }
+
+    #[test]
+    fn calculator_knows_constants_and_functions() {
+        assert_eq!(calculator("sqrt(16) + abs(-1)").unwrap(), "5");
+        assert_eq!(calculator("log10(1000)").unwrap(), "3");
+        assert_eq!(calculator("cos(pi)").unwrap(), "-1");
+    }
+
+    #[test]
+    fn calculator_rejects_bad_input() {
+        assert!(calculator("1 / 0").is_err());
+        assert!(calculator("(1 + 2").is_err());
+        assert!(calculator("1 + 2)").is_err());
+        assert!(calculator("foo(1)").is_err());
+        assert!(calculator("").is_err());
+    }
+
+    #[test]
+    fn calculator_caps_nesting() {
+        assert_eq!(calculator(&format!("{}1{}", "(".repeat(60), ")".repeat(60))).unwrap(), "1");
+        for deep in [format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000)), format!("{}1", "-".repeat(100_000)), format!("{}1", "2^".repeat(100_000))] {
+            let error = calculator(&deep).unwrap_err().to_string();
+            assert!(error.contains("nested"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn shell_commands_time_out() {
+        let error = shell("sleep 5", 0, &mut |_| true).unwrap_err().to_string();
+        assert!(error.contains("timed out"), "{}", error);
+    }
+}
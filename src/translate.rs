@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::ChatMessage;
+use crate::config::Profile;
+use crate::i18n::tr;
+use crate::layout;
+
+// Result of a /translate round: the target language and the reviewer's
+// sentence-by-sentence comparison
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TranslationCheck {
+    pub target: String,
+    #[serde(default)]
+    pub rows: Vec<TranslationRow>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TranslationRow {
+    pub source: String,
+    pub translation: String,
+    pub issues: String,
+}
+
+// At least half of the letters are Chinese characters
+pub fn is_mostly_chinese(text: &str) -> bool {
+    let cjk = text.chars().filter(|c| ('\u{4e00}'..='\u{9fff}').contains(c)).count();
+    let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+    cjk > 0 && cjk * 2 >= letters
+}
+
+// Chinese text goes to English, anything else to Chinese
+pub fn default_target(text: &str) -> &'static str {
+    if is_mostly_chinese(text) {
+        "English"
+    } else {
+        "Chinese"
+    }
+}
+
+pub fn answer_messages(text: &str, target: &str) -> Vec<ChatMessage> {
+    vec![
+        ChatMessage::system("You are a professional translator. You keep the meaning, tone and formatting of the original, and you keep code, names and technical terms accurate."),
+        ChatMessage::user(format!("Translate the following text into {}. Reply with the translation only.\n\n{}", target, text)),
+    ]
+}
+
+pub fn review_messages(profile: &Profile, text: &str, translation: &str, target: &str) -> Vec<ChatMessage> {
+    let request = format!(
+        "Source text:\n\"\"\"\n{}\n\"\"\"\n\nTranslation into {}:\n\"\"\"\n{}\n\"\"\"\n\nCheck the translation for fidelity (omissions, additions, mistranslations) and terminology. Reply with a markdown table with exactly three columns: Source | Translation | Issues, one row per sentence or short paragraph, in order. Write the issues in {} and put \"-\" when a row has none. After the table you may add a few lines of overall notes.",
+        text,
+        target,
+        translation,
+        profile.review_language()
+    );
+    vec![
+        ChatMessage::system("You are an expert translation reviewer who checks translations against their source for fidelity and terminology."),
+        ChatMessage::user(request),
+    ]
+}
+
+fn split_cells(line: &str) -> Vec<String> {
+    let inner = line.trim().trim_start_matches('|');
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    let mut cells = vec![String::new()];
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cells.last_mut().expect("never empty").push('|');
+                chars.next();
+            }
+            '|' => cells.push(String::new()),
+            _ => cells.last_mut().expect("never empty").push(c),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().replace("<br>", "\n").replace("<br/>", "\n")).collect()
+}
+
+fn is_separator(cells: &[String]) -> bool {
+    cells.iter().all(|cell| !cell.is_empty() && cell.chars().all(|c| matches!(c, '-' | ':' | ' ')))
+}
+
+// Rows of the first markdown table in the review, header and separator left out
+pub fn parse_table(review: &str) -> Vec<TranslationRow> {
+    let table: Vec<Vec<String>> = review
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with('|'))
+        .take_while(|line| line.trim_start().starts_with('|'))
+        .map(split_cells)
+        .filter(|cells| !is_separator(cells))
+        .collect();
+    table
+        .into_iter()
+        .skip(1)
+        .filter(|cells| cells.len() >= 3)
+        .map(|mut cells| {
+            cells.truncate(3);
+            let issues = cells.pop().unwrap_or_default();
+            let translation = cells.pop().unwrap_or_default();
+            let source = cells.pop().unwrap_or_default();
+            TranslationRow { source, translation, issues }
+        })
+        .collect()
+}
+
+// What the reviewer wrote besides the table
+pub fn notes(review: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in review.lines().filter(|line| !line.trim_start().starts_with('|')) {
+        // The table leaves a double blank line behind
+        if line.trim().is_empty() && lines.last().is_some_and(|l| l.trim().is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+fn row_cells(row: &TranslationRow) -> Vec<String> {
+    vec![row.source.clone(), row.translation.clone(), row.issues.clone()]
+}
+
+pub fn render_terminal(check: &TranslationCheck) -> String {
+    let rows: Vec<Vec<String>> = check.rows.iter().map(row_cells).collect();
+    layout::table(&[tr!("translate.source"), &tr!("translate.translation", check.target), tr!("translate.issues")], &rows)
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.trim().replace('|', "\\|").replace('\n', "<br>")
+}
+
+pub fn render_markdown(check: &TranslationCheck) -> String {
+    let mut lines = vec![
+        format!(
+            "| {} | {} | {} |",
+            tr!("translate.source"),
+            tr!("translate.translation", check.target),
+            tr!("translate.issues")
+        ),
+        "| --- | --- | --- |".to_string(),
+    ];
+    for row in &check.rows {
+        let cells: Vec<String> = row_cells(row).iter().map(|cell| markdown_cell(cell)).collect();
+        lines.push(format!("| {} |", cells.join(" | ")));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,231 @@
+use anyhow::{Context, Result};
+use colored::*;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+use crate::config::ThemeSettings;
+use crate::highlight;
+
+// Terminal styling by role, so the colors can be changed in one place via `[theme]`
+// (e.g. for light terminals) or switched off with NO_COLOR / --no-color.
+#[derive(Default)]
+struct Style {
+    color: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    // Parse a style such as "blue bold", "bright_black", "#005f87 underline" or "plain"
+    fn parse(spec: &str) -> Result<Self> {
+        let mut style = Style::default();
+        for token in spec.split(|c: char| c.is_whitespace() || c == '+').filter(|t| !t.is_empty()) {
+            match token.to_lowercase().as_str() {
+                "bold" => style.bold = true,
+                "dim" | "dimmed" => style.dimmed = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "plain" | "none" | "default" => {}
+                hex if hex.starts_with('#') => style.color = Some(parse_hex(hex)?),
+                name => {
+                    let name = name.replace(['_', '-'], " ");
+                    style.color = Some(name.parse().map_err(|_| anyhow::anyhow!("unknown color or style: {}", token))?);
+                }
+            }
+        }
+        Ok(style)
+    }
+
+    fn apply(&self, text: &str) -> ColoredString {
+        let mut styled = match self.color {
+            Some(color) => text.color(color),
+            None => text.normal(),
+        };
+        if self.bold {
+            styled = styled.bold();
+        }
+        if self.dimmed {
+            styled = styled.dimmed();
+        }
+        if self.italic {
+            styled = styled.italic();
+        }
+        if self.underline {
+            styled = styled.underline();
+        }
+        styled
+    }
+}
+
+fn parse_hex(hex: &str) -> Result<Color> {
+    let digits = &hex[1..];
+    // Checked before slicing: a multi-byte character would split a slice
+    if digits.len() != 6 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        anyhow::bail!("hex colors need 6 hex digits: {}", hex);
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).context(format!("invalid hex color: {}", hex));
+    Ok(Color::TrueColor {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+struct Theme {
+    user: Style,
+    answerer: Style,
+    reviewer: Style,
+    heading: Style,
+    status: Style,
+    success: Style,
+    warning: Style,
+    error: Style,
+    code: Style,
+}
+
+impl Theme {
+    fn from_settings(settings: &ThemeSettings) -> Result<Self> {
+        let style = |role: &str, spec: &str| Style::parse(spec).context(format!("Invalid [theme] {}", role));
+        Ok(Self {
+            user: style("user", &settings.user)?,
+            answerer: style("answerer", &settings.answerer)?,
+            reviewer: style("reviewer", &settings.reviewer)?,
+            heading: style("heading", &settings.heading)?,
+            status: style("status", &settings.status)?,
+            success: style("success", &settings.success)?,
+            warning: style("warning", &settings.warning)?,
+            error: style("error", &settings.error)?,
+            code: style("code", &settings.code)?,
+        })
+    }
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+fn theme() -> &'static Theme {
+    THEME.get_or_init(|| Theme::from_settings(&ThemeSettings::default()).expect("built-in theme is valid"))
+}
+
+// Color is off when NO_COLOR is set (to any non-empty value) or --no-color was passed
+pub fn color_disabled(no_color_flag: bool) -> bool {
+    no_color_flag || std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty())
+}
+
+pub fn disable_color() {
+    colored::control::set_override(false);
+}
+
+// Install the configured theme; call once at startup before printing styled output
+pub fn init(settings: &ThemeSettings) -> Result<()> {
+    let theme = Theme::from_settings(settings)?;
+    let _ = THEME.set(theme);
+    highlight::init(&settings.code_theme)
+}
+
+pub fn user(text: impl AsRef<str>) -> ColoredString {
+    theme().user.apply(text.as_ref())
+}
+
+pub fn answerer(text: impl AsRef<str>) -> ColoredString {
+    theme().answerer.apply(text.as_ref())
+}
+
+pub fn reviewer(text: impl AsRef<str>) -> ColoredString {
+    theme().reviewer.apply(text.as_ref())
+}
+
+pub fn heading(text: impl AsRef<str>) -> ColoredString {
+    theme().heading.apply(text.as_ref())
+}
+
+pub fn status(text: impl AsRef<str>) -> ColoredString {
+    theme().status.apply(text.as_ref())
+}
+
+pub fn success(text: impl AsRef<str>) -> ColoredString {
+    theme().success.apply(text.as_ref())
+}
+
+pub fn warning(text: impl AsRef<str>) -> ColoredString {
+    theme().warning.apply(text.as_ref())
+}
+
+pub fn error(text: impl AsRef<str>) -> ColoredString {
+    theme().error.apply(text.as_ref())
+}
+
+pub fn code(text: impl AsRef<str>) -> ColoredString {
+    theme().code.apply(text.as_ref())
+}
+
+// How much chrome to print: -q keeps only answers, reviews and errors;
+// -v adds timings, token counts and request details
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet = 0,
+    Normal = 1,
+    Verbose = 2,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+fn verbosity_at_least(level: Verbosity) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= level as u8
+}
+
+// Progress/status lines ("Thinking ...", retries) are human chrome: in --json mode
+// they go to stderr so stdout carries nothing but events.
+static NOTICES_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+pub fn send_notices_to_stderr() {
+    NOTICES_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+fn print_chrome(line: impl Display) {
+    if NOTICES_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+// Status line shown by default, hidden with -q
+pub fn notice(line: impl Display) {
+    if verbosity_at_least(Verbosity::Normal) {
+        print_chrome(line);
+    }
+}
+
+// Diagnostic line (timings, tokens) shown only with -v
+pub fn detail(line: impl Display) {
+    if verbosity_at_least(Verbosity::Verbose) {
+        print_chrome(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        assert!(matches!(parse_hex("#ff8000"), Ok(Color::TrueColor { r: 255, g: 128, b: 0 })));
+        assert!(parse_hex("#fff").is_err());
+        assert!(parse_hex("#gggggg").is_err());
+        assert!(parse_hex("#+1+2+3").is_err());
+    }
+
+    #[test]
+    fn rejects_non_ascii_hex_colors() {
+        // 6 bytes, but not 6 characters
+        assert!(parse_hex("#aéaaa").is_err());
+        assert!(parse_hex("#ééé").is_err());
+    }
+}
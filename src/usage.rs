@@ -0,0 +1,353 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::task::{JoinError, JoinHandle};
+
+use crate::archive::conversations_dir;
+use crate::config::{ModelPrice, Settings, UsageSettings};
+use crate::i18n::tr;
+use crate::{api, metrics, ui};
+
+// One line of `conversations/usage.jsonl`, appended for every provider request;
+// a round's records are also kept in its turn (see `session::RoundMeta`)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UsageRecord {
+    pub timestamp: String,
+    pub provider: String,
+    pub model: String,
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub completion_tokens: u32,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub latency_ms: u64,
+    // Environment variable of the key used, when the provider has several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    // Sampling parameters the request was sent with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub retries: u32,
+    // Chat user the request was made for, in `bot` and `bridge` (see `as_user`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    // Hash of the provider base URL the request went to (see `provenance`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
+fn ledger_path() -> Result<PathBuf> {
+    Ok(conversations_dir()?.join("usage.jsonl"))
+}
+
+fn append_record(record: &UsageRecord) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(ledger_path()?)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+#[derive(Serialize, Clone, Copy, Default)]
+pub struct Totals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl Totals {
+    pub fn of(records: &[UsageRecord]) -> Totals {
+        Totals {
+            requests: records.len() as u64,
+            prompt_tokens: records.iter().map(|r| r.prompt_tokens as u64).sum(),
+            completion_tokens: records.iter().map(|r| r.completion_tokens as u64).sum(),
+        }
+    }
+}
+
+// This run's usage per (provider, model), for /stats
+static RUN_ROWS: Mutex<BTreeMap<(String, String), UsageRow>> = Mutex::new(BTreeMap::new());
+
+pub fn run_rows() -> BTreeMap<(String, String), UsageRow> {
+    RUN_ROWS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+// Estimated cost of `records`; None if one of their models has no price
+pub fn records_cost(records: &[UsageRecord], pricing: &UsageSettings) -> Option<f64> {
+    records
+        .iter()
+        .map(|record| {
+            let mut row = UsageRow::default();
+            row.add(record);
+            row.cost(pricing.price_for(&record.model).as_ref())
+        })
+        .sum()
+}
+
+tokio::task_local! {
+    // Records made inside `collect`
+    static COLLECTED: RefCell<Vec<UsageRecord>>;
+    // Chat user that requests made inside `as_user` are for
+    static USER: String;
+}
+
+// Run `work` and return the records it made, apart from those of work running
+// alongside it (a background review while the next round is asked)
+pub async fn collect<T>(work: impl Future<Output = T>) -> (T, Vec<UsageRecord>) {
+    COLLECTED
+        .scope(RefCell::new(Vec::new()), async {
+            let output = work.await;
+            (output, COLLECTED.with(|records| records.take()))
+        })
+        .await
+}
+
+// Run `work` on behalf of a chat user: its requests are recorded under their
+// id and count against their quota (see `quota`)
+pub async fn as_user<T>(user: String, work: impl Future<Output = T>) -> T {
+    USER.scope(user, work).await
+}
+
+pub fn current_user() -> Option<String> {
+    USER.try_with(String::clone).ok()
+}
+
+// `tokio::spawn` for requests that belong to the caller's work: task-locals
+// don't follow a spawned task, so it runs as the caller's chat user and keeps
+// its own records, which `joined` hands to the caller's `collect`
+pub fn spawn<T: Send + 'static>(work: impl Future<Output = T> + Send + 'static) -> JoinHandle<(T, Vec<UsageRecord>)> {
+    let user = current_user();
+    tokio::spawn(async move {
+        match user {
+            Some(user) => as_user(user, collect(work)).await,
+            None => collect(work).await,
+        }
+    })
+}
+
+// The output of a task from `spawn`, its records added to the caller's `collect`
+pub async fn joined<T>(task: JoinHandle<(T, Vec<UsageRecord>)>) -> Result<T, JoinError> {
+    let (output, records) = task.await?;
+    let _ = COLLECTED.try_with(|collected| collected.borrow_mut().extend(records));
+    Ok(output)
+}
+
+// Tokens per user on the day they were used, for quotas
+static USER_TOKENS: Mutex<BTreeMap<(String, String), u64>> = Mutex::new(BTreeMap::new());
+
+fn count_user_tokens(record: &UsageRecord) {
+    let (Some(user), Some(day)) = (&record.user, record.timestamp.get(..10)) else {
+        return;
+    };
+    let mut tokens = USER_TOKENS.lock().unwrap_or_else(|e| e.into_inner());
+    *tokens.entry((day.to_string(), user.clone())).or_default() += record.prompt_tokens as u64 + record.completion_tokens as u64;
+}
+
+// Pick up today's per-user tokens from the ledger, so a restart doesn't reset quotas
+pub fn load_user_tokens() -> Result<()> {
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    for record in load_records()?.iter().filter(|record| record.timestamp.starts_with(&today)) {
+        count_user_tokens(record);
+    }
+    Ok(())
+}
+
+// Tokens used today by `user`
+pub fn user_tokens_today(user: &str) -> u64 {
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let tokens = USER_TOKENS.lock().unwrap_or_else(|e| e.into_inner());
+    tokens.get(&(today, user.to_string())).copied().unwrap_or(0)
+}
+
+// Append a record to the ledger. Failures are ignored: accounting must never break a chat.
+pub fn record(record: &UsageRecord) {
+    RUN_ROWS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry((record.provider.clone(), record.model.clone()))
+        .or_default()
+        .add(record);
+    let _ = COLLECTED.try_with(|records| records.borrow_mut().push(record.clone()));
+    metrics::observe(record);
+    count_user_tokens(record);
+    // Replayed and canned requests cost nothing
+    if !api::offline() {
+        let _ = append_record(record);
+    }
+}
+
+pub fn load_records() -> Result<Vec<UsageRecord>> {
+    let path = ledger_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(&path).context(format!("Failed to read usage ledger at {:?}", path))?;
+    // Skip lines we can't parse (e.g. a write cut short by a crash)
+    Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+#[derive(Default, Clone)]
+pub struct UsageRow {
+    pub requests: u64,
+    pub errors: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub latency_ms: u64,
+}
+
+impl UsageRow {
+    pub fn add(&mut self, record: &UsageRecord) {
+        self.requests += 1;
+        if !record.ok {
+            self.errors += 1;
+        }
+        self.prompt_tokens += record.prompt_tokens as u64;
+        self.completion_tokens += record.completion_tokens as u64;
+        self.latency_ms += record.latency_ms;
+    }
+
+    pub fn average_latency_ms(&self) -> u64 {
+        self.latency_ms.checked_div(self.requests).unwrap_or(0)
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.requests as f64 * 100.0
+        }
+    }
+
+    pub fn cost(&self, price: Option<&ModelPrice>) -> Option<f64> {
+        price.map(|p| {
+            (self.prompt_tokens as f64 * p.input_per_million + self.completion_tokens as f64 * p.output_per_million) / 1_000_000.0
+        })
+    }
+}
+
+struct UsageArgs {
+    since: Option<String>,
+    until: Option<String>,
+    csv: Option<PathBuf>,
+}
+
+pub fn parse_date(flag: &str, value: Option<&String>) -> Result<String> {
+    let value = value.context(format!("{} needs a date (YYYY-MM-DD)", flag))?;
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").context(format!("Invalid date for {}: {}", flag, value))?;
+    Ok(value.clone())
+}
+
+fn parse_args(args: &[String]) -> Result<UsageArgs> {
+    let mut parsed = UsageArgs { since: None, until: None, csv: None };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--since" => parsed.since = Some(parse_date("--since", iter.next())?),
+            "--until" => parsed.until = Some(parse_date("--until", iter.next())?),
+            "--csv" => parsed.csv = Some(PathBuf::from(iter.next().context("--csv needs a file path")?)),
+            other => anyhow::bail!("Unknown option for usage: {} (expected --since, --until, --csv)", other),
+        }
+    }
+    Ok(parsed)
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// `aivsai usage [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--csv FILE]`
+pub fn run_report(args: &[String], settings: &Settings) -> Result<()> {
+    let args = parse_args(args)?;
+
+    // Timestamps are "YYYY-MM-DD HH:MM:SS", so date bounds compare as string prefixes
+    let mut rows: BTreeMap<(String, String), UsageRow> = BTreeMap::new();
+    // Providers with several keys, per key
+    let mut keys: BTreeMap<(String, String), UsageRow> = BTreeMap::new();
+    // Requests made for chat users (`bot`, `bridge`), per user
+    let mut users: BTreeMap<String, UsageRow> = BTreeMap::new();
+    for record in load_records()? {
+        let day = record.timestamp.get(..10).unwrap_or(&record.timestamp);
+        if args.since.as_deref().is_some_and(|since| day < since) || args.until.as_deref().is_some_and(|until| day > until) {
+            continue;
+        }
+        if let Some(key) = &record.key {
+            keys.entry((record.provider.clone(), key.clone())).or_default().add(&record);
+        }
+        if let Some(user) = &record.user {
+            users.entry(user.clone()).or_default().add(&record);
+        }
+        rows.entry((record.provider.clone(), record.model.clone())).or_default().add(&record);
+    }
+
+    let range = match (&args.since, &args.until) {
+        (Some(since), Some(until)) => tr!("usage.range", since, until),
+        (Some(since), None) => tr!("usage.since", since),
+        (None, Some(until)) => tr!("usage.until", until),
+        (None, None) => tr!("usage.all_time").to_string(),
+    };
+    println!("{}", ui::heading(tr!("usage.heading", range)));
+
+    if rows.is_empty() {
+        println!("{}", ui::status(tr!("usage.empty")));
+        return Ok(());
+    }
+
+    let currency = &settings.usage.currency;
+    println!(
+        "{:<14} {:<22} {:>8} {:>7} {:>12} {:>12} {:>12}",
+        "Provider", "Model", "Requests", "Errors", "Prompt tok", "Output tok", format!("Cost ({})", currency)
+    );
+    let mut total_cost = 0.0;
+    let mut csv = String::from("provider,model,requests,errors,error_rate_percent,prompt_tokens,completion_tokens,estimated_cost\n");
+    for ((provider, model), row) in &rows {
+        let cost = row.cost(settings.usage.price_for(model).as_ref());
+        total_cost += cost.unwrap_or(0.0);
+        let cost_text = cost.map(|c| format!("{:.4}", c)).unwrap_or_else(|| "n/a".to_string());
+        println!(
+            "{:<14} {:<22} {:>8} {:>6.1}% {:>12} {:>12} {:>12}",
+            provider, model, row.requests, row.error_rate(), row.prompt_tokens, row.completion_tokens, cost_text
+        );
+        csv.push_str(&format!(
+            "{},{},{},{},{:.2},{},{},{}\n",
+            csv_field(provider), csv_field(model), row.requests, row.errors, row.error_rate(), row.prompt_tokens, row.completion_tokens,
+            cost.map(|c| format!("{:.6}", c)).unwrap_or_default()
+        ));
+    }
+    println!("{}", ui::status(tr!("usage.total", format!("{:.4}", total_cost), currency)));
+
+    // Only answered requests carry their key
+    if !keys.is_empty() {
+        println!("\n{:<14} {:<22} {:>8} {:>12} {:>12}", "Provider", "Key", "Requests", "Prompt tok", "Output tok");
+        for ((provider, key), row) in &keys {
+            println!("{:<14} {:<22} {:>8} {:>12} {:>12}", provider, key, row.requests, row.prompt_tokens, row.completion_tokens);
+        }
+    }
+    if !users.is_empty() {
+        println!("\n{:<37} {:>8} {:>7} {:>12} {:>12}", "User", "Requests", "Errors", "Prompt tok", "Output tok");
+        for (user, row) in &users {
+            println!("{:<37} {:>8} {:>7} {:>12} {:>12}", user, row.requests, row.errors, row.prompt_tokens, row.completion_tokens);
+        }
+    }
+
+    if let Some(path) = args.csv {
+        std::fs::write(&path, csv).context(format!("Failed to write CSV to {:?}", path))?;
+        println!("{}", ui::success(tr!("usage.csv_written", path.display())));
+    }
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use anyhow::{Context, Result};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::i18n::tr;
+use crate::ui;
+
+// Record and replay of provider traffic, for demos and for regression runs of
+// rendering and exports that shouldn't depend on a live model:
+//
+//   aivsai --record demo.cassette ...          every provider response is written down
+//   aivsai --replay demo.cassette ...          and served back offline, with its timing
+//   aivsai --replay demo.cassette --fast-forward ...   or as fast as it can be read
+//
+// A cassette is JSON lines, one request per line: the provider, the request body
+// and the response as it arrived, in timed chunks (streamed answers keep their
+// pacing). API keys and headers are not recorded. On replay a request gets the
+// recorded response to the same provider with the same body; failing that, the
+// next unused one of that provider, so small prompt changes still replay.
+
+#[derive(Serialize, Deserialize)]
+struct Chunk {
+    // Since the request was sent
+    after_ms: u64,
+    data: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Interaction {
+    provider: String,
+    request: Value,
+    chunks: Vec<Chunk>,
+}
+
+enum Mode {
+    Record { path: PathBuf, lock: Mutex<()> },
+    Replay { interactions: Mutex<Vec<Option<Interaction>>>, fast: bool },
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+// `--record FILE`: start an empty cassette
+pub fn record(path: &Path) -> Result<()> {
+    std::fs::write(path, "").context(format!("Failed to create cassette {:?}", path))?;
+    let _ = MODE.set(Mode::Record {
+        path: path.to_path_buf(),
+        lock: Mutex::new(()),
+    });
+    Ok(())
+}
+
+// `--replay FILE [--fast-forward]`
+pub fn replay(path: &Path, fast: bool) -> Result<()> {
+    let text = std::fs::read_to_string(path).context(format!("Failed to read cassette {:?}", path))?;
+    let interactions = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| serde_json::from_str(line).map(Some).context(format!("Invalid cassette {:?} (entry {})", path, i + 1)))
+        .collect::<Result<_>>()?;
+    let _ = MODE.set(Mode::Replay {
+        interactions: Mutex::new(interactions),
+        fast,
+    });
+    Ok(())
+}
+
+// No request leaves the machine; responses come from the cassette
+pub fn replaying() -> bool {
+    matches!(MODE.get(), Some(Mode::Replay { .. }))
+}
+
+pub fn recording() -> bool {
+    matches!(MODE.get(), Some(Mode::Record { .. }))
+}
+
+enum Source {
+    Live(Response),
+    Replayed { chunks: VecDeque<Chunk>, fast: bool },
+}
+
+// A provider response body, read live (and recorded when `--record` is on) or
+// served from the cassette
+pub struct Reply {
+    source: Source,
+    sent: Instant,
+    // The interaction being recorded, and bytes of a UTF-8 character split
+    // across chunks
+    recording: Option<(Interaction, Vec<u8>)>,
+}
+
+impl Reply {
+    // The response to a request sent at `sent`
+    pub fn live(response: Response, sent: Instant, provider: &str, request: &impl Serialize) -> Self {
+        let recording = recording().then(|| {
+            let interaction = Interaction {
+                provider: provider.to_string(),
+                request: serde_json::to_value(request).unwrap_or(Value::Null),
+                chunks: Vec::new(),
+            };
+            (interaction, Vec::new())
+        });
+        Self {
+            source: Source::Live(response),
+            sent,
+            recording,
+        }
+    }
+
+    // The recorded response to this request
+    pub fn replayed(provider: &str, request: &impl Serialize) -> Result<Self> {
+        let Some(Mode::Replay { interactions, fast }) = MODE.get() else {
+            anyhow::bail!("No cassette is being replayed");
+        };
+        let request = serde_json::to_value(request).context("Failed to encode the request")?;
+        let mut interactions = interactions.lock().unwrap_or_else(|e| e.into_inner());
+        let of_provider = |entry: &Option<Interaction>| entry.as_ref().is_some_and(|recorded| recorded.provider == provider);
+        let interaction = interactions
+            .iter()
+            .position(|entry| of_provider(entry) && entry.as_ref().is_some_and(|recorded| recorded.request == request))
+            .or_else(|| interactions.iter().position(of_provider))
+            .and_then(|index| interactions[index].take())
+            .context(format!("The cassette has no more recorded responses from {}", provider))?;
+        Ok(Self {
+            source: Source::Replayed {
+                chunks: interaction.chunks.into(),
+                fast: *fast,
+            },
+            sent: Instant::now(),
+            recording: None,
+        })
+    }
+
+    // A body made up on the spot (`--dry-run`), served at once
+    pub fn canned(chunks: Vec<String>) -> Self {
+        Self {
+            source: Source::Replayed {
+                chunks: chunks.into_iter().map(|data| Chunk { after_ms: 0, data }).collect(),
+                fast: true,
+            },
+            sent: Instant::now(),
+            recording: None,
+        }
+    }
+
+    // The next piece of the body; None at the end
+    pub async fn chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        match &mut self.source {
+            Source::Live(response) => {
+                let chunk = response.chunk().await?.map(|bytes| bytes.to_vec());
+                if let (Some(chunk), Some((interaction, partial))) = (&chunk, &mut self.recording) {
+                    partial.extend_from_slice(chunk);
+                    // Keep an incomplete character for the next chunk
+                    let complete = match std::str::from_utf8(partial) {
+                        Ok(_) => partial.len(),
+                        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                        Err(_) => partial.len(),
+                    };
+                    let data: Vec<u8> = partial.drain(..complete).collect();
+                    interaction.chunks.push(Chunk {
+                        after_ms: self.sent.elapsed().as_millis() as u64,
+                        data: String::from_utf8_lossy(&data).into_owned(),
+                    });
+                }
+                Ok(chunk)
+            }
+            Source::Replayed { chunks, fast } => {
+                let Some(chunk) = chunks.pop_front() else {
+                    return Ok(None);
+                };
+                if !*fast {
+                    let due = self.sent + Duration::from_millis(chunk.after_ms);
+                    tokio::time::sleep(due.saturating_duration_since(Instant::now())).await;
+                }
+                Ok(Some(chunk.data.into_bytes()))
+            }
+        }
+    }
+
+    pub async fn bytes(mut self) -> Result<Vec<u8>> {
+        let mut body = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    pub async fn json<T: DeserializeOwned>(self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.bytes().await?)?)
+    }
+}
+
+fn append(path: &Path, interaction: &Interaction) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new().append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(interaction)?)?;
+    Ok(())
+}
+
+// The interaction is written once its body has been read, or dropped half-read
+// (an answer stopped with Ctrl+C)
+impl Drop for Reply {
+    fn drop(&mut self) {
+        let (Some((mut interaction, partial)), Some(Mode::Record { path, lock })) = (self.recording.take(), MODE.get()) else {
+            return;
+        };
+        if !partial.is_empty() {
+            interaction.chunks.push(Chunk {
+                after_ms: self.sent.elapsed().as_millis() as u64,
+                data: String::from_utf8_lossy(&partial).into_owned(),
+            });
+        }
+        let _lock = lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = append(path, &interaction) {
+            ui::notice(ui::warning(tr!("vcr.write_failed", path.display(), e)));
+        }
+    }
+}
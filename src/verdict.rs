@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+// Machine-readable outcome of a review, so `ask` / `batch` can gate CI jobs:
+//
+//   0 = clean, 1 = minor issues, 2 = errors found, 3 = no verdict (answer or review failed)
+//
+// The reviewer is asked to end with a `VERDICT: ...` line, which is parsed back here.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Clean,
+    Minor,
+    Errors,
+}
+
+pub const EXIT_FAILED: i32 = 3;
+
+pub const INSTRUCTION: &str = "\n\nEnd your review with one final line in exactly this form, with the keyword in English: `VERDICT: clean` (no problems), `VERDICT: minor` (small issues or omissions that don't make the answer wrong) or `VERDICT: errors` (factual errors, bugs or hallucinations).";
+
+impl Severity {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Severity::Clean => 0,
+            Severity::Minor => 1,
+            Severity::Errors => 2,
+        }
+    }
+
+    // The English keyword of the `VERDICT:` line (also its JSON form)
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Severity::Clean => "clean",
+            Severity::Minor => "minor",
+            Severity::Errors => "errors",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Clean => tr!("verdict.clean"),
+            Severity::Minor => tr!("verdict.minor"),
+            Severity::Errors => tr!("verdict.errors"),
+        }
+    }
+}
+
+// The last `VERDICT: ...` line of a review, tolerating markdown decoration around it
+pub fn parse(review: &str) -> Option<Severity> {
+    review.lines().rev().find_map(|line| {
+        let line = line.trim().trim_matches(|c: char| matches!(c, '*' | '`' | '#' | '>' | '_') || c.is_whitespace());
+        let (keyword, value) = line.split_once([':', '：'])?;
+        if !keyword.trim().trim_matches(|c| c == '*' || c == '`').eq_ignore_ascii_case("verdict") {
+            return None;
+        }
+        let value = value.trim().trim_matches(|c: char| !c.is_alphabetic()).to_lowercase();
+        match value.as_str() {
+            "clean" => Some(Severity::Clean),
+            "minor" => Some(Severity::Minor),
+            "errors" | "error" => Some(Severity::Errors),
+            _ => None,
+        }
+    })
+}
+
+// Rubric grades from the reviewer's `SCORES:` line, 1-10 each; `code` is None
+// when the answer has no code
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Scores {
+    pub accuracy: u8,
+    pub completeness: u8,
+    pub clarity: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u8>,
+}
+
+pub const RUBRIC: &str = "\n\nNear the end of your review, grade the answer from 1 (poor) to 10 (excellent) on one line of its own in exactly this form, with the keywords in English: `SCORES: accuracy=N completeness=N clarity=N code=N` (write `code=n/a` if the answer contains no code).";
+
+impl Scores {
+    // Mean of the grades given
+    pub fn overall(&self) -> f64 {
+        let grades: Vec<u8> = [Some(self.accuracy), Some(self.completeness), Some(self.clarity), self.code].into_iter().flatten().collect();
+        grades.iter().map(|&g| g as f64).sum::<f64>() / grades.len() as f64
+    }
+
+    // "accuracy 8 · completeness 7 · clarity 9 · code 6"
+    pub fn summary(&self) -> String {
+        let code = self.code.map_or("n/a".to_string(), |g| g.to_string());
+        tr!("scores.line", self.accuracy, self.completeness, self.clarity, code)
+    }
+}
+
+// The last `SCORES: accuracy=8 ...` line of a review; the three general grades
+// are required, `code` may be missing or "n/a". Grades are clamped to 1-10.
+pub fn parse_scores(review: &str) -> Option<Scores> {
+    review.lines().rev().find_map(|line| {
+        let line = line.trim().trim_matches(|c: char| matches!(c, '*' | '`' | '#' | '>' | '_') || c.is_whitespace());
+        let (keyword, value) = line.split_once([':', '：'])?;
+        if !keyword.trim().trim_matches(|c| c == '*' || c == '`').eq_ignore_ascii_case("scores") {
+            return None;
+        }
+        let mut grades = std::collections::HashMap::new();
+        for pair in value.split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '|' | '，')) {
+            let Some((name, grade)) = pair.split_once(['=', ':']) else {
+                continue;
+            };
+            let grade = grade.trim_matches(|c: char| !c.is_ascii_digit()).split('/').next().and_then(|g| g.parse::<u8>().ok());
+            grades.insert(name.trim_matches(|c: char| !c.is_alphabetic()).to_lowercase(), grade.map(|g| g.clamp(1, 10)));
+        }
+        Some(Scores {
+            accuracy: grades.get("accuracy").copied().flatten()?,
+            completeness: grades.get("completeness").copied().flatten()?,
+            clarity: grades.get("clarity").copied().flatten()?,
+            code: grades.get("code").copied().flatten(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdict_lines_are_parsed() {
+        assert_eq!(parse("Looks right.\n\nVERDICT: clean"), Some(Severity::Clean));
+        assert_eq!(parse("**VERDICT:** Errors"), Some(Severity::Errors));
+        assert_eq!(parse("`verdict: minor.`"), Some(Severity::Minor));
+        assert_eq!(parse("> VERDICT：error"), Some(Severity::Errors));
+    }
+
+    #[test]
+    fn the_last_verdict_line_wins() {
+        assert_eq!(parse("VERDICT: clean\nOn second thought...\nVERDICT: minor"), Some(Severity::Minor));
+    }
+
+    #[test]
+    fn reviews_without_a_verdict_have_none() {
+        assert_eq!(parse("No problems found."), None);
+        assert_eq!(parse("VERDICT: maybe"), None);
+        assert_eq!(parse("Verdicts: clean"), None);
+    }
+
+    #[test]
+    fn scores_are_parsed_and_clamped() {
+        let scores = parse_scores("SCORES: accuracy=8 completeness=7/10 clarity=12 code=n/a").unwrap();
+        assert_eq!(scores, Scores { accuracy: 8, completeness: 7, clarity: 10, code: None });
+        assert_eq!(parse_scores("SCORES: accuracy=8 clarity=9"), None);
+    }
+}
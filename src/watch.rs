@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::archive;
+use crate::config::{Profile, Settings};
+use crate::i18n::tr;
+use crate::pipeline::Pipeline;
+use crate::session::ConversationSession;
+use crate::ui;
+
+// `aivsai watch inbox/`: a question queue. Every `.md` or `.txt` file dropped
+// into the directory is one question; it is answered and reviewed, the result is
+// written next to it as NAME.answer.md, and the question moves to done/ (or to
+// failed/ when no answer could be produced; move it back to retry).
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Files modified more recently than this may still be being written
+const SETTLE: Duration = Duration::from_secs(2);
+const ANSWER_SUFFIX: &str = ".answer.md";
+
+// Question files ready to be picked up, oldest first
+fn pending(inbox: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(inbox).context(format!("Failed to read {:?}", inbox))?;
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            // Skip our own answers and editors' hidden temporary files
+            let question = (name.ends_with(".md") || name.ends_with(".txt")) && !name.ends_with(ANSWER_SUFFIX) && !name.starts_with('.');
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            let settled = modified.elapsed().is_ok_and(|age| age >= SETTLE);
+            (question && settled).then(|| (modified, entry.path()))
+        })
+        .collect();
+    files.sort();
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+// NAME.answer.md for NAME.md / NAME.txt
+fn answer_path(question: &Path) -> PathBuf {
+    let stem = question.file_stem().unwrap_or_default().to_string_lossy();
+    question.with_file_name(format!("{}{}", stem, ANSWER_SUFFIX))
+}
+
+// Move `path` into `dir`, numbering the name if it's taken (q.md, q-2.md, ...)
+fn move_into(path: &Path, dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).context(format!("Failed to create {:?}", dir))?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    let mut target = dir.join(path.file_name().unwrap_or_default());
+    let mut n = 2;
+    while target.exists() {
+        target = dir.join(format!("{}-{}.{}", stem, n, extension));
+        n += 1;
+    }
+    std::fs::rename(path, &target).context(format!("Failed to move {:?} to {:?}", path, target))?;
+    Ok(target)
+}
+
+// Answer and review one question file; returns the answer file written
+async fn answer_file(pipeline: &Pipeline, path: &Path) -> Result<PathBuf> {
+    let question = std::fs::read_to_string(path).context(format!("Failed to read {:?}", path))?;
+    let question = question.trim();
+    if question.is_empty() {
+        anyhow::bail!("The file is empty");
+    }
+    let mut session = ConversationSession::new();
+    let turn = pipeline.run_round(&session.start_time, 1, question).await.context("No answer was produced")?;
+    session.add_turn(turn);
+    archive::write_file(&answer_path(path), &pipeline.render(&session))
+}
+
+// `aivsai watch DIR`
+pub async fn run(args: &[String], settings: &Settings, profile: &Profile) -> Result<()> {
+    let [dir] = args else {
+        anyhow::bail!("Usage: aivsai watch DIR (answers each .md/.txt question file dropped into DIR)");
+    };
+    let inbox = PathBuf::from(dir);
+    if !inbox.is_dir() {
+        anyhow::bail!("{:?} is not a directory", inbox);
+    }
+    let pipeline = Pipeline::new(settings, profile, false)?;
+    ui::notice(ui::success(tr!("watch.watching", inbox.display())));
+
+    loop {
+        for path in pending(&inbox)? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            ui::notice(ui::heading(format!("\n📥 {}", name)));
+            let destination = match answer_file(&pipeline, &path).await {
+                Ok(answer) => {
+                    ui::notice(ui::success(tr!("watch.answered", name, answer.display())));
+                    "done"
+                }
+                Err(e) => {
+                    ui::notice(ui::warning(tr!("watch.failed", name, format!("{:#}", e))));
+                    "failed"
+                }
+            };
+            // A file that can't be moved would be picked up again forever
+            move_into(&path, &inbox.join(destination))?;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::api;
+use crate::config::{WebhookEvent, WebhookSettings};
+use crate::i18n::tr;
+use crate::session::{ConversationSession, ConversationTurn};
+use crate::ui;
+use crate::usage::Totals;
+
+// `[[webhooks]]`: a JSON POST after every round and/or save. Round payloads:
+//
+//   {"event": "round", "session": "2025-03-01 14:30:25", "round": 3,
+//    "question": "...", "answer": "...", "answer_model": "...",
+//    "review": "..." | null, "review_model": "...", "review_error": null,
+//    "verdict": "clean" | "minor" | "errors" | null, "elapsed_ms": 8123,
+//    "usage": {"requests": 2, "prompt_tokens": 812, "completion_tokens": 640}}
+//
+// Save payloads list the rounds of the saved session:
+//
+//   {"event": "save", "session": "...", "file": "conversations/....md",
+//    "rounds": [{"round": 1, "question": "...", "verdict": null, "starred": false}]}
+//
+// Requests are sent in the background so a slow relay never holds up the chat;
+// failures are only reported. Pending requests are awaited before exiting.
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+static HOOKS: OnceLock<Vec<WebhookSettings>> = OnceLock::new();
+static PENDING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+pub fn init(settings: &[WebhookSettings]) -> Result<()> {
+    for hook in settings {
+        if !hook.url.starts_with("http://") && !hook.url.starts_with("https://") {
+            anyhow::bail!("[[webhooks]] url must be an http(s) URL, got {:?}", hook.url);
+        }
+    }
+    let _ = HOOKS.set(settings.to_vec());
+    Ok(())
+}
+
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| Client::builder().timeout(TIMEOUT).build().unwrap_or_default())
+}
+
+async fn post(hook: WebhookSettings, payload: Value) {
+    let mut request = client().post(&hook.url).json(&payload);
+    for (name, value) in &hook.headers {
+        request = request.header(name, value);
+    }
+    let failure = match request.send().await {
+        Ok(response) if response.status().is_success() => return,
+        Ok(response) => format!("HTTP {}", response.status()),
+        Err(e) => e.to_string(),
+    };
+    ui::notice(ui::warning(tr!("webhook.failed", hook.url, failure)));
+}
+
+fn send(event: WebhookEvent, payload: Value) {
+    for hook in HOOKS.get().into_iter().flatten().filter(|hook| hook.events.contains(&event)) {
+        if let Err(e) = api::check_destination("webhook", &hook.url) {
+            ui::notice(ui::warning(tr!("webhook.failed", hook.url, format!("{:#}", e))));
+            continue;
+        }
+        let task = tokio::spawn(post(hook.clone(), payload.clone()));
+        let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+        pending.retain(|task| !task.is_finished());
+        pending.push(task);
+    }
+}
+
+// `session` is the start time identifying the session, as in save payloads
+pub fn round_finished(session: &str, turn: &ConversationTurn, usage: Totals, elapsed: Duration) {
+    send(
+        WebhookEvent::Round,
+        json!({
+            "event": "round",
+            "session": session,
+            "round": turn.round,
+            "question": turn.user_question,
+            "answer": turn.moonshot_answer,
+            "answer_model": turn.answer_model,
+            "review": turn.deepseek_review,
+            "review_model": turn.review_model,
+            "review_error": turn.review_error,
+            "verdict": turn.verdict,
+            "elapsed_ms": elapsed.as_millis() as u64,
+            "usage": usage,
+        }),
+    );
+}
+
+pub fn session_saved(session: &ConversationSession, file: &Path) {
+    let rounds: Vec<Value> = session
+        .turns
+        .iter()
+        .map(|turn| json!({ "round": turn.round, "question": turn.user_question, "verdict": turn.verdict, "starred": turn.starred }))
+        .collect();
+    send(
+        WebhookEvent::Save,
+        json!({
+            "event": "save",
+            "session": session.start_time,
+            "file": file.display().to_string(),
+            "rounds": rounds,
+        }),
+    );
+}
+
+// Wait for requests still in flight (each one is bounded by TIMEOUT)
+pub async fn flush() {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap_or_else(PoisonError::into_inner));
+    for task in pending {
+        let _ = task.await;
+    }
+}